@@ -260,24 +260,10 @@ impl App {
     }
 
     fn update_commutation(&mut self) {
-        let motor = MotorConstant::kraken_x60();
-        let config = MotorControllerConfig::new(motor)
-            .with_mode(ControlMode::Position)
-            .with_position_controller(
-                PidfConfig::p(0.5).with_limits(-200.0, 200.0)
-            )
-            .with_velocity_controller(
-                PidfConfig::pi(0.002, 0.01).with_limits(-1.0, 1.0).with_i_max(0.5)
-            )
-            .with_max_current(60.0);
-
         if self.use_trapezoidal {
-            self.motor_controller = MotorController::with_commutation(
-                config,
-                Box::new(TrapezoidalCommutation::default()),
-            );
+            self.motor_controller.set_commutation(Box::new(TrapezoidalCommutation::default()));
         } else {
-            self.motor_controller = MotorController::with_commutation(config, Box::new(FocCommutation));
+            self.motor_controller.set_commutation(Box::new(FocCommutation));
         }
     }
 
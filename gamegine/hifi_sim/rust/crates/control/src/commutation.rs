@@ -213,6 +213,18 @@ impl CommutationStrategy for SinusoidalCommutation {
     }
 }
 
+/// Look up a default-configured commutation strategy by name
+///
+/// Accepts "foc", "trapezoidal" or "sinusoidal".
+pub fn commutation_from_name(name: &str) -> Option<Box<dyn CommutationStrategy>> {
+    match name {
+        "foc" => Some(Box::new(FocCommutation)),
+        "trapezoidal" => Some(Box::new(TrapezoidalCommutation::default())),
+        "sinusoidal" => Some(Box::new(SinusoidalCommutation::default())),
+        _ => None,
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -0,0 +1,148 @@
+//! Differential Drive Command Mixing
+//!
+//! Turns driver commands into left/right side outputs for tank-style
+//! drivetrains, following WPILib's `DifferentialDrive`: arcade (throttle and
+//! turn), curvature (throttle and path curvature) and plain tank.
+//!
+//! There is no reusable differential drivetrain model to drive yet; until
+//! there is, the side outputs feed a hand-built `MotorBank` (one
+//! `MotorConstant` per motor, so the sides may mix motor types) as in the
+//! realtime tank drive example.
+
+/// Output for each side of a differential drive, in [-1, 1]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DifferentialOutput {
+    pub left: f64,
+    pub right: f64,
+}
+
+impl DifferentialOutput {
+    pub fn new(left: f64, right: f64) -> Self {
+        DifferentialOutput { left, right }
+    }
+
+    /// Scale both sides down together so neither exceeds 1
+    fn desaturate(self) -> Self {
+        let largest = self.left.abs().max(self.right.abs());
+        if largest > 1.0 {
+            DifferentialOutput::new(self.left / largest, self.right / largest)
+        } else {
+            self
+        }
+    }
+}
+
+/// Mixes driver commands into per-side differential drive outputs
+///
+/// Turn and curvature commands are counter-clockwise positive, so a positive
+/// turn speeds up the right side.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DifferentialMixer {
+    /// Square input magnitudes (keeping sign) for finer control at low speed
+    pub square_inputs: bool,
+}
+
+impl DifferentialMixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Square input magnitudes before mixing
+    pub fn with_square_inputs(mut self, square_inputs: bool) -> Self {
+        self.square_inputs = square_inputs;
+        self
+    }
+
+    /// Clamp a command to [-1, 1], squaring it if configured
+    fn shape(&self, input: f64) -> f64 {
+        let input = input.clamp(-1.0, 1.0);
+        if self.square_inputs {
+            input * input.abs()
+        } else {
+            input
+        }
+    }
+
+    /// Arcade drive: `throttle` forward and `turn` rotation
+    ///
+    /// The sides are scaled by the largest sum the stick direction can reach,
+    /// so full throttle with full turn gives one side at 1 and the other at 0.
+    pub fn arcade(&self, throttle: f64, turn: f64) -> DifferentialOutput {
+        let throttle = self.shape(throttle);
+        let turn = self.shape(turn);
+
+        let greater = throttle.abs().max(turn.abs());
+        if greater == 0.0 {
+            return DifferentialOutput::default();
+        }
+        let saturated = (greater + throttle.abs().min(turn.abs())) / greater;
+        DifferentialOutput::new((throttle - turn) / saturated, (throttle + turn) / saturated)
+    }
+
+    /// Curvature drive: `throttle` forward and `curvature` steering
+    ///
+    /// The turn rate scales with throttle so the robot follows the same arc
+    /// at any speed. With `allow_turn_in_place` the curvature acts as a
+    /// plain turn command instead, for spinning at zero throttle.
+    pub fn curvature(&self, throttle: f64, curvature: f64, allow_turn_in_place: bool) -> DifferentialOutput {
+        let throttle = self.shape(throttle);
+        let curvature = curvature.clamp(-1.0, 1.0);
+
+        let turn = if allow_turn_in_place { curvature } else { throttle.abs() * curvature };
+        DifferentialOutput::new(throttle - turn, throttle + turn).desaturate()
+    }
+
+    /// Tank drive: each stick commands its own side
+    pub fn tank(&self, left: f64, right: f64) -> DifferentialOutput {
+        DifferentialOutput::new(self.shape(left), self.shape(right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arcade_without_turn_drives_sides_equally() {
+        let mixer = DifferentialMixer::new();
+        for throttle in [-1.0, -0.4, 0.0, 0.7, 1.0] {
+            let output = mixer.arcade(throttle, 0.0);
+            assert_eq!(output.left, output.right);
+            assert_eq!(output.left, throttle);
+        }
+
+        // Full throttle and full turn pivots about the left side
+        let output = mixer.arcade(1.0, 1.0);
+        assert_eq!((output.left, output.right), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_curvature_turn_scales_with_throttle() {
+        let mixer = DifferentialMixer::new();
+        let slow = mixer.curvature(0.2, 0.5, false);
+        let fast = mixer.curvature(0.6, 0.5, false);
+
+        let turn = |output: DifferentialOutput| output.right - output.left;
+        assert!(turn(slow) > 0.0);
+        assert!((turn(fast) - 3.0 * turn(slow)).abs() < 1e-12);
+        // The ratio of side speeds, and so the arc, is the same at both speeds
+        assert!((slow.right / slow.left - fast.right / fast.left).abs() < 1e-12);
+
+        // No throttle means no turning unless turning in place is allowed
+        assert_eq!(mixer.curvature(0.0, 0.5, false), DifferentialOutput::default());
+        assert_eq!(mixer.curvature(0.0, 0.5, true), DifferentialOutput::new(-0.5, 0.5));
+
+        // Outputs stay in range at full command
+        let output = mixer.curvature(1.0, 1.0, false);
+        assert!(output.left.abs() <= 1.0 && output.right.abs() <= 1.0);
+    }
+
+    #[test]
+    fn test_tank_passes_sticks_through() {
+        assert_eq!(DifferentialMixer::new().tank(0.3, -0.6), DifferentialOutput::new(0.3, -0.6));
+        assert_eq!(DifferentialMixer::new().tank(1.5, -2.0), DifferentialOutput::new(1.0, -1.0));
+
+        let squared = DifferentialMixer::new().with_square_inputs(true).tank(0.5, -0.5);
+        assert_eq!(squared, DifferentialOutput::new(0.25, -0.25));
+    }
+}
@@ -0,0 +1,55 @@
+//! Motor Feedforward
+//!
+//! Permanent-magnet DC motor feedforward, `duty = kS·sign(v) + kV·v + kA·a`,
+//! applied with gains identified by drive characterization.
+
+/// Static, velocity and acceleration feedforward for a motor
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SimpleMotorFeedforward {
+    /// Static gain: output needed to overcome friction (duty)
+    pub ks: f64,
+    /// Velocity gain (duty per rad/s)
+    pub kv: f64,
+    /// Acceleration gain (duty per rad/s²)
+    pub ka: f64,
+}
+
+impl SimpleMotorFeedforward {
+    /// Create a feedforward from characterized gains
+    pub fn new(ks: f64, kv: f64, ka: f64) -> Self {
+        Self { ks, kv, ka }
+    }
+
+    /// Output for a reference velocity (rad/s) and acceleration (rad/s²)
+    ///
+    /// The static term follows the sign of the velocity and is zero at rest.
+    pub fn calculate(&self, velocity: f64, acceleration: f64) -> f64 {
+        let direction = if velocity == 0.0 { 0.0 } else { velocity.signum() };
+        self.ks * direction + self.kv * velocity + self.ka * acceleration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steady_velocity_is_static_plus_velocity_term() {
+        let ff = SimpleMotorFeedforward::new(0.05, 0.002, 0.0004);
+
+        assert!((ff.calculate(100.0, 0.0) - (0.05 + 0.002 * 100.0)).abs() < 1e-12);
+        assert!((ff.calculate(-100.0, 0.0) + (0.05 + 0.002 * 100.0)).abs() < 1e-12);
+        assert_eq!(ff.calculate(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_acceleration_term_adds_to_velocity_terms() {
+        let ff = SimpleMotorFeedforward::new(0.05, 0.002, 0.0004);
+
+        let cruise = ff.calculate(50.0, 0.0);
+        assert!((ff.calculate(50.0, 200.0) - (cruise + 0.0004 * 200.0)).abs() < 1e-12);
+        assert!((ff.calculate(50.0, -200.0) - (cruise - 0.0004 * 200.0)).abs() < 1e-12);
+        // Accelerating from rest needs only the acceleration term
+        assert!((ff.calculate(0.0, 200.0) - 0.0004 * 200.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,263 @@
+//! Closed-loop Flywheel Shooter Simulation
+//!
+//! Composes a motor, battery, mechanical link and velocity PIDF controller into
+//! a flywheel that can be spun up to a setpoint and disturbed by a shot, so
+//! RPM dip and recovery time can be studied.
+//!
+//! [`shooter_exit_velocity`] turns the flywheel speed at contact into a
+//! projectile exit speed.
+//!
+//! Like the general mechanism simulator, the motor uses the steady-state model
+//! (V = IR + Ke*ω, T = Kt*I), which is stable at millisecond time steps.
+
+use electrical::analysis::voltage_under_load;
+use electrical::battery::BatteryConstant;
+use electrical::motor::MotorConstant;
+use mechanics::link::{LinkConfig, MechanicalLink};
+
+use crate::pidf::{PidfConfig, PidfController};
+
+/// Typical brushless motor rotor inertia (kg*m^2)
+const ROTOR_INERTIA: f64 = 0.0001;
+
+/// One recorded flywheel step
+#[derive(Debug, Clone, Copy)]
+pub struct FlywheelSample {
+    /// Simulation time (s)
+    pub time: f64,
+    /// Flywheel velocity (rad/s)
+    pub velocity: f64,
+    /// Motor current (A)
+    pub current: f64,
+    /// Battery terminal voltage (V)
+    pub voltage: f64,
+    /// Controller duty output (-1 to 1)
+    pub duty: f64,
+}
+
+/// Result of launching one projectile off a flywheel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShotResult {
+    /// Projectile speed leaving the shooter (m/s)
+    pub exit_velocity: f64,
+    /// Flywheel velocity once the projectile has left (rad/s)
+    pub flywheel_velocity: f64,
+}
+
+/// Projectile exit speed for a flywheel spinning at `flywheel_velocity` (rad/s)
+///
+/// During contact the projectile (`projectile_mass` kg, gripped at
+/// `wheel_radius` m) is brought up to the wheel surface speed, sharing the
+/// flywheel's angular momentum: `w' = I w / (I + m r^2)`, with `I` the total
+/// flywheel inertia (kg*m^2). `transfer_efficiency` (0-1) then scales the
+/// surface speed `w' r` to the exit speed; about 0.5 for a single wheel
+/// against a fixed hood, where the projectile rolls, and near 1.0 for
+/// opposed wheels.
+pub fn shooter_exit_velocity(
+    flywheel_velocity: f64,
+    flywheel_inertia: f64,
+    wheel_radius: f64,
+    projectile_mass: f64,
+    transfer_efficiency: f64,
+) -> ShotResult {
+    let projectile_inertia = projectile_mass.max(0.0) * wheel_radius * wheel_radius;
+    let total_inertia = flywheel_inertia + projectile_inertia;
+    let shared_velocity = if total_inertia > 0.0 {
+        flywheel_velocity * flywheel_inertia / total_inertia
+    } else {
+        flywheel_velocity
+    };
+    ShotResult {
+        exit_velocity: transfer_efficiency.clamp(0.0, 1.0) * shared_velocity * wheel_radius,
+        flywheel_velocity: shared_velocity,
+    }
+}
+
+/// Velocity-controlled flywheel driven through a mechanical link
+#[derive(Debug, Clone)]
+pub struct FlywheelSim {
+    motor: MotorConstant,
+    battery: BatteryConstant,
+    link: MechanicalLink,
+    controller: PidfController,
+    time: f64,
+    /// Flywheel velocity (rad/s)
+    velocity: f64,
+    battery_soc: f64,
+    battery_voltage: f64,
+}
+
+impl FlywheelSim {
+    /// Create a flywheel at rest on a full battery
+    ///
+    /// `link.load_inertia` is the flywheel moment of inertia (kg*m^2) and the
+    /// controller maps velocity error (rad/s) to duty.
+    pub fn new(
+        motor: MotorConstant,
+        battery: BatteryConstant,
+        link: LinkConfig,
+        controller: PidfConfig,
+    ) -> Self {
+        let battery_voltage = (battery.open_circuit_voltage_function)(1.0);
+        Self {
+            motor,
+            battery,
+            link: MechanicalLink::new(link),
+            controller: PidfController::new(controller.with_limits(-1.0, 1.0)),
+            time: 0.0,
+            velocity: 0.0,
+            battery_soc: 1.0,
+            battery_voltage,
+        }
+    }
+
+    /// Flywheel free speed at the battery's full-charge voltage (rad/s)
+    pub fn free_speed(&self) -> f64 {
+        let voltage = (self.battery.open_circuit_voltage_function)(1.0);
+        self.link.motor_to_load_velocity(self.motor.free_speed(voltage))
+    }
+
+    /// Current flywheel velocity (rad/s)
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// Current simulation time (s)
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Advance one step toward `target` (rad/s)
+    pub fn step(&mut self, target: f64, dt: f64) -> FlywheelSample {
+        self.controller.set_setpoint(target);
+        let duty = self.controller.update(self.velocity, dt);
+
+        let motor_velocity = self.link.load_to_motor_velocity(self.velocity);
+        let current = (duty * self.battery_voltage - self.motor.ke() * motor_velocity) / self.motor.resistance;
+        let sample = FlywheelSample {
+            time: self.time,
+            velocity: self.velocity,
+            current,
+            voltage: self.battery_voltage,
+            duty,
+        };
+
+        let (acceleration, _) =
+            self.link.compute_load_acceleration(self.motor.kt() * current, ROTOR_INERTIA, self.velocity, 0.0);
+        self.velocity += acceleration * dt;
+        self.time += dt;
+
+        // The battery supplies the duty-scaled motor current
+        let battery_current = (duty * current).abs();
+        self.battery_soc = (self.battery_soc
+            - battery_current * dt / 3600.0 / self.battery.rated_capacity_ah)
+            .clamp(0.0, 1.0);
+        self.battery_voltage = voltage_under_load(&self.battery, battery_current, self.battery_soc);
+
+        sample
+    }
+
+    /// Run toward `target` (rad/s) for `duration` seconds, recording every step
+    pub fn spin_up(&mut self, target: f64, duration: f64, dt: f64) -> Vec<FlywheelSample> {
+        let end_time = self.time + duration;
+        let mut samples = Vec::with_capacity((duration / dt).ceil().max(0.0) as usize);
+        while self.time < end_time {
+            samples.push(self.step(target, dt));
+        }
+        samples
+    }
+
+    /// Ball contact: the flywheel instantly shares its angular momentum with a
+    /// ball of `ball_inertia` (kg*m^2 about the axle, i.e. m*r^2)
+    pub fn shoot(&mut self, ball_inertia: f64) {
+        let flywheel_inertia = self.link.total_effective_inertia(ROTOR_INERTIA);
+        self.velocity *= flywheel_inertia / (flywheel_inertia + ball_inertia.max(0.0));
+    }
+
+    /// Launch a projectile of `projectile_mass` (kg) gripped at `wheel_radius`
+    /// (m), leaving the flywheel at the shared velocity
+    pub fn shoot_projectile(&mut self, projectile_mass: f64, wheel_radius: f64, transfer_efficiency: f64) -> ShotResult {
+        let flywheel_inertia = self.link.total_effective_inertia(ROTOR_INERTIA);
+        let shot = shooter_exit_velocity(
+            self.velocity,
+            flywheel_inertia,
+            wheel_radius,
+            projectile_mass,
+            transfer_efficiency,
+        );
+        self.velocity = shot.flywheel_velocity;
+        shot
+    }
+
+    /// Return to rest on a full battery
+    pub fn reset(&mut self) {
+        self.time = 0.0;
+        self.velocity = 0.0;
+        self.battery_soc = 1.0;
+        self.battery_voltage = (self.battery.open_circuit_voltage_function)(1.0);
+        self.controller.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shooter() -> FlywheelSim {
+        let motor = MotorConstant::kraken_x60();
+        let battery = BatteryConstant::default();
+        let link = LinkConfig::new().with_gear_ratio(1.0).with_load_inertia(0.002);
+        // Feedforward carries the steady state; the I-zone keeps spin-up from winding up
+        let kf = 1.0 / motor.free_speed((battery.open_circuit_voltage_function)(1.0));
+        let controller = PidfConfig::pidf(0.05, 0.05, 0.0, kf).with_i_zone(20.0);
+        FlywheelSim::new(motor, battery, link, controller)
+    }
+
+    #[test]
+    fn test_shot_dips_and_recovers() {
+        let mut sim = shooter();
+        let target = 400.0;
+        let dt = 0.001;
+
+        sim.spin_up(target, 2.0, dt);
+        assert!((sim.velocity() - target).abs() < 0.01 * target, "spun up to {}", sim.velocity());
+
+        // A 0.27 kg ball gripped at 5 cm
+        sim.shoot(0.27 * 0.05 * 0.05);
+        let dip = sim.velocity();
+        assert!(dip < 0.8 * target, "shot only dipped to {}", dip);
+
+        let recovery = sim.spin_up(target, 1.0, dt);
+        let recovered_at = recovery.iter()
+            .find(|s| (s.velocity - target).abs() < 0.02 * target)
+            .map(|s| s.time)
+            .expect("flywheel never recovered");
+        assert!(recovered_at - recovery[0].time < 0.5);
+        assert!((sim.velocity() - target).abs() < 0.02 * target);
+    }
+
+    #[test]
+    fn test_heavier_projectile_drops_more_rpm_and_exits_slower() {
+        let (velocity, inertia, radius, efficiency) = (400.0, 0.002, 0.05, 0.9);
+        let ideal = efficiency * velocity * radius;
+        let light = shooter_exit_velocity(velocity, inertia, radius, 0.1, efficiency);
+        let heavy = shooter_exit_velocity(velocity, inertia, radius, 0.5, efficiency);
+
+        assert!(heavy.flywheel_velocity < light.flywheel_velocity);
+        assert!(light.flywheel_velocity < velocity);
+        assert!(heavy.exit_velocity < light.exit_velocity);
+        assert!(light.exit_velocity < ideal);
+        // Momentum is conserved across the contact
+        let momentum_after = inertia * heavy.flywheel_velocity + 0.5 * radius * radius * heavy.flywheel_velocity;
+        assert!((momentum_after - inertia * velocity).abs() < 1e-12);
+
+        // The closed-loop sim takes the same RPM drop
+        let mut sim = shooter();
+        sim.spin_up(velocity, 2.0, 0.001);
+        let before = sim.velocity();
+        let shot = sim.shoot_projectile(0.27, radius, 0.5);
+        assert_eq!(sim.velocity(), shot.flywheel_velocity);
+        assert!(shot.flywheel_velocity < before);
+        assert!(shot.exit_velocity < 0.5 * before * radius);
+    }
+}
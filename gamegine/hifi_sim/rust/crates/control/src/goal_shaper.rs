@@ -0,0 +1,264 @@
+//! Goal Shaping
+//!
+//! Pluggable strategies for turning a raw goal into the reference a controller
+//! actually tracks (pass-through, slew-rate limited, trapezoidal, S-curve).
+
+use std::collections::VecDeque;
+
+/// Trait for goal shaping strategies
+///
+/// A shaper holds its own reference state and moves it toward the goal each
+/// step, so the same controller can follow a step, a ramp, or a profile.
+pub trait GoalShaper: Send + Sync {
+    /// Advance the shaped reference toward `goal` by `dt` and return it
+    fn shape(&mut self, goal: f64, dt: f64) -> f64;
+
+    /// Reset the shaped reference to `value` at rest
+    fn reset(&mut self, value: f64);
+
+    /// Clone this shaper into a boxed trait object
+    fn box_clone(&self) -> Box<dyn GoalShaper>;
+}
+
+impl Clone for Box<dyn GoalShaper> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+// ============================================================================
+// Direct
+// ============================================================================
+
+/// Pass the goal through unchanged
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DirectGoal;
+
+impl GoalShaper for DirectGoal {
+    fn shape(&mut self, goal: f64, _dt: f64) -> f64 {
+        goal
+    }
+
+    fn reset(&mut self, _value: f64) {}
+
+    fn box_clone(&self) -> Box<dyn GoalShaper> {
+        Box::new(*self)
+    }
+}
+
+// ============================================================================
+// Slew Rate
+// ============================================================================
+
+/// Limit how fast the reference may change (units per second)
+#[derive(Debug, Clone, Copy)]
+pub struct SlewRateGoal {
+    /// Maximum rate of change of the reference
+    pub max_rate: f64,
+    value: f64,
+}
+
+impl SlewRateGoal {
+    pub fn new(max_rate: f64) -> Self {
+        Self { max_rate, value: 0.0 }
+    }
+}
+
+impl GoalShaper for SlewRateGoal {
+    fn shape(&mut self, goal: f64, dt: f64) -> f64 {
+        let max_step = self.max_rate * dt;
+        self.value += (goal - self.value).clamp(-max_step, max_step);
+        self.value
+    }
+
+    fn reset(&mut self, value: f64) {
+        self.value = value;
+    }
+
+    fn box_clone(&self) -> Box<dyn GoalShaper> {
+        Box::new(*self)
+    }
+}
+
+// ============================================================================
+// Trapezoidal
+// ============================================================================
+
+/// Velocity- and acceleration-limited reference (trapezoidal velocity profile)
+#[derive(Debug, Clone, Copy)]
+pub struct TrapezoidalGoal {
+    /// Maximum reference velocity
+    pub max_velocity: f64,
+    /// Maximum reference acceleration
+    pub max_acceleration: f64,
+    position: f64,
+    velocity: f64,
+}
+
+impl TrapezoidalGoal {
+    pub fn new(max_velocity: f64, max_acceleration: f64) -> Self {
+        Self { max_velocity, max_acceleration, position: 0.0, velocity: 0.0 }
+    }
+}
+
+/// Fastest velocity toward `error` that can still stop in time at `accel`
+fn stoppable_velocity(error: f64, max_velocity: f64, accel: f64) -> f64 {
+    error.signum() * (2.0 * accel * error.abs()).sqrt().min(max_velocity)
+}
+
+impl GoalShaper for TrapezoidalGoal {
+    fn shape(&mut self, goal: f64, dt: f64) -> f64 {
+        let error = goal - self.position;
+        let target_velocity = stoppable_velocity(error, self.max_velocity, self.max_acceleration);
+        let max_dv = self.max_acceleration * dt;
+        self.velocity += (target_velocity - self.velocity).clamp(-max_dv, max_dv);
+
+        // Land exactly on the goal instead of stepping past it
+        if self.velocity * dt * error.signum() >= error.abs() {
+            self.position = goal;
+            self.velocity = 0.0;
+        } else {
+            self.position += self.velocity * dt;
+        }
+        self.position
+    }
+
+    fn reset(&mut self, value: f64) {
+        self.position = value;
+        self.velocity = 0.0;
+    }
+
+    fn box_clone(&self) -> Box<dyn GoalShaper> {
+        Box::new(*self)
+    }
+}
+
+// ============================================================================
+// S-Curve
+// ============================================================================
+
+/// Jerk-limited reference (S-curve velocity profile)
+///
+/// Built as a trapezoidal profile averaged over a window of `max_acceleration /
+/// max_jerk` seconds. Averaging a monotonic approach keeps it monotonic, so the
+/// reference never overshoots, and ramps acceleration at the jerk limit.
+#[derive(Debug, Clone)]
+pub struct SCurveGoal {
+    /// Maximum reference jerk
+    pub max_jerk: f64,
+    trapezoid: TrapezoidalGoal,
+    /// Recent (dt, trapezoid position) samples covering the averaging window
+    window: VecDeque<(f64, f64)>,
+    window_duration: f64,
+}
+
+impl SCurveGoal {
+    pub fn new(max_velocity: f64, max_acceleration: f64, max_jerk: f64) -> Self {
+        let mut shaper = Self {
+            max_jerk,
+            trapezoid: TrapezoidalGoal::new(max_velocity, max_acceleration),
+            window: VecDeque::new(),
+            window_duration: 0.0,
+        };
+        shaper.reset(0.0);
+        shaper
+    }
+
+    fn window_length(&self) -> f64 {
+        self.trapezoid.max_acceleration / self.max_jerk
+    }
+}
+
+impl GoalShaper for SCurveGoal {
+    fn shape(&mut self, goal: f64, dt: f64) -> f64 {
+        let position = self.trapezoid.shape(goal, dt);
+        let length = self.window_length();
+        if length.is_nan() || length <= 0.0 {
+            return position;
+        }
+
+        self.window.push_back((dt, position));
+        self.window_duration += dt;
+        while let Some(&(front_dt, _)) = self.window.front() {
+            if self.window_duration - front_dt < length {
+                break;
+            }
+            self.window_duration -= front_dt;
+            self.window.pop_front();
+        }
+
+        // Only the part of the oldest sample that falls inside the window counts
+        let overflow = self.window_duration - length;
+        self.window.iter().enumerate()
+            .map(|(i, &(w, p))| if i == 0 { (w - overflow) * p } else { w * p })
+            .sum::<f64>() / length
+    }
+
+    fn reset(&mut self, value: f64) {
+        self.trapezoid.reset(value);
+        self.window.clear();
+        let length = self.window_length();
+        if length > 0.0 {
+            self.window.push_back((length, value));
+        }
+        self.window_duration = length.max(0.0);
+    }
+
+    fn box_clone(&self) -> Box<dyn GoalShaper> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(shaper: &mut dyn GoalShaper, goal: f64, dt: f64, steps: usize) -> Vec<f64> {
+        (0..steps).map(|_| shaper.shape(goal, dt)).collect()
+    }
+
+    #[test]
+    fn test_direct_passes_through() {
+        let mut shaper = DirectGoal;
+        assert_eq!(shaper.shape(3.0, 0.01), 3.0);
+    }
+
+    #[test]
+    fn test_slew_rate_limits_change() {
+        let mut shaper = SlewRateGoal::new(2.0);
+        let trace = run(&mut shaper, 1.0, 0.01, 100);
+        assert!((trace[9] - 0.2).abs() < 1e-9);
+        assert!((trace[99] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trapezoidal_respects_limits_and_arrives() {
+        let mut shaper = TrapezoidalGoal::new(1.0, 2.0);
+        let dt = 0.001;
+        let trace = run(&mut shaper, 2.0, dt, 4000);
+
+        for pair in trace.windows(2) {
+            assert!((pair[1] - pair[0]).abs() / dt <= 1.0 + 1e-9);
+        }
+        assert!((trace.last().unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_s_curve_arrives_without_large_overshoot() {
+        let mut shaper = SCurveGoal::new(1.0, 2.0, 10.0);
+        let trace = run(&mut shaper, 2.0, 0.001, 6000);
+
+        let peak = trace.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        assert!(peak <= 2.0 + 1e-9, "overshoot to {}", peak);
+        assert!((trace.last().unwrap() - 2.0).abs() < 1e-9);
+
+        // Starts more gently than the trapezoid it is built from
+        let mut trapezoid = TrapezoidalGoal::new(1.0, 2.0);
+        let trap_trace = run(&mut trapezoid, 2.0, 0.001, 100);
+        assert!(trace[99] < trap_trace[99]);
+    }
+}
@@ -2,14 +2,45 @@
 //!
 //! This crate provides:
 //! - PIDF controllers for closed-loop control
+//! - kS/kV/kA motor feedforward
 //! - Commutation strategies (FOC, Trapezoidal, Sinusoidal)
+//! - Goal shapers (slew rate, trapezoidal, S-curve) for smoothing setpoints
 //! - Motor controllers with multiple control modes
+//! - Closed-loop flywheel shooter simulation
+//! - Full-match battery simulation over a drive command timeline
+//! - Swerve kinematics with speed/acceleration limits, chassis commands,
+//!   driver assists (heading hold) and a holonomic
+//!   path follower with configurable pose latency and acceleration
+//!   feedforward
+//! - Torque vectoring that splits a chassis force and yaw moment across
+//!   swerve modules within their traction limits
+//! - A reactive obstacle avoidance clamp on chassis velocity commands
+//! - Driver stick conditioning (deadband, expo, slew) into chassis speeds
+//! - A battery governor that slows chassis commands at low state of charge
+//! - A charge-station auto-balance that drives chassis pitch to level
+//! - Alliance flipping of field poses, speeds and trajectories
+//! - A pose estimator fusing swerve odometry with latent vision measurements
+//! - Arcade, curvature and tank command mixing for differential drives
+//! - A single swerve module test stand (drive, steer and tire)
 
 pub mod commutation;
+pub mod differential;
+pub mod feedforward;
+pub mod flywheel;
+pub mod goal_shaper;
+pub mod match_sim;
+pub mod module_sim;
 pub mod motor_controller;
 pub mod pidf;
 pub mod swerve_ctrl;
 
 pub use commutation::*;
+pub use differential::*;
+pub use feedforward::*;
+pub use flywheel::*;
+pub use goal_shaper::*;
+pub use match_sim::*;
+pub use module_sim::*;
 pub use motor_controller::*;
 pub use pidf::*;
+pub use swerve_ctrl::*;
@@ -0,0 +1,453 @@
+//! Full-Match Battery Simulation
+//!
+//! Runs a swerve robot through a timeline of drive commands covering a whole
+//! match (auto + teleop) while the battery discharges, so endgame plans can be
+//! checked against the voltage the battery can still deliver.
+//!
+//! The robot is the tire manager and swerve drivetrain the batch simulator
+//! steps, with the battery model closing the loop on supply voltage. Like the
+//! flywheel and mechanism simulators, the drive motors use the steady-state
+//! model (V = IR + Ke*ω, T = Kt*I) so millisecond steps over a whole match
+//! stay stable.
+//!
+//! Steering snaps to the commanded angle by default. With a steer motor
+//! configured, each module instead turns through its steer dynamics under a
+//! PD angle loop, and the steer motor current can be charged to the battery.
+
+use electrical::analysis::DEFAULT_BROWNOUT_VOLTAGE;
+use electrical::battery::{Battery, BatteryConstant};
+use electrical::motor::{MotorConstant, MotorGroup};
+use mechanics::tire::{TireConstants, TireManager};
+use mechanics::{SwerveDrivetrain, SwerveDrivetrainConfig};
+use std::f64::consts::PI;
+
+use simcore::{
+    checked_step_count, ActuatorInput, BatteryState, BodyState, ElectricalModel, MechanicsModel, Model,
+    MotorInput, MotorState, SensorBus, SimContext, SimState, StepBudgetError, TireState, TrueState, WheelState,
+    DEFAULT_MAX_STEPS,
+};
+
+/// Length of an FRC match: 15 s auto + 2:00 teleop (s)
+pub const MATCH_DURATION: f64 = 135.0;
+
+/// Interval between recorded battery samples (s), one robot loop
+const SAMPLE_PERIOD: f64 = 0.02;
+
+/// Default per-motor stator current limit (A), a typical drive motor setting
+pub const DEFAULT_DRIVE_CURRENT_LIMIT: f64 = 80.0;
+
+/// Default per-motor stator current limit for steer motors (A)
+pub const DEFAULT_STEER_CURRENT_LIMIT: f64 = 40.0;
+
+/// Steer motor turning each module's azimuth through a reduction
+#[derive(Debug, Clone, Copy)]
+pub struct SteerMotorConfig {
+    pub motor: MotorConstant,
+    /// Motor turns per module turn
+    pub gear_ratio: f64,
+    /// Per-motor stator current limit (A)
+    pub current_limit: f64,
+    /// Angle loop proportional gain (duty per rad)
+    pub kp: f64,
+    /// Angle loop derivative gain (duty per rad/s of module rate)
+    pub kd: f64,
+}
+
+impl SteerMotorConfig {
+    /// Steer motor with default current limit and angle loop gains
+    pub fn new(motor: MotorConstant, gear_ratio: f64) -> Self {
+        Self {
+            motor,
+            gear_ratio,
+            current_limit: DEFAULT_STEER_CURRENT_LIMIT,
+            kp: 1.0,
+            kd: 0.05,
+        }
+    }
+
+    /// Per-motor stator current limit (A)
+    pub fn with_current_limit(mut self, amps: f64) -> Self {
+        self.current_limit = amps.abs();
+        self
+    }
+
+    /// Angle loop gains (duty per rad, duty per rad/s)
+    pub fn with_gains(mut self, kp: f64, kd: f64) -> Self {
+        self.kp = kp;
+        self.kd = kd;
+        self
+    }
+
+    /// Torque at the module's steer axis at the current limit (N*m)
+    pub fn max_torque(&self) -> f64 {
+        self.motor.kt() * self.current_limit * self.gear_ratio
+    }
+}
+
+/// Drive command held for one timeline entry
+#[derive(Debug, Clone, Default)]
+pub struct DriveCommand {
+    /// Drive duty cycle per module; missing modules get 0
+    pub duty_cycles: Vec<f64>,
+    /// Steering angle per module (rad); missing modules get 0
+    pub steer_angles: Vec<f64>,
+}
+
+impl DriveCommand {
+    /// Same duty and steering angle on every module
+    pub fn uniform(num_modules: usize, duty: f64, steer_angle: f64) -> Self {
+        DriveCommand {
+            duty_cycles: vec![duty; num_modules],
+            steer_angles: vec![steer_angle; num_modules],
+        }
+    }
+}
+
+/// Battery condition at one point in the match
+#[derive(Debug, Clone, Copy)]
+pub struct MatchSample {
+    /// Match time (s)
+    pub time: f64,
+    /// Battery state of charge (0-1)
+    pub state_of_charge: f64,
+    /// Battery terminal voltage (V)
+    pub voltage: f64,
+    /// Current drawn from the battery (A)
+    pub current_draw: f64,
+}
+
+/// Battery trace and verdict for a simulated match
+#[derive(Debug, Clone)]
+pub struct MatchReport {
+    pub samples: Vec<MatchSample>,
+    /// Lowest terminal voltage seen at any step (V)
+    pub min_voltage: f64,
+    /// Highest battery current draw seen at any step (A)
+    pub peak_current_draw: f64,
+    /// State of charge at the end of the match (0-1)
+    pub final_state_of_charge: f64,
+    /// Voltage the battery had to stay above (V)
+    pub voltage_floor: f64,
+    /// Whether the voltage never dropped below the floor
+    pub passed: bool,
+}
+
+/// Swerve robot and battery stepped through a command timeline
+pub struct MatchSim {
+    drivetrain: SwerveDrivetrain,
+    tire_manager: TireManager,
+    /// Drive motor of each module, current limited
+    drive_motor: MotorGroup,
+    battery: Battery,
+    steer_motor: Option<SteerMotorConfig>,
+    include_steer_current: bool,
+    initial_soc: f64,
+    voltage_floor: f64,
+}
+
+impl MatchSim {
+    /// Create a match simulation with one `motor` per module
+    pub fn new(config: SwerveDrivetrainConfig, motor: MotorConstant, battery: BatteryConstant) -> Self {
+        let num_modules = config.module_positions.len();
+        let mut tire_manager = TireManager::new();
+        for _ in 0..num_modules {
+            tire_manager.add_tire(TireConstants::default());
+        }
+        Self {
+            drivetrain: SwerveDrivetrain::new(config),
+            tire_manager,
+            drive_motor: MotorGroup::uniform(motor, 1).with_current_limit(DEFAULT_DRIVE_CURRENT_LIMIT),
+            battery: Battery { constants: battery },
+            steer_motor: None,
+            include_steer_current: true,
+            initial_soc: 1.0,
+            voltage_floor: DEFAULT_BROWNOUT_VOLTAGE,
+        }
+    }
+
+    /// Start the match at a partial state of charge (0-1)
+    pub fn with_initial_soc(mut self, soc: f64) -> Self {
+        self.initial_soc = soc.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Per-motor stator current limit (A)
+    pub fn with_current_limit(mut self, amps: f64) -> Self {
+        self.drive_motor.current_limit = amps.abs();
+        self
+    }
+
+    /// Drive steering through steer motors instead of snapping to the command
+    pub fn with_steer_motor(mut self, steer_motor: SteerMotorConfig) -> Self {
+        self.steer_motor = Some(steer_motor);
+        self
+    }
+
+    /// Whether steer motor current counts towards the battery draw (default
+    /// true). Only has an effect with a steer motor configured.
+    pub fn with_steer_current_draw(mut self, include: bool) -> Self {
+        self.include_steer_current = include;
+        self
+    }
+
+    /// Voltage the battery must stay above for the match to pass (V)
+    pub fn with_voltage_floor(mut self, voltage: f64) -> Self {
+        self.voltage_floor = voltage;
+        self
+    }
+
+    fn initial_state(&self) -> SimState {
+        let num_modules = self.drivetrain.config.module_positions.len();
+        let tire_load = self.drivetrain.config.mass * 9.81 / num_modules as f64;
+        let wheel_states = (0..num_modules)
+            .map(|_| WheelState {
+                driving_angular_velocity: 0.0,
+                wheel_radius: 0.05,
+                turning_angular_velocity: 0.0,
+                longitudinal_translational_velocity: 0.0,
+                lateral_translational_velocity: 0.0,
+                tire: TireState {
+                    slip_angle: 0.0,
+                    slip_ratio: 0.0,
+                    longitudinal_force: 0.0,
+                    lateral_force: 0.0,
+                    tire_load,
+                },
+                angle: 0.0,
+                contact_fraction: 1.0,
+            })
+            .collect();
+
+        let initial_voltage = (self.battery.constants.open_circuit_voltage_function)(self.initial_soc);
+        SimState {
+            true_state: TrueState {
+                wheel_states,
+                body_state: BodyState::default(),
+                motors: vec![MotorState::default(); num_modules],
+                battery_state: BatteryState {
+                    state_of_charge: self.initial_soc,
+                    voltage: initial_voltage,
+                    ..BatteryState::default()
+                },
+            },
+            control_input: ActuatorInput {
+                motor_inputs: vec![MotorInput { duty_cycle_q: 0.0, duty_cycle_d: 0.0 }; num_modules],
+                // The drivetrain turns the modules only when a steer motor drives them
+                steer_torques: if self.steer_motor.is_some() { vec![0.0; num_modules] } else { Vec::new() },
+            },
+            sensor_bus: SensorBus::default(),
+        }
+    }
+
+    /// Run `duration` seconds of match from rest with step `dt`
+    ///
+    /// `timeline` holds `(start_time, end_time, command)` entries; the robot
+    /// idles outside them and the later entry wins where two overlap.
+    ///
+    /// Deterministic: the same timeline, duration and step give bit-identical
+    /// reports on every call and every machine.
+    ///
+    /// Fails without stepping if `dt` or `duration` is invalid or the run
+    /// would take more than `DEFAULT_MAX_STEPS` steps.
+    pub fn run(
+        &mut self,
+        timeline: &[(f64, f64, DriveCommand)],
+        duration: f64,
+        dt: f64,
+    ) -> Result<MatchReport, StepBudgetError> {
+        let n_steps = checked_step_count(duration, dt, DEFAULT_MAX_STEPS)?;
+        // Clear model state left by a previous run, e.g. chassis twist
+        self.drivetrain.reset();
+        self.tire_manager.reset();
+        let mut state = self.initial_state();
+        let idle = DriveCommand::default();
+        let mut samples = Vec::with_capacity((duration / SAMPLE_PERIOD).ceil().max(0.0) as usize + 1);
+        let mut min_voltage = state.true_state.battery_state.voltage;
+        let mut peak_current_draw: f64 = 0.0;
+        let mut next_sample = 0.0;
+
+        for step in 0..n_steps {
+            let time = step as f64 * dt;
+            let command = timeline
+                .iter()
+                .rev()
+                .find(|(start, end, _)| time >= *start && time < *end)
+                .map_or(&idle, |(_, _, command)| command);
+
+            let voltage = state.true_state.battery_state.voltage;
+            let mut total_current = 0.0;
+            for (i, wheel) in state.true_state.wheel_states.iter_mut().enumerate() {
+                let target = command.steer_angles.get(i).copied().unwrap_or(0.0);
+                let Some(steer) = self.steer_motor else {
+                    wheel.angle = target;
+                    continue;
+                };
+
+                let error = (target - wheel.angle + PI).rem_euclid(2.0 * PI) - PI;
+                let duty = (steer.kp * error - steer.kd * wheel.turning_angular_velocity).clamp(-1.0, 1.0);
+                let velocity = wheel.turning_angular_velocity * steer.gear_ratio;
+                let current = ((duty * voltage - steer.motor.ke() * velocity) / steer.motor.resistance)
+                    .clamp(-steer.current_limit, steer.current_limit);
+                // The drivetrain turns the module under this torque as it steps
+                state.control_input.steer_torques[i] = steer.motor.kt() * current * steer.gear_ratio;
+                if self.include_steer_current {
+                    total_current += duty * current;
+                }
+            }
+            for i in 0..state.true_state.motors.len() {
+                let duty = command.duty_cycles.get(i).copied().unwrap_or(0.0).clamp(-1.0, 1.0);
+                // Direct drive: each motor turns with its wheel
+                let direction = self.drivetrain.config.drive_direction(i);
+                let velocity = state.true_state.wheel_states[i].driving_angular_velocity * direction;
+                let output = self.drive_motor.steady_state(duty, voltage, velocity);
+
+                let motor = &mut state.true_state.motors[i];
+                motor.mechanical_velocity = velocity;
+                motor.current_q = output.current;
+                motor.applied_torque = output.torque;
+                state.control_input.motor_inputs[i].duty_cycle_q = duty;
+                total_current += output.supply_current;
+            }
+            state.true_state.battery_state.total_current_draw = total_current;
+
+            let ctx = SimContext { dt, t: time };
+            self.battery.step_electrical(ctx, &mut state);
+            self.tire_manager.step_physics(ctx, &mut state);
+            self.drivetrain.step_physics(ctx, &mut state);
+            let time = (step + 1) as f64 * dt;
+
+            let battery_state = state.true_state.battery_state;
+            min_voltage = min_voltage.min(battery_state.voltage);
+            peak_current_draw = peak_current_draw.max(battery_state.total_current_draw);
+            if time >= next_sample {
+                samples.push(MatchSample {
+                    time,
+                    state_of_charge: battery_state.state_of_charge,
+                    voltage: battery_state.voltage,
+                    current_draw: battery_state.total_current_draw,
+                });
+                next_sample += SAMPLE_PERIOD;
+            }
+        }
+
+        Ok(MatchReport {
+            samples,
+            min_voltage,
+            peak_current_draw,
+            final_state_of_charge: state.true_state.battery_state.state_of_charge,
+            voltage_floor: self.voltage_floor,
+            passed: min_voltage >= self.voltage_floor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shuttle back and forth at `duty`, reversing every 2 seconds
+    fn shuttle_timeline(duty: f64, duration: f64) -> Vec<(f64, f64, DriveCommand)> {
+        (0..(duration / 2.0) as usize)
+            .map(|i| {
+                let direction = if i % 2 == 0 { 1.0 } else { -1.0 };
+                let start = i as f64 * 2.0;
+                (start, start + 2.0, DriveCommand::uniform(4, direction * duty, 0.0))
+            })
+            .collect()
+    }
+
+    fn run_match(duty: f64) -> MatchReport {
+        let mut sim = MatchSim::new(
+            SwerveDrivetrainConfig::default(),
+            MotorConstant::kraken_x60(),
+            BatteryConstant::default(),
+        );
+        let duration = 10.0;
+        sim.run(&shuttle_timeline(duty, duration), duration, 0.001).unwrap()
+    }
+
+    /// Idle robot that swings every module a quarter turn at t = 0.5 s
+    fn run_steer_flip(include_steer_current: bool) -> MatchReport {
+        let mut sim = MatchSim::new(
+            SwerveDrivetrainConfig::default(),
+            MotorConstant::kraken_x60(),
+            BatteryConstant::default(),
+        )
+        .with_steer_motor(SteerMotorConfig::new(MotorConstant::neo(), 12.8))
+        .with_steer_current_draw(include_steer_current);
+        let timeline = vec![(0.5, 2.0, DriveCommand::uniform(4, 0.0, PI / 2.0))];
+        sim.run(&timeline, 2.0, 0.001).unwrap()
+    }
+
+    #[test]
+    fn test_steer_flip_spikes_battery_current() {
+        let counted = run_steer_flip(true);
+        let ignored = run_steer_flip(false);
+
+        // No drive current while idle: any draw comes from the steer motors
+        assert!(ignored.peak_current_draw < 1e-9);
+        assert!(counted.peak_current_draw > 50.0, "steer flip peaked at only {} A", counted.peak_current_draw);
+        assert!(counted.min_voltage < ignored.min_voltage);
+
+        // The spike is transient: settled modules draw almost nothing
+        let before = counted.samples.iter().filter(|s| s.time < 0.5).map(|s| s.current_draw.abs()).fold(0.0, f64::max);
+        let settled = counted.samples.last().unwrap().current_draw.abs();
+        assert!(before < 1e-9);
+        assert!(settled < 1.0, "steer motors still drawing {} A", settled);
+    }
+
+    #[test]
+    fn test_repeated_runs_are_bit_identical() {
+        let build = || {
+            MatchSim::new(SwerveDrivetrainConfig::default(), MotorConstant::kraken_x60(), BatteryConstant::default())
+                .with_steer_motor(SteerMotorConfig::new(MotorConstant::neo(), 12.8))
+        };
+        let mut timeline = shuttle_timeline(0.8, 3.0);
+        timeline.push((1.0, 1.5, DriveCommand::uniform(4, 0.5, PI / 3.0)));
+        let bits = |report: &MatchReport| -> Vec<[u64; 4]> {
+            report.samples.iter()
+                .map(|s| [s.time, s.state_of_charge, s.voltage, s.current_draw].map(f64::to_bits))
+                .collect()
+        };
+
+        // The same simulator run again, and a fresh one, reproduce every bit
+        let mut sim = build();
+        let first = sim.run(&timeline, 3.0, 0.001).unwrap();
+        let again = sim.run(&timeline, 3.0, 0.001).unwrap();
+        let fresh = build().run(&timeline, 3.0, 0.001).unwrap();
+        assert!(!first.samples.is_empty());
+        assert_eq!(bits(&first), bits(&again));
+        assert_eq!(bits(&first), bits(&fresh));
+        assert_eq!(first.min_voltage.to_bits(), again.min_voltage.to_bits());
+    }
+
+    #[test]
+    fn test_invalid_step_is_rejected_before_running() {
+        let mut sim = MatchSim::new(
+            SwerveDrivetrainConfig::default(),
+            MotorConstant::kraken_x60(),
+            BatteryConstant::default(),
+        );
+        let timeline = shuttle_timeline(0.5, 4.0);
+
+        // Each of these would otherwise step forever
+        assert_eq!(sim.run(&timeline, 4.0, 0.0).unwrap_err(), StepBudgetError::InvalidTimestep(0.0));
+        assert!(matches!(sim.run(&timeline, 4.0, -0.001), Err(StepBudgetError::InvalidTimestep(_))));
+        assert!(matches!(sim.run(&timeline, 4.0, f64::NAN), Err(StepBudgetError::InvalidTimestep(_))));
+        assert!(matches!(sim.run(&timeline, f64::INFINITY, 0.001), Err(StepBudgetError::InvalidDuration(_))));
+        assert!(matches!(sim.run(&timeline, MATCH_DURATION, 1e-9), Err(StepBudgetError::TooManySteps { .. })));
+    }
+
+    #[test]
+    fn test_aggressive_driving_drains_battery_faster() {
+        let conservative = run_match(0.3);
+        let aggressive = run_match(1.0);
+
+        assert!(conservative.final_state_of_charge < 1.0);
+        assert!(aggressive.final_state_of_charge < conservative.final_state_of_charge,
+            "aggressive {} vs conservative {}", aggressive.final_state_of_charge, conservative.final_state_of_charge);
+        assert!(aggressive.min_voltage < conservative.min_voltage);
+        assert!(conservative.passed);
+        assert!((aggressive.samples.last().unwrap().time - 10.0).abs() < 0.01);
+    }
+}
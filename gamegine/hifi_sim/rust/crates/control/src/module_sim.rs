@@ -0,0 +1,456 @@
+//! Single Swerve Module Simulation
+//!
+//! A test stand for tuning one module in isolation: a drive motor spins the
+//! wheel through its reduction, a steer motor turns the azimuth under the same
+//! PD angle loop the match simulator uses, and the tire pushes a share of the
+//! robot's mass across the floor. Like the other batch simulators, both motors
+//! use the steady-state model (V = IR + Ke*ω, T = Kt*I) from a fixed supply.
+
+use electrical::motor::{MotorConstant, MotorGroup};
+use mechanics::gearbox::{Gear, TwoSpeedGearbox};
+use mechanics::tire::{TireConstants, TireManager};
+use simcore::{MechanicsModel, SimContext, SimState, TireState, WheelState};
+use std::f64::consts::PI;
+
+use crate::match_sim::SteerMotorConfig;
+
+/// Snapshot of a swerve module's state
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModuleSample {
+    pub time: f64,
+    /// Wheel angular velocity (rad/s)
+    pub wheel_speed: f64,
+    /// Module azimuth (rad), wrapped to [-pi, pi)
+    pub steer_angle: f64,
+    /// Absolute encoder reading of the azimuth, offset by the encoder's
+    /// zero (rad), wrapped to [-pi, pi)
+    pub encoder_angle: f64,
+    /// Azimuth rate (rad/s)
+    pub steer_rate: f64,
+    /// Tire longitudinal force, braking convention (N)
+    pub longitudinal_force: f64,
+    /// Tire lateral force (N)
+    pub lateral_force: f64,
+    /// Speed of the carried mass over the floor (m/s)
+    pub ground_speed: f64,
+    /// Drive motor phase current (A)
+    pub drive_current: f64,
+    /// Steer motor phase current (A)
+    pub steer_current: f64,
+    /// Drive speed the duty commands: the wheel surface speed at which the
+    /// unloaded drive motor settles (m/s)
+    pub commanded_speed: f64,
+    /// Wheel surface speed, angular velocity times radius (m/s)
+    pub wheel_surface_speed: f64,
+    /// Commanded speed less the module's ground speed along its heading
+    /// (m/s). Large while the module slips (wheel surface speed well above
+    /// the ground speed) or cannot deliver the command (both below it).
+    ///
+    /// Measured against ground speed rather than wheel surface speed: a
+    /// slipping wheel spins up to its commanded speed, so commanded less
+    /// surface speed would read near zero exactly when the module is not
+    /// delivering. That difference is still available from
+    /// `commanded_speed - wheel_surface_speed`. Only this single-module
+    /// stand reports tracking error, in every sample; the full drivetrain
+    /// simulator's drive motors carry no back-EMF from their wheels unless
+    /// a gearbox couples them, so a duty there has no settled speed to
+    /// track.
+    pub tracking_error: f64,
+}
+
+/// Wrap an angle to [-pi, pi)
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// One swerve module carrying a share of the robot's mass
+pub struct SwerveModuleSim {
+    drive_motor: MotorGroup,
+    /// Motor turns per wheel turn in the active gear
+    pub drive_gear_ratio: f64,
+    /// Two-speed gearbox, if fitted
+    gearbox: Option<TwoSpeedGearbox>,
+    gear: Gear,
+    pub steer: SteerMotorConfig,
+    /// Absolute encoder reading with the wheel pointing forward (rad)
+    pub angle_offset: f64,
+    tire_manager: TireManager,
+    /// Wheel rotational inertia (kg*m^2)
+    pub wheel_inertia: f64,
+    /// Azimuth rotational inertia (kg*m^2)
+    pub steer_inertia: f64,
+    /// Robot mass riding on this module (kg)
+    pub carried_mass: f64,
+    /// Supply voltage for both motors (V)
+    pub voltage: f64,
+    wheel_radius: f64,
+    state: SimState,
+    /// Field-frame velocity of the carried mass (m/s)
+    ground_velocity: [f64; 2],
+    drive_duty: f64,
+    steer_target: f64,
+    drive_current: f64,
+    steer_current: f64,
+    time: f64,
+}
+
+impl SwerveModuleSim {
+    /// Module with a quarter of a 50 kg robot on a 4 inch wheel at 12 V
+    pub fn new(drive_motor: MotorConstant, drive_gear_ratio: f64, steer: SteerMotorConfig, tire: TireConstants) -> Self {
+        let mut tire_manager = TireManager::new();
+        tire_manager.add_tire(tire);
+        let mut sim = SwerveModuleSim {
+            drive_motor: MotorGroup::uniform(drive_motor, 1),
+            drive_gear_ratio,
+            gearbox: None,
+            gear: Gear::Low,
+            steer,
+            angle_offset: 0.0,
+            tire_manager,
+            wheel_inertia: 0.01,
+            steer_inertia: 0.005,
+            carried_mass: 12.5,
+            voltage: 12.0,
+            wheel_radius: 0.0508,
+            state: SimState::default(),
+            ground_velocity: [0.0; 2],
+            drive_duty: 0.0,
+            steer_target: 0.0,
+            drive_current: 0.0,
+            steer_current: 0.0,
+            time: 0.0,
+        };
+        sim.reset();
+        sim
+    }
+
+    /// Wheel radius (m)
+    pub fn with_wheel_radius(mut self, radius: f64) -> Self {
+        self.wheel_radius = radius;
+        self.reset();
+        self
+    }
+
+    /// Robot mass riding on this module (kg)
+    pub fn with_carried_mass(mut self, mass: f64) -> Self {
+        self.carried_mass = mass;
+        self.reset();
+        self
+    }
+
+    /// Supply voltage for both motors (V)
+    pub fn with_voltage(mut self, voltage: f64) -> Self {
+        self.voltage = voltage;
+        self
+    }
+
+    /// Absolute encoder zero: the reading with the wheel pointing forward (rad)
+    pub fn with_angle_offset(mut self, offset: f64) -> Self {
+        self.angle_offset = offset;
+        self
+    }
+
+    /// Fit a two-speed gearbox, starting in low gear
+    pub fn with_two_speed_gearbox(mut self, gearbox: TwoSpeedGearbox) -> Self {
+        self.gearbox = Some(gearbox);
+        self.gear = Gear::Low;
+        self.drive_gear_ratio = gearbox.ratio(Gear::Low);
+        self
+    }
+
+    /// Shift the drive gearbox into `gear`
+    ///
+    /// The wheel keeps its speed through the shift; the motor, being geared
+    /// to it, jumps to the new ratio's speed. Ignored without a two-speed
+    /// gearbox.
+    pub fn shift(&mut self, gear: Gear) {
+        if let Some(gearbox) = self.gearbox {
+            self.gear = gear;
+            self.drive_gear_ratio = gearbox.ratio(gear);
+        }
+    }
+
+    /// Active gear (always `Gear::Low` without a two-speed gearbox)
+    pub fn gear(&self) -> Gear {
+        self.gear
+    }
+
+    /// Drive motor shaft speed (rad/s)
+    pub fn drive_motor_speed(&self) -> f64 {
+        self.wheel().driving_angular_velocity * self.drive_gear_ratio
+    }
+
+    /// Largest wheel torque the drive can apply at the current speed, at
+    /// full duty in the active gear (N*m)
+    pub fn available_wheel_torque(&self) -> f64 {
+        self.drive_motor.steady_state(1.0, self.voltage, self.drive_motor_speed()).torque * self.drive_gear_ratio
+    }
+
+    /// Drive motor duty cycle (-1 to 1)
+    pub fn set_drive_duty(&mut self, duty: f64) {
+        self.drive_duty = duty.clamp(-1.0, 1.0);
+    }
+
+    /// Encoder angle the steer loop turns the module to (rad)
+    ///
+    /// Like a real module, the loop closes on the raw encoder reading, so
+    /// the wheel settles at `angle - angle_offset`.
+    pub fn set_steer_target(&mut self, angle: f64) {
+        self.steer_target = angle;
+    }
+
+    /// Encoder angle to command for the wheel to point at `wheel_angle` (rad)
+    pub fn encoder_target(&self, wheel_angle: f64) -> f64 {
+        wrap_angle(wheel_angle + self.angle_offset)
+    }
+
+    /// Lift the wheel off the floor so it spins freely, or set it back down
+    pub fn set_lifted(&mut self, lifted: bool) {
+        self.wheel_mut().contact_fraction = if lifted { 0.0 } else { 1.0 };
+    }
+
+    fn wheel(&self) -> &WheelState {
+        &self.state.true_state.wheel_states[0]
+    }
+
+    fn wheel_mut(&mut self) -> &mut WheelState {
+        &mut self.state.true_state.wheel_states[0]
+    }
+
+    /// Wheel surface speed (m/s) the drive duty settles at with no load
+    fn commanded_speed(&self) -> f64 {
+        // duty_for_torque is linear in velocity: this is the duty per rad/s
+        // of unloaded motor speed
+        let duty_per_velocity = self.drive_motor.duty_for_torque(0.0, 1.0, self.voltage);
+        if duty_per_velocity <= 0.0 || self.drive_gear_ratio == 0.0 {
+            return 0.0;
+        }
+        self.drive_duty / duty_per_velocity / self.drive_gear_ratio * self.wheel_radius
+    }
+
+    /// Current state of the module
+    pub fn sample(&self) -> ModuleSample {
+        let wheel = self.wheel();
+        let (sin, cos) = wheel.angle.sin_cos();
+        let rolling_speed = self.ground_velocity[0] * cos + self.ground_velocity[1] * sin;
+        let commanded_speed = self.commanded_speed();
+        ModuleSample {
+            time: self.time,
+            wheel_speed: wheel.driving_angular_velocity,
+            steer_angle: wheel.angle,
+            encoder_angle: wrap_angle(wheel.angle + self.angle_offset),
+            steer_rate: wheel.turning_angular_velocity,
+            longitudinal_force: wheel.tire.longitudinal_force,
+            lateral_force: wheel.tire.lateral_force,
+            ground_speed: self.ground_velocity[0].hypot(self.ground_velocity[1]),
+            drive_current: self.drive_current,
+            steer_current: self.steer_current,
+            commanded_speed,
+            wheel_surface_speed: wheel.driving_angular_velocity * wheel.wheel_radius,
+            tracking_error: commanded_speed - rolling_speed,
+        }
+    }
+
+    /// Advance the module by `dt` seconds
+    pub fn step(&mut self, dt: f64) {
+        let steer = self.steer;
+        let voltage = self.voltage;
+
+        // Steer: PD loop on the encoder angle into the steer motor, turning
+        // the azimuth inertia
+        let wheel = &mut self.state.true_state.wheel_states[0];
+        let error = wrap_angle(self.steer_target - (wheel.angle + self.angle_offset));
+        let steer_duty = (steer.kp * error - steer.kd * wheel.turning_angular_velocity).clamp(-1.0, 1.0);
+        let steer_velocity = wheel.turning_angular_velocity * steer.gear_ratio;
+        self.steer_current = ((steer_duty * voltage - steer.motor.ke() * steer_velocity) / steer.motor.resistance)
+            .clamp(-steer.current_limit, steer.current_limit);
+        let steer_torque = steer.motor.kt() * self.steer_current * steer.gear_ratio;
+        wheel.turning_angular_velocity += steer_torque / self.steer_inertia * dt;
+        wheel.angle = wrap_angle(wheel.angle + wheel.turning_angular_velocity * dt);
+
+        // Floor velocity seen by the wheel, in the module frame
+        let (sin, cos) = wheel.angle.sin_cos();
+        let [vx, vy] = self.ground_velocity;
+        wheel.longitudinal_translational_velocity = vx * cos + vy * sin;
+        wheel.lateral_translational_velocity = -vx * sin + vy * cos;
+
+        let ctx = SimContext { dt, t: self.time };
+        self.tire_manager.step_physics(ctx, &mut self.state);
+
+        // Drive: motor torque against the tire's braking-convention reaction
+        let wheel = &mut self.state.true_state.wheel_states[0];
+        let motor_velocity = wheel.driving_angular_velocity * self.drive_gear_ratio;
+        let output = self.drive_motor.steady_state(self.drive_duty, voltage, motor_velocity);
+        self.drive_current = output.current;
+        let wheel_torque = output.torque * self.drive_gear_ratio + wheel.tire.longitudinal_force * wheel.wheel_radius;
+        wheel.driving_angular_velocity += wheel_torque / self.wheel_inertia * dt;
+
+        // The floor pushes the carried mass opposite the braking-convention force
+        if self.carried_mass > 0.0 {
+            let (fx, fy) = (-wheel.tire.longitudinal_force, wheel.tire.lateral_force);
+            self.ground_velocity[0] += (fx * cos - fy * sin) / self.carried_mass * dt;
+            self.ground_velocity[1] += (fx * sin + fy * cos) / self.carried_mass * dt;
+        }
+        self.time += dt;
+    }
+
+    /// Step for `duration` seconds, sampling after every step
+    pub fn run(&mut self, duration: f64, dt: f64) -> Vec<ModuleSample> {
+        let steps = (duration / dt).round().max(0.0) as usize;
+        (0..steps)
+            .map(|_| {
+                self.step(dt);
+                self.sample()
+            })
+            .collect()
+    }
+
+    /// Put the module back at rest, pointing forward, on the floor
+    pub fn reset(&mut self) {
+        self.state.true_state.wheel_states = vec![WheelState {
+            driving_angular_velocity: 0.0,
+            wheel_radius: self.wheel_radius,
+            turning_angular_velocity: 0.0,
+            longitudinal_translational_velocity: 0.0,
+            lateral_translational_velocity: 0.0,
+            tire: TireState {
+                slip_angle: 0.0,
+                slip_ratio: 0.0,
+                longitudinal_force: 0.0,
+                lateral_force: 0.0,
+                tire_load: self.carried_mass * 9.81,
+            },
+            angle: 0.0,
+            contact_fraction: 1.0,
+        }];
+        self.ground_velocity = [0.0; 2];
+        self.drive_duty = 0.0;
+        self.steer_target = 0.0;
+        self.drive_current = 0.0;
+        self.steer_current = 0.0;
+        self.time = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module() -> SwerveModuleSim {
+        SwerveModuleSim::new(
+            MotorConstant::kraken_x60(),
+            6.75,
+            SteerMotorConfig::new(MotorConstant::neo(), 12.8),
+            TireConstants::new(1.2, 1.2, 2000.0, 2000.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn test_steer_command_turns_module_to_target() {
+        let mut sim = module();
+        sim.set_steer_target(1.2);
+        sim.run(0.5, 1e-4);
+
+        let sample = sim.sample();
+        assert!((sample.steer_angle - 1.2).abs() < 0.02, "settled at {}", sample.steer_angle);
+        assert!(sample.steer_rate.abs() < 0.5);
+        assert_eq!(sample.wheel_speed, 0.0);
+    }
+
+    #[test]
+    fn test_angle_offset_shifts_raw_steer_command() {
+        let offset = 0.4;
+        let mut sim = module().with_angle_offset(offset);
+
+        // Commanding the desired direction directly lands the wheel off by the offset
+        sim.set_steer_target(1.0);
+        sim.run(0.5, 1e-4);
+        let sample = sim.sample();
+        assert!((sample.steer_angle - (1.0 - offset)).abs() < 0.02, "settled at {}", sample.steer_angle);
+        assert!((sample.encoder_angle - 1.0).abs() < 0.02);
+
+        // The calibrated raw command points the wheel where intended
+        let target = sim.encoder_target(1.0);
+        assert!((target - (1.0 + offset)).abs() < 1e-12);
+        sim.set_steer_target(target);
+        sim.run(0.5, 1e-4);
+        let sample = sim.sample();
+        assert!((sample.steer_angle - 1.0).abs() < 0.02, "settled at {}", sample.steer_angle);
+        assert!((sample.encoder_angle - target).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_drive_duty_spins_wheel_against_tire_reaction() {
+        let spin_up = |lifted: bool| {
+            let mut sim = module();
+            sim.set_lifted(lifted);
+            sim.set_drive_duty(0.5);
+            sim.run(0.05, 1e-4);
+            sim.sample()
+        };
+        let on_floor = spin_up(false);
+        let lifted = spin_up(true);
+
+        // On the floor the tire pushes back on the wheel and forward on the mass
+        assert!(on_floor.wheel_speed > 0.0);
+        assert!(on_floor.longitudinal_force < 0.0);
+        assert!(on_floor.ground_speed > 0.0);
+        assert!(on_floor.wheel_speed < lifted.wheel_speed,
+            "loaded {} should lag lifted {}", on_floor.wheel_speed, lifted.wheel_speed);
+        assert_eq!(lifted.longitudinal_force, 0.0);
+        assert_eq!(lifted.ground_speed, 0.0);
+    }
+
+    #[test]
+    fn test_upshift_keeps_wheel_speed_and_drops_motor_speed() {
+        let mut sim = module().with_two_speed_gearbox(TwoSpeedGearbox::new(10.0, 5.0).unwrap());
+        assert_eq!(sim.gear(), Gear::Low);
+        sim.set_drive_duty(0.5);
+        sim.run(0.2, 1e-4);
+
+        let wheel_speed = sim.sample().wheel_speed;
+        let low_motor_speed = sim.drive_motor_speed();
+        let low_torque = sim.available_wheel_torque();
+        assert!((low_motor_speed - 10.0 * wheel_speed).abs() < 1e-9);
+
+        sim.shift(Gear::High);
+        assert_eq!(sim.gear(), Gear::High);
+        assert_eq!(sim.sample().wheel_speed, wheel_speed);
+        assert!((sim.drive_motor_speed() - 0.5 * low_motor_speed).abs() < 1e-9);
+        // Half the reduction: less torque multiplication, but less back-EMF
+        let high_torque = sim.available_wheel_torque();
+        assert!(high_torque > 0.0 && (high_torque - low_torque).abs() > 1.0, "{} vs {}", high_torque, low_torque);
+
+        // A single-speed module ignores shifts
+        let mut fixed = module();
+        fixed.shift(Gear::High);
+        assert_eq!(fixed.drive_gear_ratio, 6.75);
+    }
+
+    #[test]
+    fn test_tracking_error_reveals_slip_on_low_grip() {
+        let drive = |duty: f64, mu: f64, duration: f64| {
+            let mut sim = SwerveModuleSim::new(
+                MotorConstant::kraken_x60(),
+                6.75,
+                SteerMotorConfig::new(MotorConstant::neo(), 12.8),
+                TireConstants::new(mu, mu, 2000.0, 2000.0, 0.0, 0.0),
+            );
+            sim.set_drive_duty(duty);
+            sim.run(duration, 1e-4)
+        };
+
+        // Full duty on a slick floor: the wheel races ahead of the ground
+        let slipping = drive(1.0, 0.1, 0.3);
+        let last = slipping.last().unwrap();
+        assert!(last.commanded_speed > 4.0);
+        assert!(last.tracking_error > 0.5 * last.commanded_speed, "error {}", last.tracking_error);
+        assert!(last.wheel_surface_speed > last.commanded_speed - last.tracking_error + 1.0);
+
+        // A gentle command on a grippy floor is delivered almost exactly once settled
+        let gentle = drive(0.05, 1.2, 1.5);
+        let last = gentle.last().unwrap();
+        assert!(last.commanded_speed > 0.1);
+        assert!(last.tracking_error.abs() < 0.02 * last.commanded_speed, "error {}", last.tracking_error);
+    }
+}
@@ -4,9 +4,11 @@
 //! (duty cycle, current, velocity, position) with different commutation strategies.
 
 use electrical::motor::MotorConstant;
-use simcore::{ControlModel, Model, MotorInput, MotorState, SimContext, SimState};
+use simcore::{ControlModel, Model, MotorInput, MotorState, NoiseRng, SimContext, SimState};
 
 use crate::commutation::{CommutationStrategy, FocCommutation};
+use crate::feedforward::SimpleMotorFeedforward;
+use crate::goal_shaper::{DirectGoal, GoalShaper};
 use crate::pidf::{PidfConfig, PidfController};
 
 /// Control mode for the motor controller
@@ -23,6 +25,18 @@ pub enum ControlMode {
     Position,
 }
 
+/// Noise and filtering on the current measurement fed to the current loop
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurrentSenseConfig {
+    /// Standard deviation of the Gaussian noise on each sample (A)
+    pub noise_std: f64,
+    /// Time constant of the first-order low-pass filter (s); zero passes
+    /// the noisy samples through unfiltered
+    pub filter_time_constant: f64,
+    /// Seed of the noise stream
+    pub seed: u64,
+}
+
 /// Configuration for a motor controller
 #[derive(Debug, Clone)]
 pub struct MotorControllerConfig {
@@ -40,6 +54,18 @@ pub struct MotorControllerConfig {
     pub max_current: f64,
     /// Maximum motor velocity for velocity/position control output limiting (rad/s)
     pub max_velocity: f64,
+    /// Feedforward added to the velocity loop output in velocity and
+    /// position modes (None = feedback only)
+    pub feedforward: Option<SimpleMotorFeedforward>,
+    /// Soft position limits (min, max) in radians (None = unlimited)
+    pub soft_limits: Option<(f64, f64)>,
+    /// Noisy, filtered current measurement for current mode (None = the
+    /// loop sees the true current)
+    pub current_sense: Option<CurrentSenseConfig>,
+    /// Nominal bus voltage (V) for voltage-limited velocity control: the
+    /// velocity reference is capped at the free speed the bus can reach and
+    /// the loop holds its integral while pinned at full duty (None = unlimited)
+    pub voltage_limit: Option<f64>,
 }
 
 impl MotorControllerConfig {
@@ -53,6 +79,10 @@ impl MotorControllerConfig {
             position_config: PidfConfig::p(5.0).with_limits(-100.0, 100.0),
             max_current: 60.0,
             max_velocity: 600.0, // ~6000 RPM
+            feedforward: None,
+            soft_limits: None,
+            current_sense: None,
+            voltage_limit: None,
         }
     }
 
@@ -85,21 +115,62 @@ impl MotorControllerConfig {
         self.max_current = max_current;
         self
     }
+
+    /// Add a kS/kV/kA feedforward on the velocity reference
+    pub fn with_feedforward(mut self, feedforward: SimpleMotorFeedforward) -> Self {
+        self.feedforward = Some(feedforward);
+        self
+    }
+
+    /// Set soft position limits (rad); the bounds may be given in either order
+    pub fn with_soft_limits(mut self, min: f64, max: f64) -> Self {
+        self.soft_limits = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// Feed the current loop a measurement with Gaussian noise of `noise_std`
+    /// (A), low-pass filtered with `filter_time_constant` (s)
+    pub fn with_current_sense(mut self, noise_std: f64, filter_time_constant: f64, seed: u64) -> Self {
+        self.current_sense = Some(CurrentSenseConfig {
+            noise_std: noise_std.max(0.0),
+            filter_time_constant: filter_time_constant.max(0.0),
+            seed,
+        });
+        self
+    }
+
+    /// Limit velocity control to what `bus_voltage` (V) can drive against
+    /// back-EMF
+    pub fn with_voltage_limit(mut self, bus_voltage: f64) -> Self {
+        self.voltage_limit = Some(bus_voltage.max(0.0));
+        self
+    }
 }
 
 /// Motor controller with state
 pub struct MotorController {
     config: MotorControllerConfig,
     commutation: Box<dyn CommutationStrategy>,
+    goal_shaper: Box<dyn GoalShaper>,
     current_controller: PidfController,
     velocity_controller: PidfController,
     position_controller: PidfController,
     /// Current setpoint (units depend on control mode)
     setpoint: f64,
+    /// Setpoint after goal shaping, as tracked by the control loops
+    shaped_setpoint: f64,
     /// Accumulated position estimate (for position control)
     position_estimate: f64,
     /// Whether position was set externally this frame (skip auto-integration)
     position_externally_set: bool,
+    /// Velocity reference from the previous update, for feedforward acceleration
+    prev_velocity_reference: Option<f64>,
+    /// Noise stream of the current sense
+    current_noise: NoiseRng,
+    /// Current measurement last fed to the current loop (None before the first)
+    current_feedback: Option<f64>,
+    /// Bus voltage the velocity loop is limited by (V), when voltage limited
+    bus_voltage: Option<f64>,
     /// Torque constant derived from motor constants: kt = 1.5 * pole_pairs * flux_linkage
     kt: f64,
 }
@@ -116,6 +187,8 @@ impl MotorController {
         commutation: Box<dyn CommutationStrategy>,
     ) -> Self {
         let kt = 1.5 * (config.motor_constants.pole_pairs as f64) * config.motor_constants.flux_linkage;
+        let current_noise = NoiseRng::new(config.current_sense.map_or(0, |sense| sense.seed));
+        let bus_voltage = config.voltage_limit;
         
         Self {
             current_controller: PidfController::new(config.current_config.clone()),
@@ -123,13 +196,36 @@ impl MotorController {
             position_controller: PidfController::new(config.position_config.clone()),
             config,
             commutation,
+            goal_shaper: Box::new(DirectGoal),
             setpoint: 0.0,
+            shaped_setpoint: 0.0,
             position_estimate: 0.0,
             position_externally_set: false,
+            prev_velocity_reference: None,
+            current_noise,
+            current_feedback: None,
+            bus_voltage,
             kt,
         }
     }
 
+    /// Use a goal shaper to smooth setpoint changes (builder pattern)
+    pub fn with_goal_shaper(mut self, shaper: Box<dyn GoalShaper>) -> Self {
+        self.set_goal_shaper(shaper);
+        self
+    }
+
+    /// Swap the goal shaper, starting it from the current shaped setpoint
+    pub fn set_goal_shaper(&mut self, mut shaper: Box<dyn GoalShaper>) {
+        shaper.reset(self.shaped_setpoint);
+        self.goal_shaper = shaper;
+    }
+
+    /// Get the setpoint after goal shaping
+    pub fn shaped_setpoint(&self) -> f64 {
+        self.shaped_setpoint
+    }
+
     /// Set the setpoint (units depend on control mode)
     /// - DutyCycle: duty cycle (-1 to 1)
     /// - Current: amps
@@ -160,6 +256,39 @@ impl MotorController {
         self.position_estimate
     }
 
+    /// Current measurement last fed to the current loop (A)
+    pub fn current_feedback(&self) -> Option<f64> {
+        self.current_feedback
+    }
+
+    /// Measure `current` (A) through the configured current sense
+    fn sense_current(&mut self, current: f64, dt: f64) -> f64 {
+        let Some(sense) = self.config.current_sense else {
+            return current;
+        };
+        let sample = current + sense.noise_std * self.current_noise.gaussian();
+        let tau = sense.filter_time_constant;
+        let alpha = if tau > 0.0 { dt / (tau + dt) } else { 1.0 };
+        match self.current_feedback {
+            Some(previous) => previous + alpha * (sample - previous),
+            None => sample,
+        }
+    }
+
+    /// Update the bus voltage a voltage-limited velocity loop works against
+    /// (V); ignored unless the config sets a voltage limit
+    pub fn set_bus_voltage(&mut self, voltage: f64) {
+        if self.bus_voltage.is_some() {
+            self.bus_voltage = Some(voltage.max(0.0));
+        }
+    }
+
+    /// Fastest motor velocity the bus can reach (rad/s), the free speed at
+    /// the bus voltage; None unless voltage limited
+    pub fn achievable_velocity(&self) -> Option<f64> {
+        self.bus_voltage.map(|voltage| self.config.motor_constants.free_speed(voltage))
+    }
+
     /// Update the controller and compute motor input
     pub fn update(&mut self, motor_state: &MotorState, dt: f64) -> MotorInput {
         // Update position estimate from velocity (unless set externally this frame)
@@ -172,34 +301,52 @@ impl MotorController {
         // Compute electrical angle for commutation
         let electrical_angle = self.position_estimate * (self.config.motor_constants.pole_pairs as f64);
 
+        // Shape the raw setpoint into the reference the loops track; position
+        // targets beyond a soft limit are pulled back to it first
+        let goal = match (self.config.control_mode, self.config.soft_limits) {
+            (ControlMode::Position, Some((min, max))) => self.setpoint.clamp(min, max),
+            _ => self.setpoint,
+        };
+        self.shaped_setpoint = self.goal_shaper.shape(goal, dt);
+        let setpoint = self.shaped_setpoint;
+
         // Cascade through control loops based on mode
         let duty = match self.config.control_mode {
             ControlMode::DutyCycle => {
-                self.setpoint.clamp(-1.0, 1.0)
+                setpoint.clamp(-1.0, 1.0)
             }
             ControlMode::Current => {
-                let target_current = self.setpoint.clamp(-self.config.max_current, self.config.max_current);
+                let target_current = setpoint.clamp(-self.config.max_current, self.config.max_current);
                 self.current_controller.set_setpoint(target_current);
-                self.current_controller.update(motor_state.current_q, dt)
+                let measured = self.sense_current(motor_state.current_q, dt);
+                self.current_feedback = Some(measured);
+                self.current_controller.update(measured, dt)
             }
             ControlMode::Velocity => {
                 // Velocity loop outputs duty directly (bypasses current loop for stability)
                 // The velocity controller should be tuned to output duty cycle values
-                self.velocity_controller.set_setpoint(self.setpoint);
-                self.velocity_controller.update(motor_state.mechanical_velocity, dt)
+                self.velocity_loop(setpoint, motor_state.mechanical_velocity, dt)
             }
             ControlMode::Position => {
                 // Position loop outputs target velocity
-                self.position_controller.set_setpoint(self.setpoint);
+                self.position_controller.set_setpoint(setpoint);
                 let target_velocity = self.position_controller.update(self.position_estimate, dt);
                 let target_velocity = target_velocity.clamp(-self.config.max_velocity, self.config.max_velocity);
                 
                 // Velocity loop outputs duty directly (bypasses current loop for stability)
-                self.velocity_controller.set_setpoint(target_velocity);
-                self.velocity_controller.update(motor_state.mechanical_velocity, dt)
+                self.velocity_loop(target_velocity, motor_state.mechanical_velocity, dt)
             }
         };
 
+        // At a soft limit, refuse to drive further and stop the loops winding up
+        let duty = if self.pushing_past_soft_limit(duty) {
+            self.velocity_controller.reset();
+            self.position_controller.reset();
+            0.0
+        } else {
+            duty
+        };
+
         // Apply commutation
         let comm_output = self.commutation.compute(duty, electrical_angle);
 
@@ -209,13 +356,54 @@ impl MotorController {
         }
     }
 
+    /// Duty from the velocity loop plus feedforward. When voltage limited, the
+    /// reference is capped at the achievable velocity and the loop saturates
+    /// at full duty without winding up.
+    fn velocity_loop(&mut self, reference: f64, velocity: f64, dt: f64) -> f64 {
+        let Some(max_velocity) = self.achievable_velocity() else {
+            self.velocity_controller.set_setpoint(reference);
+            return self.velocity_controller.update(velocity, dt) + self.feedforward_output(reference, dt);
+        };
+        let reference = reference.clamp(-max_velocity, max_velocity);
+        let feedforward = self.feedforward_output(reference, dt);
+        self.velocity_controller.set_setpoint(reference);
+        self.velocity_controller.update_saturating(velocity, dt, -1.0 - feedforward, 1.0 - feedforward) + feedforward
+    }
+
+    /// Whether `duty` would drive the mechanism further past a soft limit it
+    /// has already reached
+    fn pushing_past_soft_limit(&self, duty: f64) -> bool {
+        self.config.soft_limits.is_some_and(|(min, max)| {
+            (self.position_estimate >= max && duty > 0.0) || (self.position_estimate <= min && duty < 0.0)
+        })
+    }
+
+    /// Feedforward duty for a velocity reference, differentiating the
+    /// reference for the acceleration term
+    fn feedforward_output(&mut self, velocity_reference: f64, dt: f64) -> f64 {
+        let acceleration = match self.prev_velocity_reference {
+            Some(prev) if dt > 0.0 => (velocity_reference - prev) / dt,
+            _ => 0.0,
+        };
+        self.prev_velocity_reference = Some(velocity_reference);
+        self.config
+            .feedforward
+            .map_or(0.0, |ff| ff.calculate(velocity_reference, acceleration))
+    }
+
     /// Reset all controller states
     pub fn reset(&mut self) {
         self.current_controller.reset();
         self.velocity_controller.reset();
         self.position_controller.reset();
         self.position_estimate = 0.0;
+        self.prev_velocity_reference = None;
+        self.current_noise = NoiseRng::new(self.config.current_sense.map_or(0, |sense| sense.seed));
+        self.current_feedback = None;
+        self.bus_voltage = self.config.voltage_limit;
         self.setpoint = 0.0;
+        self.shaped_setpoint = 0.0;
+        self.goal_shaper.reset(0.0);
     }
 
     /// Get a reference to the current configuration
@@ -223,6 +411,11 @@ impl MotorController {
         &self.config
     }
 
+    /// Swap the commutation strategy without disturbing controller state
+    pub fn set_commutation(&mut self, commutation: Box<dyn CommutationStrategy>) {
+        self.commutation = commutation;
+    }
+
     /// Get the commutation strategy's average efficiency
     pub fn commutation_efficiency(&self) -> f64 {
         self.commutation.average_efficiency()
@@ -308,6 +501,9 @@ impl ControlModel for MotorControllerBank {
         }
 
         for (i, ctrl) in self.controllers.iter_mut().enumerate() {
+            if state.sensor_bus.battery_voltage > 0.0 {
+                ctrl.set_bus_voltage(state.sensor_bus.battery_voltage);
+            }
             if i < state.true_state.motors.len() {
                 let motor_input = ctrl.update(&state.true_state.motors[i], dt);
                 state.control_input.motor_inputs[i] = motor_input;
@@ -376,6 +572,206 @@ mod tests {
         assert!((ctrl.position() - 1.0).abs() < 1e-9);
     }
 
+    #[test]
+    fn test_goal_shaper_changes_trajectory_not_goal() {
+        use crate::goal_shaper::SlewRateGoal;
+
+        let config = MotorControllerConfig::new(test_motor())
+            .with_mode(ControlMode::DutyCycle);
+        let mut ctrl = MotorController::new(config);
+        let motor_state = MotorState::default();
+        let dt = 0.01;
+
+        // Direct: full duty on the first step
+        ctrl.set_setpoint(1.0);
+        let direct_first = ctrl.update(&motor_state, dt).duty_cycle_q;
+
+        // Swap to a slew limiter on the same controller and re-run the step
+        ctrl.reset();
+        ctrl.set_goal_shaper(Box::new(SlewRateGoal::new(2.0)));
+        ctrl.set_setpoint(1.0);
+        let slewed: Vec<f64> = (0..100).map(|_| ctrl.update(&motor_state, dt).duty_cycle_q).collect();
+
+        assert!((direct_first - 1.0).abs() < 1e-6);
+        assert!((slewed[0] - 0.02).abs() < 1e-6);
+        assert!((slewed[99] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_switching_commutation_keeps_integral() {
+        use crate::commutation::commutation_from_name;
+
+        let config = MotorControllerConfig::new(test_motor())
+            .with_mode(ControlMode::Velocity)
+            .with_velocity_controller(PidfConfig::pi(0.1, 1.0));
+        let mut ctrl = MotorController::new(config);
+        ctrl.set_setpoint(100.0);
+        let motor_state = MotorState::default();
+        for _ in 0..10 {
+            ctrl.update(&motor_state, 0.001);
+        }
+        let integral = ctrl.velocity_controller.integral();
+        assert!(integral > 0.0);
+        assert_eq!(ctrl.commutation_efficiency(), 1.0);
+
+        ctrl.set_commutation(commutation_from_name("trapezoidal").unwrap());
+        assert!(ctrl.commutation_efficiency() < 1.0);
+        assert_eq!(ctrl.velocity_controller.integral(), integral);
+        assert_eq!(ctrl.setpoint(), 100.0);
+    }
+
+    #[test]
+    fn test_feedforward_adds_to_velocity_loop() {
+        let config = MotorControllerConfig::new(test_motor())
+            .with_mode(ControlMode::Velocity)
+            .with_velocity_controller(PidfConfig::p(0.0))
+            .with_feedforward(SimpleMotorFeedforward::new(0.05, 0.002, 0.0));
+        let mut ctrl = MotorController::new(config);
+        ctrl.set_setpoint(100.0);
+
+        // Feedback contributes nothing, so the duty is pure feedforward
+        let motor_state = MotorState { mechanical_velocity: 100.0, ..Default::default() };
+        let first = ctrl.update(&motor_state, 0.001);
+        let second = ctrl.update(&motor_state, 0.001);
+        assert!((first.duty_cycle_q - 0.25).abs() < 1e-9);
+        assert!((second.duty_cycle_q - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_soft_limits_clamp_setpoint_and_block_outward_output() {
+        let config = MotorControllerConfig::new(test_motor())
+            .with_mode(ControlMode::Position)
+            .with_velocity_controller(PidfConfig::pi(0.01, 0.5))
+            .with_soft_limits(2.0, -1.0);
+        let mut ctrl = MotorController::new(config);
+
+        // A target past the limit is tracked as the limit itself
+        ctrl.set_setpoint(5.0);
+        ctrl.update(&MotorState::default(), 0.001);
+        assert_eq!(ctrl.shaped_setpoint(), 2.0);
+        assert_eq!(ctrl.setpoint(), 5.0);
+
+        // At the limit and drifting back, the loop would push outward: refused
+        let drifting = MotorState { mechanical_velocity: -50.0, ..Default::default() };
+        for _ in 0..100 {
+            ctrl.set_position(2.0);
+            let output = ctrl.update(&drifting, 0.001);
+            assert_eq!(output.duty_cycle_q, 0.0);
+        }
+        assert_eq!(ctrl.velocity_controller.integral(), 0.0);
+
+        // Driving back inside the range is still allowed
+        ctrl.set_setpoint(0.0);
+        ctrl.set_position(2.0);
+        assert!(ctrl.update(&MotorState::default(), 0.001).duty_cycle_q < 0.0);
+    }
+
+    /// Velocity loop on a lightly loaded motor at 12 V with quasi-static
+    /// current, chasing `first` for 1 s and then `second` for 0.2 s. Returns
+    /// the duty and velocity of each step.
+    fn velocity_loop_on_free_motor(ctrl: &mut MotorController, first: f64, second: f64) -> Vec<(f64, f64)> {
+        let motor = ctrl.config().motor_constants;
+        let (inertia, dt) = (1e-4, 1e-4);
+        let mut velocity = 0.0;
+        let mut trace = Vec::new();
+        for step in 0..12000 {
+            ctrl.set_setpoint(if step < 10000 { first } else { second });
+            let motor_state = MotorState { mechanical_velocity: velocity, ..Default::default() };
+            let duty = ctrl.update(&motor_state, dt).duty_cycle_q;
+            let current = (duty * 12.0 - motor.ke() * velocity) / motor.resistance;
+            velocity += motor.kt() * current / inertia * dt;
+            trace.push((duty, velocity));
+        }
+        trace
+    }
+
+    #[test]
+    fn test_voltage_limited_velocity_saturates_without_windup() {
+        let free_speed = test_motor().free_speed(12.0);
+        let config = MotorControllerConfig::new(test_motor())
+            .with_mode(ControlMode::Velocity)
+            .with_velocity_controller(PidfConfig::pi(0.01, 0.5));
+        let mut limited = MotorController::new(config.clone().with_voltage_limit(12.0));
+        let mut unlimited = MotorController::new(config);
+        assert_eq!(limited.achievable_velocity(), Some(free_speed));
+        assert_eq!(unlimited.achievable_velocity(), None);
+
+        // Asking for 1.5x free speed pins the output at full duty and the
+        // motor settles at the voltage-limited free speed
+        let limited_trace = velocity_loop_on_free_motor(&mut limited, 1.5 * free_speed, 0.5 * free_speed);
+        let (duty, velocity) = limited_trace[9999];
+        assert_eq!(duty, 1.0);
+        assert!((velocity - free_speed).abs() < 0.01 * free_speed, "velocity {}", velocity);
+        let unlimited_trace = velocity_loop_on_free_motor(&mut unlimited, 1.5 * free_speed, 0.5 * free_speed);
+        assert_eq!(unlimited_trace[9999].0, 1.0);
+
+        // The held integral lets the limited loop back off at once; the
+        // unlimited one stays pinned while its wound-up integral drains
+        assert!(limited_trace[10000].0 < 1.0);
+        assert!(unlimited_trace[11999].0 == 1.0);
+        let settled = limited_trace[11999].1;
+        assert!((settled - 0.5 * free_speed).abs() < 0.05 * free_speed, "velocity {}", settled);
+    }
+
+    /// Current loop on a locked-rotor R-L winding. Returns the true current
+    /// and the fed-back measurement over the last 0.1 s of a 0.3 s run.
+    fn locked_rotor_current_loop(config: MotorControllerConfig) -> (Vec<f64>, Vec<f64>) {
+        let motor = config.motor_constants;
+        let mut ctrl = MotorController::new(config);
+        ctrl.set_setpoint(20.0);
+        let (dt, voltage) = (1e-4, 12.0);
+        let decay = (-dt * motor.resistance / motor.inductance_q).exp();
+        let mut state = MotorState::default();
+        let (mut currents, mut feedback) = (Vec::new(), Vec::new());
+        for step in 0..3000 {
+            let duty = ctrl.update(&state, dt).duty_cycle_q;
+            let steady = duty * voltage / motor.resistance;
+            state.current_q = steady + (state.current_q - steady) * decay;
+            if step >= 2000 {
+                currents.push(state.current_q);
+                feedback.push(ctrl.current_feedback().unwrap());
+            }
+        }
+        (currents, feedback)
+    }
+
+    fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        (mean, variance)
+    }
+
+    #[test]
+    fn test_noisy_current_sense_tracks_and_filter_cuts_variance() {
+        let config = |filter_time_constant: f64| {
+            MotorControllerConfig::new(test_motor())
+                .with_mode(ControlMode::Current)
+                .with_current_controller(PidfConfig::pi(0.001, 1.0).with_limits(-1.0, 1.0))
+                .with_current_sense(2.0, filter_time_constant, 7)
+        };
+
+        let (raw_currents, raw_feedback) = locked_rotor_current_loop(config(0.0));
+        let (currents, feedback) = locked_rotor_current_loop(config(1e-3));
+
+        // Both settle around the setpoint without running away
+        for run in [&raw_currents, &currents] {
+            let (mean, _) = mean_and_variance(run);
+            assert!((mean - 20.0).abs() < 1.0, "mean current {}", mean);
+            assert!(run.iter().all(|i| i.abs() < 40.0));
+        }
+
+        // The raw feedback carries the full sensor noise; the filter removes most of it
+        let (_, raw_variance) = mean_and_variance(&raw_feedback);
+        let (_, filtered_variance) = mean_and_variance(&feedback);
+        assert!(raw_variance > 2.0, "raw feedback variance {}", raw_variance);
+        assert!(filtered_variance < 0.5 * raw_variance,
+            "filtered {} vs raw {}", filtered_variance, raw_variance);
+
+        // The same seed replays the same noise
+        let (_, again) = locked_rotor_current_loop(config(1e-3));
+        assert_eq!(again, feedback);
+    }
+
     #[test]
     fn test_controller_bank_step() {
         let motor = test_motor();
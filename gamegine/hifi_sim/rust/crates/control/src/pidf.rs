@@ -121,6 +121,29 @@ impl PidfController {
     ///
     /// Uses derivative-on-measurement to avoid derivative kick on setpoint changes.
     pub fn update(&mut self, measurement: f64, dt: f64) -> f64 {
+        self.unclamped_update(measurement, dt)
+            .clamp(self.config.output_min, self.config.output_max)
+    }
+
+    /// Update like `update`, also clamping the output to [min, max]
+    ///
+    /// While the output is saturated in the direction the error pushes, the
+    /// integral is held instead of accumulated (conditional integration), so
+    /// a loop asking for more than the actuator can give does not wind up.
+    pub fn update_saturating(&mut self, measurement: f64, dt: f64, min: f64, max: f64) -> f64 {
+        let held_integral = self.integral;
+        let raw = self.unclamped_update(measurement, dt);
+        let output = raw.clamp(self.config.output_min, self.config.output_max).clamp(min, max);
+
+        let error = self.setpoint - measurement;
+        if (raw > output && error > 0.0) || (raw < output && error < 0.0) {
+            self.integral = held_integral;
+        }
+        output
+    }
+
+    /// Advance the loop state and return the output before clamping
+    fn unclamped_update(&mut self, measurement: f64, dt: f64) -> f64 {
         let error = self.setpoint - measurement;
 
         // Proportional term
@@ -154,9 +177,7 @@ impl PidfController {
         // Feedforward term
         let f_term = self.config.kf * self.setpoint;
 
-        // Sum and clamp output
-        let output = p_term + i_term + d_term + f_term;
-        output.clamp(self.config.output_min, self.config.output_max)
+        p_term + i_term + d_term + f_term
     }
 
     /// Get the current integral accumulator value
@@ -225,6 +246,28 @@ mod tests {
         assert!(ctrl.integral().abs() <= 5.0);
     }
 
+    #[test]
+    fn test_saturating_update_holds_integral() {
+        let mut ctrl = PidfController::new(PidfConfig::pi(0.5, 1.0));
+        ctrl.set_setpoint(1.0);
+
+        // Below the limit the integral accumulates as usual
+        assert!((ctrl.update_saturating(0.0, 0.1, -1.0, 1.0) - 0.6).abs() < 1e-9);
+        assert!((ctrl.integral() - 0.1).abs() < 1e-9);
+
+        // Pinned at the limit, further error is not accumulated
+        ctrl.set_setpoint(10.0);
+        for _ in 0..100 {
+            assert_eq!(ctrl.update_saturating(0.0, 0.1, -1.0, 1.0), 1.0);
+        }
+        assert!((ctrl.integral() - 0.1).abs() < 1e-9);
+
+        // Error pulling back out of saturation still integrates
+        ctrl.set_setpoint(-10.0);
+        ctrl.update_saturating(0.0, 0.1, -100.0, 100.0);
+        assert!((ctrl.integral() + 0.9).abs() < 1e-9);
+    }
+
     #[test]
     fn test_output_saturation() {
         let config = PidfConfig::p(100.0).with_limits(-1.0, 1.0);
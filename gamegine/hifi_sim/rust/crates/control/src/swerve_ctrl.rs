@@ -0,0 +1,1653 @@
+//! Swerve Drive Control Helpers
+//!
+//! Chassis-level commands and driver assists that sit above the per-module
+//! motor controllers.
+
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+use simcore::{integrate_twist, BatteryState, DelayLine};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use crate::goal_shaper::{DirectGoal, GoalShaper, SlewRateGoal};
+use crate::pidf::{PidfConfig, PidfController};
+
+/// Desired robot velocity
+///
+/// Robot-relative (x forward, y left) unless the API taking or returning it
+/// says the speeds are field-relative.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChassisSpeeds {
+    /// Velocity along x (m/s)
+    pub vx: f64,
+    /// Velocity along y (m/s)
+    pub vy: f64,
+    /// Counter-clockwise yaw rate (rad/s)
+    pub omega: f64,
+}
+
+impl ChassisSpeeds {
+    pub fn new(vx: f64, vy: f64, omega: f64) -> Self {
+        ChassisSpeeds { vx, vy, omega }
+    }
+}
+
+/// Robot pose in the field frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pose2d {
+    /// Position (m)
+    pub x: f64,
+    pub y: f64,
+    /// Counter-clockwise heading (rad)
+    pub heading: f64,
+}
+
+impl Pose2d {
+    pub fn new(x: f64, y: f64, heading: f64) -> Self {
+        Pose2d { x, y, heading }
+    }
+
+    /// Pose reached by following `twist` from this pose along a
+    /// constant-curvature arc (see `simcore::integrate_twist`)
+    pub fn exp(&self, twist: Twist2d) -> Pose2d {
+        let [x, y, heading] = integrate_twist([self.x, self.y, self.heading], [twist.dx, twist.dy, twist.dtheta]);
+        Pose2d::new(x, y, heading)
+    }
+}
+
+/// Change in pose expressed in the robot frame, e.g. chassis speeds times dt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Twist2d {
+    /// Forward displacement (m)
+    pub dx: f64,
+    /// Leftward displacement (m)
+    pub dy: f64,
+    /// Counter-clockwise rotation (rad)
+    pub dtheta: f64,
+}
+
+impl Twist2d {
+    pub fn new(dx: f64, dy: f64, dtheta: f64) -> Self {
+        Twist2d { dx, dy, dtheta }
+    }
+
+    /// Twist covered by robot-relative `speeds` held for `dt`
+    pub fn from_speeds(speeds: ChassisSpeeds, dt: f64) -> Self {
+        Twist2d::new(speeds.vx * dt, speeds.vy * dt, speeds.omega * dt)
+    }
+}
+
+/// Wrap an angle to [-pi, pi)
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+// ============================================================================
+// Field Transform
+// ============================================================================
+
+/// How one alliance's half of the field maps onto the other's
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldSymmetry {
+    /// Mirrored across the center line: x flips, y is kept
+    #[default]
+    Mirrored,
+    /// Rotated 180 degrees about the field center: x and y both flip
+    Rotational,
+}
+
+/// Flips field-frame poses, speeds and trajectories between the blue and
+/// red alliance origins
+///
+/// Flipping is its own inverse, so the same call converts either way.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldTransform {
+    /// Field length along x (m)
+    pub length: f64,
+    /// Field width along y (m)
+    pub width: f64,
+    pub symmetry: FieldSymmetry,
+}
+
+impl FieldTransform {
+    /// Mirrored field of `length` by `width` (m)
+    pub fn new(length: f64, width: f64) -> Self {
+        FieldTransform { length, width, symmetry: FieldSymmetry::Mirrored }
+    }
+
+    /// How the alliance halves map onto each other
+    pub fn with_symmetry(mut self, symmetry: FieldSymmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    /// The same pose seen from the other alliance's origin
+    pub fn flip_pose(&self, pose: Pose2d) -> Pose2d {
+        match self.symmetry {
+            FieldSymmetry::Mirrored => Pose2d::new(self.length - pose.x, pose.y, wrap_angle(PI - pose.heading)),
+            FieldSymmetry::Rotational => {
+                Pose2d::new(self.length - pose.x, self.width - pose.y, wrap_angle(pose.heading + PI))
+            }
+        }
+    }
+
+    /// Field-frame speeds (or accelerations) for the flipped field
+    pub fn flip_speeds(&self, speeds: ChassisSpeeds) -> ChassisSpeeds {
+        match self.symmetry {
+            FieldSymmetry::Mirrored => ChassisSpeeds::new(-speeds.vx, speeds.vy, -speeds.omega),
+            FieldSymmetry::Rotational => ChassisSpeeds::new(-speeds.vx, -speeds.vy, speeds.omega),
+        }
+    }
+
+    /// A trajectory sample for the flipped field
+    pub fn flip_sample(&self, sample: &TrajectorySample) -> TrajectorySample {
+        TrajectorySample {
+            pose: self.flip_pose(sample.pose),
+            speeds: self.flip_speeds(sample.speeds),
+            acceleration: self.flip_speeds(sample.acceleration),
+        }
+    }
+
+    /// Every sample of a trajectory for the flipped field
+    pub fn flip_trajectory(&self, samples: &[TrajectorySample]) -> Vec<TrajectorySample> {
+        samples.iter().map(|sample| self.flip_sample(sample)).collect()
+    }
+}
+
+// ============================================================================
+// Heading Hold
+// ============================================================================
+
+/// Teleop assist that holds a locked heading while the driver translates
+///
+/// Translation passes through untouched; omega comes from a heading PID that
+/// corrects drift from the locked heading along the shortest way around.
+#[derive(Debug, Clone)]
+pub struct HeadingHold {
+    controller: PidfController,
+    locked_heading: f64,
+}
+
+impl HeadingHold {
+    /// Create a heading hold with the given PID gains (output in rad/s)
+    pub fn new(config: PidfConfig) -> Self {
+        Self {
+            controller: PidfController::new(config),
+            locked_heading: 0.0,
+        }
+    }
+
+    /// Lock onto a new heading (rad)
+    pub fn set_locked_heading(&mut self, heading: f64) {
+        self.locked_heading = wrap_angle(heading);
+        self.controller.reset();
+    }
+
+    /// Get the locked heading (rad)
+    pub fn locked_heading(&self) -> f64 {
+        self.locked_heading
+    }
+
+    /// Chassis command for a translation request at the measured heading
+    ///
+    /// `vx` and `vy` pass through unchanged, in whichever frame they were
+    /// requested.
+    pub fn calculate(&mut self, vx: f64, vy: f64, heading: f64, dt: f64) -> ChassisSpeeds {
+        // Measure relative to the lock so the PID sees the wrapped error
+        let error = wrap_angle(self.locked_heading - heading);
+        self.controller.set_setpoint(self.locked_heading);
+        let omega = self.controller.update(self.locked_heading - error, dt);
+        ChassisSpeeds::new(vx, vy, omega)
+    }
+
+    /// Reset the heading PID state
+    pub fn reset(&mut self) {
+        self.controller.reset();
+    }
+}
+
+// ============================================================================
+// Holonomic Path Follower
+// ============================================================================
+
+/// One sample of a trajectory: where the robot should be, how fast it should
+/// be moving and how fast that velocity is changing, all in the field frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrajectorySample {
+    pub pose: Pose2d,
+    pub speeds: ChassisSpeeds,
+    /// Rate of change of `speeds` (m/s^2 and rad/s^2)
+    pub acceleration: ChassisSpeeds,
+}
+
+/// Trajectory follower for holonomic drivetrains
+///
+/// Adds x, y and heading PID corrections to the reference velocity, plus an
+/// optional acceleration feedforward that leads the command by kA times the
+/// reference acceleration to make up for the drivetrain's response lag. The
+/// measured pose can be passed through a fixed latency to reproduce the
+/// behavior of laggy vision pose estimates, and the resulting command can be
+/// shaped per axis (e.g. slew limited) so high gains do not produce jerky
+/// corrections.
+#[derive(Clone)]
+pub struct HolonomicFollower {
+    x_controller: PidfController,
+    y_controller: PidfController,
+    heading_controller: PidfController,
+    pose_delay: DelayLine<Pose2d>,
+    /// Goal shapers on the commanded vx, vy and omega
+    output_shapers: [Box<dyn GoalShaper>; 3],
+    /// Velocity command added per unit of reference acceleration (s)
+    acceleration_gain: f64,
+}
+
+impl HolonomicFollower {
+    /// Create a follower with translation gains (m/s per m) and heading gains
+    /// (rad/s per rad), acting on the pose as measured
+    pub fn new(translation: PidfConfig, heading: PidfConfig) -> Self {
+        Self {
+            x_controller: PidfController::new(translation.clone()),
+            y_controller: PidfController::new(translation),
+            heading_controller: PidfController::new(heading),
+            pose_delay: DelayLine::new(0.0),
+            output_shapers: [Box::new(DirectGoal), Box::new(DirectGoal), Box::new(DirectGoal)],
+            acceleration_gain: 0.0,
+        }
+    }
+
+    /// Feed forward `ka` seconds of reference acceleration on every axis
+    ///
+    /// For a drivetrain whose velocity lags its command with time constant
+    /// tau, ka = tau cancels the lag on the reference motion.
+    pub fn with_acceleration_feedforward(mut self, ka: f64) -> Self {
+        self.acceleration_gain = ka;
+        self
+    }
+
+    /// Limit how fast the commanded vx, vy (m/s^2) and omega (rad/s^2) change
+    pub fn with_output_slew_rate(self, vx_rate: f64, vy_rate: f64, omega_rate: f64) -> Self {
+        self.with_goal_shaper(
+            Box::new(SlewRateGoal::new(vx_rate)),
+            Box::new(SlewRateGoal::new(vy_rate)),
+            Box::new(SlewRateGoal::new(omega_rate)),
+        )
+    }
+
+    /// Shape the commanded vx, vy and omega with a goal shaper per axis
+    /// (builder pattern); each starts from a zero command
+    pub fn with_goal_shaper(
+        mut self,
+        vx: Box<dyn GoalShaper>,
+        vy: Box<dyn GoalShaper>,
+        omega: Box<dyn GoalShaper>,
+    ) -> Self {
+        self.output_shapers = [vx, vy, omega];
+        for shaper in &mut self.output_shapers {
+            shaper.reset(0.0);
+        }
+        self
+    }
+
+    /// Delay pose measurements by `latency` seconds before the controller sees them
+    pub fn with_pose_latency(mut self, latency: f64) -> Self {
+        self.pose_delay = DelayLine::new(latency);
+        self
+    }
+
+    /// Pose measurement latency (s)
+    pub fn pose_latency(&self) -> f64 {
+        self.pose_delay.delay()
+    }
+
+    /// Field-relative command to track `reference` moving at field-relative
+    /// `reference_speeds`
+    ///
+    /// `measured` is the pose observed at `time`; the controller acts on the
+    /// delayed copy.
+    pub fn calculate(
+        &mut self,
+        reference: Pose2d,
+        reference_speeds: ChassisSpeeds,
+        measured: Pose2d,
+        time: f64,
+        dt: f64,
+    ) -> ChassisSpeeds {
+        let sample = TrajectorySample { pose: reference, speeds: reference_speeds, acceleration: ChassisSpeeds::default() };
+        self.follow(&sample, measured, time, dt)
+    }
+
+    /// Field-relative command to track a trajectory sample (field-relative,
+    /// see `TrajectorySample`), feeding forward its acceleration when an
+    /// acceleration gain is set
+    pub fn follow(&mut self, sample: &TrajectorySample, measured: Pose2d, time: f64, dt: f64) -> ChassisSpeeds {
+        let TrajectorySample { pose: reference, speeds: reference_speeds, acceleration } = *sample;
+        let ka = self.acceleration_gain;
+        let pose = self.pose_delay.push(time, measured);
+
+        self.x_controller.set_setpoint(reference.x);
+        self.y_controller.set_setpoint(reference.y);
+        self.heading_controller.set_setpoint(reference.heading);
+        let heading_error = wrap_angle(reference.heading - pose.heading);
+
+        let goals = [
+            reference_speeds.vx + ka * acceleration.vx + self.x_controller.update(pose.x, dt),
+            reference_speeds.vy + ka * acceleration.vy + self.y_controller.update(pose.y, dt),
+            reference_speeds.omega + ka * acceleration.omega + self.heading_controller.update(reference.heading - heading_error, dt),
+        ];
+        let [vx, vy, omega] = [0, 1, 2].map(|axis| self.output_shapers[axis].shape(goals[axis], dt));
+        ChassisSpeeds::new(vx, vy, omega)
+    }
+
+    /// Reset controller state and drop buffered pose measurements
+    pub fn reset(&mut self) {
+        self.x_controller.reset();
+        self.y_controller.reset();
+        self.heading_controller.reset();
+        self.pose_delay.clear();
+        for shaper in &mut self.output_shapers {
+            shaper.reset(0.0);
+        }
+    }
+}
+
+// ============================================================================
+// Swerve Kinematics
+// ============================================================================
+
+/// Speed and steering angle command for one swerve module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SwerveModuleState {
+    /// Wheel ground speed (m/s)
+    pub speed: f64,
+    /// Steering angle relative to the robot (rad)
+    pub angle: f64,
+}
+
+impl SwerveModuleState {
+    fn velocity(&self) -> (f64, f64) {
+        (self.speed * self.angle.cos(), self.speed * self.angle.sin())
+    }
+
+    fn from_velocity(vx: f64, vy: f64, fallback_angle: f64) -> Self {
+        let speed = vx.hypot(vy);
+        let angle = if speed > 1e-9 { vy.atan2(vx) } else { fallback_angle };
+        SwerveModuleState { speed, angle }
+    }
+}
+
+/// Inverse kinematics from robot-relative chassis speeds to module states
+///
+/// Optionally constrains the generated commands to a maximum module speed and
+/// a maximum module acceleration. Both limits scale every module by the same
+/// factor so the direction of chassis motion is preserved.
+#[derive(Debug, Clone)]
+pub struct SwerveKinematics {
+    /// Module [x, y] positions relative to the robot center (m)
+    pub module_positions: Vec<[f64; 2]>,
+    /// Maximum module ground speed (m/s); infinite by default
+    pub max_module_speed: f64,
+    /// Maximum change in module velocity per second (m/s^2); infinite by default
+    pub max_module_acceleration: f64,
+}
+
+impl SwerveKinematics {
+    pub fn new(module_positions: Vec<[f64; 2]>) -> Self {
+        Self {
+            module_positions,
+            max_module_speed: f64::INFINITY,
+            max_module_acceleration: f64::INFINITY,
+        }
+    }
+
+    /// Cap module ground speed (m/s)
+    pub fn with_max_module_speed(mut self, max_speed: f64) -> Self {
+        self.max_module_speed = max_speed;
+        self
+    }
+
+    /// Cap how fast module velocity may change (m/s^2), e.g. mu * g for traction
+    pub fn with_max_module_acceleration(mut self, max_acceleration: f64) -> Self {
+        self.max_module_acceleration = max_acceleration;
+        self
+    }
+
+    /// Unconstrained module states for robot-relative `speeds`
+    ///
+    /// The robot rotates about `center_of_rotation` (robot-relative, m), or
+    /// about its center when `None`.
+    pub fn to_module_states(&self, speeds: ChassisSpeeds, center_of_rotation: Option<[f64; 2]>) -> Vec<SwerveModuleState> {
+        let [cx, cy] = center_of_rotation.unwrap_or([0.0, 0.0]);
+        self.module_positions
+            .iter()
+            .map(|&[x, y]| {
+                let vx = speeds.vx - speeds.omega * (y - cy);
+                let vy = speeds.vy + speeds.omega * (x - cx);
+                SwerveModuleState::from_velocity(vx, vy, 0.0)
+            })
+            .collect()
+    }
+
+    /// Robot-relative chassis speeds that best explain measured module states
+    ///
+    /// Least-squares forward kinematics: with more modules than the three
+    /// chassis degrees of freedom, disagreeing modules (e.g. one slipping)
+    /// are averaged rather than trusted individually.
+    pub fn to_chassis_speeds(&self, states: &[SwerveModuleState]) -> ChassisSpeeds {
+        let n = self.module_positions.len().min(states.len());
+        let kinematics = DMatrix::from_fn(2 * n, 3, |row, col| {
+            let [x, y] = self.module_positions[row / 2];
+            match (row % 2, col) {
+                (0, 0) | (1, 1) => 1.0,
+                (0, 2) => -y,
+                (1, 2) => x,
+                _ => 0.0,
+            }
+        });
+        let measured = DVector::from_iterator(2 * n, states[..n].iter().flat_map(|state| {
+            let (vx, vy) = state.velocity();
+            [vx, vy]
+        }));
+        match kinematics.pseudo_inverse(1e-9) {
+            Ok(inverse) => {
+                let speeds = inverse * measured;
+                ChassisSpeeds::new(speeds[0], speeds[1], speeds[2])
+            }
+            Err(_) => ChassisSpeeds::default(),
+        }
+    }
+
+    /// Scale all module speeds down together so none exceeds `max_speed`
+    pub fn desaturate(states: &mut [SwerveModuleState], max_speed: f64) {
+        let fastest = states.iter().map(|s| s.speed.abs()).fold(0.0, f64::max);
+        if fastest > max_speed && fastest > 0.0 {
+            let scale = max_speed / fastest;
+            for state in states.iter_mut() {
+                state.speed *= scale;
+            }
+        }
+    }
+
+    /// Fastest module ground speed needed for `speeds` about the robot center
+    fn fastest_module_speed(&self, speeds: ChassisSpeeds) -> f64 {
+        self.to_module_states(speeds, None).iter().map(|s| s.speed).fold(0.0, f64::max)
+    }
+
+    /// Whether every module can reach the speed `speeds` asks of it
+    pub fn is_feasible(&self, speeds: ChassisSpeeds, max_module_speed: f64) -> bool {
+        self.fastest_module_speed(speeds) <= max_module_speed * (1.0 + 1e-12)
+    }
+
+    /// Closest achievable command in the same direction as `speeds`
+    ///
+    /// Module speeds scale linearly with the chassis speeds, so scaling vx,
+    /// vy and omega together by the fastest module's overshoot brings that
+    /// module exactly to the limit while keeping the path's shape. Feasible
+    /// commands are returned unchanged.
+    pub fn nearest_feasible(&self, speeds: ChassisSpeeds, max_module_speed: f64) -> ChassisSpeeds {
+        let fastest = self.fastest_module_speed(speeds);
+        if fastest <= max_module_speed || fastest == 0.0 {
+            return speeds;
+        }
+        let scale = max_module_speed.max(0.0) / fastest;
+        ChassisSpeeds::new(speeds.vx * scale, speeds.vy * scale, speeds.omega * scale)
+    }
+
+    /// Module states for `speeds` that respect the speed and acceleration limits
+    ///
+    /// `previous` holds the states commanded `dt` seconds ago. The change in
+    /// each module's velocity vector is scaled so the largest one stays within
+    /// `max_module_acceleration * dt`.
+    pub fn constrained_module_states(
+        &self,
+        speeds: ChassisSpeeds,
+        previous: &[SwerveModuleState],
+        dt: f64,
+    ) -> Vec<SwerveModuleState> {
+        let mut desired = self.to_module_states(speeds, None);
+        Self::desaturate(&mut desired, self.max_module_speed);
+        if previous.len() != desired.len() {
+            return desired;
+        }
+
+        let deltas: Vec<((f64, f64), (f64, f64))> = previous
+            .iter()
+            .zip(&desired)
+            .map(|(prev, want)| {
+                let (px, py) = prev.velocity();
+                let (wx, wy) = want.velocity();
+                ((px, py), (wx - px, wy - py))
+            })
+            .collect();
+        let largest_change = deltas.iter().map(|&(_, (dx, dy))| dx.hypot(dy)).fold(0.0, f64::max);
+        let allowed_change = self.max_module_acceleration * dt;
+        if largest_change <= allowed_change {
+            return desired;
+        }
+
+        let scale = allowed_change / largest_change;
+        deltas
+            .iter()
+            .zip(&desired)
+            .map(|(&((px, py), (dx, dy)), want)| {
+                SwerveModuleState::from_velocity(px + dx * scale, py + dy * scale, want.angle)
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Torque Vectoring
+// ============================================================================
+
+/// Drive command for one module produced by [`TorqueVectoring`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ModuleDriveCommand {
+    /// Force along the module's heading (N)
+    pub force: f64,
+    /// Drive duty cycle producing that force, in [-1, 1]
+    pub duty: f64,
+}
+
+/// Distributes a chassis force and yaw moment across swerve module drive forces
+///
+/// Each module pushes along its current heading, so the modules span the
+/// requested (Fx, Fy, Mz) through a 3 x N allocation matrix. The
+/// minimum-norm solution is taken through its pseudo-inverse, which also copes
+/// with modules that all point the same way. If any module would exceed its
+/// traction limit, every force is scaled down together so the split between
+/// translation and rotation is preserved.
+#[derive(Debug, Clone)]
+pub struct TorqueVectoring {
+    /// Module positions relative to the robot center (m)
+    pub module_positions: Vec<[f64; 2]>,
+    /// Largest drive force each module can put down before slipping (N)
+    pub traction_limits: Vec<f64>,
+    /// Wheel force produced per unit of drive duty (N)
+    pub force_per_duty: f64,
+}
+
+impl TorqueVectoring {
+    pub fn new(module_positions: Vec<[f64; 2]>) -> Self {
+        let traction_limits = vec![f64::INFINITY; module_positions.len()];
+        TorqueVectoring {
+            module_positions,
+            traction_limits,
+            force_per_duty: 1.0,
+        }
+    }
+
+    /// Use the same traction limit for every module (N)
+    pub fn with_traction_limit(mut self, limit: f64) -> Self {
+        self.traction_limits = vec![limit.abs(); self.module_positions.len()];
+        self
+    }
+
+    /// Use per-module traction limits, e.g. from the normal load on each wheel (N)
+    pub fn with_traction_limits(mut self, limits: Vec<f64>) -> Self {
+        self.traction_limits = limits.iter().map(|limit| limit.abs()).collect();
+        self
+    }
+
+    /// Wheel force produced at full duty (N), used to convert forces to duty
+    pub fn with_force_per_duty(mut self, force_per_duty: f64) -> Self {
+        self.force_per_duty = force_per_duty;
+        self
+    }
+
+    /// Per-module drive commands for a robot-frame force and yaw moment
+    ///
+    /// `module_angles` are the current module headings (rad). `force` is
+    /// (Fx, Fy) in N and `moment` is counter-clockwise in N*m.
+    pub fn allocate(&self, force: [f64; 2], moment: f64, module_angles: &[f64]) -> Vec<ModuleDriveCommand> {
+        let n = self.module_positions.len().min(module_angles.len());
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let allocation = DMatrix::from_fn(3, n, |row, i| {
+            let [x, y] = self.module_positions[i];
+            let (sin, cos) = module_angles[i].sin_cos();
+            match row {
+                0 => cos,
+                1 => sin,
+                _ => x * sin - y * cos,
+            }
+        });
+        let request = DVector::from_column_slice(&[force[0], force[1], moment]);
+        let forces = match allocation.pseudo_inverse(1e-9) {
+            Ok(pinv) => pinv * request,
+            Err(_) => DVector::zeros(n),
+        };
+
+        // Uniform scale keeps the force/moment ratio when traction runs out
+        let scale = forces
+            .iter()
+            .zip(&self.traction_limits)
+            .map(|(f, limit)| if f.abs() > *limit { limit / f.abs() } else { 1.0 })
+            .fold(1.0, f64::min);
+
+        forces
+            .iter()
+            .map(|f| {
+                let force = f * scale;
+                let duty = if self.force_per_duty != 0.0 {
+                    (force / self.force_per_duty).clamp(-1.0, 1.0)
+                } else {
+                    0.0
+                };
+                ModuleDriveCommand { force, duty }
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Obstacle Avoidance
+// ============================================================================
+
+/// Circular keep-out region in the field frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CircleObstacle {
+    /// Center (m)
+    pub x: f64,
+    pub y: f64,
+    /// Radius (m)
+    pub radius: f64,
+}
+
+impl CircleObstacle {
+    pub fn new(x: f64, y: f64, radius: f64) -> Self {
+        CircleObstacle { x, y, radius }
+    }
+}
+
+/// Reactive velocity clamp that keeps a chassis command from running into
+/// circular obstacles
+///
+/// An obstacle is considered when the commanded velocity would bring the
+/// robot into contact within `time_horizon`. The velocity component towards
+/// it is then cut so the remaining clearance takes the whole horizon to
+/// close; the tangential component is kept, so the command slides around the
+/// obstacle rather than just stopping. This is a last-moment safety layer,
+/// not a planner.
+#[derive(Debug, Clone, Copy)]
+pub struct ObstacleAvoidance {
+    /// Radius of a circle enclosing the robot (m)
+    pub robot_radius: f64,
+    /// Time-to-collision below which the command is clamped (s)
+    pub time_horizon: f64,
+}
+
+impl ObstacleAvoidance {
+    pub fn new(robot_radius: f64) -> Self {
+        ObstacleAvoidance {
+            robot_radius,
+            time_horizon: 1.0,
+        }
+    }
+
+    /// Time-to-collision below which the command is clamped (s)
+    pub fn with_time_horizon(mut self, time_horizon: f64) -> Self {
+        self.time_horizon = time_horizon.max(0.0);
+        self
+    }
+
+    /// Time until the robot at `pose` moving at (vx, vy) touches `obstacle`,
+    /// zero if already touching, None if it never does
+    pub fn time_to_collision(&self, pose: Pose2d, vx: f64, vy: f64, obstacle: &CircleObstacle) -> Option<f64> {
+        let (dx, dy) = (obstacle.x - pose.x, obstacle.y - pose.y);
+        let reach = obstacle.radius + self.robot_radius;
+        let gap = dx * dx + dy * dy - reach * reach;
+        if gap <= 0.0 {
+            return Some(0.0);
+        }
+
+        // Solve |d - v t| = reach for the first t > 0
+        let speed_sq = vx * vx + vy * vy;
+        let closing = dx * vx + dy * vy;
+        if speed_sq == 0.0 || closing <= 0.0 {
+            return None;
+        }
+        let discriminant = closing * closing - speed_sq * gap;
+        (discriminant >= 0.0).then(|| (closing - discriminant.sqrt()) / speed_sq)
+    }
+
+    /// Clamp field-frame `speeds` for a robot at `pose`; the yaw rate passes through
+    pub fn calculate(&self, speeds: ChassisSpeeds, pose: Pose2d, obstacles: &[CircleObstacle]) -> ChassisSpeeds {
+        let (mut vx, mut vy) = (speeds.vx, speeds.vy);
+        for obstacle in obstacles {
+            match self.time_to_collision(pose, vx, vy, obstacle) {
+                Some(time) if time < self.time_horizon => {}
+                _ => continue,
+            }
+
+            let (dx, dy) = (obstacle.x - pose.x, obstacle.y - pose.y);
+            let distance = dx.hypot(dy);
+            if distance == 0.0 {
+                return ChassisSpeeds::new(0.0, 0.0, speeds.omega);
+            }
+            let (ux, uy) = (dx / distance, dy / distance);
+            let clearance = (distance - obstacle.radius - self.robot_radius).max(0.0);
+            let closing = vx * ux + vy * uy;
+            let allowed = if self.time_horizon > 0.0 { clearance / self.time_horizon } else { f64::INFINITY };
+            if closing > allowed {
+                vx -= (closing - allowed) * ux;
+                vy -= (closing - allowed) * uy;
+            }
+        }
+        ChassisSpeeds::new(vx, vy, speeds.omega)
+    }
+}
+
+// ============================================================================
+// Input Conditioning
+// ============================================================================
+
+/// Turns raw driver stick values into chassis speed commands
+///
+/// Each axis in [-1, 1] goes through a deadband (rescaled so the output
+/// still starts at zero just outside it), an expo curve
+/// `(1 - expo) * x + expo * x^3`, scaling to the robot's top speeds and
+/// finally a slew limit on the resulting command.
+#[derive(Debug, Clone)]
+pub struct InputConditioner {
+    /// Stick magnitude treated as zero
+    pub deadband: f64,
+    /// Blend between a linear (0) and cubic (1) response
+    pub expo: f64,
+    /// Translation speed at full stick (m/s)
+    pub max_linear_speed: f64,
+    /// Yaw rate at full stick (rad/s)
+    pub max_angular_rate: f64,
+    /// Slew limits on vx, vy and omega
+    slew: [SlewRateGoal; 3],
+}
+
+impl InputConditioner {
+    pub fn new(max_linear_speed: f64, max_angular_rate: f64) -> Self {
+        InputConditioner {
+            deadband: 0.1,
+            expo: 0.0,
+            max_linear_speed,
+            max_angular_rate,
+            slew: [SlewRateGoal::new(f64::INFINITY); 3],
+        }
+    }
+
+    /// Stick magnitude treated as zero, in [0, 1)
+    pub fn with_deadband(mut self, deadband: f64) -> Self {
+        self.deadband = deadband.clamp(0.0, 0.99);
+        self
+    }
+
+    /// Expo blend in [0, 1]; higher values give finer control near center
+    pub fn with_expo(mut self, expo: f64) -> Self {
+        self.expo = expo.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Limit how fast the commands change (m/s^2 for translation, rad/s^2 for rotation)
+    pub fn with_slew_rate(mut self, linear_rate: f64, angular_rate: f64) -> Self {
+        self.slew = [
+            SlewRateGoal::new(linear_rate),
+            SlewRateGoal::new(linear_rate),
+            SlewRateGoal::new(angular_rate),
+        ];
+        self
+    }
+
+    /// Deadband and expo applied to one stick axis, without scaling or slew
+    pub fn condition_axis(&self, raw: f64) -> f64 {
+        let x = raw.clamp(-1.0, 1.0);
+        if x.abs() <= self.deadband {
+            return 0.0;
+        }
+        let x = x.signum() * (x.abs() - self.deadband) / (1.0 - self.deadband);
+        (1.0 - self.expo) * x + self.expo * x.powi(3)
+    }
+
+    /// Conditioned chassis speeds for forward, left and counter-clockwise
+    /// stick values
+    pub fn calculate(&mut self, forward: f64, left: f64, rotation: f64, dt: f64) -> ChassisSpeeds {
+        let goals = [
+            self.condition_axis(forward) * self.max_linear_speed,
+            self.condition_axis(left) * self.max_linear_speed,
+            self.condition_axis(rotation) * self.max_angular_rate,
+        ];
+        let [vx, vy, omega] = [0, 1, 2].map(|axis| self.slew[axis].shape(goals[axis], dt));
+        ChassisSpeeds::new(vx, vy, omega)
+    }
+
+    /// Drop any slewed state and restart from rest
+    pub fn reset(&mut self) {
+        for slew in &mut self.slew {
+            slew.reset(0.0);
+        }
+    }
+}
+
+// ============================================================================
+// Battery Governor
+// ============================================================================
+
+/// Scales chassis commands down as the battery runs low, to avoid brownouts
+///
+/// Above `soc_threshold` commands pass unchanged. Below it the speed (and,
+/// when set, the acceleration limit) is scaled linearly down to
+/// `min_scale` at `min_soc` and held there below it.
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryGovernor {
+    /// State of charge below which commands are reduced (0-1)
+    pub soc_threshold: f64,
+    /// State of charge at which the full reduction applies (0-1)
+    pub min_soc: f64,
+    /// Command scale at and below `min_soc`
+    pub min_scale: f64,
+    /// Translational acceleration limit at full charge (m/s^2)
+    pub max_acceleration: f64,
+    /// Last governed command, for the acceleration limit
+    previous: ChassisSpeeds,
+}
+
+impl BatteryGovernor {
+    pub fn new(soc_threshold: f64, min_scale: f64) -> Self {
+        BatteryGovernor {
+            soc_threshold: soc_threshold.clamp(0.0, 1.0),
+            min_soc: 0.0,
+            min_scale: min_scale.clamp(0.0, 1.0),
+            max_acceleration: f64::INFINITY,
+            previous: ChassisSpeeds::default(),
+        }
+    }
+
+    /// State of charge at which the full reduction is reached (default 0)
+    pub fn with_min_soc(mut self, min_soc: f64) -> Self {
+        self.min_soc = min_soc.clamp(0.0, self.soc_threshold);
+        self
+    }
+
+    /// Limit translational acceleration (m/s^2), scaled with the speed
+    pub fn with_max_acceleration(mut self, max_acceleration: f64) -> Self {
+        self.max_acceleration = max_acceleration.abs();
+        self
+    }
+
+    /// Command scale for a battery at `soc`
+    pub fn scale(&self, soc: f64) -> f64 {
+        if soc >= self.soc_threshold {
+            return 1.0;
+        }
+        let span = self.soc_threshold - self.min_soc;
+        let depth = if span > 0.0 { ((self.soc_threshold - soc) / span).min(1.0) } else { 1.0 };
+        1.0 - depth * (1.0 - self.min_scale)
+    }
+
+    /// Governed chassis command for the present battery state
+    pub fn calculate(&mut self, speeds: ChassisSpeeds, battery: &BatteryState, dt: f64) -> ChassisSpeeds {
+        let scale = self.scale(battery.state_of_charge);
+        let mut governed = ChassisSpeeds::new(speeds.vx * scale, speeds.vy * scale, speeds.omega * scale);
+
+        let (dx, dy) = (governed.vx - self.previous.vx, governed.vy - self.previous.vy);
+        let change = dx.hypot(dy);
+        let allowed = self.max_acceleration * scale * dt;
+        if change > allowed {
+            governed.vx = self.previous.vx + dx * allowed / change;
+            governed.vy = self.previous.vy + dy * allowed / change;
+        }
+        self.previous = governed;
+        governed
+    }
+
+    /// Forget the last command, e.g. when the robot is re-enabled at rest
+    pub fn reset(&mut self) {
+        self.previous = ChassisSpeeds::default();
+    }
+}
+
+// ============================================================================
+// Charge Station Balance
+// ============================================================================
+
+/// Drives along the robot's forward axis to level a tilting platform, as in
+/// the 2023 charge-station auto-balance
+///
+/// A nose-up pitch means the robot sits on the low end, so it drives
+/// forward, uphill, at `kp` per radian. The pitch rate damping slows the
+/// robot once the platform starts to tip back, before the pitch itself
+/// changes sign; within `tolerance` the robot holds still.
+#[derive(Debug, Clone, Copy)]
+pub struct ChargeStationBalance {
+    /// Forward speed per radian of pitch ((m/s)/rad)
+    pub kp: f64,
+    /// Speed removed per rad/s of pitch rate ((m/s)/(rad/s))
+    pub kd: f64,
+    /// Speed limit (m/s)
+    pub max_speed: f64,
+    /// Pitch treated as level (rad)
+    pub tolerance: f64,
+}
+
+impl ChargeStationBalance {
+    pub fn new(kp: f64, max_speed: f64) -> Self {
+        ChargeStationBalance {
+            kp,
+            kd: 0.0,
+            max_speed: max_speed.abs(),
+            tolerance: 2.5_f64.to_radians(),
+        }
+    }
+
+    /// Pitch rate damping ((m/s)/(rad/s))
+    pub fn with_pitch_rate_damping(mut self, kd: f64) -> Self {
+        self.kd = kd;
+        self
+    }
+
+    /// Pitch treated as level (rad)
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance.abs();
+        self
+    }
+
+    /// Whether `pitch` (rad) is within the level tolerance
+    pub fn is_balanced(&self, pitch: f64) -> bool {
+        pitch.abs() <= self.tolerance
+    }
+
+    /// Robot-relative command for the measured pitch (rad, nose up
+    /// positive) and pitch rate (rad/s)
+    pub fn calculate(&self, pitch: f64, pitch_rate: f64) -> ChassisSpeeds {
+        if self.is_balanced(pitch) {
+            return ChassisSpeeds::default();
+        }
+        let vx = (self.kp * pitch + self.kd * pitch_rate).clamp(-self.max_speed, self.max_speed);
+        // Damping may slow the robot but never reverses it off the platform
+        let vx = if vx * pitch < 0.0 { 0.0 } else { vx };
+        ChassisSpeeds::new(vx, 0.0, 0.0)
+    }
+
+    /// Command from the IMU readings on the sensor bus
+    /// ([roll, pitch, yaw, roll_rate, pitch_rate, yaw_rate])
+    pub fn calculate_from_imu(&self, imu: &[f64; 6]) -> ChassisSpeeds {
+        self.calculate(imu[1], imu[4])
+    }
+}
+
+// ============================================================================
+// Pose Estimation
+// ============================================================================
+
+/// Odometry and estimated pose recorded at one update
+#[derive(Debug, Clone, Copy)]
+struct PoseSnapshot {
+    time: f64,
+    odometry: Pose2d,
+    estimate: Pose2d,
+}
+
+/// Fuses swerve odometry with latent, noisy vision pose measurements
+///
+/// Odometry from the module states moves the estimate every update. A
+/// vision measurement is compared with the estimate at its capture time, the
+/// estimate there is pulled toward it by a steady-state Kalman gain, and the
+/// odometry since then is replayed on top, as WPILib's pose estimators do.
+/// Each axis gets gain q / (q + sqrt(q * r)) from the odometry variance q and
+/// the vision variance r.
+#[derive(Debug, Clone)]
+pub struct PoseEstimator {
+    pub kinematics: SwerveKinematics,
+    /// Trust in odometry: std devs of x, y (m) and heading (rad)
+    pub state_std_devs: [f64; 3],
+    /// Trust in vision: std devs of x, y (m) and heading (rad)
+    pub vision_std_devs: [f64; 3],
+    /// How far back vision measurements may be applied (s)
+    pub history_length: f64,
+    odometry: Pose2d,
+    estimate: Pose2d,
+    history: VecDeque<PoseSnapshot>,
+}
+
+impl PoseEstimator {
+    pub fn new(kinematics: SwerveKinematics, initial_pose: Pose2d) -> Self {
+        PoseEstimator {
+            kinematics,
+            state_std_devs: [0.1, 0.1, 0.1],
+            vision_std_devs: [0.9, 0.9, 0.9],
+            history_length: 1.5,
+            odometry: initial_pose,
+            estimate: initial_pose,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Odometry std devs of x, y (m) and heading (rad)
+    pub fn with_state_std_devs(mut self, x: f64, y: f64, heading: f64) -> Self {
+        self.state_std_devs = [x, y, heading];
+        self
+    }
+
+    /// Vision std devs of x, y (m) and heading (rad)
+    pub fn with_vision_std_devs(mut self, x: f64, y: f64, heading: f64) -> Self {
+        self.vision_std_devs = [x, y, heading];
+        self
+    }
+
+    /// How far back vision measurements may be applied (s)
+    pub fn with_history_length(mut self, history_length: f64) -> Self {
+        self.history_length = history_length;
+        self
+    }
+
+    /// Fused pose estimate
+    pub fn pose(&self) -> Pose2d {
+        self.estimate
+    }
+
+    /// Pose from odometry alone, never corrected by vision
+    pub fn odometry_pose(&self) -> Pose2d {
+        self.odometry
+    }
+
+    /// Jump both the estimate and the odometry to `pose`, forgetting history
+    pub fn reset_pose(&mut self, pose: Pose2d) {
+        self.odometry = pose;
+        self.estimate = pose;
+        self.history.clear();
+    }
+
+    /// Advance by the module states measured over the last `dt` at `time` (s)
+    pub fn update(&mut self, time: f64, module_states: &[SwerveModuleState], dt: f64) -> Pose2d {
+        let twist = Twist2d::from_speeds(self.kinematics.to_chassis_speeds(module_states), dt);
+        self.odometry = self.odometry.exp(twist);
+        self.estimate = self.estimate.exp(twist);
+
+        self.history.push_back(PoseSnapshot { time, odometry: self.odometry, estimate: self.estimate });
+        while self.history.front().is_some_and(|oldest| oldest.time < time - self.history_length) {
+            self.history.pop_front();
+        }
+        self.estimate
+    }
+
+    /// Correct the estimate with a vision pose captured at `timestamp` (s)
+    ///
+    /// Returns false, ignoring the measurement, when it predates the history
+    /// or arrives before any odometry.
+    pub fn add_vision_measurement(&mut self, pose: Pose2d, timestamp: f64) -> bool {
+        let Some(captured) = self.snapshot_at(timestamp) else {
+            return false;
+        };
+
+        let gain = |i: usize| {
+            let q = self.state_std_devs[i].powi(2);
+            let r = self.vision_std_devs[i].powi(2);
+            if q == 0.0 { 0.0 } else { q / (q + (q * r).sqrt()) }
+        };
+        let corrected = Pose2d::new(
+            captured.estimate.x + gain(0) * (pose.x - captured.estimate.x),
+            captured.estimate.y + gain(1) * (pose.y - captured.estimate.y),
+            captured.estimate.heading + gain(2) * wrap_angle(pose.heading - captured.estimate.heading),
+        );
+
+        // Replay the odometry since the capture on top of the corrected pose
+        for snapshot in self.history.iter_mut().filter(|snapshot| snapshot.time >= timestamp) {
+            let moved = relative_pose(captured.odometry, snapshot.odometry);
+            snapshot.estimate = compose_pose(corrected, moved);
+        }
+        self.estimate = compose_pose(corrected, relative_pose(captured.odometry, self.odometry));
+        true
+    }
+
+    /// Snapshot interpolated at `time`, None outside the recorded history
+    fn snapshot_at(&self, time: f64) -> Option<PoseSnapshot> {
+        let newest = self.history.back()?;
+        if time >= newest.time {
+            return Some(*newest);
+        }
+        let after = self.history.iter().position(|snapshot| snapshot.time >= time)?;
+        if after == 0 {
+            return (self.history[0].time == time).then_some(self.history[0]);
+        }
+        let (before, after) = (self.history[after - 1], self.history[after]);
+        let s = (time - before.time) / (after.time - before.time);
+        let lerp = |a: Pose2d, b: Pose2d| Pose2d::new(
+            a.x + s * (b.x - a.x),
+            a.y + s * (b.y - a.y),
+            a.heading + s * wrap_angle(b.heading - a.heading),
+        );
+        Some(PoseSnapshot { time, odometry: lerp(before.odometry, after.odometry), estimate: lerp(before.estimate, after.estimate) })
+    }
+}
+
+/// `to` expressed in the frame of `from`
+fn relative_pose(from: Pose2d, to: Pose2d) -> Pose2d {
+    let (sin, cos) = from.heading.sin_cos();
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    Pose2d::new(dx * cos + dy * sin, -dx * sin + dy * cos, to.heading - from.heading)
+}
+
+/// Pose `relative` (in the frame of `base`) expressed in the field frame
+fn compose_pose(base: Pose2d, relative: Pose2d) -> Pose2d {
+    let (sin, cos) = base.heading.sin_cos();
+    Pose2d::new(
+        base.x + relative.x * cos - relative.y * sin,
+        base.y + relative.x * sin + relative.y * cos,
+        base.heading + relative.heading,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goal_shaper::SCurveGoal;
+
+    #[test]
+    fn test_pose_exp_follows_the_arc() {
+        // Quarter turn on a 1 m radius arc in a single step
+        let (vx, omega, dt) = (1.0, PI / 2.0, 1.0);
+        let twist = Twist2d::from_speeds(ChassisSpeeds::new(vx, 0.0, omega), dt);
+        let pose = Pose2d::default().exp(twist);
+        let radius = vx / omega;
+        assert!((pose.x - radius).abs() < 1e-12);
+        assert!((pose.y - radius).abs() < 1e-12);
+        assert!((pose.heading - PI / 2.0).abs() < 1e-12);
+
+        // A straight segment lands well short of the arc end
+        let straight = (vx * dt, 0.0);
+        assert!((straight.0 - pose.x).hypot(straight.1 - pose.y) > 0.5);
+
+        // Many small steps stay on the same arc
+        let mut stepped = Pose2d::default();
+        for _ in 0..100 {
+            stepped = stepped.exp(Twist2d::from_speeds(ChassisSpeeds::new(vx, 0.0, omega), dt / 100.0));
+        }
+        assert!((stepped.x - pose.x).hypot(stepped.y - pose.y) < 1e-12);
+
+        // Pure translation is unaffected by the small-angle branch
+        let shifted = Pose2d::new(1.0, 2.0, PI).exp(Twist2d::new(0.5, 0.0, 0.0));
+        assert!((shifted.x - 0.5).abs() < 1e-12 && (shifted.y - 2.0).abs() < 1e-12);
+    }
+
+    fn heading_hold() -> HeadingHold {
+        HeadingHold::new(PidfConfig::pid(5.0, 0.0, 0.1).with_limits(-6.0, 6.0))
+    }
+
+    #[test]
+    fn test_heading_hold_corrects_disturbance() {
+        let mut hold = heading_hold();
+        hold.set_locked_heading(0.5);
+
+        // Robot gets knocked 0.4 rad off its locked heading
+        let mut heading = 0.9;
+        let dt = 0.01;
+        for _ in 0..200 {
+            let speeds = hold.calculate(2.0, -1.0, heading, dt);
+            assert_eq!((speeds.vx, speeds.vy), (2.0, -1.0));
+            heading += speeds.omega * dt;
+        }
+
+        assert!((heading - 0.5).abs() < 1e-3, "heading settled at {}", heading);
+    }
+
+    #[test]
+    fn test_heading_hold_turns_short_way_across_pi() {
+        let mut hold = heading_hold();
+        hold.set_locked_heading(PI - 0.05);
+
+        // Just across the +/-pi seam: turning clockwise is 0.1 rad, not 2pi - 0.1
+        let speeds = hold.calculate(0.0, 0.0, -PI + 0.05, 0.01);
+        assert!(speeds.omega < 0.0);
+        assert!(speeds.omega.abs() < 1.0);
+    }
+
+    /// Peak position error following a 1 m radius circle with a kinematic robot
+    fn circle_tracking_error(latency: f64) -> f64 {
+        let mut follower = HolonomicFollower::new(PidfConfig::p(8.0), PidfConfig::p(8.0))
+            .with_pose_latency(latency);
+        let (radius, rate, dt) = (1.0, 2.0, 0.005);
+        // Start off the path so feedback has work to do
+        let mut pose = Pose2d::new(radius + 0.2, 0.0, 0.0);
+        let mut peak_error: f64 = 0.0;
+
+        for i in 0..1000 {
+            let t = i as f64 * dt;
+            let angle = rate * t;
+            let reference = Pose2d::new(radius * angle.cos(), radius * angle.sin(), 0.0);
+            let reference_speeds =
+                ChassisSpeeds::new(-radius * rate * angle.sin(), radius * rate * angle.cos(), 0.0);
+
+            let speeds = follower.calculate(reference, reference_speeds, pose, t, dt);
+            pose.x += speeds.vx * dt;
+            pose.y += speeds.vy * dt;
+            pose.heading += speeds.omega * dt;
+
+            if t > 1.0 {
+                let error = ((pose.x - radius * (rate * (t + dt)).cos()).powi(2)
+                    + (pose.y - radius * (rate * (t + dt)).sin()).powi(2))
+                .sqrt();
+                peak_error = peak_error.max(error);
+            }
+        }
+        peak_error
+    }
+
+    #[test]
+    fn test_pose_latency_degrades_tracking() {
+        let fresh = circle_tracking_error(0.0);
+        let laggy = circle_tracking_error(0.05);
+        let very_laggy = circle_tracking_error(0.15);
+
+        assert!(fresh < 0.01, "fresh pose error {}", fresh);
+        assert!(laggy > fresh && very_laggy > laggy, "{} {} {}", fresh, laggy, very_laggy);
+    }
+
+    #[test]
+    fn test_output_slew_ramps_correction_for_pose_jump() {
+        let dt = 0.01;
+        let reference = Pose2d::new(0.0, 0.0, 0.0);
+        let track = |mut follower: HolonomicFollower| {
+            // The robot is suddenly measured 1 m off a stationary reference
+            let mut pose = Pose2d::new(1.0, 0.0, 0.0);
+            let mut commands = Vec::new();
+            for i in 0..300 {
+                let speeds = follower.calculate(reference, ChassisSpeeds::default(), pose, i as f64 * dt, dt);
+                pose.x += speeds.vx * dt;
+                commands.push(speeds.vx);
+            }
+            (commands, pose.x)
+        };
+
+        let gains = || HolonomicFollower::new(PidfConfig::p(10.0), PidfConfig::p(10.0));
+        let (raw, _) = track(gains());
+        let (ramped, final_x) = track(gains().with_output_slew_rate(8.0, 8.0, 20.0));
+
+        assert!(raw[0] < -9.0, "unlimited follower jumps straight to {}", raw[0]);
+        assert!((ramped[0] + 8.0 * dt).abs() < 1e-9, "first command {}", ramped[0]);
+        for pair in ramped.windows(2) {
+            assert!((pair[1] - pair[0]).abs() <= 8.0 * dt + 1e-9);
+        }
+        assert!(final_x.abs() < 0.01, "settled {} m off the reference", final_x);
+
+        // The same limits as per-axis shapers; an S-curve starts more gently still
+        let (shaped, _) = track(gains().with_goal_shaper(
+            Box::new(SlewRateGoal::new(8.0)),
+            Box::new(SlewRateGoal::new(8.0)),
+            Box::new(SlewRateGoal::new(20.0)),
+        ));
+        assert_eq!(shaped, ramped);
+        let (smooth, _) = track(gains().with_goal_shaper(
+            Box::new(SCurveGoal::new(8.0, 40.0, 400.0)),
+            Box::new(DirectGoal),
+            Box::new(DirectGoal),
+        ));
+        assert!(smooth[0] < 0.0 && smooth[0] > ramped[0], "first command {}", smooth[0]);
+    }
+
+    #[test]
+    fn test_acceleration_feedforward_reduces_tracking_lag() {
+        // The drivetrain reaches its commanded velocity with a 0.1 s lag
+        let (tau, dt) = (0.1, 0.005);
+        let (amplitude, rate) = (1.0, 4.0);
+        let track = |mut follower: HolonomicFollower| {
+            let (mut x, mut vx) = (0.0, amplitude * rate);
+            let mut peak_error: f64 = 0.0;
+            for i in 0..800 {
+                let t = i as f64 * dt;
+                let angle = rate * t;
+                let sample = TrajectorySample {
+                    pose: Pose2d::new(amplitude * angle.sin(), 0.0, 0.0),
+                    speeds: ChassisSpeeds::new(amplitude * rate * angle.cos(), 0.0, 0.0),
+                    acceleration: ChassisSpeeds::new(-amplitude * rate * rate * angle.sin(), 0.0, 0.0),
+                };
+                let command = follower.follow(&sample, Pose2d::new(x, 0.0, 0.0), t, dt);
+                vx += (command.vx - vx) / tau * dt;
+                x += vx * dt;
+                if t > 2.0 {
+                    peak_error = peak_error.max((x - amplitude * (rate * (t + dt)).sin()).abs());
+                }
+            }
+            peak_error
+        };
+
+        let gains = || HolonomicFollower::new(PidfConfig::p(3.0), PidfConfig::p(3.0));
+        let velocity_only = track(gains());
+        let with_acceleration = track(gains().with_acceleration_feedforward(tau));
+        assert!(velocity_only > 0.05, "velocity feedforward alone lags by {}", velocity_only);
+        assert!(with_acceleration < 0.25 * velocity_only, "{} vs {}", with_acceleration, velocity_only);
+    }
+
+    fn square_kinematics() -> SwerveKinematics {
+        SwerveKinematics::new(vec![[0.3, 0.3], [0.3, -0.3], [-0.3, 0.3], [-0.3, -0.3]])
+    }
+
+    #[test]
+    fn test_rotation_about_front_module_holds_it_still() {
+        let kinematics = square_kinematics();
+        let front_left = kinematics.module_positions[0];
+        let states = kinematics.to_module_states(ChassisSpeeds::new(0.0, 0.0, 2.0), Some(front_left));
+
+        assert!(states[0].speed.abs() < 1e-9);
+        for state in &states[1..] {
+            assert!(state.speed > 1.0, "module speed {}", state.speed);
+        }
+        // The diagonal module is farthest from the pivot and moves fastest
+        assert!((states[3].speed - 2.0 * 0.6 * 2.0_f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_kinematics_desaturates_module_speeds() {
+        let kinematics = square_kinematics().with_max_module_speed(4.0);
+        let states = kinematics.constrained_module_states(ChassisSpeeds::new(4.0, 0.0, 4.0), &[], 0.02);
+
+        let fastest = states.iter().map(|s| s.speed).fold(0.0, f64::max);
+        assert!((fastest - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_nearest_feasible_scales_command_to_module_limit() {
+        let kinematics = square_kinematics();
+        let fast = ChassisSpeeds::new(3.0, 1.0, 4.0);
+        assert!(!kinematics.is_feasible(fast, 4.0));
+        assert!(kinematics.is_feasible(ChassisSpeeds::new(1.0, 0.0, 1.0), 4.0));
+
+        let limited = kinematics.nearest_feasible(fast, 4.0);
+        assert!(kinematics.is_feasible(limited, 4.0));
+        let fastest = kinematics.to_module_states(limited, None).iter().map(|s| s.speed).fold(0.0, f64::max);
+        assert!((fastest - 4.0).abs() < 1e-9);
+        // Same direction: every component shrinks by the same factor
+        let scale = limited.vx / fast.vx;
+        assert!(scale < 1.0);
+        assert!((limited.vy - scale * fast.vy).abs() < 1e-12 && (limited.omega - scale * fast.omega).abs() < 1e-12);
+
+        let slow = ChassisSpeeds::new(1.0, 0.0, 1.0);
+        assert_eq!(kinematics.nearest_feasible(slow, 4.0), slow);
+    }
+
+    #[test]
+    fn test_step_command_is_acceleration_limited() {
+        let kinematics = square_kinematics().with_max_module_acceleration(10.0);
+        let dt = 0.02;
+        let target = ChassisSpeeds::new(3.0, 1.0, 2.0);
+
+        let mut states = vec![SwerveModuleState::default(); 4];
+        let mut steps = 0;
+        while states != kinematics.to_module_states(target, None) {
+            let next = kinematics.constrained_module_states(target, &states, dt);
+            for (prev, cmd) in states.iter().zip(&next) {
+                let (px, py) = prev.velocity();
+                let (nx, ny) = cmd.velocity();
+                assert!((nx - px).hypot(ny - py) <= 10.0 * dt + 1e-9);
+            }
+            states = next;
+            steps += 1;
+            assert!(steps < 100, "never reached the commanded speeds");
+        }
+        // The fastest module needs several steps to cover its ~4 m/s change
+        assert!(steps >= 15, "reached target in {} steps", steps);
+    }
+
+    #[test]
+    fn test_pure_yaw_moment_splits_left_and_right() {
+        let vectoring = TorqueVectoring::new(square_kinematics().module_positions)
+            .with_traction_limit(200.0)
+            .with_force_per_duty(400.0);
+        let angles = [0.0; 4];
+        let commands = vectoring.allocate([0.0, 0.0], 60.0, &angles);
+
+        // Left modules (y > 0) pull back, right modules push forward
+        for (command, [_, y]) in commands.iter().zip(&vectoring.module_positions) {
+            assert!(command.force * y < 0.0, "force {} at y {}", command.force, y);
+            assert!((command.duty - command.force / 400.0).abs() < 1e-12);
+        }
+        let net_force: f64 = commands.iter().map(|c| c.force).sum();
+        let moment: f64 = commands
+            .iter()
+            .zip(&vectoring.module_positions)
+            .map(|(c, [_, y])| -y * c.force)
+            .sum();
+        assert!(net_force.abs() < 1e-9);
+        assert!((moment - 60.0).abs() < 1e-9, "moment {}", moment);
+
+        // Asking for more than traction allows scales everything down together
+        let limited = vectoring.allocate([800.0, 0.0], 60.0, &angles);
+        let peak = limited.iter().map(|c| c.force.abs()).fold(0.0, f64::max);
+        assert!((peak - 200.0).abs() < 1e-9);
+        assert!(limited.iter().all(|c| c.force > 0.0));
+    }
+
+    #[test]
+    fn test_obstacle_ahead_slows_approach() {
+        let avoidance = ObstacleAvoidance::new(0.4).with_time_horizon(1.0);
+        let obstacle = CircleObstacle::new(2.0, 0.0, 0.3);
+        let command = ChassisSpeeds::new(2.0, 0.0, 0.5);
+
+        // Far away the collision is beyond the horizon
+        let far = avoidance.calculate(command, Pose2d::new(-2.0, 0.0, 0.0), &[obstacle]);
+        assert_eq!(far, command);
+
+        let mut previous = command.vx;
+        for x in [-0.5, 0.0, 0.5, 1.0] {
+            let clamped = avoidance.calculate(command, Pose2d::new(x, 0.0, 0.0), &[obstacle]);
+            assert!(clamped.vx < previous, "vx {} at x {}", clamped.vx, x);
+            assert!(clamped.vy.abs() < 1e-12);
+            assert_eq!(clamped.omega, 0.5);
+            previous = clamped.vx;
+        }
+        // Touching the obstacle stops all forward motion
+        let touching = avoidance.calculate(command, Pose2d::new(1.3, 0.0, 0.0), &[obstacle]);
+        assert!(touching.vx.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_clear_path_passes_command_unchanged() {
+        let avoidance = ObstacleAvoidance::new(0.4);
+        // Off to the side and behind the robot
+        let obstacles = [CircleObstacle::new(1.0, 2.0, 0.3), CircleObstacle::new(-1.0, 0.0, 0.3)];
+        let command = ChassisSpeeds::new(2.0, 0.0, 0.0);
+        assert_eq!(avoidance.calculate(command, Pose2d::default(), &obstacles), command);
+    }
+
+    #[test]
+    fn test_stick_inside_deadband_commands_nothing() {
+        let mut conditioner = InputConditioner::new(4.0, 6.0).with_deadband(0.1).with_expo(0.5);
+        for raw in [0.0, 0.05, -0.1, 0.099] {
+            assert_eq!(conditioner.condition_axis(raw), 0.0);
+        }
+        assert_eq!(conditioner.calculate(0.08, -0.03, 0.1, 0.02), ChassisSpeeds::default());
+        // Just outside the deadband the output starts near zero, not at 0.1
+        assert!(conditioner.condition_axis(0.11).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_expo_curve_is_monotonic_through_full_scale() {
+        let conditioner = InputConditioner::new(4.0, 6.0).with_deadband(0.05).with_expo(0.8);
+        assert!((conditioner.condition_axis(1.0) - 1.0).abs() < 1e-12);
+        assert!((conditioner.condition_axis(-1.0) + 1.0).abs() < 1e-12);
+
+        let mut previous = conditioner.condition_axis(-1.0);
+        for i in 1..=200 {
+            let value = conditioner.condition_axis(-1.0 + i as f64 * 0.01);
+            assert!(value >= previous, "curve fell at {}", -1.0 + i as f64 * 0.01);
+            previous = value;
+        }
+        // Expo softens the response at half stick
+        assert!(conditioner.condition_axis(0.5) < 0.5 * (0.5 - 0.05) / 0.95 + 1e-12);
+    }
+
+    #[test]
+    fn test_slew_limit_caps_command_rate() {
+        let mut conditioner = InputConditioner::new(4.0, 6.0).with_slew_rate(8.0, 20.0);
+        let dt = 0.02;
+        let mut previous = ChassisSpeeds::default();
+        for _ in 0..10 {
+            let speeds = conditioner.calculate(1.0, 0.0, -1.0, dt);
+            assert!((speeds.vx - previous.vx - 8.0 * dt).abs() < 1e-12);
+            assert!((speeds.omega - previous.omega + 20.0 * dt).abs() < 1e-12);
+            previous = speeds;
+        }
+        // Eventually reaches the full-stick speed
+        for _ in 0..100 {
+            previous = conditioner.calculate(1.0, 0.0, -1.0, dt);
+        }
+        assert_eq!((previous.vx, previous.omega), (4.0, -6.0));
+    }
+
+    #[test]
+    fn test_battery_governor_slows_robot_at_low_charge() {
+        let mut governor = BatteryGovernor::new(0.3, 0.5).with_min_soc(0.1);
+        let command = ChassisSpeeds::new(3.0, -2.0, 4.0);
+        let battery_at = |soc| BatteryState { state_of_charge: soc, ..BatteryState::default() };
+
+        assert_eq!(governor.calculate(command, &battery_at(1.0), 0.02), command);
+        assert_eq!(governor.calculate(command, &battery_at(0.3), 0.02), command);
+
+        let low = governor.calculate(command, &battery_at(0.05), 0.02);
+        let magnitude = |s: ChassisSpeeds| s.vx.hypot(s.vy);
+        assert!((magnitude(low) - 0.5 * magnitude(command)).abs() < 1e-12);
+        assert!((low.omega - 2.0).abs() < 1e-12);
+        // Halfway through the taper the command is scaled by 0.75
+        assert!((governor.scale(0.2) - 0.75).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_charge_station_balance_levels_platform_and_settles() {
+        // Seesaw platform pivoting at its center with a centering spring.
+        // Pitch is positive with the +x end up, so a robot facing +x on the
+        // -x side sees its nose up.
+        let (mass, gravity, dt) = (55.0, 9.81, 1e-3);
+        let (inertia, spring, damping, stop) = (25.0, 150.0, 80.0, 15.0_f64.to_radians());
+        let balance = ChargeStationBalance::new(1.0, 0.5).with_pitch_rate_damping(3.0);
+
+        let (mut x, mut v) = (-0.55, 0.0);
+        let (mut pitch, mut pitch_rate) = (stop, 0.0);
+        let mut pitches = Vec::new();
+        for _ in 0..10000 {
+            let command = balance.calculate(pitch, pitch_rate);
+            // The drivetrain tracks the command with a 0.1 s lag
+            v += (command.vx - v) / 0.1 * dt;
+            x += v * dt;
+            let torque = -mass * gravity * x * pitch.cos() - spring * pitch - damping * pitch_rate;
+            pitch_rate += torque / inertia * dt;
+            pitch += pitch_rate * dt;
+            if pitch.abs() >= stop {
+                pitch = pitch.clamp(-stop, stop);
+                pitch_rate = 0.0;
+            }
+            pitches.push(pitch);
+        }
+
+        // The robot climbs toward the pivot and the platform levels out
+        assert!(x > -0.1 && x < 0.1, "robot stopped at x = {}", x);
+        assert!(balance.is_balanced(pitch), "final pitch {}", pitch.to_degrees());
+        assert!(v.abs() < 1e-3);
+        // No sustained oscillation over the last 3 s
+        let tail_peak = pitches[7000..].iter().fold(0.0_f64, |peak, p| peak.max(p.abs()));
+        assert!(tail_peak <= balance.tolerance + 1e-3, "pitch still swinging to {} deg", tail_peak.to_degrees());
+
+        // Level and IMU readings route through the same law
+        assert_eq!(balance.calculate(0.01, 0.0).vx, 0.0);
+        let imu = [0.0, 0.2, 0.0, 0.0, -0.05, 0.0];
+        assert_eq!(balance.calculate_from_imu(&imu).vx, balance.calculate(0.2, -0.05).vx);
+    }
+
+    #[test]
+    fn test_field_transform_flips_between_alliance_origins() {
+        let near_blue = Pose2d::new(0.5, 1.0, 0.3);
+        for symmetry in [FieldSymmetry::Mirrored, FieldSymmetry::Rotational] {
+            let field = FieldTransform::new(16.54, 8.21).with_symmetry(symmetry);
+
+            // Flipping twice is the identity
+            let twice = field.flip_pose(field.flip_pose(near_blue));
+            assert!((twice.x - near_blue.x).abs() < 1e-12 && (twice.y - near_blue.y).abs() < 1e-12);
+            assert!(wrap_angle(twice.heading - near_blue.heading).abs() < 1e-12);
+
+            // Half a meter off the blue wall becomes half a meter off the red wall
+            let flipped = field.flip_pose(near_blue);
+            assert!((flipped.x - 16.04).abs() < 1e-9, "{:?}", flipped);
+
+            // A sample driving away from the blue wall drives away from the red one
+            let sample = TrajectorySample {
+                pose: near_blue,
+                speeds: ChassisSpeeds::new(2.0, 0.5, 1.0),
+                acceleration: ChassisSpeeds::new(1.0, 0.0, 0.0),
+            };
+            let flipped = field.flip_trajectory(&[sample])[0];
+            assert_eq!(flipped.speeds.vx, -2.0);
+            assert_eq!(flipped.acceleration.vx, -1.0);
+            let back = field.flip_sample(&flipped);
+            assert_eq!((back.speeds, back.acceleration), (sample.speeds, sample.acceleration));
+        }
+
+        // Mirroring keeps y and reflects the heading; rotation turns it around
+        let mirrored = FieldTransform::new(16.54, 8.21).flip_pose(near_blue);
+        assert_eq!(mirrored.y, 1.0);
+        assert!((mirrored.heading - (PI - 0.3)).abs() < 1e-12);
+        let rotated = FieldTransform::new(16.54, 8.21).with_symmetry(FieldSymmetry::Rotational).flip_pose(near_blue);
+        assert!((rotated.y - 7.21).abs() < 1e-9);
+        assert!((rotated.heading - (0.3 - PI)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_battery_governor_limits_acceleration_more_when_low() {
+        let low = BatteryState { state_of_charge: 0.0, ..BatteryState::default() };
+        let mut governor = BatteryGovernor::new(0.3, 0.5).with_max_acceleration(10.0);
+        let speeds = governor.calculate(ChassisSpeeds::new(4.0, 0.0, 0.0), &low, 0.02);
+        assert!((speeds.vx - 0.5 * 10.0 * 0.02).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_forward_kinematics_inverts_module_states() {
+        let kinematics = SwerveKinematics::new(vec![[0.3, 0.3], [0.3, -0.3], [-0.3, 0.3], [-0.3, -0.3]]);
+        let speeds = ChassisSpeeds::new(1.5, -0.5, 2.0);
+        let recovered = kinematics.to_chassis_speeds(&kinematics.to_module_states(speeds, None));
+        assert!((recovered.vx - speeds.vx).abs() < 1e-9);
+        assert!((recovered.vy - speeds.vy).abs() < 1e-9);
+        assert!((recovered.omega - speeds.omega).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_vision_corrects_odometry_drift() {
+        use simcore::NoiseRng;
+
+        let kinematics = SwerveKinematics::new(vec![[0.3, 0.3], [0.3, -0.3], [-0.3, 0.3], [-0.3, -0.3]]);
+        let mut odometry_only = PoseEstimator::new(kinematics.clone(), Pose2d::default());
+        let mut fused = PoseEstimator::new(kinematics.clone(), Pose2d::default())
+            .with_vision_std_devs(0.05, 0.05, 0.05);
+        let mut rng = NoiseRng::new(7);
+
+        // Drive a circle; the wheels over-read by 3% and jitter
+        let speeds = ChassisSpeeds::new(2.0, 0.0, 0.5);
+        let (dt, latency) = (0.02, 0.06);
+        let mut truth = vec![Pose2d::default()];
+        for step in 1..=500 {
+            let time = step as f64 * dt;
+            truth.push(truth[step - 1].exp(Twist2d::from_speeds(speeds, dt)));
+
+            let measured: Vec<SwerveModuleState> = kinematics.to_module_states(speeds, None).into_iter()
+                .map(|state| SwerveModuleState { speed: state.speed * 1.03 + 0.05 * rng.gaussian(), ..state })
+                .collect();
+            odometry_only.update(time, &measured, dt);
+            fused.update(time, &measured, dt);
+
+            // Every 0.1 s a camera frame captured `latency` ago arrives
+            if step % 5 == 0 {
+                let captured = truth[step - (latency / dt).round() as usize];
+                let seen = Pose2d::new(
+                    captured.x + 0.02 * rng.gaussian(),
+                    captured.y + 0.02 * rng.gaussian(),
+                    captured.heading + 0.01 * rng.gaussian(),
+                );
+                assert!(fused.add_vision_measurement(seen, time - latency));
+            }
+        }
+
+        let error = |pose: Pose2d| (pose.x - truth[500].x).hypot(pose.y - truth[500].y);
+        assert!(error(odometry_only.pose()) > 0.3, "odometry error {}", error(odometry_only.pose()));
+        assert!(error(fused.pose()) < 0.1, "fused error {}", error(fused.pose()));
+        assert!(wrap_angle(fused.pose().heading - truth[500].heading).abs() < 0.02);
+        // The fused estimator's raw odometry drifts just the same
+        assert_eq!(fused.odometry_pose(), odometry_only.pose());
+
+        // Frames older than the history are ignored
+        assert!(!fused.add_vision_measurement(Pose2d::default(), 0.0));
+    }
+}
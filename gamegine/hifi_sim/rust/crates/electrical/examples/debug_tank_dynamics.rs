@@ -25,6 +25,8 @@ fn main() {
     for _ in 0..4 {
         motors.add_motor(MotorConstant::kraken_x60());
     }
+    // Motor velocities follow the wheels through the reduction each step
+    motors.gear_ratios = vec![GEAR_RATIO; 4];
 
     // Initialize battery
     let mut batt = Battery {
@@ -63,6 +65,7 @@ fn main() {
                 tire_load: MASS * G / 4.0,
             },
             angle: 0.0,
+            contact_fraction: 1.0,
         })
         .collect();
 
@@ -98,26 +101,20 @@ fn main() {
             input.duty_cycle_d = 0.0;
         }
 
-        // 1. Update motor mechanical velocity from wheel state
-        for i in 0..4 {
-            bus.true_state.motors[i].mechanical_velocity =
-                bus.true_state.wheel_states[i].driving_angular_velocity * GEAR_RATIO;
-        }
-
-        // 2. Step electrical (motor currents and torques)
+        // 1. Step electrical (motor currents and torques)
         motors.step_electrical(ctx, &mut bus);
 
-        // 3. Update wheel kinematics for tire model (stationary robot for now)
+        // 2. Update wheel kinematics for tire model (stationary robot for now)
         for i in 0..4 {
             let wheel = &mut bus.true_state.wheel_states[i];
             wheel.longitudinal_translational_velocity = v;
             wheel.lateral_translational_velocity = 0.0;
         }
 
-        // 4. Step tire model
+        // 3. Step tire model
         tires.step_physics(ctx, &mut bus);
 
-        // 5. Wheel rotational dynamics
+        // 4. Wheel rotational dynamics
         for i in 0..4 {
             let fx = bus.true_state.wheel_states[i].tire.longitudinal_force;
             let tq_motor = bus.true_state.motors[i].applied_torque;
@@ -129,7 +126,7 @@ fn main() {
             bus.true_state.wheel_states[i].driving_angular_velocity = omega + domega * DT;
         }
 
-        // 6. Sum forces on chassis (simplified - just sum fx)
+        // 5. Sum forces on chassis (simplified - just sum fx)
         let mut f_long_total = 0.0;
         for i in 0..4 {
             f_long_total += bus.true_state.wheel_states[i].tire.longitudinal_force;
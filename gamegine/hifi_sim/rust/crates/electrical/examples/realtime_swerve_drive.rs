@@ -161,11 +161,9 @@ struct App {
 
 impl App {
     fn new() -> Self {
-        // 4 drive motors
-        let mut drive_motors = MotorBank::default();
-        for _ in 0..4 {
-            drive_motors.add_motor(MotorConstant::kraken_x60());
-        }
+        // 4 drive motors, each geared to its wheel
+        let drive_motors = MotorBank::new(vec![MotorConstant::kraken_x60(); 4])
+            .with_gear_ratios(vec![DRIVE_GEAR_RATIO; 4]);
 
         // 4 steer motors (smaller, like NEO 550 or Falcon 500 steer)
         let mut steer_motors = MotorBank::default();
@@ -194,6 +192,7 @@ impl App {
                     tire_load: MASS * G / 4.0,
                 },
                 angle: 0.0,
+                contact_fraction: 1.0,
             })
             .collect();
 
@@ -334,11 +333,7 @@ impl App {
             while t_inner < outer_dt {
                 let dt = (outer_dt - t_inner).min(DT_ELEC);
                 
-                // Drive motors
-                for i in 0..4 {
-                    let omega_wheel = self.drive_bus.true_state.wheel_states[i].driving_angular_velocity;
-                    self.drive_bus.true_state.motors[i].mechanical_velocity = omega_wheel * DRIVE_GEAR_RATIO;
-                }
+                // Drive motors (the bank syncs motor speed from the wheels)
                 self.drive_motors.step_electrical(SimContext { dt, t: self.t + t_inner }, &mut self.drive_bus);
                 
                 // Steer motors
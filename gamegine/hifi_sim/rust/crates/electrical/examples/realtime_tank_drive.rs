@@ -245,10 +245,9 @@ struct App {
 impl App {
     fn new() -> Self {
         let batt = Battery { constants: BatteryConstant::default() };
-        let mut motors = MotorBank::default();
-        // 4 identical motors
-        let mconst = MotorConstant::kraken_x60();
-        for _ in 0..4 { motors.add_motor(mconst); }
+        // 4 identical motors, each geared to its wheel
+        let motors = MotorBank::new(vec![MotorConstant::kraken_x60(); 4])
+            .with_gear_ratios(vec![GEAR_RATIO; 4]);
 
         let mut bus = SimState::default();
         bus.control_input.motor_inputs = vec![MotorInput { duty_cycle_d: 0.0, duty_cycle_q: 0.0 }; 4];
@@ -262,6 +261,7 @@ impl App {
             lateral_translational_velocity: 0.0,
             tire: TireState { slip_angle: 0.0, slip_ratio: 0.0, longitudinal_force: 0.0, lateral_force: 0.0, tire_load: MASS * G / 4.0 },
             angle: 0.0,
+            contact_fraction: 1.0,
         }).collect();
 
         // Tire manager with 4 identical tires
@@ -322,12 +322,8 @@ impl App {
 
     fn reset(&mut self) {
         self.batt = Battery { constants: BatteryConstant::default() };
-        self.motors = {
-            let mut m = MotorBank::default();
-            let mc = MotorConstant::kraken_x60();
-            for _ in 0..4 { m.add_motor(mc); }
-            m
-        };
+        self.motors = MotorBank::new(vec![MotorConstant::kraken_x60(); 4])
+            .with_gear_ratios(vec![GEAR_RATIO; 4]);
         self.bus = SimState::default();
         self.bus.control_input.motor_inputs = vec![MotorInput { duty_cycle_d: 0.0, duty_cycle_q: 0.0 }; 4];
         self.bus.true_state.motors = vec![MotorState::default(); 4];
@@ -339,6 +335,7 @@ impl App {
             lateral_translational_velocity: 0.0,
             tire: TireState { slip_angle: 0.0, slip_ratio: 0.0, longitudinal_force: 0.0, lateral_force: 0.0, tire_load: MASS * G / 4.0 },
             angle: 0.0,
+            contact_fraction: 1.0,
         }).collect();
         // reset tire manager
         self.tires.reset();
@@ -371,14 +368,9 @@ impl App {
             while t_inner < outer_dt {
                 let dt = (outer_dt - t_inner).min(DT_ELEC);
 
-                // Provide current mechanical velocities to the motor model from wheel rotational state
+                // Step electrical model for all 4 motors; the bank takes each
+                // motor's velocity from its wheel's rotational state
                 // Map wheels: 0=FL,1=RL,2=FR,3=RR; motors index the same
-                for i in 0..4 {
-                    let omega_wheel = self.bus.true_state.wheel_states[i].driving_angular_velocity;
-                    self.bus.true_state.motors[i].mechanical_velocity = omega_wheel * GEAR_RATIO;
-                }
-
-                // Step electrical model for all 4 motors
                 self.motors
                     .step_electrical(SimContext { dt, t: self.t + t_inner }, &mut self.bus);
 
@@ -6,6 +6,12 @@
 use crate::battery::{BatteryConstant, default_ocv_from_soc, default_r0_from_soc};
 use crate::motor::MotorConstant;
 
+/// Fractional rise in copper winding resistance per degree C
+pub const COPPER_TEMPERATURE_COEFFICIENT: f64 = 0.003_93;
+
+/// Winding temperature at which motor resistances are specified (C)
+pub const NOMINAL_WINDING_TEMPERATURE: f64 = 25.0;
+
 /// Data point for torque-velocity curve
 #[derive(Debug, Clone, Copy)]
 pub struct TorqueVelocityPoint {
@@ -124,6 +130,28 @@ impl MotorConstant {
         }
     }
 
+    /// Winding resistance at `temp_c` degrees C (ohm)
+    ///
+    /// Copper resistance rises linearly from its value at
+    /// `NOMINAL_WINDING_TEMPERATURE`.
+    pub fn resistance_at_temp(&self, temp_c: f64) -> f64 {
+        self.resistance * (1.0 + COPPER_TEMPERATURE_COEFFICIENT * (temp_c - NOMINAL_WINDING_TEMPERATURE))
+    }
+
+    /// This motor with its windings at `temp_c` degrees C
+    pub fn at_temperature(&self, temp_c: f64) -> MotorConstant {
+        MotorConstant { resistance: self.resistance_at_temp(temp_c), ..*self }
+    }
+
+    /// Torque-velocity curve with the windings at `temp_c` degrees C
+    ///
+    /// The hotter winding's higher resistance passes less current at every
+    /// speed, cutting stall torque and power. The free speed is set by the
+    /// back-EMF alone and does not change.
+    pub fn torque_velocity_curve_at_temp(&self, voltage: f64, temp_c: f64, n_points: usize) -> MotorAnalysisResult {
+        self.at_temperature(temp_c).torque_velocity_curve(voltage, n_points)
+    }
+
     /// Find the velocity at which maximum power occurs
     pub fn max_power_velocity(&self, voltage: f64) -> f64 {
         // For a DC motor with linear torque-speed, max power is at half free speed
@@ -145,6 +173,262 @@ impl MotorConstant {
         let gear_ratio = optimal_motor_speed / desired_wheel_speed_rad_s;
         (gear_ratio, optimal_motor_speed / gear_ratio)
     }
+
+    /// Steady-state efficiency driving `load_torque` (Nm) at `load_speed`
+    /// (rad/s) through `gear_ratio`, with at most `voltage` available
+    ///
+    /// Returns 0 if the operating point needs more than `voltage` or delivers
+    /// no mechanical power.
+    pub fn efficiency_at_load(&self, load_torque: f64, load_speed: f64, gear_ratio: f64, voltage: f64) -> f64 {
+        let motor_speed = gear_ratio * load_speed.abs();
+        let current = load_torque.abs() / gear_ratio / self.kt();
+        let back_emf = self.ke() * motor_speed;
+        let required_voltage = current * self.resistance + back_emf;
+        if required_voltage > voltage || required_voltage <= 0.0 || current <= 0.0 {
+            return 0.0;
+        }
+        back_emf / required_voltage
+    }
+
+    /// Gear ratio maximizing average efficiency over a duty profile
+    ///
+    /// `load_torques` and `load_speeds` pair up into representative operating
+    /// points at the load side. Ratios are searched on a log grid; a point the
+    /// motor cannot reach at a given ratio counts as zero efficiency there. With
+    /// only resistive losses modelled the best ratio runs the motor as fast as
+    /// the voltage allows, which is where this model's efficiency peaks.
+    pub fn gear_ratio_for_duty_cycle(&self, load_torques: &[f64], load_speeds: &[f64], voltage: f64) -> f64 {
+        const MIN_RATIO: f64 = 0.1;
+        const MAX_RATIO: f64 = 500.0;
+        const COARSE_STEPS: usize = 2000;
+        const FINE_STEPS: usize = 200;
+
+        let average_efficiency = |ratio: f64| -> f64 {
+            let mut total = 0.0;
+            let mut count = 0;
+            for (&torque, &speed) in load_torques.iter().zip(load_speeds) {
+                total += self.efficiency_at_load(torque, speed, ratio, voltage);
+                count += 1;
+            }
+            if count == 0 { 0.0 } else { total / count as f64 }
+        };
+        let best_on_grid = |low: f64, high: f64, steps: usize| -> f64 {
+            let log_step = (high / low).ln() / steps as f64;
+            let mut best = (low, average_efficiency(low));
+            for i in 1..=steps {
+                let ratio = low * (log_step * i as f64).exp();
+                let efficiency = average_efficiency(ratio);
+                if efficiency > best.1 {
+                    best = (ratio, efficiency);
+                }
+            }
+            best.0
+        };
+
+        // Coarse log grid, then refine between the neighbours of the best ratio
+        let coarse = best_on_grid(MIN_RATIO, MAX_RATIO, COARSE_STEPS);
+        let spacing = (MAX_RATIO / MIN_RATIO).ln() / COARSE_STEPS as f64;
+        best_on_grid(coarse * (-spacing).exp(), coarse * spacing.exp(), FINE_STEPS)
+    }
+}
+
+// ============================================================================
+// Motor Selection
+// ============================================================================
+
+/// Operating point a mechanism needs from its motor and gearbox
+#[derive(Debug, Clone, Copy)]
+pub struct MotorRequirement {
+    /// Load-side torque (Nm)
+    pub torque: f64,
+    /// Load-side speed (rad/s)
+    pub speed: f64,
+    /// Fraction of the time spent at this operating point, in (0, 1]
+    pub duty_cycle: f64,
+    /// Supply voltage (V)
+    pub voltage: f64,
+    /// Fractional margin on torque and speed, e.g. 0.2 for 20%
+    pub margin: f64,
+    /// Continuous (RMS) current a motor may draw (A)
+    pub continuous_current_limit: f64,
+}
+
+/// A motor preset and gear ratio that meets a `MotorRequirement`
+#[derive(Debug, Clone, Copy)]
+pub struct MotorRecommendation {
+    /// Preset name, as accepted by `MotorConstant::from_preset`
+    pub preset: &'static str,
+    /// Motor turns per load turn
+    pub gear_ratio: f64,
+    /// Motor current at the operating point (A)
+    pub current: f64,
+    /// Current averaged over the duty cycle, RMS (A)
+    pub rms_current: f64,
+    /// Motor efficiency at the operating point
+    pub efficiency: f64,
+    /// Unused fraction of the continuous current limit
+    pub current_headroom: f64,
+}
+
+impl MotorRequirement {
+    /// Requirement at 12 V with 20% margin and a 40 A continuous limit
+    pub fn new(torque: f64, speed: f64, duty_cycle: f64) -> Self {
+        MotorRequirement {
+            torque: torque.abs(),
+            speed: speed.abs(),
+            duty_cycle: duty_cycle.clamp(1e-6, 1.0),
+            voltage: 12.0,
+            margin: 0.2,
+            continuous_current_limit: 40.0,
+        }
+    }
+
+    /// Supply voltage (V)
+    pub fn with_voltage(mut self, voltage: f64) -> Self {
+        self.voltage = voltage;
+        self
+    }
+
+    /// Fractional margin applied to both torque and speed
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin.max(0.0);
+        self
+    }
+
+    /// Continuous (RMS) current limit per motor (A)
+    pub fn with_current_limit(mut self, current_limit: f64) -> Self {
+        self.continuous_current_limit = current_limit;
+        self
+    }
+
+    /// How `motor` geared at `gear_ratio` would run this requirement, if it can
+    ///
+    /// Feasible when the motor reaches the torque and speed plus margin within
+    /// the supply voltage, and its RMS current over the duty cycle stays under
+    /// the continuous limit.
+    pub fn evaluate(&self, preset: &'static str, motor: &MotorConstant, gear_ratio: f64) -> Option<MotorRecommendation> {
+        if gear_ratio <= 0.0 {
+            return None;
+        }
+        let scale = 1.0 + self.margin;
+        let margin_current = self.torque * scale / gear_ratio / motor.kt();
+        let margin_voltage = margin_current * motor.resistance + motor.ke() * self.speed * scale * gear_ratio;
+        if margin_voltage > self.voltage {
+            return None;
+        }
+
+        let current = self.torque / gear_ratio / motor.kt();
+        let rms_current = current * self.duty_cycle.sqrt();
+        if rms_current > self.continuous_current_limit {
+            return None;
+        }
+        Some(MotorRecommendation {
+            preset,
+            gear_ratio,
+            current,
+            rms_current,
+            efficiency: motor.efficiency_at_load(self.torque, self.speed, gear_ratio, self.voltage),
+            current_headroom: 1.0 - rms_current / self.continuous_current_limit,
+        })
+    }
+
+    /// Every feasible preset motor and candidate ratio, best first
+    ///
+    /// Ranked by efficiency at the operating point, with current headroom
+    /// breaking ties.
+    pub fn recommend(&self, gear_ratios: &[f64]) -> Vec<MotorRecommendation> {
+        let mut feasible: Vec<MotorRecommendation> = MotorConstant::PRESET_NAMES
+            .iter()
+            .filter_map(|&name| MotorConstant::from_preset(name).map(|motor| (name, motor)))
+            .flat_map(|(name, motor)| {
+                gear_ratios.iter().filter_map(move |&ratio| self.evaluate(name, &motor, ratio))
+            })
+            .collect();
+        feasible.sort_by(|a, b| {
+            b.efficiency.total_cmp(&a.efficiency)
+                .then(b.current_headroom.total_cmp(&a.current_headroom))
+        });
+        feasible
+    }
+}
+
+/// `count` gear ratios spaced evenly in log between `min` and `max`
+pub fn log_spaced_ratios(min: f64, max: f64, count: usize) -> Vec<f64> {
+    if count < 2 {
+        return vec![min; count];
+    }
+    let log_step = (max / min).ln() / (count - 1) as f64;
+    (0..count).map(|i| min * (log_step * i as f64).exp()).collect()
+}
+
+// ============================================================================
+// Output Units
+// ============================================================================
+
+/// RPM per rad/s
+pub const RAD_S_TO_RPM: f64 = 60.0 / (2.0 * std::f64::consts::PI);
+/// lb*ft per N*m
+pub const NM_TO_LB_FT: f64 = 0.737_562_149;
+/// Mechanical horsepower per watt
+pub const W_TO_HP: f64 = 1.0 / 745.699_872;
+
+/// Unit system for reporting analysis results
+///
+/// Computation is always done in SI; results are converted on the way out.
+/// Imperial reports speed in RPM, torque in lb*ft and power in hp. Current
+/// and efficiency are unitless across systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    /// rad/s, N*m, W
+    #[default]
+    Si,
+    /// RPM, lb*ft, hp
+    Imperial,
+}
+
+impl UnitSystem {
+    /// Parse "si" or "imperial" (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "si" | "metric" => Some(UnitSystem::Si),
+            "imperial" => Some(UnitSystem::Imperial),
+            _ => None,
+        }
+    }
+
+    /// Convert an angular speed from rad/s
+    pub fn speed(self, rad_s: f64) -> f64 {
+        match self {
+            UnitSystem::Si => rad_s,
+            UnitSystem::Imperial => rad_s * RAD_S_TO_RPM,
+        }
+    }
+
+    /// Convert a torque from N*m
+    pub fn torque(self, nm: f64) -> f64 {
+        match self {
+            UnitSystem::Si => nm,
+            UnitSystem::Imperial => nm * NM_TO_LB_FT,
+        }
+    }
+
+    /// Convert a power from W
+    pub fn power(self, watts: f64) -> f64 {
+        match self {
+            UnitSystem::Si => watts,
+            UnitSystem::Imperial => watts * W_TO_HP,
+        }
+    }
+}
+
+impl MotorAnalysisResult {
+    /// Convert speeds, torques and powers to the given unit system
+    pub fn in_units(mut self, units: UnitSystem) -> Self {
+        self.velocities.iter_mut().for_each(|v| *v = units.speed(*v));
+        self.torques.iter_mut().for_each(|t| *t = units.torque(*t));
+        self.powers.iter_mut().for_each(|p| *p = units.power(*p));
+        self
+    }
 }
 
 // ============================================================================
@@ -242,10 +526,7 @@ pub fn voltage_sag_analysis(constants: &BatteryConstant, peak_current: f64) -> (
     let mut soc_at_min = 1.0;
 
     for &soc in &test_socs {
-        let ocv = (constants.open_circuit_voltage_function)(soc);
-        let r0 = (constants.ohmic_resistance_function)(soc);
-        // Simplified: just ohmic drop, no transient polarization
-        let voltage = ocv - peak_current * r0;
+        let voltage = voltage_under_load(constants, peak_current, soc);
         
         if voltage < min_voltage {
             min_voltage = voltage;
@@ -256,6 +537,60 @@ pub fn voltage_sag_analysis(constants: &BatteryConstant, peak_current: f64) -> (
     (min_voltage, soc_at_min)
 }
 
+/// Instantaneous terminal voltage at a given current and state of charge
+///
+/// Computes OCV(soc) - I * R0(soc), i.e. the ohmic sag only, ignoring
+/// transient polarization.
+pub fn voltage_under_load(constants: &BatteryConstant, current: f64, soc: f64) -> f64 {
+    let ocv = (constants.open_circuit_voltage_function)(soc);
+    let r0 = (constants.ohmic_resistance_function)(soc);
+    ocv - current * r0
+}
+
+/// Find the minimum terminal voltage while drawing a current profile
+///
+/// The profile is sampled every `dt` seconds starting from `initial_soc`.
+/// Unlike `voltage_under_load`, this tracks SoC depletion and the RC
+/// polarization branches over the profile.
+///
+/// Returns (min_voltage, time_of_min_voltage), or None for an empty profile
+/// or a non-positive `dt`
+pub fn minimum_voltage_during(
+    constants: &BatteryConstant,
+    currents: &[f64],
+    dt: f64,
+    initial_soc: f64,
+) -> Option<(f64, f64)> {
+    if currents.is_empty() || dt.is_nan() || dt <= 0.0 {
+        return None;
+    }
+    let mut soc = initial_soc;
+    let mut fast_pol_v = 0.0;
+    let mut slow_pol_v = 0.0;
+    let mut min_voltage = f64::MAX;
+    let mut time_at_min = 0.0;
+
+    let fast = &constants.fast_polarization_constants;
+    let slow = &constants.slow_polarization_constants;
+    let decay_fast = (-dt / (fast.resistance * fast.capacitance)).exp();
+    let decay_slow = (-dt / (slow.resistance * slow.capacitance)).exp();
+
+    for (i, &current) in currents.iter().enumerate() {
+        fast_pol_v = decay_fast * fast_pol_v + current * fast.resistance * (1.0 - decay_fast);
+        slow_pol_v = decay_slow * slow_pol_v + current * slow.resistance * (1.0 - decay_slow);
+
+        let voltage = voltage_under_load(constants, current, soc) - fast_pol_v - slow_pol_v;
+        if voltage < min_voltage {
+            min_voltage = voltage;
+            time_at_min = i as f64 * dt;
+        }
+
+        soc = (soc - current / (constants.rated_capacity_ah * 3600.0) * dt).clamp(0.0, 1.0);
+    }
+
+    Some((min_voltage, time_at_min))
+}
+
 /// Calculate effective capacity at given discharge rate
 /// 
 /// Returns capacity in Ah accounting for Peukert effect
@@ -265,6 +600,401 @@ pub fn effective_capacity_ah(constants: &BatteryConstant, discharge_current: f64
         * (peukert.reference_discharge_current / discharge_current.abs()).powf(peukert.constant - 1.0)
 }
 
+// ============================================================================
+// Drivetrain Analysis
+// ============================================================================
+
+const GRAVITY: f64 = 9.81;
+
+/// Battery voltage below which the robot controller browns out (V)
+pub const DEFAULT_BROWNOUT_VOLTAGE: f64 = 6.8;
+
+/// Straight-line drivetrain model for launch and braking queries
+///
+/// Treats the robot as a point mass driven by one motor per wheel through a
+/// fixed reduction, with wheel force capped by the traction limit mu * m * g.
+#[derive(Debug, Clone)]
+pub struct StraightLineDrive {
+    /// Drive motors, one per driven wheel
+    pub motors: Vec<MotorConstant>,
+    /// Robot mass (kg)
+    pub mass: f64,
+    /// Wheel radius (m)
+    pub wheel_radius: f64,
+    /// Motor-to-wheel reduction (motor turns per wheel turn)
+    pub gear_ratio: f64,
+    /// Longitudinal tire coefficient of friction
+    pub friction_coefficient: f64,
+    /// Supply voltage (V)
+    pub voltage: f64,
+}
+
+impl StraightLineDrive {
+    /// Traction-limited force available at the ground (N)
+    pub fn traction_limit(&self) -> f64 {
+        self.friction_coefficient * self.mass * GRAVITY
+    }
+
+    /// Total ground force at chassis speed `v` with every motor at `duty` (N)
+    ///
+    /// Uses the steady-state motor model, so reversing duty while moving
+    /// forward produces plugging (braking) force.
+    pub fn drive_force(&self, v: f64, duty: f64) -> f64 {
+        let wheel_omega = v / self.wheel_radius;
+        let motor_omega = wheel_omega * self.gear_ratio;
+        let motor_force: f64 = self.motors.iter()
+            .map(|m| {
+                let current = (duty * self.voltage - m.ke() * motor_omega) / m.resistance;
+                m.kt() * current * self.gear_ratio / self.wheel_radius
+            })
+            .sum();
+        let limit = self.traction_limit();
+        motor_force.clamp(-limit, limit)
+    }
+
+    /// Maximum forward acceleration at chassis speed `v` without browning out (m/s^2)
+    ///
+    /// Powers the motors from `battery` at `soc` instead of the fixed `voltage`:
+    /// the battery supplies the duty-scaled sum of motor currents, sagging the
+    /// terminal voltage through R0(soc). The duty is limited to keep the
+    /// terminal voltage at or above `brownout_voltage`, and the resulting force
+    /// is still capped by traction.
+    pub fn power_limited_accel(
+        &self,
+        v: f64,
+        battery: &BatteryConstant,
+        soc: f64,
+        brownout_voltage: f64,
+    ) -> f64 {
+        const DUTY_STEPS: usize = 1000;
+        let motor_omega = v / self.wheel_radius * self.gear_ratio;
+        let ocv = (battery.open_circuit_voltage_function)(soc);
+        let r0 = (battery.ohmic_resistance_function)(soc);
+
+        // Terminal voltage and ground force with every motor at `duty`.
+        // Solves V = ocv - r0 * sum(duty * I_i) with I_i = (duty * V - ke_i * omega) / R_i.
+        let operating_point = |duty: f64| {
+            let load: f64 = self.motors.iter().map(|m| duty * duty / m.resistance).sum();
+            let back_emf: f64 = self.motors.iter()
+                .map(|m| duty * m.ke() * motor_omega / m.resistance)
+                .sum();
+            let voltage = (ocv + r0 * back_emf) / (1.0 + r0 * load);
+            let force: f64 = self.motors.iter()
+                .map(|m| {
+                    let current = (duty * voltage - m.ke() * motor_omega) / m.resistance;
+                    m.kt() * current * self.gear_ratio / self.wheel_radius
+                })
+                .sum();
+            (voltage, force)
+        };
+
+        let max_force = (0..=DUTY_STEPS)
+            .map(|i| operating_point(i as f64 / DUTY_STEPS as f64))
+            .filter(|&(voltage, _)| voltage >= brownout_voltage)
+            .map(|(_, force)| force)
+            .fold(0.0, f64::max);
+        max_force.min(self.traction_limit()) / self.mass
+    }
+
+    /// Time to accelerate from rest to `target_v` at full duty (s)
+    ///
+    /// Returns None if the target is not reached within `max_time` seconds
+    /// (e.g. it is above the drivetrain's free speed) or `dt` is not positive.
+    pub fn time_to_speed(&self, target_v: f64, dt: f64, max_time: f64) -> Option<f64> {
+        if dt.is_nan() || dt <= 0.0 {
+            return None;
+        }
+        let mut v = 0.0;
+        let mut t = 0.0;
+        while v < target_v {
+            if t >= max_time {
+                return None;
+            }
+            v += self.drive_force(v, 1.0) / self.mass * dt;
+            t += dt;
+        }
+        Some(t)
+    }
+
+    /// Ground speed at which the motors stop producing torque at full duty (m/s)
+    pub fn free_speed(&self) -> f64 {
+        self.motors.iter()
+            .map(|m| m.free_speed(self.voltage) / self.gear_ratio * self.wheel_radius)
+            .fold(0.0, f64::max)
+    }
+
+    /// Steady top speed at full duty against rolling resistance and drag (m/s)
+    ///
+    /// Solves drive force = m * g * C_RR + rho * C_D*A * v^2 / 2 by bisection
+    /// between rest and the free speed. With no resistance this is the free
+    /// speed; if the robot cannot overcome rolling resistance it is zero.
+    pub fn top_speed(&self, rolling_resistance: f64, drag_area: f64) -> f64 {
+        let surplus = |v: f64| {
+            self.drive_force(v, 1.0) - self.mass * coast_down_deceleration(v, self.mass, rolling_resistance, drag_area)
+        };
+        let (mut low, mut high) = (0.0, self.free_speed());
+        if surplus(low) <= 0.0 {
+            return 0.0;
+        }
+        for _ in 0..100 {
+            let mid = 0.5 * (low + high);
+            if surplus(mid) > 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        0.5 * (low + high)
+    }
+
+    /// Distance to stop from `from_v` with full reverse duty (m)
+    ///
+    /// Returns None if the robot does not stop within `max_time` seconds
+    /// (e.g. no traction or no motors to brake with) or `dt` is not positive.
+    pub fn braking_distance(&self, from_v: f64, dt: f64, max_time: f64) -> Option<f64> {
+        if dt.is_nan() || dt <= 0.0 {
+            return None;
+        }
+        let mut v = from_v;
+        let mut x = 0.0;
+        let mut t = 0.0;
+        while v > 0.0 {
+            if t >= max_time {
+                return None;
+            }
+            let v_next = v + self.drive_force(v, -1.0) / self.mass * dt;
+            // Integrate the final partial step exactly to the stop
+            x += 0.5 * (v + v_next.max(0.0)) * dt;
+            v = v_next;
+            t += dt;
+        }
+        Some(x)
+    }
+
+    /// Time and final speed to cover `distance` from rest with every motor at `duty`
+    ///
+    /// Returns None if the distance is not covered within `max_time` seconds
+    /// (e.g. the duty is too small to move the robot) or `distance` or `dt`
+    /// is not positive.
+    pub fn time_to_distance(&self, distance: f64, duty: f64, dt: f64, max_time: f64) -> Option<OpenLoopEstimate> {
+        integrate_to_target(distance, dt, max_time, |v| self.drive_force(v, duty) / self.mass)
+    }
+
+    /// Time and final yaw rate to turn in place through `angle` from rest
+    ///
+    /// The wheels sit `turn_radius` meters from the center and push
+    /// tangentially at `duty`, so the yaw acceleration is
+    /// F(omega * r) * r / `moment_of_inertia`. Returns None if the angle is
+    /// not reached within `max_time` seconds or `angle` or `dt` is not positive.
+    pub fn time_to_turn(
+        &self,
+        angle: f64,
+        duty: f64,
+        turn_radius: f64,
+        moment_of_inertia: f64,
+        dt: f64,
+        max_time: f64,
+    ) -> Option<OpenLoopEstimate> {
+        integrate_to_target(angle, dt, max_time, |omega| {
+            self.drive_force(omega * turn_radius, duty) * turn_radius / moment_of_inertia
+        })
+    }
+
+    /// Slowest trapezoidal profile covering `distance` in `time` seconds
+    ///
+    /// Energy is dominated by the kinetic energy given to the robot and the
+    /// I^2 R losses of accelerating it, so the profile uses the lowest cruise
+    /// speed that still arrives on time. It accelerates and brakes at the
+    /// power-limited acceleration at that cruise speed (the lowest along the
+    /// ramp), so the battery at `soc` stays above `brownout_voltage`
+    /// throughout. Returns None when the distance cannot be covered in time.
+    pub fn energy_optimal_profile(
+        &self,
+        distance: f64,
+        time: f64,
+        battery: &BatteryConstant,
+        soc: f64,
+        brownout_voltage: f64,
+        dt: f64,
+    ) -> Option<VelocityProfile> {
+        if distance <= 0.0 || time <= 0.0 || dt <= 0.0 {
+            return None;
+        }
+
+        // Cruise speed of a symmetric trapezoid: distance = v * (time - v / accel)
+        let cruise_speed = |accel: f64| {
+            let discriminant = (accel * time).powi(2) - 4.0 * accel * distance;
+            (accel > 0.0 && discriminant >= 0.0).then(|| 0.5 * (accel * time - discriminant.sqrt()))
+        };
+        // Available acceleration falls with speed; settle on a consistent pair
+        let mut accel = self.power_limited_accel(0.0, battery, soc, brownout_voltage);
+        let mut cruise = cruise_speed(accel)?;
+        for _ in 0..50 {
+            accel = self.power_limited_accel(cruise, battery, soc, brownout_voltage);
+            cruise = cruise_speed(accel)?;
+        }
+        let ramp_time = cruise / accel;
+
+        let steps = (time / dt).ceil() as usize;
+        let mut profile = VelocityProfile {
+            cruise_speed: cruise,
+            acceleration: accel,
+            ..Default::default()
+        };
+        for i in 0..=steps {
+            let t = (i as f64 * dt).min(time);
+            let (velocity, acceleration) = if t < ramp_time {
+                (accel * t, accel)
+            } else if t <= time - ramp_time {
+                (cruise, 0.0)
+            } else {
+                (accel * (time - t), -accel)
+            };
+            if i > 0 {
+                let step = t - profile.times[i - 1];
+                profile.energy += self.electrical_power(velocity, acceleration).max(0.0) * step;
+            }
+            profile.times.push(t);
+            profile.velocities.push(velocity);
+        }
+        Some(profile)
+    }
+
+    /// Battery power drawn by the motors holding acceleration `accel` at speed `v` (W)
+    ///
+    /// Negative while braking; the force is shared evenly between the motors.
+    fn electrical_power(&self, v: f64, accel: f64) -> f64 {
+        if self.motors.is_empty() {
+            return 0.0;
+        }
+        let wheel_torque = self.mass * accel * self.wheel_radius / self.motors.len() as f64;
+        let motor_omega = v / self.wheel_radius * self.gear_ratio;
+        self.motors.iter()
+            .map(|m| {
+                let current = wheel_torque / self.gear_ratio / m.kt();
+                (current * m.resistance + m.ke() * motor_omega) * current
+            })
+            .sum()
+    }
+}
+
+/// Velocity profile from `StraightLineDrive::energy_optimal_profile`
+#[derive(Debug, Clone, Default)]
+pub struct VelocityProfile {
+    /// Sample times (s)
+    pub times: Vec<f64>,
+    /// Chassis speed at each sample (m/s)
+    pub velocities: Vec<f64>,
+    /// Peak (cruise) speed (m/s)
+    pub cruise_speed: f64,
+    /// Acceleration and braking rate (m/s^2)
+    pub acceleration: f64,
+    /// Electrical energy drawn over the profile, without regeneration (J)
+    pub energy: f64,
+}
+
+/// Outcome of driving open loop at a fixed duty until a target is reached
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpenLoopEstimate {
+    /// Time to reach the target (s)
+    pub time: f64,
+    /// Speed at the target (m/s for distance, rad/s for turns)
+    pub final_speed: f64,
+}
+
+/// Integrate from rest under `accel(speed)` until the position reaches `target`
+///
+/// The final step is interpolated so the time lands on the target rather
+/// than the next step boundary. Only positive targets are reachable: the
+/// position starts at zero and the check is for reaching the target from below.
+fn integrate_to_target(target: f64, dt: f64, max_time: f64, accel: impl Fn(f64) -> f64) -> Option<OpenLoopEstimate> {
+    if target.is_nan() || target <= 0.0 || dt.is_nan() || dt <= 0.0 {
+        return None;
+    }
+    let mut position = 0.0;
+    let mut speed = 0.0;
+    let mut t = 0.0;
+    while t < max_time {
+        let next_speed = speed + accel(speed) * dt;
+        let next_position = position + next_speed * dt;
+        if next_position >= target {
+            let fraction = if next_position > position { (target - position) / (next_position - position) } else { 1.0 };
+            return Some(OpenLoopEstimate {
+                time: t + fraction * dt,
+                final_speed: next_speed,
+            });
+        }
+        position = next_position;
+        speed = next_speed;
+        t += dt;
+    }
+    None
+}
+
+// ============================================================================
+// Coast-Down Identification
+// ============================================================================
+
+/// Sea-level air density (kg/m^3)
+pub const AIR_DENSITY: f64 = 1.225;
+
+/// Resistance coefficients fitted from a coast-down test
+#[derive(Debug, Clone, Copy)]
+pub struct CoastDownFit {
+    /// Rolling resistance coefficient C_RR (dimensionless)
+    pub rolling_resistance: f64,
+    /// Drag area C_D * A (m^2)
+    pub drag_area: f64,
+}
+
+/// Deceleration of a coasting robot (m/s^2, positive while slowing)
+///
+/// `a = C_RR * g + rho * C_DA * v^2 / (2 * m)`
+pub fn coast_down_deceleration(speed: f64, mass: f64, rolling_resistance: f64, drag_area: f64) -> f64 {
+    rolling_resistance * GRAVITY + 0.5 * AIR_DENSITY * drag_area * speed * speed / mass
+}
+
+/// Fit C_RR and C_DA to a coast-down speed trace by least squares
+///
+/// Deceleration is estimated between consecutive samples and regressed
+/// against speed squared: the intercept gives rolling resistance and the
+/// slope gives drag. Samples near standstill are ignored since the robot no
+/// longer decelerates once stopped. Returns None if fewer than two usable
+/// intervals remain or the speeds do not vary.
+pub fn fit_coast_down(times: &[f64], speeds: &[f64], mass: f64) -> Option<CoastDownFit> {
+    const MIN_SPEED: f64 = 0.05;
+
+    let points: Vec<(f64, f64)> = times
+        .windows(2)
+        .zip(speeds.windows(2))
+        .filter(|(t, v)| t[1] > t[0] && v[0].abs() > MIN_SPEED && v[1].abs() > MIN_SPEED)
+        .map(|(t, v)| {
+            let speed = 0.5 * (v[0].abs() + v[1].abs());
+            let deceleration = (v[0].abs() - v[1].abs()) / (t[1] - t[0]);
+            (speed * speed, deceleration)
+        })
+        .collect();
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    if sxx <= 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    Some(CoastDownFit {
+        rolling_resistance: intercept / GRAVITY,
+        drag_area: 2.0 * mass * slope / AIR_DENSITY,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,6 +1017,298 @@ mod tests {
             "Stall torque {} Nm should be in reasonable range", stall_torque);
     }
 
+    #[test]
+    fn test_duty_cycle_gearing_lands_near_peak_efficiency() {
+        let motor = MotorConstant::kraken_x60();
+        let voltage = 12.0;
+        let (load_torque, load_speed) = (2.0, 40.0);
+
+        let ratio = motor.gear_ratio_for_duty_cycle(&[load_torque], &[load_speed], voltage);
+
+        // The motor runs close to the top of its voltage-limited curve ...
+        let motor_speed = ratio * load_speed;
+        let curve_torque = motor.torque_at_velocity(motor_speed, voltage);
+        assert!((curve_torque - load_torque / ratio).abs() < 0.01 * load_torque / ratio);
+        assert!(motor.efficiency_at_velocity(motor_speed, voltage) > 0.9);
+
+        // ... and beats the 75%-of-free-speed heuristic and nearby ratios
+        let efficiency = motor.efficiency_at_load(load_torque, load_speed, ratio, voltage);
+        let (heuristic, _) = motor.optimal_gearing(voltage, load_speed);
+        assert!(efficiency >= motor.efficiency_at_load(load_torque, load_speed, heuristic, voltage));
+        assert!(efficiency > motor.efficiency_at_load(load_torque, load_speed, ratio * 0.9, voltage));
+        assert_eq!(motor.efficiency_at_load(load_torque, load_speed, ratio * 1.1, voltage), 0.0);
+    }
+
+    #[test]
+    fn test_voltage_under_load_sags_with_current_and_soc() {
+        let battery = BatteryConstant::default();
+
+        // Zero current is exactly OCV
+        let ocv = (battery.open_circuit_voltage_function)(0.8);
+        assert!((voltage_under_load(&battery, 0.0, 0.8) - ocv).abs() < 1e-12);
+
+        // Higher current sags more
+        assert!(voltage_under_load(&battery, 200.0, 0.8) < voltage_under_load(&battery, 100.0, 0.8));
+
+        // Lower SoC sags more at the same current
+        assert!(voltage_under_load(&battery, 200.0, 0.2) < voltage_under_load(&battery, 200.0, 0.8));
+    }
+
+    #[test]
+    fn test_minimum_voltage_during_profile() {
+        let battery = BatteryConstant::default();
+        let mut profile = vec![20.0; 50];
+        profile.extend(vec![200.0; 50]);
+        profile.extend(vec![20.0; 50]);
+
+        let (min_voltage, t_min) = minimum_voltage_during(&battery, &profile, 0.02, 1.0).unwrap();
+
+        // Polarization adds to the ohmic sag, and the minimum is during the peak
+        assert!(min_voltage < voltage_under_load(&battery, 200.0, 1.0));
+        assert!((1.0..2.0).contains(&t_min), "min at t={}", t_min);
+
+        // No samples or no time step: nothing to report
+        assert_eq!(minimum_voltage_during(&battery, &[], 0.02, 1.0), None);
+        assert_eq!(minimum_voltage_during(&battery, &profile, 0.0, 1.0), None);
+        assert_eq!(minimum_voltage_during(&battery, &profile, -0.02, 1.0), None);
+    }
+
+    fn test_drive() -> StraightLineDrive {
+        StraightLineDrive {
+            motors: vec![MotorConstant::kraken_x60(); 4],
+            mass: 50.0,
+            wheel_radius: 0.05,
+            gear_ratio: 6.75,
+            friction_coefficient: 1.0,
+            voltage: 12.0,
+        }
+    }
+
+    #[test]
+    fn test_resistance_lowers_top_speed() {
+        let drive = test_drive();
+        let free = drive.top_speed(0.0, 0.0);
+        assert!((free - drive.free_speed()).abs() < 1e-6, "{} vs free speed {}", free, drive.free_speed());
+
+        let rolling = drive.top_speed(0.02, 0.0);
+        let dragged = drive.top_speed(0.0, 0.5);
+        let both = drive.top_speed(0.02, 0.5);
+        assert!(rolling < free && dragged < free);
+        assert!(both < rolling && both < dragged);
+
+        // Steady state: the drive force balances the resistance
+        let resistance = drive.mass * coast_down_deceleration(both, drive.mass, 0.02, 0.5);
+        assert!((drive.drive_force(both, 1.0) - resistance).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_mass_sweep_lowers_acceleration() {
+        // Grippy enough that the motors, not traction, limit acceleration
+        let masses = [30.0, 45.0, 60.0, 75.0];
+        let result = simcore::sweep(
+            &masses,
+            |mass| StraightLineDrive { mass, friction_coefficient: 10.0, ..test_drive() },
+            |drive| drive.drive_force(2.0, 1.0) / drive.mass,
+        );
+
+        assert_eq!(result.values, masses);
+        for pair in result.metrics.windows(2) {
+            assert!(pair[1] < pair[0], "accelerations {:?}", result.metrics);
+        }
+    }
+
+    #[test]
+    fn test_higher_friction_shortens_braking_distance() {
+        let drive = test_drive();
+        let grippy = StraightLineDrive { friction_coefficient: 2.0, ..test_drive() };
+
+        let base = drive.braking_distance(4.0, 1e-4, 10.0).unwrap();
+        let short = grippy.braking_distance(4.0, 1e-4, 10.0).unwrap();
+        assert!(short < base, "{} should be shorter than {}", short, base);
+
+        // Traction-limited braking approaches v^2 / (2 mu g)
+        let expected = 4.0_f64.powi(2) / (2.0 * 1.0 * 9.81);
+        assert!((base - expected).abs() / expected < 0.05);
+    }
+
+    #[test]
+    fn test_braking_without_traction_or_time_step_gives_up() {
+        // No grip, no motors: nothing slows the robot
+        let frictionless = StraightLineDrive { friction_coefficient: 0.0, ..test_drive() };
+        assert_eq!(frictionless.braking_distance(4.0, 1e-3, 10.0), None);
+        let motorless = StraightLineDrive { motors: Vec::new(), ..test_drive() };
+        assert_eq!(motorless.braking_distance(4.0, 1e-3, 10.0), None);
+
+        let drive = test_drive();
+        assert_eq!(drive.braking_distance(4.0, 0.0, 10.0), None);
+        assert_eq!(drive.time_to_speed(1.0, 0.0, 10.0), None);
+        assert_eq!(drive.time_to_speed(1.0, -1e-3, 10.0), None);
+    }
+
+    #[test]
+    fn test_power_limited_accel_drops_at_low_soc() {
+        // Direct drive on grippy tires so the battery, not traction, is the limit
+        let drive = StraightLineDrive { gear_ratio: 1.0, friction_coefficient: 3.0, ..test_drive() };
+        let battery = BatteryConstant::default();
+
+        let full = drive.power_limited_accel(0.0, &battery, 1.0, DEFAULT_BROWNOUT_VOLTAGE);
+        let low = drive.power_limited_accel(0.0, &battery, 0.1, DEFAULT_BROWNOUT_VOLTAGE);
+
+        assert!(low > 0.0);
+        assert!(low < full, "low SoC {} should be below full charge {}", low, full);
+        assert!(full <= drive.traction_limit() / drive.mass);
+    }
+
+    #[test]
+    fn test_time_to_speed_increases_with_mass() {
+        let light = test_drive();
+        let heavy = StraightLineDrive { mass: 70.0, ..test_drive() };
+
+        // Near free speed the launch is motor-limited, not traction-limited
+        let t_light = light.time_to_speed(6.0, 1e-4, 10.0).unwrap();
+        let t_heavy = heavy.time_to_speed(6.0, 1e-4, 10.0).unwrap();
+        assert!(t_heavy > t_light);
+
+        // Above free speed is unreachable
+        assert!(light.time_to_speed(100.0, 1e-3, 10.0).is_none());
+    }
+
+    #[test]
+    fn test_higher_duty_covers_distance_sooner() {
+        let drive = test_drive();
+        let half = drive.time_to_distance(3.0, 0.5, 1e-4, 10.0).unwrap();
+        let full = drive.time_to_distance(3.0, 1.0, 1e-4, 10.0).unwrap();
+        assert!(full.time < half.time, "full {} should beat half {}", full.time, half.time);
+        assert!(full.final_speed > half.final_speed);
+
+        // Acceleration never increases from rest, so the average speed lies
+        // between half the final speed and the final speed
+        for estimate in [half, full] {
+            let average = 3.0 / estimate.time;
+            assert!(average > 0.49 * estimate.final_speed && average < estimate.final_speed);
+        }
+
+        // Zero duty never arrives
+        assert!(drive.time_to_distance(3.0, 0.0, 1e-3, 5.0).is_none());
+        // Nor does a time step that never advances
+        assert!(drive.time_to_distance(3.0, 1.0, 0.0, 5.0).is_none());
+        // A target behind the start is not passed on the first step
+        assert!(drive.time_to_distance(-3.0, 1.0, 1e-4, 5.0).is_none());
+        assert!(drive.time_to_distance(0.0, 1.0, 1e-4, 5.0).is_none());
+        assert!(drive.time_to_turn(-1.0, 1.0, 0.35, 5.0, 1e-4, 10.0).is_none());
+
+        let turn = drive.time_to_turn(std::f64::consts::PI, 1.0, 0.35, 5.0, 1e-4, 10.0).unwrap();
+        assert!(turn.time > 0.0 && turn.final_speed > 0.0);
+    }
+
+    #[test]
+    fn test_looser_deadline_profile_cruises_slower_on_less_energy() {
+        let drive = test_drive();
+        let battery = BatteryConstant::default();
+        let profile = |time: f64| {
+            drive.energy_optimal_profile(4.0, time, &battery, 1.0, DEFAULT_BROWNOUT_VOLTAGE, 1e-3).unwrap()
+        };
+        let tight = profile(2.0);
+        let loose = profile(4.0);
+
+        for p in [&tight, &loose] {
+            let distance: f64 = p.times.windows(2)
+                .zip(p.velocities.windows(2))
+                .map(|(t, v)| 0.5 * (v[0] + v[1]) * (t[1] - t[0]))
+                .sum();
+            assert!((distance - 4.0).abs() < 1e-2, "covered {} m", distance);
+        }
+        assert!(loose.cruise_speed < tight.cruise_speed);
+        assert!(loose.energy < tight.energy, "loose {} J vs tight {} J", loose.energy, tight.energy);
+
+        // Too little time to cover the distance at any speed
+        assert!(drive.energy_optimal_profile(4.0, 0.2, &battery, 1.0, DEFAULT_BROWNOUT_VOLTAGE, 1e-3).is_none());
+    }
+
+    #[test]
+    fn test_high_torque_low_speed_needs_high_reduction() {
+        // 60 Nm at 2 rad/s for half the time, like an arm shoulder
+        let requirement = MotorRequirement::new(60.0, 2.0, 0.5);
+        let ratios = log_spaced_ratios(1.0, 400.0, 200);
+        let recommendations = requirement.recommend(&ratios);
+
+        assert!(!recommendations.is_empty());
+        let best = recommendations[0];
+        assert!(best.gear_ratio > 50.0, "recommended {}:1", best.gear_ratio);
+        assert!(best.current_headroom > 0.0);
+        for pair in recommendations.windows(2) {
+            assert!(pair[0].efficiency >= pair[1].efficiency);
+        }
+
+        // A low reduction cannot supply the torque within the current limit
+        let kraken = MotorConstant::kraken_x60();
+        assert!(requirement.evaluate("kraken_x60", &kraken, 5.0).is_none());
+        assert!(MotorConstant::from_preset(best.preset).is_some());
+    }
+
+    #[test]
+    fn test_imperial_units_convert_si_values() {
+        let motor = MotorConstant::kraken_x60();
+        let units = UnitSystem::parse("Imperial").unwrap();
+
+        let free_speed = motor.free_speed(12.0);
+        let stall_torque = motor.stall_torque(12.0);
+        assert!((units.speed(free_speed) - free_speed * 60.0 / (2.0 * std::f64::consts::PI)).abs() < 1e-9);
+        assert!((units.torque(stall_torque) - stall_torque * 0.737562149).abs() < 1e-9);
+        assert!((units.power(745.699872) - 1.0).abs() < 1e-9);
+
+        let si = motor.torque_velocity_curve(12.0, 5);
+        let imperial = si.clone().in_units(units);
+        assert!((imperial.torques[0] - si.torques[0] * NM_TO_LB_FT).abs() < 1e-12);
+        assert_eq!(imperial.currents, si.currents);
+        assert_eq!(UnitSystem::parse("si"), Some(UnitSystem::Si));
+        assert_eq!(UnitSystem::parse("furlongs"), None);
+    }
+
+    #[test]
+    fn test_coast_down_fit_recovers_coefficients() {
+        let (mass, rolling_resistance, drag_area) = (60.0, 0.015, 0.3);
+
+        // Coast from 5 m/s, integrating finely and sampling at 100 Hz
+        let (mut times, mut speeds) = (vec![0.0], vec![5.0]);
+        let (mut t, mut v) = (0.0, 5.0);
+        let dt = 1e-4;
+        while v > 0.1 {
+            for _ in 0..100 {
+                v -= coast_down_deceleration(v, mass, rolling_resistance, drag_area) * dt;
+                t += dt;
+            }
+            times.push(t);
+            speeds.push(v);
+        }
+
+        let fit = fit_coast_down(&times, &speeds, mass).unwrap();
+        assert!((fit.rolling_resistance - rolling_resistance).abs() < 0.01 * rolling_resistance,
+            "C_RR {}", fit.rolling_resistance);
+        assert!((fit.drag_area - drag_area).abs() < 0.02 * drag_area, "C_DA {}", fit.drag_area);
+        assert!(fit_coast_down(&times[..2], &speeds[..2], mass).is_none());
+    }
+
+    #[test]
+    fn test_hot_windings_derate_torque_and_power() {
+        let motor = MotorConstant::kraken_x60();
+        let nominal = motor.torque_velocity_curve_at_temp(12.0, NOMINAL_WINDING_TEMPERATURE, 50);
+        let hot = motor.torque_velocity_curve_at_temp(12.0, 100.0, 50);
+        assert_eq!(nominal.torques, motor.torque_velocity_curve(12.0, 50).torques);
+
+        // 75 C over nominal: ~29% more resistance, ~23% less stall torque
+        let derate = 1.0 / (1.0 + COPPER_TEMPERATURE_COEFFICIENT * 75.0);
+        assert!((hot.torques[0] - nominal.torques[0] * derate).abs() < 1e-9);
+        assert!(hot.torques[0] < 0.8 * nominal.torques[0]);
+        let peak = |curve: &MotorAnalysisResult| curve.powers.iter().cloned().fold(0.0, f64::max);
+        assert!(peak(&hot) < peak(&nominal));
+        for (hot_torque, nominal_torque) in hot.torques.iter().zip(&nominal.torques).take(49) {
+            assert!(hot_torque < nominal_torque);
+        }
+        // Back-EMF alone sets the free speed
+        assert_eq!(hot.velocities.last(), nominal.velocities.last());
+    }
+
     #[test]
     fn test_torque_velocity_curve_length() {
         let motor = MotorConstant::neo();
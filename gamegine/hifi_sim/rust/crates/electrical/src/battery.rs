@@ -33,6 +33,47 @@ impl Default for RCBranch {
     }
 }
 
+/// Cell temperature the ohmic resistance function is characterized at (°C)
+pub const REFERENCE_CELL_TEMPERATURE: f64 = 25.0;
+
+/// Lumped thermal model of a pack
+///
+/// The cells heat from their ohmic and polarization losses and cool toward
+/// ambient through a thermal resistance. Ohmic resistance follows an
+/// Arrhenius-like relation, so a cold pack sags hard until it warms up.
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryThermal {
+    /// Heat capacity of the pack (J/K)
+    pub heat_capacity: f64,
+    /// Thermal resistance from the cells to ambient (K/W)
+    pub thermal_resistance: f64,
+    /// Ambient temperature (°C)
+    pub ambient_temperature: f64,
+    /// Activation energy over the gas constant (K); larger values make the
+    /// resistance more sensitive to temperature
+    pub activation_temperature: f64,
+}
+
+impl Default for BatteryThermal {
+    fn default() -> Self {
+        // ~5.5 kg SLA pack; resistance roughly doubles from 25 to 0 °C
+        BatteryThermal {
+            heat_capacity: 5000.0,
+            thermal_resistance: 0.5,
+            ambient_temperature: REFERENCE_CELL_TEMPERATURE,
+            activation_temperature: 2250.0,
+        }
+    }
+}
+
+impl BatteryThermal {
+    /// Factor on the reference ohmic resistance at `temp_c` (°C)
+    pub fn resistance_multiplier(&self, temp_c: f64) -> f64 {
+        let kelvin = |celsius: f64| celsius + 273.15;
+        (self.activation_temperature * (1.0 / kelvin(temp_c) - 1.0 / kelvin(REFERENCE_CELL_TEMPERATURE))).exp()
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct BatteryConstant {
     pub peukert_constant: Peukert,
@@ -40,7 +81,24 @@ pub struct BatteryConstant {
     pub open_circuit_voltage_function: fn(f64) -> f64,
     pub ohmic_resistance_function: fn(f64) -> f64,
     pub fast_polarization_constants: RCBranch,
-    pub slow_polarization_constants: RCBranch
+    pub slow_polarization_constants: RCBranch,
+    /// Self-heating and temperature-dependent resistance (None = the cells
+    /// stay at the reference temperature)
+    pub thermal: Option<BatteryThermal>,
+}
+
+impl BatteryConstant {
+    /// Model cell temperature with `thermal`
+    pub fn with_thermal(mut self, thermal: BatteryThermal) -> Self {
+        self.thermal = Some(thermal);
+        self
+    }
+
+    /// Ohmic resistance at `soc` and cell temperature `temp_c` (°C)
+    pub fn ohmic_resistance(&self, soc: f64, temp_c: f64) -> f64 {
+        let multiplier = self.thermal.map_or(1.0, |thermal| thermal.resistance_multiplier(temp_c));
+        (self.ohmic_resistance_function)(soc) * multiplier
+    }
 }
 
 pub fn default_ocv_from_soc(soc: f64) -> f64 {
@@ -122,6 +180,7 @@ impl Default for BatteryConstant {
             ohmic_resistance_function: |soc| default_r0_from_soc(soc, 0.008),
             fast_polarization_constants: RCBranch { resistance: 0.0027, capacitance: 741.0 },
             slow_polarization_constants: RCBranch { resistance: 0.0018, capacitance: 66667.0 },
+            thermal: None,
         }
     }
 }
@@ -131,7 +190,7 @@ impl Default for BatteryConstant {
 pub struct Battery {
     pub constants: BatteryConstant,
 }
-fn peukert_effective_capacity(current: f64, constants: &BatteryConstant) -> f64 {
+pub(crate) fn peukert_effective_capacity(current: f64, constants: &BatteryConstant) -> f64 {
     let peukert = constants.peukert_constant;
     (constants.rated_capacity_ah * 3600.0) * (peukert.reference_discharge_current / current.abs()).powf(peukert.constant - 1.0)
 }
@@ -140,7 +199,7 @@ fn state_of_charge_derivative(battery_state: &BatteryState, constants: &BatteryC
     -battery_state.total_current_draw / peukert_effective_capacity(battery_state.total_current_draw, constants)
 }
 
-fn update_rc_branch_voltage(dt: f64, current: f64, branch_voltage: f64, branch: &RCBranch) -> f64 {
+pub(crate) fn update_rc_branch_voltage(dt: f64, current: f64, branch_voltage: f64, branch: &RCBranch) -> f64 {
     (-dt / (branch.capacitance * branch.resistance)).exp() * branch_voltage + current * branch.resistance * (1.0 - (-dt / (branch.capacitance * branch.resistance)).exp())
 }
 
@@ -150,11 +209,9 @@ impl Model for Battery {
     }
 }
 
-impl ElectricalModel for Battery {
-    fn step_electrical(&mut self, ctx: SimContext, state: &mut SimState) {
-        let battery_state = &mut state.true_state.battery_state;
-
-        let dt = ctx.dt;
+impl Battery {
+    /// Advance one pack's state by `dt` at its `total_current_draw`
+    fn step_state(&self, dt: f64, battery_state: &mut BatteryState) {
         let total_current_draw = battery_state.total_current_draw;
 
         // Update the battery state based on the current draw and other factors
@@ -167,7 +224,259 @@ impl ElectricalModel for Battery {
 
 
         // Update the battery voltage
-        battery_state.voltage = (self.constants.open_circuit_voltage_function)(battery_state.state_of_charge) - total_current_draw * (self.constants.ohmic_resistance_function)(battery_state.state_of_charge) - battery_state.fast_polarization_voltage - battery_state.slow_polarization_voltage;
+        let ohmic_resistance = self.ohmic_resistance(battery_state);
+        battery_state.voltage = self.open_circuit_voltage(battery_state) - total_current_draw * ohmic_resistance;
+
+        // Self-heating from the ohmic and polarization losses, cooling to ambient
+        if let Some(thermal) = self.constants.thermal {
+            let polarization_voltage = battery_state.fast_polarization_voltage + battery_state.slow_polarization_voltage;
+            let heat = total_current_draw * (total_current_draw * ohmic_resistance + polarization_voltage);
+            let cooling = (battery_state.temperature - thermal.ambient_temperature) / thermal.thermal_resistance;
+            battery_state.temperature += (heat - cooling) / thermal.heat_capacity * dt;
+        }
+
+        // Accumulate charge and energy drawn over the run
+        let hours = dt / 3600.0;
+        battery_state.amp_hours_drawn += total_current_draw * hours;
+        battery_state.watt_hours_drawn += total_current_draw * battery_state.voltage * hours;
+    }
+
+    /// Ohmic resistance at the pack's state of charge and cell temperature (Ω)
+    fn ohmic_resistance(&self, battery_state: &BatteryState) -> f64 {
+        self.constants.ohmic_resistance(battery_state.state_of_charge, battery_state.temperature)
+    }
+
+    /// Voltage behind the ohmic resistance: open-circuit voltage less the
+    /// polarization branch voltages
+    fn open_circuit_voltage(&self, battery_state: &BatteryState) -> f64 {
+        (self.constants.open_circuit_voltage_function)(battery_state.state_of_charge)
+            - battery_state.fast_polarization_voltage
+            - battery_state.slow_polarization_voltage
+    }
+}
+
+impl ElectricalModel for Battery {
+    fn step_electrical(&mut self, ctx: SimContext, state: &mut SimState) {
+        self.step_state(ctx.dt, &mut state.true_state.battery_state);
+    }
+
+}
+
+// ============================================================================
+// Pack Arrays
+// ============================================================================
+
+/// How the packs of a `BatteryPackArray` are wired together
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackConnection {
+    /// Pack voltages add; every pack carries the full current
+    Series,
+    /// Packs share the terminal voltage; the current splits between them
+    /// in inverse proportion to their internal resistance
+    Parallel,
+}
+
+/// Several battery packs stepped as one source
+///
+/// Each pack keeps its own state of charge and polarization. The state in
+/// `SimState` is the combined view: the array reads its total current draw
+/// and writes back the terminal voltage, the mean state of charge and the
+/// totals drawn.
+pub struct BatteryPackArray {
+    pub packs: Vec<Battery>,
+    pub connection: PackConnection,
+    pack_states: Vec<BatteryState>,
+}
+
+impl BatteryPackArray {
+    pub fn new(packs: Vec<Battery>, connection: PackConnection) -> Self {
+        let pack_states = packs.iter().map(|_| BatteryState::default()).collect();
+        BatteryPackArray { packs, connection, pack_states }
+    }
+
+    /// `count` identical packs wired in series
+    pub fn series(constants: BatteryConstant, count: usize) -> Self {
+        Self::new((0..count).map(|_| Battery { constants }).collect(), PackConnection::Series)
+    }
+
+    /// `count` identical packs wired in parallel
+    pub fn parallel(constants: BatteryConstant, count: usize) -> Self {
+        Self::new((0..count).map(|_| Battery { constants }).collect(), PackConnection::Parallel)
+    }
+
+    /// State of each pack
+    pub fn pack_states(&self) -> &[BatteryState] {
+        &self.pack_states
+    }
+
+    /// Set every pack's state of charge
+    pub fn set_state_of_charge(&mut self, soc: f64) {
+        for pack_state in &mut self.pack_states {
+            pack_state.state_of_charge = soc;
+        }
+    }
+
+    /// Combined ohmic resistance at the packs' current state of charge and
+    /// temperature (Ω)
+    pub fn ohmic_resistance(&self) -> f64 {
+        let resistances = self.packs.iter().zip(&self.pack_states)
+            .map(|(pack, pack_state)| pack.ohmic_resistance(pack_state));
+        match self.connection {
+            PackConnection::Series => resistances.sum(),
+            PackConnection::Parallel => 1.0 / resistances.map(|r| 1.0 / r).sum::<f64>(),
+        }
+    }
+
+    /// Split the array current between the packs
+    fn assign_pack_currents(&mut self, current: f64) {
+        match self.connection {
+            PackConnection::Series => {
+                for pack_state in &mut self.pack_states {
+                    pack_state.total_current_draw = current;
+                }
+            }
+            PackConnection::Parallel => {
+                // Common terminal voltage V with Σ (E_i - V) / R_i = I
+                let sources: Vec<(f64, f64)> = self.packs.iter().zip(&self.pack_states)
+                    .map(|(pack, pack_state)| {
+                        (pack.open_circuit_voltage(pack_state), pack.ohmic_resistance(pack_state))
+                    })
+                    .collect();
+                let conductance: f64 = sources.iter().map(|(_, r)| 1.0 / r).sum();
+                let driven: f64 = sources.iter().map(|(e, r)| e / r).sum();
+                let terminal = (driven - current) / conductance;
+                for (pack_state, (e, r)) in self.pack_states.iter_mut().zip(sources) {
+                    pack_state.total_current_draw = (e - terminal) / r;
+                }
+            }
+        }
+    }
+}
+
+impl Model for BatteryPackArray {
+    fn reset(&mut self) {
+        for pack_state in &mut self.pack_states {
+            *pack_state = BatteryState::default();
+        }
+    }
+}
+
+impl ElectricalModel for BatteryPackArray {
+    fn step_electrical(&mut self, ctx: SimContext, state: &mut SimState) {
+        let battery_state = &mut state.true_state.battery_state;
+        self.assign_pack_currents(battery_state.total_current_draw);
+        for (pack, pack_state) in self.packs.iter().zip(&mut self.pack_states) {
+            pack.step_state(ctx.dt, pack_state);
+        }
+
+        let count = self.pack_states.len().max(1) as f64;
+        let sum = |field: fn(&BatteryState) -> f64| self.pack_states.iter().map(field).sum::<f64>();
+        let (voltage, polarization_scale, amp_hours) = match self.connection {
+            PackConnection::Series => (sum(|s| s.voltage), 1.0, sum(|s| s.amp_hours_drawn) / count),
+            PackConnection::Parallel => (sum(|s| s.voltage) / count, 1.0 / count, sum(|s| s.amp_hours_drawn)),
+        };
+        battery_state.voltage = voltage;
+        battery_state.state_of_charge = sum(|s| s.state_of_charge) / count;
+        battery_state.fast_polarization_voltage = sum(|s| s.fast_polarization_voltage) * polarization_scale;
+        battery_state.slow_polarization_voltage = sum(|s| s.slow_polarization_voltage) * polarization_scale;
+        battery_state.amp_hours_drawn = amp_hours;
+        battery_state.watt_hours_drawn = sum(|s| s.watt_hours_drawn);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_draw_accumulates_amp_hours() {
+        let mut battery = Battery { constants: BatteryConstant::default() };
+        let mut state = SimState::default();
+        state.true_state.battery_state.total_current_draw = 10.0;
+
+        let dt = 0.1;
+        for i in 0..3600 {
+            battery.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+        }
+
+        // 10 A for 360 s = 1.0 Ah
+        let battery_state = state.true_state.battery_state;
+        assert!((battery_state.amp_hours_drawn - 1.0).abs() < 1e-9);
+        assert!(battery_state.watt_hours_drawn > 0.0);
+        assert!(battery_state.watt_hours_drawn < battery_state.amp_hours_drawn * 13.0);
+    }
+
+    #[test]
+    fn test_injected_soc_sets_voltage_on_next_step() {
+        let mut battery = Battery { constants: BatteryConstant::default() };
+        let mut state = SimState::default();
+        battery.step_electrical(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+        let full_voltage = state.true_state.battery_state.voltage;
+
+        // Inject a nearly flat battery between steps
+        state.true_state.battery_state.state_of_charge = 0.1;
+        assert_eq!(state.true_state.battery_state.state_of_charge, 0.1);
+
+        battery.step_electrical(SimContext { dt: 0.001, t: 0.001 }, &mut state);
+        let battery_state = state.true_state.battery_state;
+        assert!((battery_state.state_of_charge - 0.1).abs() < 1e-12);
+        assert!((battery_state.voltage - default_ocv_from_soc(0.1)).abs() < 1e-9);
+        assert!(battery_state.voltage < full_voltage - 0.5);
     }
 
+    #[test]
+    fn test_cold_battery_sag_recovers_as_it_self_heats() {
+        // A light pack so it warms within the test
+        let thermal = BatteryThermal { heat_capacity: 200.0, ambient_temperature: -10.0, ..Default::default() };
+        let constants = BatteryConstant::default().with_thermal(thermal);
+        assert!((thermal.resistance_multiplier(REFERENCE_CELL_TEMPERATURE) - 1.0).abs() < 1e-12);
+        assert!(thermal.resistance_multiplier(-10.0) > 2.0);
+
+        let mut battery = Battery { constants };
+        let mut state = SimState::default();
+        state.true_state.battery_state.temperature = -10.0;
+        state.true_state.battery_state.total_current_draw = 100.0;
+
+        let dt = 0.01;
+        let mut voltages = Vec::new();
+        for i in 0..3000 {
+            battery.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+            voltages.push(state.true_state.battery_state.voltage);
+        }
+
+        // Once the fast polarization has settled, warming lifts the voltage
+        let battery_state = state.true_state.battery_state;
+        assert!(battery_state.temperature > 10.0, "temperature {}", battery_state.temperature);
+        let settled = voltages[999];
+        assert!(voltages[2999] > settled + 0.3, "{} -> {}", settled, voltages[2999]);
+    }
+
+    #[test]
+    fn test_pack_wiring_scales_voltage_and_resistance() {
+        let constants = BatteryConstant::default();
+        let terminal_voltage = |source: &mut dyn ElectricalModel, current: f64| {
+            let mut state = SimState::default();
+            state.true_state.battery_state.total_current_draw = current;
+            source.step_electrical(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+            state.true_state.battery_state.voltage
+        };
+
+        let single_open = terminal_voltage(&mut Battery { constants }, 0.0);
+        let series_open = terminal_voltage(&mut BatteryPackArray::series(constants, 2), 0.0);
+        assert!((series_open - 2.0 * single_open).abs() < 1e-9);
+
+        let single_sag = single_open - terminal_voltage(&mut Battery { constants }, 100.0);
+        let parallel = BatteryPackArray::parallel(constants, 2);
+        let single_r = (constants.ohmic_resistance_function)(1.0);
+        assert!((parallel.ohmic_resistance() - single_r / 2.0).abs() < 1e-12);
+
+        let mut parallel = parallel;
+        let parallel_open = terminal_voltage(&mut BatteryPackArray::parallel(constants, 2), 0.0);
+        let parallel_sag = parallel_open - terminal_voltage(&mut parallel, 100.0);
+        assert!((parallel_sag - single_sag / 2.0).abs() < 0.01 * single_sag);
+
+        // Identical packs split the current evenly
+        let currents: Vec<f64> = parallel.pack_states().iter().map(|s| s.total_current_draw).collect();
+        assert!((currents[0] - 50.0).abs() < 1e-9 && (currents[1] - 50.0).abs() < 1e-9);
+    }
 }
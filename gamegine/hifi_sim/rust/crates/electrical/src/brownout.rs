@@ -0,0 +1,144 @@
+//! roboRIO-style brownout protection
+//!
+//! When the battery sags below the trip voltage the controller disables motor
+//! outputs. Outputs only return once the voltage has climbed past a higher
+//! recovery threshold and they have been off for a minimum time, so a voltage
+//! hovering around the trip point does not toggle the motors every cycle.
+
+use crate::analysis::DEFAULT_BROWNOUT_VOLTAGE;
+
+/// Voltage the roboRIO waits for before re-enabling outputs (V)
+pub const DEFAULT_RECOVERY_VOLTAGE: f64 = 7.5;
+
+/// Brownout state machine with trip/recovery hysteresis
+#[derive(Debug, Clone)]
+pub struct Brownout {
+    /// Outputs are disabled below this voltage (V)
+    pub trip_voltage: f64,
+    /// Outputs may return above this voltage (V)
+    pub recovery_voltage: f64,
+    /// Shortest time outputs stay disabled after tripping (s)
+    pub min_off_time: f64,
+    browned_out: bool,
+    off_time: f64,
+    trip_count: u32,
+}
+
+impl Default for Brownout {
+    fn default() -> Self {
+        Brownout {
+            trip_voltage: DEFAULT_BROWNOUT_VOLTAGE,
+            recovery_voltage: DEFAULT_RECOVERY_VOLTAGE,
+            min_off_time: 0.02,
+            browned_out: false,
+            off_time: 0.0,
+            trip_count: 0,
+        }
+    }
+}
+
+impl Brownout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the trip and recovery voltages; recovery is raised to at least the trip voltage
+    pub fn with_thresholds(mut self, trip_voltage: f64, recovery_voltage: f64) -> Self {
+        self.trip_voltage = trip_voltage;
+        self.recovery_voltage = recovery_voltage.max(trip_voltage);
+        self
+    }
+
+    /// Minimum time outputs stay disabled once tripped (s)
+    pub fn with_min_off_time(mut self, min_off_time: f64) -> Self {
+        self.min_off_time = min_off_time.max(0.0);
+        self
+    }
+
+    /// Whether outputs are currently disabled
+    pub fn is_browned_out(&self) -> bool {
+        self.browned_out
+    }
+
+    /// Number of times the brownout has tripped since the last reset
+    pub fn trip_count(&self) -> u32 {
+        self.trip_count
+    }
+
+    /// Update with the measured battery `voltage` and return the output to apply
+    ///
+    /// `output` (a duty cycle or any other motor command) passes through
+    /// unchanged while powered and is zeroed while browned out.
+    pub fn apply_brownout(&mut self, voltage: f64, output: f64, dt: f64) -> f64 {
+        if self.browned_out {
+            self.off_time += dt;
+            if voltage >= self.recovery_voltage && self.off_time + 1e-9 >= self.min_off_time {
+                self.browned_out = false;
+            }
+        } else if voltage < self.trip_voltage {
+            self.browned_out = true;
+            self.off_time = 0.0;
+            self.trip_count += 1;
+        }
+
+        if self.browned_out {
+            0.0
+        } else {
+            output
+        }
+    }
+
+    /// Return to the powered state
+    pub fn reset(&mut self) {
+        self.browned_out = false;
+        self.off_time = 0.0;
+        self.trip_count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oscillating_voltage_does_not_chatter() {
+        let mut brownout = Brownout::new();
+        let dt = 0.001;
+
+        // Voltage ripples +-0.3 V around the trip point every millisecond
+        let mut toggles = 0;
+        let mut last = 1.0;
+        for i in 0..500 {
+            let voltage = DEFAULT_BROWNOUT_VOLTAGE + if i % 2 == 0 { -0.3 } else { 0.3 };
+            let output = brownout.apply_brownout(voltage, 1.0, dt);
+            if output != last {
+                toggles += 1;
+            }
+            last = output;
+        }
+        assert_eq!(toggles, 1, "outputs should trip once and stay off");
+        assert_eq!(brownout.trip_count(), 1);
+        assert!(brownout.is_browned_out());
+
+        // Crossing the recovery threshold restores outputs once the off time has passed
+        assert_eq!(brownout.apply_brownout(DEFAULT_RECOVERY_VOLTAGE + 0.1, 1.0, dt), 1.0);
+        assert!(!brownout.is_browned_out());
+    }
+
+    #[test]
+    fn test_recovery_waits_for_min_off_time() {
+        let mut brownout = Brownout::new().with_min_off_time(0.05);
+        let dt = 0.01;
+        assert_eq!(brownout.apply_brownout(6.0, 0.8, dt), 0.0);
+
+        // Voltage rebounds straight away, but outputs stay off for 50 ms
+        let mut restored_at = None;
+        for i in 1..10 {
+            if brownout.apply_brownout(12.0, 0.8, dt) == 0.8 {
+                restored_at = Some(i);
+                break;
+            }
+        }
+        assert_eq!(restored_at, Some(5));
+    }
+}
@@ -0,0 +1,69 @@
+//! Supply current budgeting across mechanisms
+//!
+//! When several mechanisms draw from one battery at once (e.g. driving while
+//! running an intake), their combined request can exceed what the supply
+//! should deliver. `CurrentBudget` splits a total limit between them.
+
+/// Priority/proportional allocation of a shared supply current limit
+pub struct CurrentBudget;
+
+impl CurrentBudget {
+    /// Allocate `total_a` amps across `(priority, requested_a)` requests
+    ///
+    /// Higher priorities are served first and receive their full request while
+    /// budget remains. The first priority level that no longer fits shares the
+    /// remainder in proportion to its requests, and lower levels get nothing.
+    /// Allocations are returned in the order of `requests`; negative requests
+    /// and a negative total are treated as zero.
+    pub fn allocate(requests: &[(u32, f64)], total_a: f64) -> Vec<f64> {
+        let mut allocation = vec![0.0; requests.len()];
+        let mut remaining = total_a.max(0.0);
+
+        let mut priorities: Vec<u32> = requests.iter().map(|&(priority, _)| priority).collect();
+        priorities.sort_unstable_by(|a, b| b.cmp(a));
+        priorities.dedup();
+
+        for priority in priorities {
+            let level: Vec<usize> = (0..requests.len()).filter(|&i| requests[i].0 == priority).collect();
+            let requested: f64 = level.iter().map(|&i| requests[i].1.max(0.0)).sum();
+            if requested <= 0.0 {
+                continue;
+            }
+
+            let scale = (remaining / requested).min(1.0);
+            for &i in &level {
+                allocation[i] = requests[i].1.max(0.0) * scale;
+            }
+            remaining -= requested * scale;
+            if remaining <= 0.0 {
+                break;
+            }
+        }
+        allocation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_budget_grants_every_request() {
+        let allocation = CurrentBudget::allocate(&[(2, 120.0), (1, 40.0)], 200.0);
+        assert_eq!(allocation, vec![120.0, 40.0]);
+    }
+
+    #[test]
+    fn test_over_budget_scales_lower_priorities() {
+        // Drivetrain (priority 2) accelerating while an intake and arm run
+        let requests = [(1, 40.0), (2, 160.0), (1, 20.0), (0, 30.0)];
+        let allocation = CurrentBudget::allocate(&requests, 190.0);
+
+        assert_eq!(allocation[1], 160.0);
+        // 30 A left for 60 A of priority-1 requests: both scaled by half
+        assert!((allocation[0] - 20.0).abs() < 1e-12);
+        assert!((allocation[2] - 10.0).abs() < 1e-12);
+        assert_eq!(allocation[3], 0.0);
+        assert!((allocation.iter().sum::<f64>() - 190.0).abs() < 1e-9);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod analysis;
 pub mod battery;
+pub mod brownout;
+pub mod budget;
 pub mod motor;
+pub mod soc_estimator;
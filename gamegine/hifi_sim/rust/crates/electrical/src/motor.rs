@@ -52,16 +52,206 @@ impl MotorConstant {
     pub fn kraken_x44() -> Self {
         MotorConstant::from_recalc_values(653.8, 0.0147, 0.071)
     }
+
+    /// Names accepted by `from_preset`
+    pub const PRESET_NAMES: [&'static str; 3] = ["kraken_x60", "neo", "kraken_x44"];
+
+    /// Look up a motor by preset name ("kraken_x60", "neo", "kraken_x44")
+    pub fn from_preset(name: &str) -> Option<Self> {
+        match name {
+            "kraken_x60" => Some(Self::kraken_x60()),
+            "neo" => Some(Self::neo()),
+            "kraken_x44" => Some(Self::kraken_x44()),
+            _ => None,
+        }
+    }
 }
 
 
 
+/// Time-based stall guard applied by `MotorBank` to each motor it steps
+///
+/// A motor turning slower than `stall_speed_threshold` while drawing at least
+/// `folded_current_a` is considered stalled; once that lasts
+/// `fold_back_after_s`, its phase current is limited to `folded_current_a`
+/// until it speeds up or the current drops.
+#[derive(Debug, Clone, Copy)]
+pub struct StallProtection {
+    /// Speed below which the motor counts as stalled (rad/s)
+    pub stall_speed_threshold: f64,
+    /// Stall duration before the current is folded back (s)
+    pub fold_back_after_s: f64,
+    /// Current limit while folded back (A)
+    pub folded_current_a: f64,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct MotorBank {
-    pub motor_constants: Vec<MotorConstant>
+    pub motor_constants: Vec<MotorConstant>,
+    /// Idle draw of each motor controller in amps, present even at zero
+    /// motor current. Zero by default.
+    pub quiescent_current: f64,
+    /// Optional stall current fold-back, disabled by default
+    pub stall_protection: Option<StallProtection>,
+    /// Per-motor controller output inversion. An inverted motor applies the
+    /// negated duty, so the same command turns it the other way; entries
+    /// past the end of the list are not inverted.
+    pub inverted: Vec<bool>,
+    /// Open-loop ramp: time for the applied voltage to rise from zero to the
+    /// full bus voltage (s). Zero applies duty changes immediately.
+    pub open_loop_ramp_time: f64,
+    /// PWM switching frequency (Hz) used to estimate each motor's current
+    /// ripple, read back through `current_ripple()`. None disables it.
+    pub pwm_frequency: Option<f64>,
+    /// Per-motor reduction to the wheel of the same index (motor turns per
+    /// wheel turn). Each step syncs the motor's mechanical velocity from the
+    /// wheel before advancing its currents; a negative ratio couples a motor
+    /// that turns against its wheel. Motors past the end of the list, or
+    /// without a wheel, keep the velocity the caller sets.
+    pub gear_ratios: Vec<f64>,
+    /// Time each motor has spent stalled (s)
+    stall_timers: Vec<f64>,
+    /// Peak-to-peak current ripple of each motor in the last step (A)
+    current_ripples: Vec<f64>,
 }
 
 impl MotorBank {
+    /// Create a bank from a per-motor list of constants, allowing mixed motor types
+    pub fn new(motor_constants: Vec<MotorConstant>) -> Self {
+        MotorBank {
+            motor_constants,
+            quiescent_current: 0.0,
+            stall_protection: None,
+            inverted: Vec::new(),
+            open_loop_ramp_time: 0.0,
+            pwm_frequency: None,
+            gear_ratios: Vec::new(),
+            stall_timers: Vec::new(),
+            current_ripples: Vec::new(),
+        }
+    }
+
+    /// Report the current ripple each motor sees when switched at `pwm_frequency` Hz
+    pub fn with_ripple_reporting(mut self, pwm_frequency: f64) -> Self {
+        self.pwm_frequency = Some(pwm_frequency);
+        self
+    }
+
+    /// Estimated peak-to-peak phase current ripple of motor `index` in the
+    /// last step (A); None unless ripple reporting is on
+    ///
+    /// Over one PWM period the winding sees the bus voltage for a fraction D
+    /// of the time, giving a ripple of V_bus * D * (1 - D) / (L * f_pwm),
+    /// largest at half duty and zero at 0 or full duty.
+    pub fn current_ripple(&self, index: usize) -> Option<f64> {
+        self.pwm_frequency?;
+        Some(self.current_ripples.get(index).copied().unwrap_or(0.0))
+    }
+
+    /// Peak-to-peak ripple (A) of `motor` switched at `duty` from `bus_voltage`
+    fn ripple(motor: &MotorConstant, duty: f64, bus_voltage: f64, pwm_frequency: f64) -> f64 {
+        let duty = duty.abs().min(1.0);
+        if pwm_frequency <= 0.0 || motor.inductance_q <= 0.0 {
+            return 0.0;
+        }
+        bus_voltage.abs() * duty * (1.0 - duty) / (motor.inductance_q * pwm_frequency)
+    }
+
+    /// Ramp the applied voltage over `ramp_time` seconds from zero to full bus voltage
+    pub fn with_open_loop_ramp(mut self, ramp_time: f64) -> Self {
+        self.open_loop_ramp_time = ramp_time.max(0.0);
+        self
+    }
+
+    /// Move the previously applied voltage toward `target`, limited by the open-loop ramp
+    fn ramp_voltage(&self, applied: f64, target: f64, bus_voltage: f64, dt: f64) -> f64 {
+        if self.open_loop_ramp_time <= 0.0 {
+            return target;
+        }
+        let max_step = bus_voltage.abs() / self.open_loop_ramp_time * dt;
+        applied + (target - applied).clamp(-max_step, max_step)
+    }
+
+    /// Couple each motor to the wheel of the same index through its
+    /// reduction, in motor order
+    pub fn with_gear_ratios(mut self, gear_ratios: Vec<f64>) -> Self {
+        self.gear_ratios = gear_ratios;
+        self
+    }
+
+    /// Set motor `index`'s velocity from its wheel, if it is geared to one
+    fn sync_to_wheel(&self, index: usize, state: &mut SimState) {
+        let (Some(&ratio), Some(wheel)) = (self.gear_ratios.get(index), state.true_state.wheel_states.get(index)) else {
+            return;
+        };
+        state.true_state.motors[index].mechanical_velocity = wheel.driving_angular_velocity * ratio;
+    }
+
+    /// Invert the controller output of each motor, in motor order
+    pub fn with_inverted(mut self, inverted: Vec<bool>) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Sign applied to the duty of motor `index`: 1.0, or -1.0 if inverted
+    pub fn output_sign(&self, index: usize) -> f64 {
+        if self.inverted.get(index).copied().unwrap_or(false) {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// State of motor `index` as its controller reports it
+    ///
+    /// `state.true_state.motors` holds every motor in the mechanism's sign
+    /// convention. An inverted controller reports velocity, currents, voltages
+    /// and torque negated, so positive duty still reads as positive motion.
+    pub fn reported_state(&self, index: usize, state: &SimState) -> MotorState {
+        let mut reported = state.true_state.motors[index];
+        let sign = self.output_sign(index);
+        reported.mechanical_velocity *= sign;
+        reported.current_q *= sign;
+        reported.current_d *= sign;
+        reported.applied_torque *= sign;
+        reported.voltage_q *= sign;
+        reported.voltage_d *= sign;
+        reported
+    }
+
+    /// Model a per-controller quiescent current draw (A)
+    pub fn with_quiescent_current(mut self, amps: f64) -> Self {
+        self.quiescent_current = amps.max(0.0);
+        self
+    }
+
+    /// Fold back the current of motors held at stall
+    pub fn with_stall_protection(mut self, protection: StallProtection) -> Self {
+        self.stall_protection = Some(protection);
+        self
+    }
+
+    /// Time motor `index` has been continuously stalled (s)
+    pub fn stall_time(&self, index: usize) -> f64 {
+        self.stall_timers.get(index).copied().unwrap_or(0.0)
+    }
+
+    /// Advance the stall timer of motor `index` and return the phase current
+    /// limit it is held to, if folded back
+    fn stall_current_limit(&mut self, index: usize, motor_state: &MotorState, dt: f64) -> Option<f64> {
+        let protection = self.stall_protection?;
+        if self.stall_timers.len() <= index {
+            self.stall_timers.resize(index + 1, 0.0);
+        }
+
+        let current = motor_state.current_q.hypot(motor_state.current_d);
+        let stalled = motor_state.mechanical_velocity.abs() < protection.stall_speed_threshold
+            && current >= protection.folded_current_a;
+        let timer = &mut self.stall_timers[index];
+        *timer = if stalled { *timer + dt } else { 0.0 };
+        (*timer >= protection.fold_back_after_s).then_some(protection.folded_current_a)
+    }
+
     pub fn add_motor(&mut self, motor: MotorConstant) {
         self.motor_constants.push(motor);
     }
@@ -69,7 +259,10 @@ impl MotorBank {
 
 impl Model for MotorBank {
     fn reset(&mut self) {
-        self.motor_constants.clear();
+        // Motor constants are configuration, not state; keep them so a reset
+        // simulator still drives the same (possibly mixed) set of motors
+        self.stall_timers.clear();
+        self.current_ripples.clear();
     }
 }
 
@@ -82,32 +275,442 @@ fn derivative_current_q(current_d: f64, current_q: f64, voltage_q: f64, resistan
 }
 
 
+/// Apply dq-axis voltages to one motor and advance its currents by `dt`
+/// (explicit Euler)
+fn step_dq_currents(motor: &MotorConstant, motor_state: &mut MotorState, voltage_q: f64, voltage_d: f64, dt: f64) {
+    motor_state.voltage_q = voltage_q;
+    motor_state.voltage_d = voltage_d;
+    let electrical_velocity = motor_state.mechanical_velocity * motor.pole_pairs as f64;
+    let current_d = motor_state.current_d;
+    let current_q = motor_state.current_q;
+
+    let d_current_d = derivative_current_d(current_d, current_q, voltage_d, motor.resistance, motor.inductance_d, motor.inductance_q, electrical_velocity);
+    let d_current_q = derivative_current_q(current_d, current_q, voltage_q, motor.resistance, motor.inductance_d, motor.inductance_q, motor.flux_linkage, electrical_velocity);
+
+    motor_state.current_d += d_current_d * dt;
+    motor_state.current_q += d_current_q * dt;
+}
+
+/// Shaft torque produced by the present dq-axis currents (Nm)
+fn electromagnetic_torque(motor: &MotorConstant, motor_state: &MotorState) -> f64 {
+    1.5 * (motor.pole_pairs as f64) * (
+        motor.flux_linkage * motor_state.current_q +
+        (motor.inductance_d - motor.inductance_q) * motor_state.current_d * motor_state.current_q
+    )
+}
+
+/// Combined output of a motor group at one operating point
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GroupOutput {
+    /// Summed shaft torque (Nm)
+    pub torque: f64,
+    /// Summed phase current (A)
+    pub current: f64,
+    /// Current drawn from the supply, the duty-scaled phase current (A)
+    pub supply_current: f64,
+}
+
+/// Motors ganged on one shaft and driven by one duty command
+///
+/// All motors turn at the shared shaft velocity, so their torques sum and
+/// each draws its own current. Mixed motor types are allowed. Offers the
+/// steady-state model (`I = (duty * V - ke * w) / R`) used by the mechanism
+/// and drivetrain sims, and the transient dq-current model of `MotorBank`.
+#[derive(Debug, Clone, Default)]
+pub struct MotorGroup {
+    pub motors: Vec<MotorConstant>,
+    /// Per-motor stator current limit (A), infinite by default
+    pub current_limit: f64,
+    /// Electrical state of each motor for the transient model
+    states: Vec<MotorState>,
+}
+
+impl MotorGroup {
+    pub fn new(motors: Vec<MotorConstant>) -> Self {
+        let states = vec![MotorState::default(); motors.len()];
+        MotorGroup {
+            motors,
+            current_limit: f64::INFINITY,
+            states,
+        }
+    }
+
+    /// `count` identical motors
+    pub fn uniform(motor: MotorConstant, count: usize) -> Self {
+        Self::new(vec![motor; count])
+    }
+
+    /// Limit each motor's stator current (A)
+    pub fn with_current_limit(mut self, amps: f64) -> Self {
+        self.current_limit = amps.abs();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.motors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.motors.is_empty()
+    }
+
+    /// Electrical state of each motor under the transient model
+    pub fn states(&self) -> &[MotorState] {
+        &self.states
+    }
+
+    /// Steady-state output at shaft `velocity` (rad/s)
+    pub fn steady_state(&self, duty: f64, voltage: f64, velocity: f64) -> GroupOutput {
+        let mut output = GroupOutput::default();
+        for motor in &self.motors {
+            let current = ((duty * voltage - motor.ke() * velocity) / motor.resistance)
+                .clamp(-self.current_limit, self.current_limit);
+            output.torque += motor.kt() * current;
+            output.current += current;
+        }
+        output.supply_current = duty * output.current;
+        output
+    }
+
+    /// Steady-state duty producing `torque` at shaft `velocity`; may exceed 1.0
+    /// when the group cannot deliver it. Ignores the current limit.
+    pub fn duty_for_torque(&self, torque: f64, velocity: f64, voltage: f64) -> f64 {
+        let (mut per_volt, mut back_emf) = (0.0, 0.0);
+        for motor in &self.motors {
+            per_volt += motor.kt() / motor.resistance;
+            back_emf += motor.kt() * motor.ke() * velocity / motor.resistance;
+        }
+        if per_volt == 0.0 || voltage == 0.0 {
+            return 0.0;
+        }
+        (torque + back_emf) / (per_volt * voltage)
+    }
+
+    /// Advance every motor's dq currents by `dt` at shaft `velocity` and
+    /// return the resulting output
+    pub fn step_transient(&mut self, duty: f64, voltage: f64, velocity: f64, dt: f64) -> GroupOutput {
+        self.states.resize(self.motors.len(), MotorState::default());
+        let mut output = GroupOutput::default();
+        for (motor, state) in self.motors.iter().zip(&mut self.states) {
+            state.mechanical_velocity = velocity;
+            step_dq_currents(motor, state, duty * voltage, 0.0, dt);
+
+            let current = state.current_q.hypot(state.current_d);
+            if current > self.current_limit {
+                state.current_q *= self.current_limit / current;
+                state.current_d *= self.current_limit / current;
+            }
+            state.applied_torque = electromagnetic_torque(motor, state);
+
+            output.torque += state.applied_torque;
+            output.current += state.current_q;
+        }
+        output.supply_current = duty * output.current;
+        output
+    }
+
+    /// Return every motor to zero current
+    pub fn reset(&mut self) {
+        for state in &mut self.states {
+            *state = MotorState::default();
+        }
+    }
+}
 
 impl ElectricalModel for MotorBank {
     fn step_electrical(&mut self, ctx: SimContext, state: &mut SimState) {
         let dt = ctx.dt;
-        for (i, motor) in self.motor_constants.iter().enumerate() {
+        let mut total_current_draw = self.quiescent_current * self.motor_constants.len() as f64;
+        for i in 0..self.motor_constants.len() {
+            let motor = self.motor_constants[i];
             let input: MotorInput = state.control_input.motor_inputs[i];
-            let voltage_q = input.duty_cycle_q * state.true_state.battery_state.voltage;
-            let voltage_d = input.duty_cycle_d * state.true_state.battery_state.voltage;
+            let sign = self.output_sign(i);
+            self.sync_to_wheel(i, state);
+            let bus_voltage = state.true_state.battery_state.voltage;
+            let previous = state.true_state.motors[i];
+            let voltage_q = self.ramp_voltage(previous.voltage_q, sign * input.duty_cycle_q * bus_voltage, bus_voltage, dt);
+            let voltage_d = self.ramp_voltage(previous.voltage_d, sign * input.duty_cycle_d * bus_voltage, bus_voltage, dt);
+            // Duty the bridge actually applies, after ramping
+            let (duty_q, duty_d) = if bus_voltage.abs() > 1e-9 {
+                (voltage_q / bus_voltage, voltage_d / bus_voltage)
+            } else {
+                (sign * input.duty_cycle_q, sign * input.duty_cycle_d)
+            };
 
             // Update motor state
             // TODO: Update to more accurate integration
-            let mech_vel = state.true_state.motors[i].mechanical_velocity;
-            let current_d = state.true_state.motors[i].current_d;
-            let current_q = state.true_state.motors[i].current_q;
-
-            let d_current_d = derivative_current_d(current_d, current_q, voltage_d, motor.resistance, motor.inductance_d, motor.inductance_q, mech_vel*motor.pole_pairs as f64);
-            let d_current_q = derivative_current_q(current_d, current_q, voltage_q, motor.resistance, motor.inductance_d, motor.inductance_q, motor.flux_linkage, mech_vel*motor.pole_pairs as f64);
+            step_dq_currents(&motor, &mut state.true_state.motors[i], voltage_q, voltage_d, dt);
 
-            state.true_state.motors[i].current_d += d_current_d * dt;
-            state.true_state.motors[i].current_q += d_current_q * dt;
+            // Scale both axes back together when stall protection has folded back
+            if let Some(limit) = self.stall_current_limit(i, &state.true_state.motors[i], dt) {
+                let motor_state = &mut state.true_state.motors[i];
+                let current = motor_state.current_q.hypot(motor_state.current_d);
+                if current > limit {
+                    motor_state.current_q *= limit / current;
+                    motor_state.current_d *= limit / current;
+                }
+            }
 
             // Update mechanical torques
-            state.true_state.motors[i].applied_torque = 1.5 * (motor.pole_pairs as f64) * (
-                motor.flux_linkage * state.true_state.motors[i].current_q +
-                (motor.inductance_d - motor.inductance_q) * state.true_state.motors[i].current_d * state.true_state.motors[i].current_q
-            )
+            state.true_state.motors[i].applied_torque = electromagnetic_torque(&motor, &state.true_state.motors[i]);
+
+            // Supply current is the duty-scaled phase current
+            total_current_draw += duty_q * state.true_state.motors[i].current_q
+                + duty_d * state.true_state.motors[i].current_d;
+
+            if let Some(pwm_frequency) = self.pwm_frequency {
+                if self.current_ripples.len() <= i {
+                    self.current_ripples.resize(i + 1, 0.0);
+                }
+                self.current_ripples[i] = Self::ripple(&motor, duty_q.hypot(duty_d), bus_voltage, pwm_frequency);
+            }
+        }
+        state.true_state.battery_state.total_current_draw = total_current_draw;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simcore::{TireState, WheelState};
+
+    #[test]
+    fn test_gear_ratios_sync_motor_velocity_from_wheels() {
+        let mut bank = MotorBank::new(vec![MotorConstant::kraken_x60(); 3]).with_gear_ratios(vec![6.75, -5.0]);
+        let mut state = SimState::default();
+        state.true_state.motors = vec![MotorState::default(); 3];
+        state.true_state.wheel_states = (0..3)
+            .map(|_| WheelState {
+                driving_angular_velocity: 10.0,
+                wheel_radius: 0.05,
+                turning_angular_velocity: 0.0,
+                longitudinal_translational_velocity: 0.0,
+                lateral_translational_velocity: 0.0,
+                tire: TireState { slip_angle: 0.0, slip_ratio: 0.0, longitudinal_force: 0.0, lateral_force: 0.0, tire_load: 0.0 },
+                angle: 0.0,
+                contact_fraction: 1.0,
+            })
+            .collect();
+        state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.5, duty_cycle_d: 0.0 }; 3];
+        state.true_state.battery_state.voltage = 12.0;
+        state.true_state.motors[2].mechanical_velocity = 3.0;
+
+        bank.step_electrical(SimContext { dt: 1e-4, t: 0.0 }, &mut state);
+        assert_eq!(state.true_state.motors[0].mechanical_velocity, 67.5);
+        assert_eq!(state.true_state.motors[1].mechanical_velocity, -50.0);
+        // No ratio: the caller's velocity stands
+        assert_eq!(state.true_state.motors[2].mechanical_velocity, 3.0);
+
+        // The synced speed's back-EMF cuts the current of the faster motor
+        assert!(state.true_state.motors[0].current_q < state.true_state.motors[2].current_q);
+
+        state.true_state.wheel_states[0].driving_angular_velocity = -2.0;
+        bank.step_electrical(SimContext { dt: 1e-4, t: 1e-4 }, &mut state);
+        assert_eq!(state.true_state.motors[0].mechanical_velocity, -13.5);
+    }
+
+    #[test]
+    fn test_current_ripple_grows_with_voltage_and_falls_with_inductance() {
+        let ripple = |motor: MotorConstant, bus_voltage: f64, pwm_frequency: Option<f64>| {
+            let mut bank = MotorBank::new(vec![motor]);
+            bank.pwm_frequency = pwm_frequency;
+            let mut state = SimState::default();
+            state.true_state.motors = vec![MotorState::default()];
+            state.true_state.battery_state.voltage = bus_voltage;
+            state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.5, duty_cycle_d: 0.0 }];
+            bank.step_electrical(SimContext { dt: 1e-5, t: 0.0 }, &mut state);
+            bank.current_ripple(0)
+        };
+        let kraken = MotorConstant::kraken_x60();
+        let base = ripple(kraken, 12.0, Some(20_000.0)).unwrap();
+        assert!((base - 12.0 * 0.25 / (kraken.inductance_q * 20_000.0)).abs() < 1e-9);
+
+        assert!(ripple(kraken, 16.0, Some(20_000.0)).unwrap() > base);
+        let low_inductance = MotorConstant { inductance_q: kraken.inductance_q / 2.0, ..kraken };
+        assert!((ripple(low_inductance, 12.0, Some(20_000.0)).unwrap() - 2.0 * base).abs() < 1e-9);
+        let infinite_inductance = MotorConstant { inductance_q: f64::INFINITY, ..kraken };
+        assert_eq!(ripple(infinite_inductance, 12.0, Some(20_000.0)), Some(0.0));
+        assert!(ripple(kraken, 12.0, None).is_none());
+    }
+
+    #[test]
+    fn test_mixed_motors_produce_different_torques() {
+        let mut bank = MotorBank::new(vec![MotorConstant::kraken_x60(), MotorConstant::neo()]);
+        let mut state = SimState::default();
+        state.true_state.motors = vec![MotorState::default(); 2];
+        state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.5, duty_cycle_d: 0.0 }; 2];
+
+        let dt = 1e-5;
+        for i in 0..200 {
+            bank.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+        }
+
+        let kraken_torque = state.true_state.motors[0].applied_torque;
+        let neo_torque = state.true_state.motors[1].applied_torque;
+        assert!(kraken_torque > 0.0 && neo_torque > 0.0);
+        assert!((kraken_torque - neo_torque).abs() > 1e-3,
+            "Kraken {} Nm and NEO {} Nm should differ", kraken_torque, neo_torque);
+    }
+
+    #[test]
+    fn test_inverted_motor_reverses_torque_and_reports_consistently() {
+        let mut bank = MotorBank::new(vec![MotorConstant::kraken_x60(); 2]).with_inverted(vec![false, true]);
+        let mut state = SimState::default();
+        state.true_state.motors = vec![MotorState::default(); 2];
+        state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.5, duty_cycle_d: 0.0 }; 2];
+        // Each motor is spinning the way its command drives it
+        state.true_state.motors[0].mechanical_velocity = 100.0;
+        state.true_state.motors[1].mechanical_velocity = -100.0;
+
+        let dt = 1e-5;
+        for i in 0..200 {
+            bank.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+        }
+
+        let normal = state.true_state.motors[0];
+        let inverted = state.true_state.motors[1];
+        assert!(normal.applied_torque > 0.0);
+        assert!((inverted.applied_torque + normal.applied_torque).abs() < 1e-9);
+
+        // Through its controller the inverted motor reads exactly like the normal one
+        let reported = bank.reported_state(1, &state);
+        assert_eq!(bank.reported_state(0, &state).applied_torque, normal.applied_torque);
+        assert!((reported.mechanical_velocity - normal.mechanical_velocity).abs() < 1e-12);
+        assert!((reported.current_q - normal.current_q).abs() < 1e-9);
+        assert!((reported.applied_torque - normal.applied_torque).abs() < 1e-9);
+        assert!((reported.voltage_q - normal.voltage_q).abs() < 1e-12);
+
+        // Both draw the same positive supply current
+        let supply = 0.5 * normal.current_q;
+        assert!((state.true_state.battery_state.total_current_draw - 2.0 * supply).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_motor_presets_by_name() {
+        assert_eq!(MotorConstant::from_preset("neo").unwrap().resistance, MotorConstant::neo().resistance);
+        assert!(MotorConstant::from_preset("cim").is_none());
+    }
+
+    #[test]
+    fn test_quiescent_current_drains_idle_battery() {
+        use crate::battery::{Battery, BatteryConstant};
+
+        let idle_soc = |bank: &mut MotorBank| {
+            let mut battery = Battery { constants: BatteryConstant::default() };
+            let mut state = SimState::default();
+            state.true_state.motors = vec![MotorState::default(); 4];
+            state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.0, duty_cycle_d: 0.0 }; 4];
+            let initial_soc = state.true_state.battery_state.state_of_charge;
+
+            let dt = 0.01;
+            for i in 0..6000 {
+                let ctx = SimContext { dt, t: i as f64 * dt };
+                bank.step_electrical(ctx, &mut state);
+                battery.step_electrical(ctx, &mut state);
+            }
+            initial_soc - state.true_state.battery_state.state_of_charge
+        };
+
+        let motors = vec![MotorConstant::kraken_x60(); 4];
+        let mut quiet = MotorBank::new(motors.clone());
+        let mut idling = MotorBank::new(motors).with_quiescent_current(0.05);
+
+        assert_eq!(idle_soc(&mut quiet), 0.0);
+        assert!(idle_soc(&mut idling) > 0.0);
+    }
+
+    #[test]
+    fn test_stall_protection_folds_back_current() {
+        let protection = StallProtection {
+            stall_speed_threshold: 1.0,
+            fold_back_after_s: 0.2,
+            folded_current_a: 40.0,
+        };
+        let mut bank = MotorBank::new(vec![MotorConstant::kraken_x60()]).with_stall_protection(protection);
+        let mut state = SimState::default();
+        state.true_state.motors = vec![MotorState::default()];
+        state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.3, duty_cycle_d: 0.0 }];
+
+        // Rotor held still, so the motor stays stalled the whole run
+        let dt = 1e-5;
+        let mut current_before_fold = 0.0;
+        for i in 0..30_000 {
+            bank.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+            if i == 15_000 {
+                current_before_fold = state.true_state.motors[0].current_q;
+            }
+        }
+
+        assert!(current_before_fold > 60.0, "stall current {}", current_before_fold);
+        let motor = state.true_state.motors[0];
+        assert!((motor.current_q.hypot(motor.current_d) - 40.0).abs() < 1e-9);
+        assert!(bank.stall_time(0) >= 0.2);
+    }
+
+    #[test]
+    fn test_group_of_identical_motors_scales_torque_and_current() {
+        let single = MotorGroup::uniform(MotorConstant::kraken_x60(), 1);
+        let mut group = MotorGroup::uniform(MotorConstant::kraken_x60(), 3);
+
+        let one = single.steady_state(0.6, 12.0, 200.0);
+        let three = group.steady_state(0.6, 12.0, 200.0);
+        assert!(one.torque > 0.0 && one.current > 0.0);
+        assert!((three.torque - 3.0 * one.torque).abs() < 1e-9);
+        assert!((three.current - 3.0 * one.current).abs() < 1e-9);
+        assert!((three.supply_current - 0.6 * three.current).abs() < 1e-9);
+
+        // The transient model settles to the same split
+        let mut single = single;
+        let dt = 1e-5;
+        let (mut one, mut three) = Default::default();
+        for _ in 0..20_000 {
+            one = single.step_transient(0.6, 12.0, 200.0, dt);
+            three = group.step_transient(0.6, 12.0, 200.0, dt);
+        }
+        assert!((three.torque - 3.0 * one.torque).abs() < 1e-9);
+        assert!((three.current - 3.0 * one.current).abs() < 1e-9);
+
+        // Inverting the steady-state model recovers the duty
+        let duty = group.duty_for_torque(group.steady_state(0.6, 12.0, 200.0).torque, 200.0, 12.0);
+        assert!((duty - 0.6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_step_records_applied_dq_voltages() {
+        let mut bank = MotorBank::new(vec![MotorConstant::kraken_x60()]);
+        let mut state = SimState::default();
+        state.true_state.battery_state.voltage = 12.5;
+        state.true_state.motors = vec![MotorState::default()];
+        state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.4, duty_cycle_d: 0.0 }];
+
+        // Rotor held at zero speed
+        bank.step_electrical(SimContext { dt: 1e-5, t: 0.0 }, &mut state);
+
+        let motor = state.true_state.motors[0];
+        assert_eq!(motor.voltage_q, 0.4 * 12.5);
+        assert_eq!(motor.voltage_d, 0.0);
+        assert!(motor.current_q > 0.0);
+    }
+
+    #[test]
+    fn test_open_loop_ramp_raises_voltage_linearly() {
+        let mut bank = MotorBank::new(vec![MotorConstant::kraken_x60()]).with_open_loop_ramp(0.1);
+        let mut state = SimState::default();
+        state.true_state.battery_state.voltage = 12.0;
+        state.true_state.motors = vec![MotorState::default()];
+        state.control_input.motor_inputs = vec![MotorInput { duty_cycle_q: 0.5, duty_cycle_d: 0.0 }];
+
+        // Full bus voltage takes 0.1 s, so half of it takes 50 ms at 120 V/s
+        let dt = 1e-4;
+        let mut voltages = Vec::new();
+        for i in 0..1000 {
+            bank.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+            voltages.push(state.true_state.motors[0].voltage_q);
+        }
+        for (i, &voltage) in voltages.iter().enumerate().take(499) {
+            let expected = 120.0 * (i + 1) as f64 * dt;
+            assert!((voltage - expected).abs() < 1e-9, "step {}: {} V, expected {} V", i, voltage, expected);
         }
+        assert!(voltages[500..].iter().all(|&v| (v - 6.0).abs() < 1e-9));
     }
 }
@@ -0,0 +1,188 @@
+//! Battery state-of-charge estimation
+//!
+//! The battery model in `battery` is the ground truth; a robot only sees noisy
+//! terminal voltage and current readings. `SocEstimator` recovers the state of
+//! charge from those with a complementary filter: coulomb counting tracks fast
+//! changes, and a slow pull towards the SoC implied by the open-circuit voltage
+//! removes the drift and initial error that counting alone never corrects.
+
+use crate::battery::{peukert_effective_capacity, update_rc_branch_voltage, BatteryConstant};
+
+/// Default rate at which the voltage-based estimate corrects the count (1/s)
+pub const DEFAULT_VOLTAGE_CORRECTION_GAIN: f64 = 0.05;
+
+/// Default time constant of the measurement moving average (s)
+pub const DEFAULT_AVERAGING_TIME: f64 = 1.0;
+
+/// Bisection iterations used to invert the OCV curve
+const OCV_INVERSION_ITERATIONS: usize = 40;
+
+/// Complementary-filter SoC estimator fed by voltage and current measurements
+#[derive(Debug, Clone)]
+pub struct SocEstimator {
+    constants: BatteryConstant,
+    soc: f64,
+    voltage_gain: f64,
+    averaging_time: f64,
+    average_voltage: Option<f64>,
+    average_current: f64,
+    fast_polarization_voltage: f64,
+    slow_polarization_voltage: f64,
+}
+
+impl SocEstimator {
+    /// Create an estimator for a battery described by `constants`, starting
+    /// from a guessed `initial_soc` (0-1)
+    pub fn new(constants: BatteryConstant, initial_soc: f64) -> Self {
+        Self {
+            constants,
+            soc: initial_soc.clamp(0.0, 1.0),
+            voltage_gain: DEFAULT_VOLTAGE_CORRECTION_GAIN,
+            averaging_time: DEFAULT_AVERAGING_TIME,
+            average_voltage: None,
+            average_current: 0.0,
+            fast_polarization_voltage: 0.0,
+            slow_polarization_voltage: 0.0,
+        }
+    }
+
+    /// Rate at which the estimate is pulled towards the voltage-implied SoC (1/s)
+    ///
+    /// Zero gives pure coulomb counting.
+    pub fn with_voltage_correction(mut self, gain: f64) -> Self {
+        self.voltage_gain = gain.max(0.0);
+        self
+    }
+
+    /// Time constant of the moving average applied to the measurements (s)
+    pub fn with_averaging_time(mut self, tau: f64) -> Self {
+        self.averaging_time = tau.max(0.0);
+        self
+    }
+
+    /// Current state-of-charge estimate (0-1)
+    pub fn soc(&self) -> f64 {
+        self.soc
+    }
+
+    /// Restart the estimate from `soc`, clearing the filter history
+    pub fn reset(&mut self, soc: f64) {
+        self.soc = soc.clamp(0.0, 1.0);
+        self.average_voltage = None;
+        self.average_current = 0.0;
+        self.fast_polarization_voltage = 0.0;
+        self.slow_polarization_voltage = 0.0;
+    }
+
+    /// Open-circuit voltage implied by the averaged measurements (V)
+    fn estimated_open_circuit_voltage(&self, voltage: f64) -> f64 {
+        voltage
+            + self.average_current * (self.constants.ohmic_resistance_function)(self.soc)
+            + self.fast_polarization_voltage
+            + self.slow_polarization_voltage
+    }
+
+    /// SoC whose open-circuit voltage is `ocv`, assuming a monotone OCV curve
+    fn soc_from_open_circuit_voltage(&self, ocv: f64) -> f64 {
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..OCV_INVERSION_ITERATIONS {
+            let mid = 0.5 * (low + high);
+            if (self.constants.open_circuit_voltage_function)(mid) < ocv {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        0.5 * (low + high)
+    }
+
+    /// Fold in one terminal voltage (V) and current draw (A) measurement taken
+    /// over `dt` seconds and return the updated SoC estimate
+    pub fn update(&mut self, voltage: f64, current: f64, dt: f64) -> f64 {
+        if dt <= 0.0 {
+            return self.soc;
+        }
+
+        // Coulomb counting on the raw current, matching the battery model
+        self.soc -= current / peukert_effective_capacity(current, &self.constants) * dt;
+
+        // Moving average of the measurements for the voltage path
+        let alpha = if self.averaging_time > 0.0 { dt / (self.averaging_time + dt) } else { 1.0 };
+        let average_voltage = match self.average_voltage {
+            Some(previous) => previous + alpha * (voltage - previous),
+            None => voltage,
+        };
+        self.average_voltage = Some(average_voltage);
+        self.average_current += alpha * (current - self.average_current);
+
+        self.fast_polarization_voltage = update_rc_branch_voltage(
+            dt,
+            self.average_current,
+            self.fast_polarization_voltage,
+            &self.constants.fast_polarization_constants,
+        );
+        self.slow_polarization_voltage = update_rc_branch_voltage(
+            dt,
+            self.average_current,
+            self.slow_polarization_voltage,
+            &self.constants.slow_polarization_constants,
+        );
+
+        // Slow correction towards the voltage-implied SoC
+        let voltage_soc = self.soc_from_open_circuit_voltage(self.estimated_open_circuit_voltage(average_voltage));
+        let blend = (self.voltage_gain * dt).min(1.0);
+        self.soc = (self.soc + blend * (voltage_soc - self.soc)).clamp(0.0, 1.0);
+        self.soc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battery::Battery;
+    use simcore::{ElectricalModel, SimContext, SimState};
+
+    /// Deterministic uniform noise in [-1, 1]
+    struct Noise(u64);
+
+    impl Noise {
+        fn next(&mut self) -> f64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+        }
+    }
+
+    #[test]
+    fn test_estimator_tracks_true_soc_through_noise() {
+        let constants = BatteryConstant::default();
+        let mut battery = Battery { constants };
+        let mut state = SimState::default();
+        state.true_state.battery_state.state_of_charge = 1.0;
+        state.true_state.battery_state.total_current_draw = 30.0;
+
+        // Start well off the true SoC so the voltage correction has work to do
+        let mut estimator = SocEstimator::new(constants, 0.7);
+        let mut noise = Noise(0x9e37_79b9_7f4a_7c15);
+
+        let dt = 0.01;
+        let mut worst_late_error: f64 = 0.0;
+        for i in 0..30_000 {
+            battery.step_electrical(SimContext { dt, t: i as f64 * dt }, &mut state);
+            let truth = state.true_state.battery_state;
+
+            let measured_voltage = truth.voltage + 0.1 * noise.next();
+            let measured_current = truth.total_current_draw + 2.0 * noise.next();
+            let estimate = estimator.update(measured_voltage, measured_current, dt);
+
+            if i * 2 >= 30_000 {
+                worst_late_error = worst_late_error.max((estimate - truth.state_of_charge).abs());
+            }
+        }
+
+        let truth = state.true_state.battery_state.state_of_charge;
+        assert!(truth < 0.8, "battery should have discharged, soc {}", truth);
+        assert!(worst_late_error < 0.02, "estimate drifted {} from truth", worst_late_error);
+    }
+}
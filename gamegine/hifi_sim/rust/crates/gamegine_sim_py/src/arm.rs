@@ -0,0 +1,260 @@
+//! Double-jointed arm simulation with batch execution
+//!
+//! Couples the two-link manipulator dynamics from `mechanics::arm` to a
+//! geared motor group on each joint. Like the mechanism simulator, motors use
+//! the steady-state model so millisecond time steps stay stable.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyDict;
+use numpy::ToPyArray;
+
+use mechanics::arm::{ArmLink, DoubleJointedArm};
+use electrical::motor::MotorGroup;
+use simcore::{checked_step_count, DEFAULT_MAX_STEPS};
+
+/// Rotor inertia of a typical brushless motor (kg*m^2)
+const ROTOR_INERTIA: f64 = 0.0001;
+
+/// Two-link arm (shoulder and elbow) driven by two geared motor groups
+///
+/// The shoulder angle is measured from horizontal and the elbow angle
+/// relative to the upper link, both counter-clockwise in radians. State
+/// persists between run() calls so duty cycles can be changed from Python.
+#[pyclass]
+pub struct PyDoubleJointedArmSim {
+    time: f64,
+    arm: DoubleJointedArm,
+
+    // Joint drives: shoulder then elbow
+    motors: [MotorGroup; 2],
+    gear_ratios: [f64; 2],
+    duty_cycles: [f64; 2],
+
+    // Battery model
+    battery_capacity_ah: f64,
+    battery_soc: f64,
+    battery_voltage: f64,
+
+    // Limit on steps per run() call
+    max_steps: u64,
+}
+
+#[pymethods]
+impl PyDoubleJointedArmSim {
+    /// Create a new double-jointed arm simulator
+    ///
+    /// Each link is modelled as a uniform rod. Rotor inertia is reflected
+    /// through each gearbox onto its joint.
+    ///
+    /// Args:
+    ///     shoulder_motor: Motor model driving the shoulder (PyMotor)
+    ///     elbow_motor: Motor model driving the elbow (PyMotor)
+    ///     battery: Battery model (PyBattery)
+    ///     shoulder_gearing: Shoulder reduction (motor turns per joint turn)
+    ///     elbow_gearing: Elbow reduction (motor turns per joint turn)
+    ///     upper_length: Upper link length in meters
+    ///     upper_mass: Upper link mass in kg
+    ///     forearm_length: Forearm length in meters
+    ///     forearm_mass: Forearm mass in kg
+    ///     num_shoulder_motors: Number of identical motors on the shoulder
+    ///     num_elbow_motors: Number of identical motors on the elbow
+    ///
+    /// Raises:
+    ///     ValueError: if a joint has no motors or a non-positive gearing
+    #[new]
+    #[pyo3(signature = (
+        shoulder_motor, elbow_motor, battery, shoulder_gearing, elbow_gearing,
+        upper_length, upper_mass, forearm_length, forearm_mass,
+        num_shoulder_motors=1, num_elbow_motors=1
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        shoulder_motor: &crate::motor::PyMotor,
+        elbow_motor: &crate::motor::PyMotor,
+        battery: &crate::battery::PyBattery,
+        shoulder_gearing: f64,
+        elbow_gearing: f64,
+        upper_length: f64,
+        upper_mass: f64,
+        forearm_length: f64,
+        forearm_mass: f64,
+        num_shoulder_motors: usize,
+        num_elbow_motors: usize,
+    ) -> PyResult<Self> {
+        if num_shoulder_motors == 0 || num_elbow_motors == 0 {
+            return Err(PyValueError::new_err("each arm joint needs at least one motor"));
+        }
+        if shoulder_gearing <= 0.0 || elbow_gearing <= 0.0 {
+            return Err(PyValueError::new_err("gearing must be positive"));
+        }
+
+        let gear_ratios = [shoulder_gearing, elbow_gearing];
+        let counts = [num_shoulder_motors, num_elbow_motors];
+        let reflected = |joint: usize| ROTOR_INERTIA * counts[joint] as f64 * gear_ratios[joint].powi(2);
+        let arm = DoubleJointedArm::new(
+            ArmLink::new(upper_length, upper_mass),
+            ArmLink::new(forearm_length, forearm_mass),
+        )
+        .with_joint_inertia(reflected(0), reflected(1));
+
+        let battery_inner = battery.inner();
+        Ok(PyDoubleJointedArmSim {
+            time: 0.0,
+            arm,
+            motors: [
+                MotorGroup::uniform(*shoulder_motor.inner(), num_shoulder_motors),
+                MotorGroup::uniform(*elbow_motor.inner(), num_elbow_motors),
+            ],
+            gear_ratios,
+            duty_cycles: [0.0; 2],
+            battery_capacity_ah: battery_inner.rated_capacity_ah,
+            battery_soc: 1.0,
+            battery_voltage: (battery_inner.open_circuit_voltage_function)(1.0),
+            max_steps: DEFAULT_MAX_STEPS,
+        })
+    }
+
+    /// Set shoulder and elbow duty cycles (-1.0 to 1.0)
+    fn set_duty_cycles(&mut self, shoulder: f64, elbow: f64) {
+        self.duty_cycles = [shoulder.clamp(-1.0, 1.0), elbow.clamp(-1.0, 1.0)];
+    }
+
+    /// Get (shoulder, elbow) angles in radians
+    fn angles(&self) -> (f64, f64) {
+        let [shoulder, elbow] = self.arm.angles();
+        (shoulder, elbow)
+    }
+
+    /// Get (shoulder, elbow) angular velocities in rad/s
+    fn velocities(&self) -> (f64, f64) {
+        let [shoulder, elbow] = self.arm.velocities();
+        (shoulder, elbow)
+    }
+
+    /// Get the (x, y) position of the forearm tip relative to the shoulder
+    fn end_effector(&self) -> (f64, f64) {
+        let [x, y] = self.arm.end_effector();
+        (x, y)
+    }
+
+    /// Get current time
+    fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Place the arm at the given joint angles and velocities
+    #[pyo3(signature = (shoulder_angle, elbow_angle, shoulder_velocity=0.0, elbow_velocity=0.0))]
+    fn set_state(&mut self, shoulder_angle: f64, elbow_angle: f64, shoulder_velocity: f64, elbow_velocity: f64) {
+        self.arm.set_state([shoulder_angle, elbow_angle], [shoulder_velocity, elbow_velocity]);
+    }
+
+    /// Duty cycles that hold the arm at rest in its current pose
+    ///
+    /// Balances the gravity torque on each joint with the steady-state motor
+    /// model at stall at the present battery voltage. A magnitude above 1.0
+    /// means that joint cannot hold the arm.
+    fn holding_duties(&self) -> (f64, f64) {
+        let gravity = self.arm.gravity_torques();
+        let duty = |joint: usize| {
+            let motor_torque = gravity[joint] / self.gear_ratios[joint];
+            self.motors[joint].duty_for_torque(motor_torque, 0.0, self.battery_voltage)
+        };
+        (duty(0), duty(1))
+    }
+
+    /// Run simulation for specified duration
+    ///
+    /// State persists between runs, allowing chained calls with different inputs.
+    ///
+    /// Args:
+    ///     duration: Simulation time (seconds)
+    ///     dt: Time step (seconds), default 0.001 (1ms)
+    ///
+    /// Returns:
+    ///     Dict of numpy arrays: times, shoulder_angle, elbow_angle,
+    ///     shoulder_velocity, elbow_velocity, shoulder_current, elbow_current,
+    ///     voltage
+    ///
+    /// Raises:
+    ///     ValueError: if dt/duration are invalid or the run exceeds max_steps
+    #[pyo3(signature = (duration, dt=0.001))]
+    fn run<'py>(&mut self, py: Python<'py>, duration: f64, dt: f64) -> PyResult<Bound<'py, PyDict>> {
+        let n_steps = checked_step_count(duration, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))? as usize;
+
+        let mut times = Vec::with_capacity(n_steps);
+        let mut angles = [Vec::with_capacity(n_steps), Vec::with_capacity(n_steps)];
+        let mut velocities = [Vec::with_capacity(n_steps), Vec::with_capacity(n_steps)];
+        let mut currents = [Vec::with_capacity(n_steps), Vec::with_capacity(n_steps)];
+        let mut voltages = Vec::with_capacity(n_steps);
+
+        for _ in 0..n_steps {
+            let joint_velocities = self.arm.velocities();
+            let mut torques = [0.0; 2];
+            let mut supply_current = 0.0;
+            for joint in 0..2 {
+                let motor_velocity = joint_velocities[joint] * self.gear_ratios[joint];
+                let output = self.motors[joint].steady_state(self.duty_cycles[joint], self.battery_voltage, motor_velocity);
+                torques[joint] = output.torque * self.gear_ratios[joint];
+                supply_current += output.supply_current;
+                currents[joint].push(output.current);
+            }
+
+            times.push(self.time);
+            let [shoulder, elbow] = self.arm.angles();
+            angles[0].push(shoulder);
+            angles[1].push(elbow);
+            velocities[0].push(joint_velocities[0]);
+            velocities[1].push(joint_velocities[1]);
+            voltages.push(self.battery_voltage);
+
+            self.arm.step(torques, dt);
+            self.time += dt;
+
+            // Simple Ah counting with voltage sag
+            let amp_hours = supply_current.abs() * (dt / 3600.0);
+            self.battery_soc = (self.battery_soc - amp_hours / self.battery_capacity_ah).clamp(0.0, 1.0);
+            self.battery_voltage = self.loaded_voltage(supply_current);
+        }
+
+        let dict = PyDict::new_bound(py);
+        dict.set_item("times", times.to_pyarray_bound(py))?;
+        dict.set_item("shoulder_angle", angles[0].to_pyarray_bound(py))?;
+        dict.set_item("elbow_angle", angles[1].to_pyarray_bound(py))?;
+        dict.set_item("shoulder_velocity", velocities[0].to_pyarray_bound(py))?;
+        dict.set_item("elbow_velocity", velocities[1].to_pyarray_bound(py))?;
+        dict.set_item("shoulder_current", currents[0].to_pyarray_bound(py))?;
+        dict.set_item("elbow_current", currents[1].to_pyarray_bound(py))?;
+        dict.set_item("voltage", voltages.to_pyarray_bound(py))?;
+        Ok(dict)
+    }
+
+    /// Set the maximum number of steps a single run() may take
+    fn set_max_steps(&mut self, max_steps: u64) {
+        self.max_steps = max_steps;
+    }
+
+    /// Get the maximum number of steps a single run() may take
+    fn max_steps(&self) -> u64 {
+        self.max_steps
+    }
+
+    /// Reset to a horizontal arm at rest with a full battery
+    fn reset(&mut self) {
+        self.time = 0.0;
+        self.arm.set_state([0.0; 2], [0.0; 2]);
+        self.duty_cycles = [0.0; 2];
+        self.battery_soc = 1.0;
+        self.battery_voltage = self.loaded_voltage(0.0);
+    }
+}
+
+impl PyDoubleJointedArmSim {
+    /// Battery terminal voltage while supplying `current`: OCV(SoC) - I * Rint
+    fn loaded_voltage(&self, current: f64) -> f64 {
+        let ocv = 10.5 + 2.5 * self.battery_soc;
+        let resistance = 0.01 + 0.01 * (1.0 - self.battery_soc);
+        ocv - current.abs() * resistance
+    }
+}
@@ -4,7 +4,10 @@ use pyo3::prelude::*;
 use numpy::ToPyArray;
 use pyo3::types::PyDict;
 use electrical::battery::{BatteryConstant, Peukert, RCBranch, default_ocv_from_soc, default_r0_from_soc};
-use electrical::analysis::{simulate_battery_discharge, voltage_sag_analysis, effective_capacity_ah};
+use electrical::analysis::{
+    simulate_battery_discharge, voltage_sag_analysis, effective_capacity_ah, voltage_under_load,
+    minimum_voltage_during,
+};
 
 /// Python-accessible battery representation with analysis functions
 #[pyclass]
@@ -48,6 +51,7 @@ impl PyBattery {
             ohmic_resistance_function: |soc| default_r0_from_soc(soc, 0.008),
             fast_polarization_constants: RCBranch { resistance: 0.0027, capacitance: 741.0 },
             slow_polarization_constants: RCBranch { resistance: 0.0018, capacitance: 66667.0 },
+            thermal: None,
         };
         PyBattery { inner: constants }
     }
@@ -103,6 +107,32 @@ impl PyBattery {
         voltage_sag_analysis(&self.inner, peak_current)
     }
 
+    /// Predict terminal voltage at a given current and state of charge
+    ///
+    /// Instantaneous sag only: OCV(soc) - I * R0(soc), ignoring transients.
+    ///
+    /// Args:
+    ///     current_a: Load current (A)
+    ///     soc: State of charge (0.0-1.0)
+    fn voltage_at_current(&self, current_a: f64, soc: f64) -> f64 {
+        voltage_under_load(&self.inner, current_a, soc)
+    }
+
+    /// Find the minimum voltage while drawing a current profile
+    ///
+    /// Includes SoC depletion and polarization transients over the profile.
+    /// Returns (min_voltage, time_of_min_voltage), or None for an empty
+    /// profile or dt <= 0
+    ///
+    /// Args:
+    ///     profile: Current draw (A) sampled every dt seconds
+    ///     dt: Profile sample period (seconds), default 0.02
+    ///     soc: Initial state of charge, default 1.0
+    #[pyo3(signature = (profile, dt=0.02, soc=1.0))]
+    fn minimum_voltage_during(&self, profile: Vec<f64>, dt: f64, soc: f64) -> Option<(f64, f64)> {
+        minimum_voltage_during(&self.inner, &profile, dt, soc)
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "Battery(capacity={:.1} Ah, OCV@100%={:.2} V)",
@@ -0,0 +1,156 @@
+//! Motor controller bindings
+//!
+//! Exposes a single closed-loop motor controller so control modes and
+//! commutation strategies can be compared from Python step by step.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+
+use control::{commutation_from_name, CommutationStrategy, ControlMode, MotorController, MotorControllerConfig};
+use simcore::MotorState;
+
+use crate::motor::PyMotor;
+
+fn parse_commutation(name: &str) -> PyResult<Box<dyn CommutationStrategy>> {
+    commutation_from_name(name).ok_or_else(|| {
+        PyValueError::new_err(format!(
+            "unknown commutation '{}', expected 'foc', 'trapezoidal' or 'sinusoidal'",
+            name
+        ))
+    })
+}
+
+fn parse_mode(mode: &str) -> PyResult<ControlMode> {
+    match mode {
+        "duty_cycle" => Ok(ControlMode::DutyCycle),
+        "current" => Ok(ControlMode::Current),
+        "velocity" => Ok(ControlMode::Velocity),
+        "position" => Ok(ControlMode::Position),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown control mode '{}', expected 'duty_cycle', 'current', 'velocity' or 'position'",
+            mode
+        ))),
+    }
+}
+
+/// Closed-loop motor controller with a swappable commutation strategy
+#[pyclass]
+pub struct PyMotorController {
+    inner: MotorController,
+}
+
+#[pymethods]
+impl PyMotorController {
+    /// Create a motor controller with default loop gains
+    ///
+    /// Args:
+    ///     motor: Motor model (PyMotor)
+    ///     mode: "duty_cycle", "current", "velocity" or "position"
+    ///     commutation: "foc", "trapezoidal" or "sinusoidal"
+    ///     soft_limits: Optional (min, max) position limits (rad); position
+    ///         setpoints are clamped to them and output that would drive past
+    ///         a reached limit is zeroed
+    ///     current_sense: Optional (noise_std, filter_time_constant, seed): in
+    ///         current mode, the loop sees the current with seeded Gaussian
+    ///         noise (A) through a first-order low-pass filter (s)
+    ///     voltage_limit: Optional bus voltage (V): velocity references are
+    ///         capped at the free speed it can reach and the velocity loop
+    ///         saturates at full duty without winding up
+    ///
+    /// Raises:
+    ///     ValueError: if the mode or commutation name is unknown
+    #[new]
+    #[pyo3(signature = (motor, mode="duty_cycle", commutation="foc", soft_limits=None, current_sense=None, voltage_limit=None))]
+    fn new(
+        motor: &PyMotor,
+        mode: &str,
+        commutation: &str,
+        soft_limits: Option<(f64, f64)>,
+        current_sense: Option<(f64, f64, u64)>,
+        voltage_limit: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut config = MotorControllerConfig::new(*motor.inner()).with_mode(parse_mode(mode)?);
+        if let Some((min, max)) = soft_limits {
+            config = config.with_soft_limits(min, max);
+        }
+        if let Some((noise_std, filter_time_constant, seed)) = current_sense {
+            config = config.with_current_sense(noise_std, filter_time_constant, seed);
+        }
+        if let Some(bus_voltage) = voltage_limit {
+            config = config.with_voltage_limit(bus_voltage);
+        }
+        Ok(PyMotorController {
+            inner: MotorController::with_commutation(config, parse_commutation(commutation)?),
+        })
+    }
+
+    /// Swap the commutation strategy, keeping setpoint and loop state
+    ///
+    /// Args:
+    ///     name: "foc", "trapezoidal" or "sinusoidal"
+    ///
+    /// Raises:
+    ///     ValueError: if the name is unknown
+    fn set_commutation(&mut self, name: &str) -> PyResult<()> {
+        self.inner.set_commutation(parse_commutation(name)?);
+        Ok(())
+    }
+
+    /// Average efficiency of the active commutation strategy (0-1)
+    fn commutation_efficiency(&self) -> f64 {
+        self.inner.commutation_efficiency()
+    }
+
+    /// Set the setpoint (units depend on the control mode)
+    fn set_setpoint(&mut self, setpoint: f64) {
+        self.inner.set_setpoint(setpoint);
+    }
+
+    /// Get the setpoint
+    fn setpoint(&self) -> f64 {
+        self.inner.setpoint()
+    }
+
+    /// Run one controller update
+    ///
+    /// Args:
+    ///     velocity: Measured motor velocity (rad/s)
+    ///     current_q: Measured q-axis current (A)
+    ///     dt: Time step (s)
+    ///
+    /// Returns:
+    ///     Tuple of (duty_cycle_q, duty_cycle_d)
+    #[pyo3(signature = (velocity, current_q, dt))]
+    fn update(&mut self, velocity: f64, current_q: f64, dt: f64) -> (f64, f64) {
+        let motor_state = MotorState {
+            mechanical_velocity: velocity,
+            current_q,
+            ..Default::default()
+        };
+        let input = self.inner.update(&motor_state, dt);
+        (input.duty_cycle_q, input.duty_cycle_d)
+    }
+
+    /// Current measurement last fed to the current loop (A), or None before
+    /// the first current-mode update
+    fn current_feedback(&self) -> Option<f64> {
+        self.inner.current_feedback()
+    }
+
+    /// Update the bus voltage the velocity loop is limited by (V); ignored
+    /// without a voltage_limit
+    fn set_bus_voltage(&mut self, voltage: f64) {
+        self.inner.set_bus_voltage(voltage);
+    }
+
+    /// Fastest motor velocity the bus can reach (rad/s), or None without a
+    /// voltage_limit
+    fn achievable_velocity(&self) -> Option<f64> {
+        self.inner.achievable_velocity()
+    }
+
+    /// Reset setpoint, position estimate and loop state
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
@@ -1,17 +1,30 @@
 //! Drivetrain and simulation bindings with batch execution
 
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
 use pyo3::prelude::*;
+use pyo3::exceptions::{PyIOError, PyIndexError, PyKeyError, PyValueError};
 use pyo3::types::PyDict;
-use numpy::ToPyArray;
+use numpy::{PyArray2, ToPyArray};
 
 use simcore::{
-    ActuatorInput, BatteryState, BodyState, MotorInput, MotorState, SimContext, SimState,
-    TireState, TrueState, WheelState, SensorBus,
+    ActuatorInput, BatteryState, BodyState, IntegratorKind, MotorInput, MotorState, SimContext, SimState,
+    TireState, TrueState, WheelState, SensorBus, WheelPeaks, WheelTrace,
+    CommandSpec, DrivetrainSpec, Scenario, ScenarioError, Decimator, StepCounts, substep_count,
+    NoiseSeeds, NoiseSource, SensorNoise, Divergence, ReplaySource, Trajectory, VelocityFrame,
 };
-use mechanics::{SwerveDrivetrain, SwerveDrivetrainConfig};
-use mechanics::tire::{TireManager, TireConstants};
+use mechanics::{PowerFlow, SwerveDrivetrain, SwerveDrivetrainConfig};
+use mechanics::gearbox::{Gear, TwoSpeedGearbox};
+use mechanics::tire::{RelaxationMode, TireManager, TireConstants};
 use electrical::motor::{MotorBank, MotorConstant};
-use simcore::{ElectricalModel, MechanicsModel, Model};
+use electrical::analysis::{fit_coast_down, StraightLineDrive, DEFAULT_BROWNOUT_VOLTAGE};
+use electrical::battery::{Battery, BatteryConstant, BatteryThermal};
+use simcore::{ElectricalModel, MechanicsModel, Model, checked_step_count, StepBudgetError, DEFAULT_MAX_STEPS};
+
+use control::{GoalShaper, SlewRateGoal, SteerMotorConfig, DEFAULT_STEER_CURRENT_LIMIT};
+
+use crate::motor::PyMotor;
 
 /// Python-accessible swerve drivetrain configuration
 #[pyclass]
@@ -28,9 +41,33 @@ impl PySwerveDrivetrain {
     ///     mass: Robot mass (kg)
     ///     moment_of_inertia: Yaw moment of inertia (kg*m^2)
     ///     module_positions: List of [x, y] module positions (m), default is square
+    ///     drive_inverted: Per-module flags for mirrored modules whose wheel spins
+    ///         backward for positive motor torque, default none inverted
+    ///     com_offset: [x, y] of the center of mass relative to the module
+    ///         layout's origin (m), default centered
+    ///     cg_height: Center of mass height (m); when positive, tire loads
+    ///         shift against the acceleration. Default 0, no load transfer
+    ///     load_transfer_time_constant: Lag of the load transfer from wheel
+    ///         and chassis compliance (s), default 0 (instantaneous)
+    ///     max_steer_rate: Fastest a module can turn (rad/s); default
+    ///         unlimited, so modules snap to their commanded angle
     #[new]
-    #[pyo3(signature = (mass=50.0, moment_of_inertia=5.0, module_positions=None))]
-    fn new(mass: f64, moment_of_inertia: f64, module_positions: Option<Vec<[f64; 2]>>) -> Self {
+    #[pyo3(signature = (
+        mass=50.0, moment_of_inertia=5.0, module_positions=None, drive_inverted=None,
+        com_offset=[0.0, 0.0], cg_height=0.0, load_transfer_time_constant=0.0,
+        max_steer_rate=f64::INFINITY
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        mass: f64,
+        moment_of_inertia: f64,
+        module_positions: Option<Vec<[f64; 2]>>,
+        drive_inverted: Option<Vec<bool>>,
+        com_offset: [f64; 2],
+        cg_height: f64,
+        load_transfer_time_constant: f64,
+        max_steer_rate: f64,
+    ) -> Self {
         let positions = module_positions.unwrap_or_else(|| {
             let half_side = 0.3;
             vec![
@@ -48,6 +85,12 @@ impl PySwerveDrivetrain {
                 moment_of_inertia,
                 wheel_inertia: 0.01,
                 steer_inertia: 0.005,
+                drive_inverted: drive_inverted.unwrap_or_default(),
+                com_offset,
+                cg_height,
+                load_transfer_time_constant,
+                max_steer_rate,
+                ..SwerveDrivetrainConfig::default()
             },
         }
     }
@@ -79,6 +122,10 @@ pub struct SimulationResult {
     velocities_y: Vec<f64>,
     angular_velocities: Vec<f64>,
     battery_voltages: Vec<f64>,
+    wheels: Option<WheelTrace>,
+    peaks: Option<WheelPeaks>,
+    power_flows: Option<Vec<PowerFlow>>,
+    step_counts: StepCounts,
 }
 
 #[pymethods]
@@ -97,6 +144,73 @@ impl SimulationResult {
         Ok(dict)
     }
     
+    /// Get per-wheel data as a dictionary of 2D numpy arrays [n_steps, n_modules]
+    ///
+    /// Keys: slip_ratio, slip_angle, fx, fy, omega. Row k is the wheel state
+    /// at times[k], before step k, so the first row is the starting state.
+    /// Returns None unless the run was made with record_wheels=True.
+    fn wheel_data<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(wheels) = &self.wheels else {
+            return Ok(None);
+        };
+        let dict = PyDict::new_bound(py);
+        dict.set_item("slip_ratio", PyArray2::from_vec2_bound(py, &wheels.slip_ratio)?)?;
+        dict.set_item("slip_angle", PyArray2::from_vec2_bound(py, &wheels.slip_angle)?)?;
+        dict.set_item("fx", PyArray2::from_vec2_bound(py, &wheels.longitudinal_force)?)?;
+        dict.set_item("fy", PyArray2::from_vec2_bound(py, &wheels.lateral_force)?)?;
+        dict.set_item("omega", PyArray2::from_vec2_bound(py, &wheels.angular_velocity)?)?;
+        Ok(Some(dict))
+    }
+
+    /// Get per-wheel peak magnitudes as a dictionary of numpy arrays [n_modules]
+    ///
+    /// Keys: fx, fy, slip_ratio, slip_angle.
+    /// Returns None unless the run was made with record_peaks=True.
+    fn wheel_peaks<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(peaks) = &self.peaks else {
+            return Ok(None);
+        };
+        let dict = PyDict::new_bound(py);
+        dict.set_item("fx", peaks.longitudinal_force.to_pyarray_bound(py))?;
+        dict.set_item("fy", peaks.lateral_force.to_pyarray_bound(py))?;
+        dict.set_item("slip_ratio", peaks.slip_ratio.to_pyarray_bound(py))?;
+        dict.set_item("slip_angle", peaks.slip_angle.to_pyarray_bound(py))?;
+        Ok(Some(dict))
+    }
+
+    /// Get per-step drivetrain power as a dictionary of numpy arrays
+    ///
+    /// Keys: mechanical (W delivered to the chassis), electrical (W drawn
+    /// from the battery), efficiency (mechanical / electrical, NaN with no
+    /// electrical input).
+    /// Returns None unless power reporting was enabled for the run.
+    fn power_data<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(flows) = &self.power_flows else {
+            return Ok(None);
+        };
+        let mechanical: Vec<f64> = flows.iter().map(|flow| flow.mechanical).collect();
+        let electrical: Vec<f64> = flows.iter().map(|flow| flow.electrical).collect();
+        let efficiency: Vec<f64> = flows.iter().map(|flow| flow.efficiency().unwrap_or(f64::NAN)).collect();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("mechanical", mechanical.to_pyarray_bound(py))?;
+        dict.set_item("electrical", electrical.to_pyarray_bound(py))?;
+        dict.set_item("efficiency", efficiency.to_pyarray_bound(py))?;
+        Ok(Some(dict))
+    }
+
+    /// Get the number of solver steps the run took
+    ///
+    /// Returns:
+    ///     Dict with electrical_steps (motor model), mechanics_steps (tires
+    ///     and drivetrain) and battery_steps
+    fn step_counts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("electrical_steps", self.step_counts.electrical)?;
+        dict.set_item("mechanics_steps", self.step_counts.mechanics)?;
+        dict.set_item("battery_steps", self.step_counts.battery)?;
+        Ok(dict)
+    }
+
     /// Get final position as (x, y, heading)
     fn final_pose(&self) -> (f64, f64, f64) {
         (
@@ -107,6 +221,68 @@ impl SimulationResult {
     }
 }
 
+impl SimulationResult {
+    /// Result with no samples, optionally ready for wheel series, peaks and power
+    fn empty(record_wheels: bool, record_peaks: bool, record_power: bool) -> Self {
+        SimulationResult {
+            times: Vec::new(),
+            positions_x: Vec::new(),
+            positions_y: Vec::new(),
+            headings: Vec::new(),
+            velocities_x: Vec::new(),
+            velocities_y: Vec::new(),
+            angular_velocities: Vec::new(),
+            battery_voltages: Vec::new(),
+            wheels: record_wheels.then(WheelTrace::default),
+            peaks: record_peaks.then(WheelPeaks::default),
+            power_flows: record_power.then(Vec::new),
+            step_counts: StepCounts::default(),
+        }
+    }
+
+    /// Append the samples of a later run
+    fn append(&mut self, mut other: SimulationResult) {
+        self.times.append(&mut other.times);
+        self.positions_x.append(&mut other.positions_x);
+        self.positions_y.append(&mut other.positions_y);
+        self.headings.append(&mut other.headings);
+        self.velocities_x.append(&mut other.velocities_x);
+        self.velocities_y.append(&mut other.velocities_y);
+        self.angular_velocities.append(&mut other.angular_velocities);
+        self.battery_voltages.append(&mut other.battery_voltages);
+        if let (Some(wheels), Some(other_wheels)) = (self.wheels.as_mut(), other.wheels) {
+            wheels.append(other_wheels);
+        }
+        if let (Some(peaks), Some(other_peaks)) = (self.peaks.as_mut(), other.peaks) {
+            peaks.merge(&other_peaks);
+        }
+        if let (Some(flows), Some(mut other_flows)) = (self.power_flows.as_mut(), other.power_flows) {
+            flows.append(&mut other_flows);
+        }
+        self.step_counts.merge(&other.step_counts);
+    }
+}
+
+/// Duty ramp rate rejected by set_duty_ramp_rate(), raised as ValueError
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct InvalidRampRate(f64);
+
+impl std::fmt::Display for InvalidRampRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "duty ramp rate must be positive, got {}", self.0)
+    }
+}
+
+impl From<InvalidRampRate> for PyErr {
+    fn from(err: InvalidRampRate) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// State handed to a run_streaming() callback:
+/// (time, x, y, heading, vx, vy, omega, battery_voltage)
+type StreamSample = (f64, f64, f64, f64, f64, f64, f64, f64);
+
 /// High-fidelity swerve simulation with batched execution
 #[pyclass]
 pub struct PySimulator {
@@ -114,7 +290,30 @@ pub struct PySimulator {
     drivetrain: SwerveDrivetrain,
     tire_manager: TireManager,
     motor_bank: MotorBank,
+    battery: Battery,
     time: f64,
+    max_steps: u64,
+    /// Commanded module angles, tracked at up to max_steer_rate
+    steer_targets: Vec<f64>,
+    /// Body state restored by reset()
+    initial_body: BodyState,
+    /// Battery state of charge restored by reset()
+    initial_soc: f64,
+    /// Time step for run_scenario()
+    scenario_dt: f64,
+    /// Command schedule loaded from a scenario
+    schedule: Vec<CommandSpec>,
+    /// Per-module ramp from commanded to applied duty
+    duty_ramps: Vec<SlewRateGoal>,
+    /// Longest physics substep; None steps physics once per run() dt
+    fixed_dt: Option<f64>,
+    /// Electrical steps per physics substep
+    elec_substeps: usize,
+    /// Seeded noise on the sensor readings
+    sensor_noise: SensorNoise,
+    /// Steer motor behind each module's steer servo, in module order; empty
+    /// for ideal servos that draw no current
+    steer_motors: Vec<SteerMotorConfig>,
 }
 
 #[pymethods]
@@ -123,15 +322,1106 @@ impl PySimulator {
     /// 
     /// Args:
     ///     drivetrain: Drivetrain configuration
+    ///     motors: Optional list of drive motors, one per module (default: Kraken X60 on all)
+    ///     seed: Base seed the per-source sensor noise seeds are derived from
+    ///     seeds: Optional dict overriding the seed of "encoder", "imu" or "battery"
+    ///
+    /// Raises:
+    ///     ValueError: if the motor count is wrong or a seed names an unknown source
     #[new]
-    fn new(drivetrain: &PySwerveDrivetrain) -> Self {
+    #[pyo3(signature = (drivetrain, motors=None, seed=0, seeds=None))]
+    fn new(
+        drivetrain: &PySwerveDrivetrain,
+        motors: Option<Vec<PyMotor>>,
+        seed: u64,
+        seeds: Option<HashMap<String, u64>>,
+    ) -> PyResult<Self> {
         let config = drivetrain.config.clone();
         let num_modules = config.module_positions.len();
-        let mass = config.mass;
+
+        let motor_constants: Vec<MotorConstant> = match motors {
+            Some(motors) => {
+                if motors.len() != num_modules {
+                    return Err(PyValueError::new_err(format!(
+                        "expected {} motors (one per module), got {}",
+                        num_modules,
+                        motors.len()
+                    )));
+                }
+                motors.iter().map(|m| *m.inner()).collect()
+            }
+            None => vec![MotorConstant::kraken_x60(); num_modules],
+        };
+
+        let mut sim = Self::with_motors(config, motor_constants);
+        sim.sensor_noise.reseed(noise_seeds(seed, seeds)?);
+        Ok(sim)
+    }
+
+    /// Run simulation for specified duration - all steps executed in Rust
+    /// 
+    /// This is the primary API for batch simulation. All physics steps
+    /// run in Rust without Python callbacks for maximum performance.
+    /// Results are deterministic: the same inputs from the same state (after
+    /// reset(), with the same noise seeds) give bit-identical arrays,
+    /// independent of machine speed.
+    /// 
+    /// Args:
+    ///     duration: Total simulation time (seconds)
+    ///     dt: Time step (seconds)
+    ///     duty_cycles: List of duty cycles for each module (0-1)
+    ///     steer_angles: List of steering angles for each module (radians)
+    ///     record_wheels: Also record per-wheel slip, force and omega series
+    ///     record_peaks: Also track per-wheel peak force and slip magnitudes
+    /// 
+    /// Returns:
+    ///     SimulationResult with all time series data; sample k is the state
+    ///     before step k, so the first sample is the starting state
+    ///
+    /// Raises:
+    ///     ValueError: if dt/duration are invalid or the run exceeds max_steps
+    #[pyo3(signature = (duration, dt=0.001, duty_cycles=None, steer_angles=None, record_wheels=false, record_peaks=false))]
+    fn run(
+        &mut self,
+        duration: f64,
+        dt: f64,
+        duty_cycles: Option<Vec<f64>>,
+        steer_angles: Option<Vec<f64>>,
+        record_wheels: bool,
+        record_peaks: bool,
+    ) -> PyResult<SimulationResult> {
+        let n_steps = checked_step_count(duration, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))? as usize;
+        let commanded_duty = self.apply_commands(duty_cycles, steer_angles);
+        Ok(self.record_run(dt, &commanded_duty, n_steps, record_wheels, record_peaks))
+    }
+
+    /// Run the simulation in Rust, handing state to a callback every few steps
+    ///
+    /// For live dashboards: no result arrays are built, and Python is only
+    /// entered once every `every` steps.
+    ///
+    /// Args:
+    ///     duration: Total simulation time (seconds)
+    ///     dt: Time step (seconds)
+    ///     callback: Called as callback(state) with the tuple
+    ///         (time, x, y, heading, vx, vy, omega, battery_voltage)
+    ///     every: Steps between callbacks
+    ///     duty_cycles: List of duty cycles for each module (0-1)
+    ///     steer_angles: List of steering angles for each module (radians)
+    ///
+    /// Returns:
+    ///     Number of callbacks made
+    ///
+    /// Raises:
+    ///     ValueError: if dt/duration/every are invalid or the run exceeds max_steps
+    ///     Any exception raised by the callback, which stops the run
+    #[pyo3(signature = (duration, dt, callback, every=10, duty_cycles=None, steer_angles=None))]
+    fn run_streaming(
+        &mut self,
+        duration: f64,
+        dt: f64,
+        callback: &Bound<'_, PyAny>,
+        every: u64,
+        duty_cycles: Option<Vec<f64>>,
+        steer_angles: Option<Vec<f64>>,
+    ) -> PyResult<u64> {
+        if every == 0 {
+            return Err(PyValueError::new_err("every must be at least 1"));
+        }
+        let n_steps = checked_step_count(duration, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let commanded_duty = self.apply_commands(duty_cycles, steer_angles);
+        self.stream_run(dt, &commanded_duty, n_steps, every, |sample| callback.call1((sample,)).map(drop))
+    }
+
+    /// Load a simulator from a scenario JSON string
+    ///
+    /// Raises:
+    ///     ValueError: if the scenario is malformed or invalid
+    #[staticmethod]
+    fn from_scenario_json(text: &str) -> PyResult<Self> {
+        Scenario::from_json(text).and_then(Self::from_scenario).map_err(scenario_error)
+    }
+
+    /// Load a simulator from a scenario JSON file
+    ///
+    /// Raises:
+    ///     IOError: if the file cannot be read
+    ///     ValueError: if the scenario is malformed or invalid
+    #[staticmethod]
+    fn from_scenario_file(path: &str) -> PyResult<Self> {
+        Scenario::from_file(path).and_then(Self::from_scenario).map_err(scenario_error)
+    }
+
+    /// Run the loaded scenario's command schedule from its initial state
+    ///
+    /// Args:
+    ///     record_wheels: Also record per-wheel slip, force and omega series
+    ///     record_peaks: Also track per-wheel peak force and slip magnitudes
+    ///
+    /// Returns:
+    ///     SimulationResult covering the whole schedule
+    #[pyo3(signature = (record_wheels=false, record_peaks=false))]
+    fn run_scenario(&mut self, record_wheels: bool, record_peaks: bool) -> PyResult<SimulationResult> {
+        self.run_schedule(record_wheels, record_peaks)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Replay the loaded scenario's commands and score the simulated
+    /// trajectory against a recorded one
+    ///
+    /// The scenario's command schedule stands in for the real robot's
+    /// command log; the sim restarts from the scenario's initial state.
+    ///
+    /// Args:
+    ///     reference: Dict of equal-length arrays with keys times, x, y,
+    ///         heading, vx, vy (as produced by SimulationResult.to_dict())
+    ///
+    /// Returns:
+    ///     Dict with position_rmse (m), heading_rmse (rad) and velocity_rmse
+    ///     (m/s), or None if the reference is empty
+    ///
+    /// Raises:
+    ///     KeyError: if a reference channel is missing
+    ///     ValueError: if the reference channels differ in length, or a
+    ///         command runs more than max_steps steps
+    fn replay_divergence<'py>(&mut self, py: Python<'py>, reference: &Bound<'py, PyDict>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let channel = |key: &str| -> PyResult<Vec<f64>> {
+            reference
+                .get_item(key)?
+                .ok_or_else(|| PyKeyError::new_err(format!("reference is missing '{}'", key)))?
+                .extract()
+        };
+        let reference = Trajectory {
+            times: channel("times")?,
+            x: channel("x")?,
+            y: channel("y")?,
+            heading: channel("heading")?,
+            vx: channel("vx")?,
+            vy: channel("vy")?,
+        };
+        let n = reference.len();
+        if [&reference.x, &reference.y, &reference.heading, &reference.vx, &reference.vy].iter().any(|c| c.len() != n) {
+            return Err(PyValueError::new_err("reference channels must all have the same length"));
+        }
+
+        let divergence = self.replay_against(&reference)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let Some(divergence) = divergence else {
+            return Ok(None);
+        };
+        let dict = PyDict::new_bound(py);
+        dict.set_item("position_rmse", divergence.position_rmse)?;
+        dict.set_item("heading_rmse", divergence.heading_rmse)?;
+        dict.set_item("velocity_rmse", divergence.velocity_rmse)?;
+        Ok(Some(dict))
+    }
+
+    /// Limit how fast the applied duty follows the commanded duty
+    ///
+    /// Models ramp-rate-configured motor controllers. Pass infinity (the
+    /// default) for an instantaneous step.
+    ///
+    /// Args:
+    ///     rate: Maximum duty change per second
+    ///
+    /// Raises:
+    ///     ValueError: if rate is not positive
+    fn set_duty_ramp_rate(&mut self, rate: f64) -> Result<(), InvalidRampRate> {
+        if rate.is_nan() || rate <= 0.0 {
+            return Err(InvalidRampRate(rate));
+        }
+        for ramp in &mut self.duty_ramps {
+            ramp.max_rate = rate;
+        }
+        Ok(())
+    }
+
+    /// Get the duty ramp rate (per second, infinity if unlimited)
+    fn duty_ramp_rate(&self) -> f64 {
+        self.duty_ramps.first().map_or(f64::INFINITY, |ramp| ramp.max_rate)
+    }
+
+    /// Choose the method used to integrate the robot body
+    ///
+    /// Args:
+    ///     name: "euler" (semi-implicit, the default) or "rk4"
+    ///
+    /// Raises:
+    ///     ValueError: if the name is unknown
+    fn set_integrator(&mut self, name: &str) -> PyResult<()> {
+        self.drivetrain.config.integrator = IntegratorKind::parse(name).ok_or_else(|| {
+            PyValueError::new_err(format!("unknown integrator '{}', expected 'euler' or 'rk4'", name))
+        })?;
+        Ok(())
+    }
+
+    /// Get the body integrator name
+    fn integrator(&self) -> &'static str {
+        self.drivetrain.config.integrator.name()
+    }
+
+    /// Report drivetrain power flow each step
+    ///
+    /// While enabled, power_flow() gives the last step's mechanical and
+    /// electrical power and run() results carry a power_data() series.
+    fn set_power_reporting(&mut self, enabled: bool) {
+        self.drivetrain.config.report_power = enabled;
+    }
+
+    /// Whether drivetrain power flow is reported
+    fn power_reporting(&self) -> bool {
+        self.drivetrain.config.report_power
+    }
+
+    /// Get the power flow of the last step
+    ///
+    /// Returns:
+    ///     Dict with mechanical (W delivered to the chassis, F·v + Mz·ω),
+    ///     electrical (W drawn from the battery) and efficiency (None with
+    ///     no electrical input), or None if power reporting is off or no
+    ///     step has run since the last reset
+    fn power_flow<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(flow) = self.drivetrain.power_flow() else {
+            return Ok(None);
+        };
+        let dict = PyDict::new_bound(py);
+        dict.set_item("mechanical", flow.mechanical)?;
+        dict.set_item("electrical", flow.electrical)?;
+        dict.set_item("efficiency", flow.efficiency())?;
+        Ok(Some(dict))
+    }
+
+    /// Report each wheel's slip power every step
+    ///
+    /// While enabled, slip_power() gives the power each tire dissipated in
+    /// its contact patch during the last step.
+    fn set_slip_power_reporting(&mut self, enabled: bool) {
+        self.tire_manager.report_slip_power = enabled;
+    }
+
+    /// Whether per-wheel slip power is reported
+    fn slip_power_reporting(&self) -> bool {
+        self.tire_manager.report_slip_power
+    }
+
+    /// Get each wheel's slip power from the last step
+    ///
+    /// Slip power is tire force times contact-patch sliding speed: the heat
+    /// generated in the tire, wasted as wheelspin or scrub. With a scrub
+    /// radius set, it includes the power lost to the patch yawing.
+    ///
+    /// Returns:
+    ///     numpy array [n_modules] in W, or None if slip power reporting is off
+    fn slip_power<'py>(&self, py: Python<'py>) -> Option<Bound<'py, PyAny>> {
+        let mut power = self.tire_manager.slip_power()?.to_vec();
+        if let Some(scrub) = self.drivetrain.scrub_power() {
+            for (power, scrub) in power.iter_mut().zip(scrub) {
+                *power += scrub;
+            }
+        }
+        Some(power.to_pyarray_bound(py).into_any())
+    }
+
+    /// Set the contact patch scrub radius (m)
+    ///
+    /// Each tire resists its patch yawing on the floor with a torque of its
+    /// load times this radius, so rotating in place costs energy. Chassis
+    /// rotation scrubs against the chassis and module steering against the
+    /// steer axis. The loss is reported through slip_power(). 0 disables
+    /// scrub.
+    ///
+    /// Raises:
+    ///     ValueError: if the radius is negative
+    fn set_scrub_radius(&mut self, radius: f64) -> PyResult<()> {
+        if radius.is_nan() || radius < 0.0 {
+            return Err(PyValueError::new_err(format!("scrub radius must be non-negative, got {}", radius)));
+        }
+        self.drivetrain.config.scrub_radius = radius;
+        Ok(())
+    }
+
+    /// Fit every module with a two-speed drive gearbox, starting in low gear
+    ///
+    /// Each drive motor then turns `ratio` times per wheel turn: it puts
+    /// `ratio` times its torque on the wheel and spins at `ratio` times the
+    /// wheel speed, so back-EMF follows the active gear.
+    ///
+    /// Args:
+    ///     low_ratio: Low gear reduction (motor turns per wheel turn)
+    ///     high_ratio: High gear reduction, lower than low_ratio
+    ///
+    /// Raises:
+    ///     ValueError: if a ratio is not positive or high_ratio is not
+    ///         lower than low_ratio
+    fn set_two_speed_gearbox(&mut self, low_ratio: f64, high_ratio: f64) -> PyResult<()> {
+        let gearbox = TwoSpeedGearbox::new(low_ratio, high_ratio)
+            .map_err(|err| PyValueError::new_err(err.to_string()))?;
+        self.fit_gearbox(gearbox);
+        Ok(())
+    }
+
+    /// Shift every module's gearbox into "low" or "high"
+    ///
+    /// The wheels keep their speed through the shift and the motors take
+    /// the new gear's speed on the next step. Ignored without a gearbox.
+    ///
+    /// Raises:
+    ///     ValueError: if the gear name is unknown
+    fn shift(&mut self, gear: &str) -> PyResult<()> {
+        let gear = Gear::parse(gear)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown gear '{}', expected 'low' or 'high'", gear)))?;
+        self.shift_gear(gear);
+        Ok(())
+    }
+
+    /// Get the active gear ("low" without a two-speed gearbox)
+    fn gear(&self) -> &'static str {
+        self.drivetrain.gear().name()
+    }
+
+    /// Get the active drive reduction (motor turns per wheel turn)
+    fn drive_gear_ratio(&self) -> f64 {
+        self.drivetrain.config.drive_gear_ratio
+    }
+
+    /// Power the steer servos from the battery through steer motors
+    ///
+    /// With a steer rate limit set, each module's steer servo is then
+    /// limited to the torque its motor makes at the current limit, and the
+    /// motors' duty-weighted current adds to the battery draw. The steer
+    /// motors may differ from each other and from the drive motors.
+    ///
+    /// Args:
+    ///     motors: Steer motor models (PyMotor), one per module, or None
+    ///         for ideal servos that draw no current (the default)
+    ///     gear_ratio: Steer reduction (motor turns per module turn)
+    ///     current_limit: Per-motor stator current limit (A)
+    ///
+    /// Raises:
+    ///     ValueError: if there is not one motor per module, or gear_ratio
+    ///         or current_limit is not positive
+    #[pyo3(signature = (motors, gear_ratio=12.8, current_limit=DEFAULT_STEER_CURRENT_LIMIT))]
+    fn set_steer_motors(&mut self, motors: Option<Vec<PyMotor>>, gear_ratio: f64, current_limit: f64) -> PyResult<()> {
+        let motors = motors.unwrap_or_default();
+        let num_modules = self.drivetrain.config.module_positions.len();
+        if !motors.is_empty() && motors.len() != num_modules {
+            return Err(PyValueError::new_err(format!(
+                "expected {} steer motors (one per module), got {}",
+                num_modules,
+                motors.len()
+            )));
+        }
+        if gear_ratio.is_nan() || gear_ratio <= 0.0 {
+            return Err(PyValueError::new_err(format!("steer gear ratio must be positive, got {}", gear_ratio)));
+        }
+        if current_limit.is_nan() || current_limit <= 0.0 {
+            return Err(PyValueError::new_err(format!("steer current limit must be positive, got {}", current_limit)));
+        }
+        self.steer_motors = motors.iter()
+            .map(|motor| SteerMotorConfig::new(*motor.inner(), gear_ratio).with_current_limit(current_limit))
+            .collect();
+        Ok(())
+    }
+
+    /// Estimate each drive motor's PWM current ripple every step
+    ///
+    /// Args:
+    ///     pwm_frequency: Controller switching frequency (Hz), or None to
+    ///         turn ripple reporting off
+    ///
+    /// Raises:
+    ///     ValueError: if pwm_frequency is not positive
+    #[pyo3(signature = (pwm_frequency=None))]
+    fn set_ripple_reporting(&mut self, pwm_frequency: Option<f64>) -> PyResult<()> {
+        if let Some(frequency) = pwm_frequency {
+            if frequency.is_nan() || frequency <= 0.0 {
+                return Err(PyValueError::new_err(format!("pwm_frequency must be positive, got {}", frequency)));
+            }
+        }
+        self.motor_bank.pwm_frequency = pwm_frequency;
+        Ok(())
+    }
+
+    /// Get each drive motor's peak-to-peak current ripple from the last step
+    ///
+    /// Returns:
+    ///     List of ripple amplitudes (A) per module, or None if ripple
+    ///     reporting is off
+    fn current_ripple(&self) -> Option<Vec<f64>> {
+        (0..self.motor_bank.motor_constants.len())
+            .map(|i| self.motor_bank.current_ripple(i))
+            .collect()
+    }
+
+    /// Cap the physics substep independently of the run() dt
+    ///
+    /// Each run() step is split into equal substeps no longer than `dt`;
+    /// samples are still recorded once per run() step. Pass None to step
+    /// physics once per run() step (the default).
+    ///
+    /// Args:
+    ///     dt: Longest physics substep (s), or None
+    ///
+    /// Raises:
+    ///     ValueError: if dt is not positive
+    #[pyo3(signature = (dt))]
+    fn set_fixed_dt(&mut self, dt: Option<f64>) -> PyResult<()> {
+        if let Some(dt) = dt {
+            if dt.is_nan() || dt <= 0.0 {
+                return Err(PyValueError::new_err(format!("fixed dt must be positive, got {}", dt)));
+            }
+        }
+        self.fixed_dt = dt;
+        Ok(())
+    }
+
+    /// Get the physics substep cap (s), or None
+    fn fixed_dt(&self) -> Option<f64> {
+        self.fixed_dt
+    }
+
+    /// Split each physics substep into several motor and battery steps
+    ///
+    /// The motor electrical dynamics are much faster than the mechanics, so
+    /// they can be resolved finely without shrinking the tire and drivetrain
+    /// step. The counts actually taken are reported by
+    /// SimulationResult.step_counts().
+    ///
+    /// Args:
+    ///     substeps: Electrical steps per physics substep (default 1)
+    ///
+    /// Raises:
+    ///     ValueError: if substeps is zero
+    fn set_elec_substeps(&mut self, substeps: NonZeroUsize) {
+        self.elec_substeps = substeps.get();
+    }
+
+    /// Get the number of electrical steps per physics substep
+    fn elec_substeps(&self) -> usize {
+        self.elec_substeps
+    }
+
+    /// Set the maximum number of steps a single run() may take
+    fn set_max_steps(&mut self, max_steps: u64) {
+        self.max_steps = max_steps;
+    }
+
+    /// Get the maximum number of steps a single run() may take
+    fn max_steps(&self) -> u64 {
+        self.max_steps
+    }
+
+    /// Get current simulation time
+    fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Get current position as (x, y, heading)
+    fn pose(&self) -> (f64, f64, f64) {
+        (
+            self.state.true_state.body_state.position[0],
+            self.state.true_state.body_state.position[1],
+            self.state.true_state.body_state.orientation[2],
+        )
+    }
+
+    /// Get current velocity as (vx, vy, omega)
+    ///
+    /// Args:
+    ///     frame: "field" (the default) for field-relative vx, vy, or "body"
+    ///         for vx forward and vy left of the robot
+    ///
+    /// Raises:
+    ///     ValueError: if the frame name is unknown
+    #[pyo3(signature = (frame="field"))]
+    fn velocity(&self, frame: &str) -> PyResult<(f64, f64, f64)> {
+        let frame = VelocityFrame::parse(frame).ok_or_else(|| {
+            PyValueError::new_err(format!("unknown frame '{}', expected 'field' or 'body'", frame))
+        })?;
+        let [vx, vy, omega] = self.state.true_state.body_state.planar_velocity(frame);
+        Ok((vx, vy, omega))
+    }
+
+    /// Current traction-circle utilization of every wheel
+    ///
+    /// Returns:
+    ///     Array of shape (num_modules, 2) holding each wheel's
+    ///     [Fx / (mu_long * load), Fy / (mu_lat * load)]; points on the unit
+    ///     circle are at the grip limit
+    fn traction_circle<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let points: Vec<Vec<f64>> = self.tire_manager.traction_utilization(&self.state)
+            .iter()
+            .map(|point| point.to_vec())
+            .collect();
+        Ok(PyArray2::from_vec2_bound(py, &points)?.into_any())
+    }
+
+    /// Current friction ellipse and operating point of every wheel
+    ///
+    /// Returns:
+    ///     Array of shape (num_modules, 4) holding each wheel's
+    ///     [mu_long * load, mu_lat * load, Fx, Fy] in newtons; the forces are
+    ///     after friction-ellipse scaling so they lie within the semi-axes
+    fn get_tire_ellipses<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let rows: Vec<Vec<f64>> = self.tire_manager.friction_ellipses(&self.state)
+            .iter()
+            .map(|ellipse| vec![
+                ellipse.longitudinal_limit,
+                ellipse.lateral_limit,
+                ellipse.longitudinal_force,
+                ellipse.lateral_force,
+            ])
+            .collect();
+        Ok(PyArray2::from_vec2_bound(py, &rows)?.into_any())
+    }
+
+    /// Start the simulation from an arbitrary pose and motion
+    ///
+    /// The configured state is kept and restored by every later reset().
+    /// Calling this resets the simulation and spins the wheels up to roll
+    /// with the body.
+    ///
+    /// Args:
+    ///     x, y: Position (m)
+    ///     heading: Heading (rad)
+    ///     vx, vy: Field-relative velocity (m/s), as returned by
+    ///         velocity("field"); the wheels are spun up for the matching
+    ///         robot-relative motion at the given heading
+    ///     omega: Yaw rate (rad/s)
+    #[pyo3(signature = (x, y, heading, vx=0.0, vy=0.0, omega=0.0))]
+    fn set_initial_state(&mut self, x: f64, y: f64, heading: f64, vx: f64, vy: f64, omega: f64) {
+        self.initial_body = BodyState::planar(x, y, heading, vx, vy, omega);
+        self.reset();
+    }
+
+    /// Set the standard deviation of each sensor's Gaussian noise
+    ///
+    /// Args:
+    ///     encoder_std: Wheel speed (rad/s) and steer angle (rad) noise
+    ///     imu_std: Orientation (rad) and angular rate (rad/s) noise
+    ///     battery_std: Battery voltage sense noise (V)
+    #[pyo3(signature = (encoder_std=0.0, imu_std=0.0, battery_std=0.0))]
+    fn set_sensor_noise(&mut self, encoder_std: f64, imu_std: f64, battery_std: f64) {
+        let noise = &self.sensor_noise;
+        self.sensor_noise = SensorNoise::new(noise.seeds())
+            .with_std_devs(encoder_std, imu_std, battery_std)
+            .with_dropout(noise.encoder_dropout, noise.imu_dropout, noise.battery_dropout);
+    }
+
+    /// Set the probability that each sensor drops an update
+    ///
+    /// A dropped update keeps the previous reading and reports the sensor
+    /// as invalid in get_sensor_readings() until its next update.
+    ///
+    /// Args:
+    ///     encoder: Encoder dropout probability (0-1)
+    ///     imu: IMU dropout probability (0-1)
+    ///     battery: Battery voltage sense dropout probability (0-1)
+    #[pyo3(signature = (encoder=0.0, imu=0.0, battery=0.0))]
+    fn set_sensor_dropout(&mut self, encoder: f64, imu: f64, battery: f64) {
+        let noise = &self.sensor_noise;
+        self.sensor_noise = SensorNoise::new(noise.seeds())
+            .with_std_devs(noise.encoder_std, noise.imu_std, noise.battery_std)
+            .with_dropout(encoder, imu, battery);
+    }
+
+    /// Reseed the sensor noise and restart every noise stream
+    ///
+    /// Args:
+    ///     seed: Base seed the per-source seeds are derived from
+    ///     seeds: Optional dict overriding the seed of "encoder", "imu" or "battery"
+    ///
+    /// Raises:
+    ///     ValueError: if a seed names an unknown source
+    #[pyo3(signature = (seed=0, seeds=None))]
+    fn set_seeds(&mut self, seed: u64, seeds: Option<HashMap<String, u64>>) -> PyResult<()> {
+        self.sensor_noise.reseed(noise_seeds(seed, seeds)?);
+        Ok(())
+    }
+
+    /// Get the effective seed of each noise source
+    ///
+    /// Returns:
+    ///     Dict mapping "encoder", "imu" and "battery" to their seeds
+    fn get_seeds<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let seeds = self.sensor_noise.seeds();
+        let dict = PyDict::new_bound(py);
+        for source in NoiseSource::ALL {
+            dict.set_item(source.name(), seeds.get(source))?;
+        }
+        Ok(dict)
+    }
+
+    /// Get the latest noisy sensor readings
+    ///
+    /// Returns:
+    ///     Dict with wheel_omega and steer_angle (per module, first four),
+    ///     imu ([roll, pitch, yaw, roll_rate, pitch_rate, yaw_rate]),
+    ///     battery_voltage, and encoder_valid, imu_valid and battery_valid
+    ///     (False while that sensor's last update was dropped)
+    fn get_sensor_readings<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let bus = &self.state.sensor_bus;
+        let dict = PyDict::new_bound(py);
+        dict.set_item("wheel_omega", bus.wheel_omega.to_vec())?;
+        dict.set_item("steer_angle", bus.steer_angle.to_vec())?;
+        dict.set_item("imu", bus.body_state.to_vec())?;
+        dict.set_item("battery_voltage", bus.battery_voltage)?;
+        dict.set_item("encoder_valid", bus.encoder_valid)?;
+        dict.set_item("imu_valid", bus.imu_valid)?;
+        dict.set_item("battery_valid", bus.battery_valid)?;
+        Ok(dict)
+    }
+
+    /// Get battery usage since the start of the run as (amp_hours, watt_hours)
+    fn get_battery_energy(&self) -> (f64, f64) {
+        let battery = &self.state.true_state.battery_state;
+        (battery.amp_hours_drawn, battery.watt_hours_drawn)
+    }
+
+    /// Get the battery's internal state
+    ///
+    /// Returns:
+    ///     Dict with voltage (V), soc (0-1), fast_pol_v and slow_pol_v
+    ///     (polarization voltages, V), current_draw (A) and temperature (°C)
+    fn get_battery_state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let battery = &self.state.true_state.battery_state;
+        let dict = PyDict::new_bound(py);
+        dict.set_item("voltage", battery.voltage)?;
+        dict.set_item("soc", battery.state_of_charge)?;
+        dict.set_item("fast_pol_v", battery.fast_polarization_voltage)?;
+        dict.set_item("slow_pol_v", battery.slow_polarization_voltage)?;
+        dict.set_item("current_draw", battery.total_current_draw)?;
+        dict.set_item("temperature", battery.temperature)?;
+        Ok(dict)
+    }
+
+    /// Overwrite parts of the battery's internal state
+    ///
+    /// Only the given fields change. The terminal voltage is re-derived from
+    /// the injected state on the next step unless it is set explicitly.
+    ///
+    /// Args:
+    ///     soc: State of charge (0-1)
+    ///     voltage: Terminal voltage (V)
+    ///     fast_pol_v: Fast polarization voltage (V)
+    ///     slow_pol_v: Slow polarization voltage (V)
+    ///     current_draw: Total current draw (A)
+    ///     temperature: Cell temperature (°C)
+    ///
+    /// Raises:
+    ///     ValueError: if soc is outside [0, 1]
+    #[pyo3(signature = (soc=None, voltage=None, fast_pol_v=None, slow_pol_v=None, current_draw=None, temperature=None))]
+    fn set_battery_state(
+        &mut self,
+        soc: Option<f64>,
+        voltage: Option<f64>,
+        fast_pol_v: Option<f64>,
+        slow_pol_v: Option<f64>,
+        current_draw: Option<f64>,
+        temperature: Option<f64>,
+    ) -> PyResult<()> {
+        if let Some(soc) = soc {
+            if !(0.0..=1.0).contains(&soc) {
+                return Err(PyValueError::new_err(format!("soc must be in [0, 1], got {}", soc)));
+            }
+        }
+        let battery = &mut self.state.true_state.battery_state;
+        battery.state_of_charge = soc.unwrap_or(battery.state_of_charge);
+        battery.voltage = voltage.unwrap_or(battery.voltage);
+        battery.fast_polarization_voltage = fast_pol_v.unwrap_or(battery.fast_polarization_voltage);
+        battery.slow_polarization_voltage = slow_pol_v.unwrap_or(battery.slow_polarization_voltage);
+        battery.total_current_draw = current_draw.unwrap_or(battery.total_current_draw);
+        battery.temperature = temperature.unwrap_or(battery.temperature);
+        Ok(())
+    }
+
+    /// Model battery self-heating and temperature-dependent resistance
+    ///
+    /// The cells heat from their losses and cool toward ambient; ohmic
+    /// resistance rises in the cold following an Arrhenius-like relation,
+    /// so a cold battery sags hard at first and recovers as it warms. The
+    /// cells start (and reset) at the ambient temperature.
+    ///
+    /// Args:
+    ///     ambient_temperature: Ambient temperature (°C)
+    ///     heat_capacity: Pack heat capacity (J/K)
+    ///     thermal_resistance: Cells-to-ambient thermal resistance (K/W)
+    ///     activation_temperature: Activation energy over the gas constant (K)
+    ///
+    /// Raises:
+    ///     ValueError: if heat_capacity or thermal_resistance is not positive
+    #[pyo3(signature = (ambient_temperature=25.0, heat_capacity=5000.0, thermal_resistance=0.5, activation_temperature=2250.0))]
+    fn set_battery_thermal(
+        &mut self,
+        ambient_temperature: f64,
+        heat_capacity: f64,
+        thermal_resistance: f64,
+        activation_temperature: f64,
+    ) -> PyResult<()> {
+        if heat_capacity.is_nan()
+            || heat_capacity <= 0.0
+            || thermal_resistance.is_nan()
+            || thermal_resistance <= 0.0
+        {
+            return Err(PyValueError::new_err("heat_capacity and thermal_resistance must be positive"));
+        }
+        self.battery.constants.thermal = Some(BatteryThermal {
+            heat_capacity,
+            thermal_resistance,
+            ambient_temperature,
+            activation_temperature,
+        });
+        self.state.true_state.battery_state.temperature = ambient_temperature;
+        Ok(())
+    }
+
+    /// Time to accelerate from rest to a target speed at full duty (seconds)
+    ///
+    /// Uses a straight-line model of the current configuration (mass, drive
+    /// motors, tire friction, battery voltage). Returns None if unreachable
+    /// within 60 s or dt <= 0.
+    ///
+    /// Args:
+    ///     target_v: Target chassis speed (m/s)
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     dt: Integration step (seconds)
+    #[pyo3(signature = (target_v, gear_ratio=1.0, dt=1e-4))]
+    fn time_to_speed(&self, target_v: f64, gear_ratio: f64, dt: f64) -> Option<f64> {
+        self.straight_line_drive(gear_ratio).time_to_speed(target_v, dt, 60.0)
+    }
+
+    /// Distance to stop from a given speed with full reverse duty (m)
+    ///
+    /// Args:
+    ///     from_v: Initial chassis speed (m/s)
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     dt: Integration step (seconds)
+    ///
+    /// Returns:
+    ///     Distance in m, or None if the robot does not stop within 60 s
+    ///     (e.g. no tire friction or no motors) or dt <= 0
+    #[pyo3(signature = (from_v, gear_ratio=1.0, dt=1e-4))]
+    fn braking_distance(&self, from_v: f64, gear_ratio: f64, dt: f64) -> Option<f64> {
+        self.straight_line_drive(gear_ratio).braking_distance(from_v, dt, 60.0)
+    }
+
+    /// Steady top speed at full duty against rolling resistance and drag (m/s)
+    ///
+    /// Uses the same straight-line model as time_to_speed() at the battery's
+    /// present voltage. With no resistance this is the gear-limited free speed.
+    ///
+    /// Args:
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     rolling_resistance: Rolling resistance coefficient C_RR
+    ///     drag_area: Drag coefficient times frontal area C_D * A (m^2)
+    #[pyo3(signature = (gear_ratio=1.0, rolling_resistance=0.0, drag_area=0.0))]
+    fn top_speed(&self, gear_ratio: f64, rolling_resistance: f64, drag_area: f64) -> f64 {
+        self.straight_line_drive(gear_ratio).top_speed(rolling_resistance, drag_area)
+    }
+
+    /// Open-loop time to drive a distance from rest at a fixed duty
+    ///
+    /// Uses the same straight-line model as time_to_speed().
+    ///
+    /// Args:
+    ///     distance: Distance to cover (m), positive
+    ///     duty: Duty cycle applied to every drive motor
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     dt: Integration step (seconds)
+    ///
+    /// Returns:
+    ///     (time, final_speed) in (s, m/s), or None if unreachable within 60 s
+    ///     or distance or dt <= 0
+    #[pyo3(signature = (distance, duty=1.0, gear_ratio=1.0, dt=1e-4))]
+    fn time_to_distance(&self, distance: f64, duty: f64, gear_ratio: f64, dt: f64) -> Option<(f64, f64)> {
+        self.straight_line_drive(gear_ratio)
+            .time_to_distance(distance, duty, dt, 60.0)
+            .map(|estimate| (estimate.time, estimate.final_speed))
+    }
+
+    /// Open-loop time to turn in place through an angle from rest at a fixed duty
+    ///
+    /// Wheels push tangentially at their average distance from the center,
+    /// turning the configured chassis moment of inertia.
+    ///
+    /// Args:
+    ///     angle: Angle to turn through (rad), positive
+    ///     duty: Duty cycle applied to every drive motor
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     dt: Integration step (seconds)
+    ///
+    /// Returns:
+    ///     (time, final_yaw_rate) in (s, rad/s), or None if unreachable within
+    ///     60 s or angle or dt <= 0
+    #[pyo3(signature = (angle, duty=1.0, gear_ratio=1.0, dt=1e-4))]
+    fn time_to_turn(&self, angle: f64, duty: f64, gear_ratio: f64, dt: f64) -> Option<(f64, f64)> {
+        let config = &self.drivetrain.config;
+        let positions = &config.module_positions;
+        let turn_radius = if positions.is_empty() {
+            0.0
+        } else {
+            positions.iter().map(|p| p[0].hypot(p[1])).sum::<f64>() / positions.len() as f64
+        };
+        self.straight_line_drive(gear_ratio)
+            .time_to_turn(angle, duty, turn_radius, config.moment_of_inertia, dt, 60.0)
+            .map(|estimate| (estimate.time, estimate.final_speed))
+    }
+
+    /// Maximum forward acceleration without browning out the battery (m/s^2)
+    ///
+    /// Couples the battery's current state of charge, the drive motors and
+    /// the traction limit at the current chassis speed. Lower charge means
+    /// more sag per amp, so less current (and acceleration) is available.
+    ///
+    /// Args:
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     brownout_voltage: Minimum allowed battery terminal voltage (V)
+    #[pyo3(signature = (gear_ratio=1.0, brownout_voltage=DEFAULT_BROWNOUT_VOLTAGE))]
+    fn power_limited_accel(&self, gear_ratio: f64, brownout_voltage: f64) -> f64 {
+        let body = &self.state.true_state.body_state;
+        let speed = body.velocity[0].hypot(body.velocity[1]);
+        let soc = self.state.true_state.battery_state.state_of_charge;
+        self.straight_line_drive(gear_ratio)
+            .power_limited_accel(speed, &self.battery.constants, soc, brownout_voltage)
+    }
+
+    /// Lowest-energy trapezoidal velocity profile covering a distance in time
+    ///
+    /// Cruises at the slowest speed that still arrives on time, ramping at
+    /// the power-limited acceleration for the battery's current charge.
+    ///
+    /// Args:
+    ///     distance: Distance to cover (m)
+    ///     time: Time allowed (s)
+    ///     gear_ratio: Motor-to-wheel reduction, default 1.0 (direct drive as simulated)
+    ///     brownout_voltage: Minimum allowed battery terminal voltage (V)
+    ///     dt: Sample spacing (seconds)
+    ///
+    /// Returns:
+    ///     Dict with numpy arrays times and velocities plus cruise_speed,
+    ///     acceleration and energy (J), or None if the time is too short
+    ///
+    /// Raises:
+    ///     ValueError: if dt/time are invalid or the profile exceeds max_steps samples
+    #[pyo3(signature = (distance, time, gear_ratio=1.0, brownout_voltage=DEFAULT_BROWNOUT_VOLTAGE, dt=0.01))]
+    fn energy_optimal_profile<'py>(
+        &self,
+        py: Python<'py>,
+        distance: f64,
+        time: f64,
+        gear_ratio: f64,
+        brownout_voltage: f64,
+        dt: f64,
+    ) -> PyResult<Option<Bound<'py, PyDict>>> {
+        checked_step_count(time, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let soc = self.state.true_state.battery_state.state_of_charge;
+        let Some(profile) = self.straight_line_drive(gear_ratio)
+            .energy_optimal_profile(distance, time, &self.battery.constants, soc, brownout_voltage, dt)
+        else {
+            return Ok(None);
+        };
+        let dict = PyDict::new_bound(py);
+        dict.set_item("times", profile.times.to_pyarray_bound(py))?;
+        dict.set_item("velocities", profile.velocities.to_pyarray_bound(py))?;
+        dict.set_item("cruise_speed", profile.cruise_speed)?;
+        dict.set_item("acceleration", profile.acceleration)?;
+        dict.set_item("energy", profile.energy)?;
+        Ok(Some(dict))
+    }
+
+    /// Fit rolling resistance and drag to a coast-down speed trace
+    ///
+    /// Works on simulated or measured data: the robot is released at speed
+    /// with the drive unpowered and its speed is sampled until it stops.
+    ///
+    /// Args:
+    ///     times: Sample times (s)
+    ///     velocities: Robot speed at each sample (m/s)
+    ///     mass: Robot mass (kg)
+    ///
+    /// Returns:
+    ///     Dict with "rolling_resistance" (C_RR) and "drag_area" (C_DA, m^2)
+    ///
+    /// Raises:
+    ///     ValueError: if the trace is too short or the speed never changes
+    #[staticmethod]
+    fn fit_coast_down<'py>(
+        py: Python<'py>,
+        times: Vec<f64>,
+        velocities: Vec<f64>,
+        mass: f64,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        if times.len() != velocities.len() {
+            return Err(PyValueError::new_err(format!(
+                "times and velocities differ in length ({} vs {})",
+                times.len(),
+                velocities.len()
+            )));
+        }
+        let fit = fit_coast_down(&times, &velocities, mass).ok_or_else(|| {
+            PyValueError::new_err("coast-down trace needs at least three moving samples with varying speed")
+        })?;
+        let dict = PyDict::new_bound(py);
+        dict.set_item("rolling_resistance", fit.rolling_resistance)?;
+        dict.set_item("drag_area", fit.drag_area)?;
+        Ok(dict)
+    }
+
+    /// Set how much of a module's load is carried by the ground
+    ///
+    /// Args:
+    ///     index: Module index
+    ///     contact_fraction: 1.0 for full contact, 0.0 for a lifted wheel;
+    ///         clamped to that range
+    ///
+    /// Raises:
+    ///     IndexError: if the module index is out of range
+    ///     ValueError: if contact_fraction is not finite
+    fn set_wheel_contact(&mut self, index: usize, contact_fraction: f64) -> PyResult<()> {
+        if !contact_fraction.is_finite() {
+            return Err(PyValueError::new_err(format!(
+                "contact_fraction must be finite, got {}",
+                contact_fraction
+            )));
+        }
+        let num_wheels = self.state.true_state.wheel_states.len();
+        let wheel = self.state.true_state.wheel_states.get_mut(index).ok_or_else(|| {
+            PyIndexError::new_err(format!("module index {} out of range for {} modules", index, num_wheels))
+        })?;
+        wheel.contact_fraction = contact_fraction.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    /// Set tire constants for a single module
+    ///
+    /// Useful for modeling a worn or damaged wheel.
+    ///
+    /// Args:
+    ///     index: Module index
+    ///     mu_long: Longitudinal coefficient of friction
+    ///     mu_lat: Lateral coefficient of friction
+    ///     c_alpha: Cornering stiffness (N/rad)
+    ///     c_kappa: Longitudinal slip stiffness (N)
+    ///     relax_long: Longitudinal relaxation length (m), 0 for instantaneous
+    ///     relax_lat: Lateral relaxation length (m), 0 for instantaneous
+    ///     relaxation_mode: "distance" (relaxation in meters, the default) or
+    ///         "time" (relaxation in seconds, independent of speed)
+    ///
+    /// Raises:
+    ///     IndexError: if the module index is out of range
+    ///     ValueError: if relaxation_mode is not recognised
+    #[pyo3(signature = (index, mu_long, mu_lat, c_alpha, c_kappa, relax_long, relax_lat, relaxation_mode="distance"))]
+    #[allow(clippy::too_many_arguments)]
+    fn set_tire_constants(
+        &mut self,
+        index: usize,
+        mu_long: f64,
+        mu_lat: f64,
+        c_alpha: f64,
+        c_kappa: f64,
+        relax_long: f64,
+        relax_lat: f64,
+        relaxation_mode: &str,
+    ) -> PyResult<()> {
+        let num_tires = self.tire_manager.tire_constants.len();
+        if index >= num_tires {
+            return Err(PyIndexError::new_err(format!(
+                "module index {} out of range for {} modules",
+                index, num_tires
+            )));
+        }
+        let mode = parse_relaxation_mode(relaxation_mode)?;
+        self.tire_manager.set_tire(
+            index,
+            TireConstants::new(mu_long, mu_lat, c_alpha, c_kappa, relax_long, relax_lat).with_relaxation_mode(mode),
+        );
+        Ok(())
+    }
+
+    /// Set the same tire constants on every module
+    ///
+    /// Args are the same as `set_tire_constants`, without the index.
+    ///
+    /// Raises:
+    ///     ValueError: if relaxation_mode is not recognised
+    #[pyo3(signature = (mu_long, mu_lat, c_alpha, c_kappa, relax_long, relax_lat, relaxation_mode="distance"))]
+    #[allow(clippy::too_many_arguments)]
+    fn set_all_tire_constants(
+        &mut self,
+        mu_long: f64,
+        mu_lat: f64,
+        c_alpha: f64,
+        c_kappa: f64,
+        relax_long: f64,
+        relax_lat: f64,
+        relaxation_mode: &str,
+    ) -> PyResult<()> {
+        let mode = parse_relaxation_mode(relaxation_mode)?;
+        self.tire_manager.set_all_tires(
+            TireConstants::new(mu_long, mu_lat, c_alpha, c_kappa, relax_long, relax_lat).with_relaxation_mode(mode),
+        );
+        Ok(())
+    }
+
+    /// Reset simulation to the configured initial state (origin at rest by default)
+    fn reset(&mut self) {
+        self.time = 0.0;
+        self.drivetrain.reset();
+        self.sync_motor_gearing();
+        self.tire_manager.reset();
+        self.motor_bank.reset();
+        self.sensor_noise.reset();
+
+        self.state.true_state.body_state = self.initial_body;
+
+        let loads = self.drivetrain.config.static_wheel_loads(9.81);
+        for (wheel, load) in self.state.true_state.wheel_states.iter_mut().zip(loads) {
+            wheel.angle = 0.0;
+            wheel.driving_angular_velocity = 0.0;
+            wheel.turning_angular_velocity = 0.0;
+            wheel.longitudinal_translational_velocity = 0.0;
+            wheel.lateral_translational_velocity = 0.0;
+            wheel.tire.slip_angle = 0.0;
+            wheel.tire.slip_ratio = 0.0;
+            wheel.tire.longitudinal_force = 0.0;
+            wheel.tire.lateral_force = 0.0;
+            wheel.tire.tire_load = load;
+            wheel.contact_fraction = 1.0;
+        }
+        self.steer_targets.fill(0.0);
+        self.state.control_input.steer_torques.clear();
+        self.drivetrain.sync_wheels_to_body(&mut self.state);
+
+        for motor in &mut self.state.true_state.motors {
+            *motor = MotorState::default();
+        }
+        for (input, ramp) in self.state.control_input.motor_inputs.iter_mut().zip(&mut self.duty_ramps) {
+            input.duty_cycle_q = 0.0;
+            ramp.reset(0.0);
+        }
+
+        let mut battery_state = BatteryState {
+            state_of_charge: self.initial_soc,
+            ..BatteryState::default()
+        };
+        if let Some(thermal) = self.battery.constants.thermal {
+            battery_state.temperature = thermal.ambient_temperature;
+        }
+        self.state.true_state.battery_state = battery_state;
+    }
+}
+
+impl PySimulator {
+    /// Build a simulator for a drivetrain with one motor per module
+    fn with_motors(config: SwerveDrivetrainConfig, motor_constants: Vec<MotorConstant>) -> Self {
+        let num_modules = config.module_positions.len();
 
         // Initialize wheel states
-        let wheel_states: Vec<WheelState> = (0..num_modules)
-            .map(|_| WheelState {
+        let wheel_states: Vec<WheelState> = config.static_wheel_loads(9.81).into_iter()
+            .map(|tire_load| WheelState {
                 driving_angular_velocity: 0.0,
                 wheel_radius: 0.05,
                 turning_angular_velocity: 0.0,
@@ -142,20 +1432,16 @@ impl PySimulator {
                     slip_ratio: 0.0,
                     longitudinal_force: 0.0,
                     lateral_force: 0.0,
-                    tire_load: mass * 9.81 / num_modules as f64,
+                    tire_load,
                 },
                 angle: 0.0,
+                contact_fraction: 1.0,
             })
             .collect();
 
         // Initialize motors
-        let mut motor_bank = MotorBank::default();
-        let motors: Vec<MotorState> = (0..num_modules)
-            .map(|_| {
-                motor_bank.add_motor(MotorConstant::kraken_x60());
-                MotorState::default()
-            })
-            .collect();
+        let motor_bank = MotorBank::new(motor_constants);
+        let motors: Vec<MotorState> = vec![MotorState::default(); num_modules];
 
         // Initialize tire manager
         let mut tire_manager = TireManager::new();
@@ -177,6 +1463,7 @@ impl PySimulator {
                         duty_cycle_d: 0.0,
                     })
                     .collect(),
+                steer_torques: Vec::new(),
             },
             sensor_bus: SensorBus::default(),
         };
@@ -186,51 +1473,136 @@ impl PySimulator {
             drivetrain: SwerveDrivetrain::new(config),
             tire_manager,
             motor_bank,
+            battery: Battery { constants: BatteryConstant::default() },
             time: 0.0,
+            max_steps: DEFAULT_MAX_STEPS,
+            steer_targets: vec![0.0; num_modules],
+            initial_body: BodyState::default(),
+            initial_soc: 1.0,
+            scenario_dt: 0.001,
+            schedule: Vec::new(),
+            duty_ramps: vec![SlewRateGoal::new(f64::INFINITY); num_modules],
+            fixed_dt: None,
+            elec_substeps: 1,
+            sensor_noise: SensorNoise::new(NoiseSeeds::from_base(0)),
+            steer_motors: Vec::new(),
         }
     }
 
-    /// Run simulation for specified duration - all steps executed in Rust
-    /// 
-    /// This is the primary API for batch simulation. All physics steps
-    /// run in Rust without Python callbacks for maximum performance.
-    /// 
-    /// Args:
-    ///     duration: Total simulation time (seconds)
-    ///     dt: Time step (seconds)
-    ///     duty_cycles: List of duty cycles for each module (0-1)
-    ///     steer_angles: List of steering angles for each module (radians)
-    /// 
-    /// Returns:
-    ///     SimulationResult with all time series data
-    #[pyo3(signature = (duration, dt=0.001, duty_cycles=None, steer_angles=None))]
-    fn run(
-        &mut self,
-        duration: f64,
-        dt: f64,
-        duty_cycles: Option<Vec<f64>>,
-        steer_angles: Option<Vec<f64>>,
-    ) -> SimulationResult {
+    /// Latch per-module duty and steer commands, returning the commanded duty
+    /// the applied duty ramps toward
+    fn fit_gearbox(&mut self, gearbox: TwoSpeedGearbox) {
+        self.drivetrain.set_two_speed_gearbox(gearbox);
+        self.sync_motor_gearing();
+    }
+
+    fn shift_gear(&mut self, gear: Gear) {
+        self.drivetrain.shift(gear);
+        self.sync_motor_gearing();
+    }
+
+    /// Gear each drive motor to its wheel through the active reduction
+    fn sync_motor_gearing(&mut self) {
+        if self.drivetrain.two_speed_gearbox().is_none() {
+            return;
+        }
+        let config = &self.drivetrain.config;
+        self.motor_bank.gear_ratios = (0..config.module_positions.len())
+            .map(|i| config.drive_direction(i) * config.drive_gear_ratio)
+            .collect();
+    }
+
+    /// Battery current the steer motors draw to deliver the steer torques (A)
+    fn steer_supply_current(&self) -> f64 {
+        let voltage = self.state.true_state.battery_state.voltage;
+        let mut supply_current = 0.0;
+        for ((&torque, wheel), steer) in self.state.control_input.steer_torques.iter()
+            .zip(&self.state.true_state.wheel_states)
+            .zip(&self.steer_motors)
+        {
+            let current = torque / (steer.motor.kt() * steer.gear_ratio);
+            if voltage > 0.0 {
+                let velocity = wheel.turning_angular_velocity * steer.gear_ratio;
+                let duty = ((current * steer.motor.resistance + steer.motor.ke() * velocity) / voltage).clamp(-1.0, 1.0);
+                supply_current += duty * current;
+            }
+        }
+        supply_current
+    }
+
+    fn apply_commands(&mut self, duty_cycles: Option<Vec<f64>>, steer_angles: Option<Vec<f64>>) -> Vec<f64> {
         let num_modules = self.drivetrain.config.module_positions.len();
-        
-        // Set control inputs
         let duty = duty_cycles.unwrap_or_else(|| vec![0.0; num_modules]);
         let steers = steer_angles.unwrap_or_else(|| vec![0.0; num_modules]);
-        
-        for (i, &d) in duty.iter().enumerate() {
-            if i < self.state.control_input.motor_inputs.len() {
-                self.state.control_input.motor_inputs[i].duty_cycle_q = d;
-            }
+
+        let mut commanded_duty = vec![0.0; self.duty_ramps.len()];
+        for (commanded, &d) in commanded_duty.iter_mut().zip(&duty) {
+            *commanded = d;
         }
-        
+
+        // Without a steer rate limit the modules snap to their commanded angle
+        let snap = !self.drivetrain.config.max_steer_rate.is_finite();
         for (i, &s) in steers.iter().enumerate() {
             if i < self.state.true_state.wheel_states.len() {
-                self.state.true_state.wheel_states[i].angle = s;
+                self.steer_targets[i] = s;
+                if snap {
+                    self.state.true_state.wheel_states[i].angle = s;
+                }
+            }
+        }
+        commanded_duty
+    }
+
+    /// Take `n_steps` steps of `commanded_duty`, handing the state to `emit`
+    /// after every `every`-th step; an error from `emit` stops the run
+    ///
+    /// Returns the number of samples emitted.
+    fn stream_run<E>(
+        &mut self,
+        dt: f64,
+        commanded_duty: &[f64],
+        n_steps: u64,
+        every: u64,
+        mut emit: impl FnMut(StreamSample) -> Result<(), E>,
+    ) -> Result<u64, E> {
+        let mut decimator = Decimator::new(every);
+        let mut step_counts = StepCounts::default();
+        let mut emitted = 0;
+        for _ in 0..n_steps {
+            self.step_commanded(commanded_duty, dt, &mut step_counts);
+            if decimator.tick() {
+                let body = &self.state.true_state.body_state;
+                emit((
+                    self.time,
+                    body.position[0],
+                    body.position[1],
+                    body.orientation[2],
+                    body.velocity[0],
+                    body.velocity[1],
+                    body.angular_velocity[2],
+                    self.state.true_state.battery_state.voltage,
+                ))?;
+                emitted += 1;
             }
         }
+        Ok(emitted)
+    }
 
+    /// Take `n_steps` steps of `commanded_duty`, sampling the state before each step
+    ///
+    /// Row k of every series, per-wheel rows included, is the state at
+    /// times[k] before step k runs, so the first row is the initial state and
+    /// the state after the last step is not recorded. Peaks and power flows
+    /// are taken after each step.
+    fn record_run(
+        &mut self,
+        dt: f64,
+        commanded_duty: &[f64],
+        n_steps: usize,
+        record_wheels: bool,
+        record_peaks: bool,
+    ) -> SimulationResult {
         // Pre-allocate result vectors
-        let n_steps = (duration / dt).ceil() as usize;
         let mut times = Vec::with_capacity(n_steps);
         let mut positions_x = Vec::with_capacity(n_steps);
         let mut positions_y = Vec::with_capacity(n_steps);
@@ -239,10 +1611,14 @@ impl PySimulator {
         let mut velocities_y = Vec::with_capacity(n_steps);
         let mut angular_velocities = Vec::with_capacity(n_steps);
         let mut battery_voltages = Vec::with_capacity(n_steps);
+        let mut wheels = record_wheels.then(|| WheelTrace::with_capacity(n_steps));
+        let mut peaks = record_peaks.then(WheelPeaks::default);
+        let mut power_flows = self.drivetrain.config.report_power.then(|| Vec::with_capacity(n_steps));
+        let mut step_counts = StepCounts::default();
 
-        // Run simulation loop entirely in Rust
-        let end_time = self.time + duration;
-        while self.time < end_time {
+        // Run simulation loop entirely in Rust, for exactly the checked step
+        // count; accumulated time can fall just short of the end and add a step
+        for _ in 0..n_steps {
             // Record state
             times.push(self.time);
             positions_x.push(self.state.true_state.body_state.position[0]);
@@ -252,14 +1628,18 @@ impl PySimulator {
             velocities_y.push(self.state.true_state.body_state.velocity[1]);
             angular_velocities.push(self.state.true_state.body_state.angular_velocity[2]);
             battery_voltages.push(self.state.true_state.battery_state.voltage);
+            if let Some(wheels) = wheels.as_mut() {
+                wheels.record(&self.state.true_state.wheel_states);
+            }
 
             // Step simulation
-            let ctx = SimContext { dt, t: self.time };
-            self.motor_bank.step_electrical(ctx, &mut self.state);
-            self.tire_manager.step_physics(ctx, &mut self.state);
-            self.drivetrain.step_physics(ctx, &mut self.state);
-
-            self.time += dt;
+            self.step_commanded(commanded_duty, dt, &mut step_counts);
+            if let Some(peaks) = peaks.as_mut() {
+                peaks.record(&self.state.true_state.wheel_states);
+            }
+            if let (Some(flows), Some(flow)) = (power_flows.as_mut(), self.drivetrain.power_flow()) {
+                flows.push(flow);
+            }
         }
 
         SimulationResult {
@@ -271,59 +1651,589 @@ impl PySimulator {
             velocities_y,
             angular_velocities,
             battery_voltages,
+            wheels,
+            peaks,
+            power_flows,
+            step_counts,
         }
     }
 
-    /// Get current simulation time
-    fn time(&self) -> f64 {
-        self.time
+    /// Ramp the applied duty toward `commanded_duty` and advance one step
+    fn step_commanded(&mut self, commanded_duty: &[f64], dt: f64, counts: &mut StepCounts) {
+        for ((input, ramp), &commanded) in self.state.control_input.motor_inputs.iter_mut()
+            .zip(&mut self.duty_ramps)
+            .zip(commanded_duty)
+        {
+            input.duty_cycle_q = ramp.shape(commanded, dt);
+        }
+        self.step_physics(dt, counts);
     }
 
-    /// Get current position as (x, y, heading)
-    fn pose(&self) -> (f64, f64, f64) {
-        (
-            self.state.true_state.body_state.position[0],
-            self.state.true_state.body_state.position[1],
-            self.state.true_state.body_state.orientation[2],
-        )
+    /// Advance motors, tires and drivetrain by `dt`, in substeps no longer
+    /// than the fixed dt if one is set, counting the solver steps taken
+    fn step_physics(&mut self, dt: f64, counts: &mut StepCounts) {
+        let substeps = substep_count(dt, self.fixed_dt);
+        let sub_dt = dt / substeps as f64;
+        let elec_dt = sub_dt / self.elec_substeps as f64;
+        for i in 0..substeps {
+            let t = self.time + i as f64 * sub_dt;
+            if self.drivetrain.config.max_steer_rate.is_finite() {
+                let drivetrain = &self.drivetrain;
+                let steer_motors = &self.steer_motors;
+                self.state.control_input.steer_torques = self.state.true_state.wheel_states.iter()
+                    .zip(&self.steer_targets)
+                    .enumerate()
+                    .map(|(i, (wheel, &target))| {
+                        let max_torque = steer_motors.get(i).map_or(f64::INFINITY, SteerMotorConfig::max_torque);
+                        drivetrain.limited_steer_servo_torque(wheel, target, sub_dt, max_torque)
+                    })
+                    .collect();
+            }
+            let steer_current = self.steer_supply_current();
+            for j in 0..self.elec_substeps {
+                let ctx = SimContext { dt: elec_dt, t: t + j as f64 * elec_dt };
+                self.motor_bank.step_electrical(ctx, &mut self.state);
+                self.state.true_state.battery_state.total_current_draw += steer_current;
+                self.battery.step_electrical(ctx, &mut self.state);
+            }
+            counts.record_step(self.elec_substeps);
+
+            let ctx = SimContext { dt: sub_dt, t };
+            self.tire_manager.step_physics(ctx, &mut self.state);
+            self.drivetrain.step_physics(ctx, &mut self.state);
+        }
+        self.sensor_noise.measure(&self.state.true_state, &mut self.state.sensor_bus);
+        self.time += dt;
     }
 
-    /// Get current velocity as (vx, vy, omega)
-    fn velocity(&self) -> (f64, f64, f64) {
-        (
-            self.state.true_state.body_state.velocity[0],
-            self.state.true_state.body_state.velocity[1],
-            self.state.true_state.body_state.angular_velocity[2],
-        )
+    /// Build a simulator from a validated scenario
+    fn from_scenario(scenario: Scenario) -> Result<Self, ScenarioError> {
+        let DrivetrainSpec::Swerve { mass, moment_of_inertia, module_positions, drive_inverted } =
+            scenario.drivetrain;
+        let num_modules = module_positions.len();
+        let motor = MotorConstant::from_preset(&scenario.motor)
+            .ok_or_else(|| ScenarioError::Invalid(format!("unknown motor preset '{}'", scenario.motor)))?;
+        let config = SwerveDrivetrainConfig {
+            module_positions,
+            mass,
+            moment_of_inertia,
+            drive_inverted,
+            ..SwerveDrivetrainConfig::default()
+        };
+
+        let mut sim = Self::with_motors(config, vec![motor; num_modules]);
+        if let Some(tire) = scenario.tire {
+            sim.tire_manager.set_all_tires(TireConstants::new(
+                tire.mu_long,
+                tire.mu_lat,
+                tire.cornering_stiffness,
+                tire.longitudinal_stiffness,
+                tire.relaxation_long,
+                tire.relaxation_lat,
+            ));
+        }
+        sim.initial_body = scenario.initial_state.body_state();
+        sim.initial_soc = scenario.battery.initial_soc;
+        sim.scenario_dt = scenario.dt;
+        sim.schedule = scenario.commands;
+        sim.reset();
+        Ok(sim)
     }
 
-    /// Reset simulation to initial state
-    fn reset(&mut self) {
-        self.time = 0.0;
-        self.drivetrain.reset();
-        self.tire_manager.reset();
-        self.motor_bank.reset();
+    /// Run the loaded command schedule from the scenario's initial state,
+    /// one segment per command
+    fn run_schedule(&mut self, record_wheels: bool, record_peaks: bool) -> Result<SimulationResult, StepBudgetError> {
+        self.reset();
+        let mut result =
+            SimulationResult::empty(record_wheels, record_peaks, self.drivetrain.config.report_power);
+        for command in self.schedule.clone() {
+            let n_steps = checked_step_count(command.duration, self.scenario_dt, self.max_steps)? as usize;
+            let commanded_duty = self.apply_commands(Some(command.duty_cycles), Some(command.steer_angles));
+            let segment =
+                self.record_run(self.scenario_dt, &commanded_duty, n_steps, record_wheels, record_peaks);
+            result.append(segment);
+        }
+        Ok(result)
+    }
 
-        let num_modules = self.drivetrain.config.module_positions.len();
-        let mass = self.drivetrain.config.mass;
+    /// Replay the schedule from the scenario's initial state and score it
+    /// against `reference`
+    fn replay_against(&mut self, reference: &Trajectory) -> Result<Option<Divergence>, StepBudgetError> {
+        self.reset();
+        let source = ReplaySource::new(self.schedule.clone());
+        let initial = self.state.true_state.body_state;
+        let mut counts = StepCounts::default();
+        source.compare(self.scenario_dt, self.max_steps, &initial, reference, |command, dt| {
+            let commanded_duty = self.apply_commands(Some(command.duty_cycles.clone()), Some(command.steer_angles.clone()));
+            self.step_commanded(&commanded_duty, dt, &mut counts);
+            self.state.true_state.body_state
+        })
+    }
 
-        self.state.true_state.body_state = BodyState::default();
+    /// Straight-line model of the current configuration for launch/braking queries
+    fn straight_line_drive(&self, gear_ratio: f64) -> StraightLineDrive {
+        let tires = &self.tire_manager.tire_constants;
+        let friction_coefficient = if tires.is_empty() {
+            0.0
+        } else {
+            tires.iter().map(|t| t.longitudinal_coefficient_of_friction).sum::<f64>() / tires.len() as f64
+        };
 
-        for wheel in &mut self.state.true_state.wheel_states {
-            wheel.driving_angular_velocity = 0.0;
-            wheel.longitudinal_translational_velocity = 0.0;
-            wheel.lateral_translational_velocity = 0.0;
-            wheel.tire.slip_angle = 0.0;
-            wheel.tire.slip_ratio = 0.0;
-            wheel.tire.longitudinal_force = 0.0;
-            wheel.tire.lateral_force = 0.0;
-            wheel.tire.tire_load = mass * 9.81 / num_modules as f64;
+        StraightLineDrive {
+            motors: self.motor_bank.motor_constants.clone(),
+            mass: self.drivetrain.config.mass,
+            wheel_radius: self.state.true_state.wheel_states.first().map_or(0.05, |w| w.wheel_radius),
+            gear_ratio,
+            friction_coefficient,
+            voltage: self.state.true_state.battery_state.voltage,
         }
+    }
+}
 
-        for motor in &mut self.state.true_state.motors {
-            *motor = MotorState::default();
+/// Parse a tire relaxation mode name ("distance" or "time")
+fn parse_relaxation_mode(name: &str) -> PyResult<RelaxationMode> {
+    match name {
+        "distance" => Ok(RelaxationMode::Distance),
+        "time" => Ok(RelaxationMode::TimeConstant),
+        _ => Err(PyValueError::new_err(format!(
+            "relaxation_mode must be 'distance' or 'time', got '{}'",
+            name
+        ))),
+    }
+}
+
+/// Per-source noise seeds from a base seed and optional per-source overrides
+fn noise_seeds(seed: u64, overrides: Option<HashMap<String, u64>>) -> PyResult<NoiseSeeds> {
+    let mut seeds = NoiseSeeds::from_base(seed);
+    for (name, value) in overrides.unwrap_or_default() {
+        let source = NoiseSource::parse(&name).ok_or_else(|| {
+            PyValueError::new_err(format!("unknown noise source '{}', expected 'encoder', 'imu' or 'battery'", name))
+        })?;
+        seeds = seeds.with_seed(source, value);
+    }
+    Ok(seeds)
+}
+
+/// Map scenario loading errors onto Python exceptions
+fn scenario_error(err: ScenarioError) -> PyErr {
+    match err {
+        ScenarioError::Io(_) => PyIOError::new_err(err.to_string()),
+        _ => PyValueError::new_err(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_simulator() -> PySimulator {
+        let config = SwerveDrivetrainConfig::default();
+        let num_modules = config.module_positions.len();
+        PySimulator::with_motors(config, vec![MotorConstant::kraken_x60(); num_modules])
+    }
+
+    #[test]
+    fn test_battery_energy_accumulates_through_the_simulator() {
+        let mut sim = test_simulator();
+        let num_modules = sim.drivetrain.config.module_positions.len();
+        assert_eq!(sim.get_battery_energy(), (0.0, 0.0));
+
+        let commanded_duty = sim.apply_commands(Some(vec![0.5; num_modules]), None);
+        let mut counts = StepCounts::default();
+        // Short steps: the motor currents are integrated explicitly
+        for _ in 0..5000 {
+            sim.step_commanded(&commanded_duty, 1e-5, &mut counts);
+        }
+        let (amp_hours, watt_hours) = sim.get_battery_energy();
+        assert!(amp_hours > 0.0, "amp_hours {}", amp_hours);
+        // Energy over charge is the mean terminal voltage
+        let mean_voltage = watt_hours / amp_hours;
+        assert!(mean_voltage > 6.0 && mean_voltage < 13.5, "mean voltage {}", mean_voltage);
+
+        sim.reset();
+        assert_eq!(sim.get_battery_energy(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_ramped_duty_raises_current_gradually() {
+        let num_modules = SwerveDrivetrainConfig::default().module_positions.len();
+        // q-current of the first drive motor after a full duty step
+        let current_after = |sim: &mut PySimulator, steps: usize| {
+            let commanded_duty = sim.apply_commands(Some(vec![1.0; num_modules]), None);
+            let mut counts = StepCounts::default();
+            for _ in 0..steps {
+                sim.step_commanded(&commanded_duty, 1e-5, &mut counts);
+            }
+            sim.state.true_state.motors[0].current_q
+        };
+
+        let instant = current_after(&mut test_simulator(), 100);
+        let mut ramped_sim = test_simulator();
+        ramped_sim.set_duty_ramp_rate(100.0).unwrap();
+        assert_eq!(ramped_sim.duty_ramp_rate(), 100.0);
+        let ramped = current_after(&mut ramped_sim, 100);
+        assert!(ramped > 0.0);
+        assert!(ramped < 0.5 * instant, "ramped {} A vs instant {} A", ramped, instant);
+
+        assert_eq!(ramped_sim.set_duty_ramp_rate(0.0), Err(InvalidRampRate(0.0)));
+        assert!(ramped_sim.set_duty_ramp_rate(f64::NAN).is_err());
+        assert_eq!(ramped_sim.duty_ramp_rate(), 100.0);
+    }
+
+    #[test]
+    fn test_recorded_rows_are_the_state_before_each_step() {
+        let num_modules = SwerveDrivetrainConfig::default().module_positions.len();
+        let duty = vec![0.5; num_modules];
+        let (dt, n_steps) = (1e-5, 200);
+
+        let mut recorded = test_simulator();
+        let commanded_duty = recorded.apply_commands(Some(duty.clone()), None);
+        let result = recorded.record_run(dt, &commanded_duty, n_steps, true, false);
+        let wheels = result.wheels.expect("wheel trace requested");
+        assert_eq!(result.times.len(), n_steps);
+        assert_eq!(wheels.len(), n_steps);
+
+        let mut manual = test_simulator();
+        let commanded_duty = manual.apply_commands(Some(duty), None);
+        let mut counts = StepCounts::default();
+        for k in 0..n_steps {
+            let body = &manual.state.true_state.body_state;
+            assert_eq!(result.times[k], manual.time);
+            assert_eq!(result.positions_x[k], body.position[0]);
+            assert_eq!(result.velocities_x[k], body.velocity[0]);
+            let omegas: Vec<f64> = manual.state.true_state.wheel_states.iter()
+                .map(|w| w.driving_angular_velocity)
+                .collect();
+            assert_eq!(wheels.angular_velocity[k], omegas, "row {}", k);
+            manual.step_commanded(&commanded_duty, dt, &mut counts);
+        }
+        // The first row is the initial state; the final state is not recorded
+        assert!(wheels.angular_velocity[0].iter().all(|&w| w == 0.0));
+        assert_eq!(recorded.time, manual.time);
+    }
+
+    #[test]
+    fn test_steer_commands_follow_the_steer_rate_limit() {
+        let config = SwerveDrivetrainConfig { max_steer_rate: 10.0, ..SwerveDrivetrainConfig::default() };
+        let num_modules = config.module_positions.len();
+        let mut sim = PySimulator::with_motors(config, vec![MotorConstant::kraken_x60(); num_modules]);
+        let target = std::f64::consts::FRAC_PI_2;
+        let commanded_duty = sim.apply_commands(None, Some(vec![target; num_modules]));
+        let mut counts = StepCounts::default();
+
+        // 0.1 s at 10 rad/s covers only 1 rad of the quarter turn
+        for _ in 0..1000 {
+            sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        }
+        for wheel in &sim.state.true_state.wheel_states {
+            assert!((wheel.angle - 1.0).abs() < 1e-6, "angle {}", wheel.angle);
         }
 
-        self.state.true_state.battery_state = BatteryState::default();
+        for _ in 0..1000 {
+            sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        }
+        for wheel in &sim.state.true_state.wheel_states {
+            assert!((wheel.angle - target).abs() < 1e-9, "angle {}", wheel.angle);
+        }
+    }
+
+    #[test]
+    fn test_steer_motor_current_shows_in_the_battery_draw() {
+        // Peak and final battery draw over a quarter turn with the drive idle
+        let quarter_turn = |steer_motors: Vec<SteerMotorConfig>| {
+            let config = SwerveDrivetrainConfig { max_steer_rate: 20.0, ..SwerveDrivetrainConfig::default() };
+            let num_modules = config.module_positions.len();
+            let mut sim = PySimulator::with_motors(config, vec![MotorConstant::kraken_x60(); num_modules]);
+            sim.steer_motors = steer_motors;
+            let commanded_duty = sim.apply_commands(None, Some(vec![std::f64::consts::FRAC_PI_2; num_modules]));
+            let mut counts = StepCounts::default();
+            let mut peak: f64 = 0.0;
+            for _ in 0..3000 {
+                sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+                peak = peak.max(sim.state.true_state.battery_state.total_current_draw);
+            }
+            (peak, sim.state.true_state.battery_state.total_current_draw)
+        };
+
+        assert_eq!(quarter_turn(Vec::new()), (0.0, 0.0));
+        let (peak, settled) = quarter_turn(vec![SteerMotorConfig::new(MotorConstant::neo(), 12.8); 4]);
+        assert!(peak > 5.0, "peak {}", peak);
+        assert!(settled.abs() < 0.01 * peak, "settled {} of peak {}", settled, peak);
+    }
+
+    #[test]
+    fn test_mixed_steer_motors_turn_their_modules_at_their_own_rate() {
+        let config = SwerveDrivetrainConfig { max_steer_rate: 20.0, ..SwerveDrivetrainConfig::default() };
+        let num_modules = config.module_positions.len();
+        let mut sim = PySimulator::with_motors(config, vec![MotorConstant::kraken_x60(); num_modules]);
+        let kraken = SteerMotorConfig::new(MotorConstant::kraken_x60(), 12.8).with_current_limit(5.0);
+        let neo = SteerMotorConfig::new(MotorConstant::neo(), 12.8).with_current_limit(5.0);
+        sim.steer_motors = vec![kraken, kraken, neo, neo];
+        let commanded_duty = sim.apply_commands(None, Some(vec![std::f64::consts::FRAC_PI_2; num_modules]));
+        let mut counts = StepCounts::default();
+        for _ in 0..200 {
+            sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        }
+
+        // Torque-limited modules are still accelerating: each pair turns
+        // together and the stronger steer motor has turned further
+        let angles: Vec<f64> = sim.state.true_state.wheel_states.iter().map(|wheel| wheel.angle).collect();
+        assert_eq!(angles[0], angles[1]);
+        assert_eq!(angles[2], angles[3]);
+        assert!(angles[0] < std::f64::consts::FRAC_PI_2 && angles[2] < std::f64::consts::FRAC_PI_2);
+        let (kraken_ahead, kraken_stronger) = (angles[0] > angles[2], kraken.max_torque() > neo.max_torque());
+        assert_eq!(kraken_ahead, kraken_stronger, "angles {:?}", angles);
+        assert!((angles[0] - angles[2]).abs() > 1e-4, "angles {:?}", angles);
+    }
+
+    #[test]
+    fn test_upshift_at_speed_drops_motor_speed() {
+        let mut sim = test_simulator();
+        sim.fit_gearbox(TwoSpeedGearbox::new(8.0, 4.0).unwrap());
+        let commanded_duty = sim.apply_commands(Some(vec![0.5; 4]), None);
+        let mut counts = StepCounts::default();
+        for _ in 0..2000 {
+            sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        }
+        let wheel_speed = sim.state.true_state.wheel_states[0].driving_angular_velocity;
+        let low_motor_speed = sim.state.true_state.motors[0].mechanical_velocity;
+        assert!(wheel_speed > 1.0);
+        assert!((low_motor_speed / wheel_speed - 8.0).abs() < 0.1, "{} vs {}", low_motor_speed, wheel_speed);
+
+        sim.shift_gear(Gear::High);
+        sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        let shifted_wheel_speed = sim.state.true_state.wheel_states[0].driving_angular_velocity;
+        let high_motor_speed = sim.state.true_state.motors[0].mechanical_velocity;
+        // The wheel carries its speed through the shift; the motor halves
+        assert!((shifted_wheel_speed / wheel_speed - 1.0).abs() < 0.01);
+        assert!((high_motor_speed / low_motor_speed - 0.5).abs() < 0.01, "{} vs {}", high_motor_speed, low_motor_speed);
+
+        sim.reset();
+        assert_eq!(sim.drivetrain.gear(), Gear::Low);
+        assert_eq!(sim.motor_bank.gear_ratios, vec![8.0; 4]);
+    }
+
+    #[test]
+    fn test_reset_after_a_steered_run_restores_the_built_state() {
+        let config = SwerveDrivetrainConfig { max_steer_rate: 10.0, ..SwerveDrivetrainConfig::default() };
+        let num_modules = config.module_positions.len();
+        let motors = vec![MotorConstant::kraken_x60(); num_modules];
+        let fresh = PySimulator::with_motors(config.clone(), motors.clone());
+        let mut sim = PySimulator::with_motors(config, motors);
+
+        let commanded_duty = sim.apply_commands(Some(vec![0.3; num_modules]), Some(vec![1.0; num_modules]));
+        let mut counts = StepCounts::default();
+        for _ in 0..500 {
+            sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        }
+        assert!(sim.state.true_state.wheel_states.iter().all(|wheel| wheel.angle > 0.1));
+        sim.reset();
+
+        assert_eq!(sim.steer_targets, fresh.steer_targets);
+        assert_eq!(sim.state.control_input.steer_torques, fresh.state.control_input.steer_torques);
+        for (wheel, built) in sim.state.true_state.wheel_states.iter().zip(&fresh.state.true_state.wheel_states) {
+            assert_eq!(wheel.angle, built.angle);
+            assert_eq!(wheel.turning_angular_velocity, built.turning_angular_velocity);
+            assert_eq!(wheel.driving_angular_velocity, built.driving_angular_velocity);
+            assert_eq!(wheel.tire.tire_load, built.tire.tire_load);
+        }
+        assert_eq!(sim.state.true_state.body_state.position, fresh.state.true_state.body_state.position);
+        assert_eq!(sim.state.true_state.body_state.velocity, fresh.state.true_state.body_state.velocity);
+    }
+
+    #[test]
+    fn test_steered_runs_after_reset_are_bit_identical() {
+        let config = SwerveDrivetrainConfig { max_steer_rate: 8.0, ..SwerveDrivetrainConfig::default() };
+        let num_modules = config.module_positions.len();
+        let mut sim = PySimulator::with_motors(config, vec![MotorConstant::kraken_x60(); num_modules]);
+        let duty = vec![0.6, 0.5, 0.4, 0.3];
+        let steers = vec![0.8, -0.4, 1.2, 0.2];
+        let (dt, n_steps) = (1e-4, 2000);
+
+        let run = |sim: &mut PySimulator| {
+            let commanded_duty = sim.apply_commands(Some(duty.clone()), Some(steers.clone()));
+            sim.record_run(dt, &commanded_duty, n_steps, true, false)
+        };
+        let first = run(&mut sim);
+        sim.reset();
+        let second = run(&mut sim);
+
+        // The modules were still turning at the rate limit partway through
+        assert!(sim.state.true_state.wheel_states[2].angle > 1.0);
+        let bits = |series: &[f64]| series.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        for (a, b) in [
+            (&first.times, &second.times),
+            (&first.positions_x, &second.positions_x),
+            (&first.positions_y, &second.positions_y),
+            (&first.headings, &second.headings),
+            (&first.velocities_x, &second.velocities_x),
+            (&first.velocities_y, &second.velocities_y),
+            (&first.angular_velocities, &second.angular_velocities),
+            (&first.battery_voltages, &second.battery_voltages),
+        ] {
+            assert_eq!(bits(a), bits(b));
+        }
+        let (first_wheels, second_wheels) = (first.wheels.unwrap(), second.wheels.unwrap());
+        for (a, b) in first_wheels.slip_angle.iter().zip(&second_wheels.slip_angle) {
+            assert_eq!(bits(a), bits(b));
+        }
+        for (a, b) in first_wheels.angular_velocity.iter().zip(&second_wheels.angular_velocity) {
+            assert_eq!(bits(a), bits(b));
+        }
+    }
+
+    #[test]
+    fn test_scenario_configures_and_drives_the_simulator() {
+        let text = r#"{
+            "drivetrain": {
+                "type": "swerve",
+                "mass": 62.5,
+                "module_positions": [[0.3, 0.0], [-0.2, 0.25], [-0.2, -0.25]]
+            },
+            "motor": "neo",
+            "dt": 0.0001,
+            "battery": { "initial_soc": 0.8 },
+            "initial_state": { "vx": 1.5 },
+            "commands": [
+                { "duration": 0.5, "duty_cycles": [1.0, 1.0, 1.0] },
+                { "duration": 0.25 }
+            ]
+        }"#;
+        let mut sim = PySimulator::from_scenario(Scenario::from_json(text).unwrap()).unwrap();
+        assert_eq!(sim.drivetrain.config.mass, 62.5);
+        assert_eq!(sim.state.true_state.wheel_states.len(), 3);
+        assert_eq!(sim.state.true_state.battery_state.state_of_charge, 0.8);
+        assert_eq!(sim.state.true_state.body_state.velocity[0], 1.5);
+
+        let driven = sim.run_schedule(false, false).unwrap();
+        // One row per step of each command, starting from the initial state
+        assert_eq!(driven.times.len(), 7500);
+        assert!((driven.times[5000] - 0.5).abs() < 1e-9);
+        assert_eq!(driven.velocities_x[0], 1.5);
+        // Rolling on from the initial velocity covers about vx * t
+        assert!((driven.positions_x[5000] - 0.75).abs() < 0.02, "x {}", driven.positions_x[5000]);
+
+        // The first command's duty pushes the robot harder than coasting does
+        let coast_text = text.replace("[1.0, 1.0, 1.0]", "[0.0, 0.0, 0.0]");
+        let mut coast = PySimulator::from_scenario(Scenario::from_json(&coast_text).unwrap()).unwrap();
+        let coasted = coast.run_schedule(false, false).unwrap();
+        assert!(driven.velocities_x[5000] > coasted.velocities_x[5000],
+            "driven {} vs coasted {}", driven.velocities_x[5000], coasted.velocities_x[5000]);
+    }
+
+    #[test]
+    fn test_replaying_a_scenario_against_its_own_run() {
+        let text = r#"{
+            "drivetrain": { "type": "swerve", "mass": 50.0 },
+            "dt": 0.0001,
+            "initial_state": { "vx": 1.0 },
+            "commands": [
+                { "duration": 0.1, "duty_cycles": [0.3, 0.3, 0.3, 0.3] },
+                { "duration": 0.05, "steer_angles": [0.2, 0.2, 0.2, 0.2] }
+            ]
+        }"#;
+        let mut sim = PySimulator::from_scenario(Scenario::from_json(text).unwrap()).unwrap();
+        let run = sim.run_schedule(false, false).unwrap();
+        let reference = Trajectory {
+            times: run.times.clone(),
+            x: run.positions_x.clone(),
+            y: run.positions_y.clone(),
+            heading: run.headings.clone(),
+            vx: run.velocities_x.clone(),
+            vy: run.velocities_y.clone(),
+        };
+
+        // The replay restarts from the initial state and retraces the run; only
+        // its final sample, one step past the reference, is held against the end
+        let own = sim.replay_against(&reference).unwrap().unwrap();
+        assert!(own.position_rmse < 1e-5, "position rmse {}", own.position_rmse);
+        assert!(own.heading_rmse < 1e-6 && own.velocity_rmse < 1e-3, "{:?}", own);
+
+        let mut shifted = reference.clone();
+        shifted.y.iter_mut().for_each(|y| *y += 0.1);
+        let off = sim.replay_against(&shifted).unwrap().unwrap();
+        assert!((off.position_rmse - 0.1).abs() < 1e-3, "position rmse {}", off.position_rmse);
+
+        assert!(sim.replay_against(&Trajectory::default()).unwrap().is_none());
+        sim.max_steps = 600;
+        assert_eq!(
+            sim.replay_against(&reference),
+            Err(StepBudgetError::TooManySteps { steps: 1000, max_steps: 600 })
+        );
+    }
+
+    #[test]
+    fn test_elec_substeps_multiply_the_electrical_step_count() {
+        let step_counts = |substeps: usize| {
+            let mut sim = test_simulator();
+            sim.fixed_dt = Some(1e-5);
+            sim.set_elec_substeps(NonZeroUsize::new(substeps).unwrap());
+            let commanded_duty = sim.apply_commands(Some(vec![0.5; 4]), None);
+            let mut counts = StepCounts::default();
+            for _ in 0..100 {
+                sim.step_commanded(&commanded_duty, 2e-5, &mut counts);
+            }
+            counts
+        };
+
+        // Two physics substeps per step, each split into the electrical steps
+        let coarse = step_counts(1);
+        assert_eq!(coarse, StepCounts { electrical: 200, mechanics: 200, battery: 200 });
+        let fine = step_counts(4);
+        assert_eq!(fine, StepCounts { electrical: 800, mechanics: 200, battery: 800 });
+    }
+
+    #[test]
+    fn test_streaming_calls_back_every_n_steps() {
+        let mut sim = test_simulator();
+        let commanded_duty = sim.apply_commands(Some(vec![0.5; 4]), None);
+        let (dt, n_steps, every) = (1e-5, 100, 7);
+
+        let mut samples = Vec::new();
+        let emitted = sim
+            .stream_run(dt, &commanded_duty, n_steps, every, |sample| {
+                samples.push(sample);
+                Ok::<(), ()>(())
+            })
+            .unwrap();
+
+        assert_eq!(emitted, n_steps / every);
+        assert_eq!(samples.len() as u64, emitted);
+        // Each sample is the state after its 7th step
+        for (k, sample) in samples.iter().enumerate() {
+            assert!((sample.0 - (k + 1) as f64 * every as f64 * dt).abs() < 1e-12, "time {}", sample.0);
+        }
+        assert!(samples.windows(2).all(|pair| pair[1].0 > pair[0].0));
+        assert!(samples.windows(2).all(|pair| pair[1].1 >= pair[0].1));
+
+        // A failing callback stops the run at once
+        let mut calls = 0;
+        let stopped = sim.stream_run(dt, &commanded_duty, n_steps, every, |_| {
+            calls += 1;
+            Err("stop")
+        });
+        assert_eq!(stopped, Err("stop"));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_initial_velocity_is_field_relative() {
+        let mut sim = test_simulator();
+        // Facing field +y and rolling forward at 2 m/s
+        sim.set_initial_state(0.0, 0.0, std::f64::consts::FRAC_PI_2, 0.0, 2.0, 0.0);
+        for wheel in &sim.state.true_state.wheel_states {
+            assert!((wheel.longitudinal_translational_velocity - 2.0).abs() < 1e-9);
+            assert!(wheel.lateral_translational_velocity.abs() < 1e-9);
+        }
+
+        let commanded_duty = sim.apply_commands(None, None);
+        let mut counts = StepCounts::default();
+        for _ in 0..100 {
+            sim.step_commanded(&commanded_duty, 1e-4, &mut counts);
+        }
+        // Coasting carries on along the heading rather than skidding sideways
+        let [vx, vy, _] = sim.state.true_state.body_state.planar_velocity(VelocityFrame::Field);
+        assert!(vx.abs() < 1e-6, "vx {}", vx);
+        assert!((vy - 2.0).abs() < 0.05, "vy {}", vy);
     }
 }
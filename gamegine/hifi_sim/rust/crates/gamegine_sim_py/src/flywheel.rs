@@ -0,0 +1,147 @@
+//! Flywheel shooter bindings
+//!
+//! Wraps the closed-loop flywheel simulation so spin-up and shot recovery can
+//! be studied from Python with a single call per segment.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyDict;
+use numpy::ToPyArray;
+
+use control::{FlywheelSample, FlywheelSim};
+use control::pidf::PidfConfig;
+use simcore::{checked_step_count, DEFAULT_MAX_STEPS};
+
+use crate::battery::PyBattery;
+use crate::mechanism::PyLinkConfig;
+use crate::motor::PyMotor;
+
+/// Velocity-controlled flywheel shooter
+///
+/// Composes a motor, battery, mechanical link (flywheel load) and a velocity
+/// PIDF controller whose output is duty cycle.
+#[pyclass]
+pub struct PyFlywheelSim {
+    inner: FlywheelSim,
+    max_steps: u64,
+}
+
+#[pymethods]
+impl PyFlywheelSim {
+    /// Create a flywheel simulator
+    ///
+    /// Args:
+    ///     motor: Motor model (PyMotor)
+    ///     battery: Battery model (PyBattery)
+    ///     link_config: Gearing between motor and flywheel
+    ///     moment_of_inertia: Flywheel moment of inertia (kg*m^2)
+    ///     kp, ki, kd: Velocity PID gains (duty per rad/s of error)
+    ///     kf: Feedforward (duty per rad/s), default 1 / free speed
+    ///     i_zone: Only integrate when |error| is below this (rad/s)
+    #[new]
+    #[pyo3(signature = (motor, battery, link_config, moment_of_inertia, kp=0.05, ki=0.05, kd=0.0, kf=None, i_zone=20.0))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        motor: &PyMotor,
+        battery: &PyBattery,
+        link_config: &PyLinkConfig,
+        moment_of_inertia: f64,
+        kp: f64,
+        ki: f64,
+        kd: f64,
+        kf: Option<f64>,
+        i_zone: f64,
+    ) -> Self {
+        let mut link = link_config.inner().clone();
+        link.load_inertia = moment_of_inertia;
+        let battery = *battery.inner();
+        let kf = kf.unwrap_or_else(|| {
+            let full_voltage = (battery.open_circuit_voltage_function)(1.0);
+            link.gear_ratio / motor.inner().free_speed(full_voltage)
+        });
+        let controller = PidfConfig::pidf(kp, ki, kd, kf).with_i_zone(i_zone);
+
+        PyFlywheelSim {
+            inner: FlywheelSim::new(*motor.inner(), battery, link, controller),
+            max_steps: DEFAULT_MAX_STEPS,
+        }
+    }
+
+    /// Drive toward a target speed and record the response
+    ///
+    /// State persists between calls, so spin_up / shoot / spin_up chains a
+    /// shot into one continuous run.
+    ///
+    /// Args:
+    ///     target_rad_s: Flywheel velocity setpoint (rad/s)
+    ///     duration: Simulation time (seconds)
+    ///     dt: Time step (seconds)
+    ///
+    /// Returns:
+    ///     Dict of numpy arrays: times, velocity, rpm, current, voltage, duty
+    ///
+    /// Raises:
+    ///     ValueError: if dt/duration are invalid or the run exceeds max_steps
+    #[pyo3(signature = (target_rad_s, duration, dt=0.001))]
+    fn spin_up<'py>(
+        &mut self,
+        py: Python<'py>,
+        target_rad_s: f64,
+        duration: f64,
+        dt: f64,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        checked_step_count(duration, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let samples = self.inner.spin_up(target_rad_s, duration, dt);
+
+        let column = |f: fn(&FlywheelSample) -> f64| samples.iter().map(f).collect::<Vec<f64>>();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("times", column(|s| s.time).to_pyarray_bound(py))?;
+        dict.set_item("velocity", column(|s| s.velocity).to_pyarray_bound(py))?;
+        dict.set_item("rpm", column(|s| s.velocity * 60.0 / (2.0 * std::f64::consts::PI)).to_pyarray_bound(py))?;
+        dict.set_item("current", column(|s| s.current).to_pyarray_bound(py))?;
+        dict.set_item("voltage", column(|s| s.voltage).to_pyarray_bound(py))?;
+        dict.set_item("duty", column(|s| s.duty).to_pyarray_bound(py))?;
+        Ok(dict)
+    }
+
+    /// Fire a ball: the flywheel shares its momentum with the ball on contact
+    ///
+    /// Args:
+    ///     ball_mass: Ball mass (kg), default an FRC-style 0.27 kg game piece
+    ///     contact_radius: Radius at which the flywheel grips the ball (m)
+    ///     transfer_efficiency: Exit speed per unit of wheel surface speed
+    ///         (0-1), about 0.5 for a single wheel against a hood
+    ///
+    /// Returns:
+    ///     Ball exit velocity (m/s)
+    #[pyo3(signature = (ball_mass=0.27, contact_radius=0.0508, transfer_efficiency=1.0))]
+    fn shoot(&mut self, ball_mass: f64, contact_radius: f64, transfer_efficiency: f64) -> f64 {
+        self.inner.shoot_projectile(ball_mass, contact_radius, transfer_efficiency).exit_velocity
+    }
+
+    /// Get flywheel velocity (rad/s)
+    fn velocity(&self) -> f64 {
+        self.inner.velocity()
+    }
+
+    /// Get flywheel velocity (RPM)
+    fn rpm(&self) -> f64 {
+        self.inner.velocity() * 60.0 / (2.0 * std::f64::consts::PI)
+    }
+
+    /// Get current simulation time
+    fn time(&self) -> f64 {
+        self.inner.time()
+    }
+
+    /// Set the maximum number of steps a single spin_up() may take
+    fn set_max_steps(&mut self, max_steps: u64) {
+        self.max_steps = max_steps;
+    }
+
+    /// Reset to rest on a full battery
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
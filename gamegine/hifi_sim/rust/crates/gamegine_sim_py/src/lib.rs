@@ -5,6 +5,11 @@
 //! - Batching simulation steps to minimize Python↔Rust crossings
 //! - Returning numpy arrays for vectorized analysis
 //! - Using lazy state access (only copy to Python when requested)
+//!
+//! The batch API is deterministic: simulation time advances only by the
+//! given `dt`, never by wall-clock time, and sensor noise draws from seeded
+//! streams. The same inputs from the same state give bit-identical arrays on
+//! any machine, however fast it runs.
 
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
@@ -14,6 +19,11 @@ mod motor;
 mod battery;
 mod drivetrain;
 mod mechanism;
+mod flywheel;
+mod controller;
+mod arm;
+mod swerve_module;
+mod sweep;
 
 pub use motor::*;
 pub use battery::*;
@@ -36,6 +46,21 @@ fn gamegine_sim_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<mechanism::PyLinkConfig>()?;
     m.add_class::<mechanism::PyMechanismSimulator>()?;
     m.add_class::<mechanism::MechanismResult>()?;
+
+    // Motor control
+    m.add_class::<controller::PyMotorController>()?;
+
+    // Flywheel shooter simulation
+    m.add_class::<flywheel::PyFlywheelSim>()?;
+
+    // Two-link arm simulation
+    m.add_class::<arm::PyDoubleJointedArmSim>()?;
+
+    // Single swerve module test stand
+    m.add_class::<swerve_module::PySwerveModuleSim>()?;
+
+    // Parameter studies
+    m.add_function(wrap_pyfunction!(sweep::sweep, m)?)?;
     
     Ok(())
 }
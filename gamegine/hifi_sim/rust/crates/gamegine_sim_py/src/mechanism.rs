@@ -10,10 +10,13 @@
 //! Uses steady-state motor model for numerical stability at reasonable time steps.
 
 use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
 use pyo3::types::PyDict;
 use numpy::ToPyArray;
 
 use mechanics::link::{MechanicalLink, LinkConfig, FrictionModel};
+use electrical::motor::MotorGroup;
+use simcore::{checked_step_count, DEFAULT_MAX_STEPS};
 
 /// Load type for mechanism simulation
 #[derive(Debug, Clone)]
@@ -55,9 +58,18 @@ pub struct PyLinkConfig {
 
 #[pymethods]
 impl PyLinkConfig {
+    /// Create a link configuration
+    ///
+    /// Args:
+    ///     gear_ratio: Reduction (motor turns per output turn)
+    ///     radius: Drum radius for linear output (m), 0 for rotational
+    ///     efficiency: Power transfer efficiency (0-1)
+    ///     friction_viscous: Viscous damping on the output
+    ///     backdrive_threshold: Load an unpowered, resting link holds without
+    ///         back-driving (N or N*m); None for a back-drivable link
     #[new]
-    #[pyo3(signature = (gear_ratio=1.0, radius=0.0, efficiency=1.0, friction_viscous=0.0))]
-    fn new(gear_ratio: f64, radius: f64, efficiency: f64, friction_viscous: f64) -> Self {
+    #[pyo3(signature = (gear_ratio=1.0, radius=0.0, efficiency=1.0, friction_viscous=0.0, backdrive_threshold=None))]
+    fn new(gear_ratio: f64, radius: f64, efficiency: f64, friction_viscous: f64, backdrive_threshold: Option<f64>) -> Self {
         let friction = if friction_viscous > 0.0 {
             FrictionModel::Viscous { damping: friction_viscous }
         } else {
@@ -71,11 +83,18 @@ impl PyLinkConfig {
                 efficiency,
                 load_inertia: 0.0, // Set from LoadType
                 friction,
+                backdrive_threshold: backdrive_threshold.map(f64::abs),
             }
         }
     }
 }
 
+impl PyLinkConfig {
+    pub fn inner(&self) -> &LinkConfig {
+        &self.inner
+    }
+}
+
 /// Simulation result with time-series data
 #[pyclass]
 pub struct MechanismResult {
@@ -128,11 +147,9 @@ pub struct PyMechanismSimulator {
     position: f64,  // Load position (linear m or rotational rad depending on config)
     velocity: f64,  // Load velocity
     
-    // Motor constants (steady-state model)
-    motor_kt: f64,         // Torque constant (Nm/A)
-    motor_ke: f64,         // Back-EMF constant (V/(rad/s))
-    motor_resistance: f64, // Winding resistance (Ohms)
-    motor_inertia: f64,    // Rotor inertia (kg*m^2)
+    // Ganged motors (steady-state model)
+    motors: MotorGroup,
+    motor_inertia: f64,    // Rotor inertia per motor (kg*m^2)
     
     // Battery model
     battery_capacity_ah: f64,
@@ -146,6 +163,9 @@ pub struct PyMechanismSimulator {
     
     // Current control input
     duty_cycle: f64,
+
+    // Limit on steps per run() call
+    max_steps: u64,
 }
 
 #[pymethods]
@@ -158,15 +178,24 @@ impl PyMechanismSimulator {
     ///     link_config: Mechanical link configuration
     ///     load_mass: Load mass in kg (for vertical/horizontal) or moment of inertia (for flywheel)
     ///     load_type: "vertical", "horizontal", or "flywheel"
+    ///     num_motors: Number of identical motors ganged on the input shaft
+    ///
+    /// Raises:
+    ///     ValueError: if num_motors is zero
     #[new]
-    #[pyo3(signature = (motor, battery, link_config, load_mass, load_type="vertical"))]
+    #[pyo3(signature = (motor, battery, link_config, load_mass, load_type="vertical", num_motors=1))]
     fn new(
         motor: &crate::motor::PyMotor,
         battery: &crate::battery::PyBattery,
         link_config: &PyLinkConfig,
         load_mass: f64,
         load_type: &str,
-    ) -> Self {
+        num_motors: usize,
+    ) -> PyResult<Self> {
+        if num_motors == 0 {
+            return Err(PyValueError::new_err("a mechanism needs at least one motor"));
+        }
+
         let load = match load_type {
             "vertical" => LoadType::Vertical { mass_kg: load_mass },
             "horizontal" => LoadType::Horizontal { mass_kg: load_mass },
@@ -178,12 +207,8 @@ impl PyMechanismSimulator {
         let mut config = link_config.inner.clone();
         config.load_inertia = load.inertia();
         
-        // Extract motor constants for steady-state model
-        let motor_inner = motor.inner();
-        let motor_kt = motor_inner.kt();
-        let motor_ke = motor_inner.ke();
-        let motor_resistance = motor_inner.resistance;
-        
+        let motors = MotorGroup::uniform(*motor.inner(), num_motors);
+
         // Extract battery parameters
         let battery_inner = battery.inner();
         let battery_capacity_ah = battery_inner.rated_capacity_ah;
@@ -191,13 +216,11 @@ impl PyMechanismSimulator {
         let battery_voltage = (battery_inner.open_circuit_voltage_function)(battery_soc);
         let battery_r0 = (battery_inner.ohmic_resistance_function)(battery_soc);
         
-        PyMechanismSimulator {
+        Ok(PyMechanismSimulator {
             time: 0.0,
             position: 0.0,
             velocity: 0.0,
-            motor_kt,
-            motor_ke,
-            motor_resistance,
+            motors,
             motor_inertia: 0.0001, // Typical brushless motor rotor inertia
             battery_capacity_ah,
             battery_soc,
@@ -206,7 +229,8 @@ impl PyMechanismSimulator {
             link: MechanicalLink::new(config),
             load_type: load,
             duty_cycle: 0.0,
-        }
+            max_steps: DEFAULT_MAX_STEPS,
+        })
     }
     
     /// Set motor duty cycle (-1.0 to 1.0)
@@ -239,10 +263,65 @@ impl PyMechanismSimulator {
     /// 
     /// Returns:
     ///     MechanismResult with time-series data for this run segment
+    ///
+    /// Raises:
+    ///     ValueError: if dt/duration are invalid or the run exceeds max_steps
     #[pyo3(signature = (duration, dt=0.001))]
-    fn run(&mut self, duration: f64, dt: f64) -> MechanismResult {
-        let n_steps = (duration / dt).ceil() as usize;
-        
+    fn run(&mut self, duration: f64, dt: f64) -> PyResult<MechanismResult> {
+        let n_steps = checked_step_count(duration, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))? as usize;
+        Ok(self.run_steps(n_steps, dt))
+    }
+
+    /// Duty cycle that holds the mechanism at rest at its current position
+    ///
+    /// Balances gravity through the link using the steady-state motor model
+    /// at zero velocity (where friction vanishes), including the battery sag
+    /// caused by the holding current. A magnitude above 1.0 means the motor
+    /// cannot hold the load.
+    fn holding_duty(&self) -> f64 {
+        let external_force = self.load_type.external_force(self.position, 0.0);
+        let torque = self.link.holding_motor_torque(external_force);
+        // Applied voltage (duty at 1 V) and the current it drives at stall
+        let applied_voltage = self.motors.duty_for_torque(torque, 0.0, 1.0);
+        let current = self.motors.steady_state(applied_voltage, 1.0, 0.0).current;
+        applied_voltage / self.loaded_voltage(current)
+    }
+
+    /// Set the maximum number of steps a single run() may take
+    fn set_max_steps(&mut self, max_steps: u64) {
+        self.max_steps = max_steps;
+    }
+
+    /// Get the maximum number of steps a single run() may take
+    fn max_steps(&self) -> u64 {
+        self.max_steps
+    }
+    
+    /// Reset simulation to initial state
+    fn reset(&mut self) {
+        self.time = 0.0;
+        self.position = 0.0;
+        self.velocity = 0.0;
+        self.duty_cycle = 0.0;
+        self.battery_soc = 1.0;
+        self.battery_voltage = 10.5 + 2.5 * self.battery_soc;
+    }
+    
+    /// Set initial position
+    fn set_position(&mut self, pos: f64) {
+        self.position = pos;
+    }
+    
+    /// Set initial velocity
+    fn set_velocity(&mut self, vel: f64) {
+        self.velocity = vel;
+    }
+}
+
+impl PyMechanismSimulator {
+    /// Advance `n_steps` steps of `dt`, recording the state before each step
+    fn run_steps(&mut self, n_steps: usize, dt: f64) -> MechanismResult {
         // Pre-allocate result vectors
         let mut times = Vec::with_capacity(n_steps);
         let mut positions = Vec::with_capacity(n_steps);
@@ -252,20 +331,17 @@ impl PyMechanismSimulator {
         let mut voltages = Vec::with_capacity(n_steps);
         let mut socs = Vec::with_capacity(n_steps);
         
-        let end_time = self.time + duration;
-        
-        while self.time < end_time {
+        for _ in 0..n_steps {
             // === Motor steady-state model ===
             // V_applied = duty_cycle * V_battery
             // V_applied = I * R + Ke * ω_motor
             // => I = (V_applied - Ke * ω_motor) / R
-            // T_motor = Kt * I
+            // T_motor = Kt * I, summed over the ganged motors
             
             let motor_velocity = self.link.velocity_b_to_a(self.velocity);
-            let v_applied = self.duty_cycle * self.battery_voltage;
-            let back_emf = self.motor_ke * motor_velocity;
-            let current = (v_applied - back_emf) / self.motor_resistance;
-            let motor_torque = self.motor_kt * current;
+            let output = self.motors.steady_state(self.duty_cycle, self.battery_voltage, motor_velocity);
+            let current = output.current;
+            let motor_torque = output.torque;
             
             // Record state
             times.push(self.time);
@@ -281,7 +357,7 @@ impl PyMechanismSimulator {
             
             let (acceleration, _net_force) = self.link.compute_load_acceleration(
                 motor_torque,
-                self.motor_inertia,
+                self.motor_inertia * self.motors.len() as f64,
                 self.velocity,
                 external_force,
             );
@@ -296,11 +372,8 @@ impl PyMechanismSimulator {
             let amp_hours = current.abs() * (dt / 3600.0);
             self.battery_soc = (self.battery_soc - amp_hours / self.battery_capacity_ah).clamp(0.0, 1.0);
             
-            // Voltage = OCV(SoC) - I * Rint
-            // For simplicity, use linear approximation for OCV
-            let ocv = 10.5 + 2.5 * self.battery_soc; // ~10.5V empty, ~13V full
-            self.battery_r0 = 0.01 + 0.01 * (1.0 - self.battery_soc); // Resistance increases as depleted
-            self.battery_voltage = ocv - current.abs() * self.battery_r0;
+            self.battery_r0 = self.internal_resistance();
+            self.battery_voltage = self.loaded_voltage(current);
         }
         
         MechanismResult {
@@ -313,24 +386,59 @@ impl PyMechanismSimulator {
             socs,
         }
     }
-    
-    /// Reset simulation to initial state
-    fn reset(&mut self) {
-        self.time = 0.0;
-        self.position = 0.0;
-        self.velocity = 0.0;
-        self.duty_cycle = 0.0;
-        self.battery_soc = 1.0;
-        self.battery_voltage = 10.5 + 2.5 * self.battery_soc;
+
+    /// Battery internal resistance at the current state of charge (Ohms)
+    fn internal_resistance(&self) -> f64 {
+        0.01 + 0.01 * (1.0 - self.battery_soc) // Resistance increases as depleted
     }
-    
-    /// Set initial position
-    fn set_position(&mut self, pos: f64) {
-        self.position = pos;
+
+    /// Battery terminal voltage while supplying `current`: OCV(SoC) - I * Rint
+    fn loaded_voltage(&self, current: f64) -> f64 {
+        // For simplicity, use linear approximation for OCV
+        let ocv = 10.5 + 2.5 * self.battery_soc; // ~10.5V empty, ~13V full
+        ocv - current.abs() * self.internal_resistance()
     }
-    
-    /// Set initial velocity
-    fn set_velocity(&mut self, vel: f64) {
-        self.velocity = vel;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use electrical::motor::MotorConstant;
+
+    #[test]
+    fn test_holding_duty_keeps_elevator_still() {
+        let config = LinkConfig::new()
+            .with_gear_ratio(12.0)
+            .with_radius(0.02)
+            .with_efficiency(0.9)
+            .with_load_inertia(8.0);
+        let mut sim = PyMechanismSimulator {
+            time: 0.0,
+            position: 0.5,
+            velocity: 0.0,
+            motors: MotorGroup::uniform(MotorConstant::kraken_x60(), 2),
+            motor_inertia: 0.0001,
+            battery_capacity_ah: 18.0,
+            battery_soc: 1.0,
+            battery_voltage: 13.0,
+            battery_r0: 0.01,
+            link: MechanicalLink::new(config),
+            load_type: LoadType::Vertical { mass_kg: 8.0 },
+            duty_cycle: 0.0,
+            max_steps: DEFAULT_MAX_STEPS,
+        };
+        let duty = sim.holding_duty();
+        assert!(duty > 0.0 && duty < 1.0, "holding duty {}", duty);
+
+        sim.duty_cycle = duty;
+        let result = sim.run_steps(1000, 0.001);
+        assert_eq!(result.times.len(), 1000);
+        assert!((sim.position - 0.5).abs() < 1e-4, "elevator drifted to {} m", sim.position);
+        assert!(sim.velocity.abs() < 1e-3, "elevator moving at {} m/s", sim.velocity);
+
+        // Unpowered, the same elevator sinks against the motors' back-EMF braking
+        sim.duty_cycle = 0.0;
+        sim.run_steps(100, 0.001);
+        assert!(sim.position < 0.5 - 1e-3, "unpowered elevator at {} m", sim.position);
     }
 }
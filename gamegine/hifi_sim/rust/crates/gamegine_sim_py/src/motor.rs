@@ -1,10 +1,30 @@
 //! Motor bindings with vectorized analysis APIs
 
 use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
 use pyo3::types::PyDict;
 use numpy::ToPyArray;
+use electrical::analysis::{log_spaced_ratios, MotorAnalysisResult, MotorRequirement, UnitSystem};
 use electrical::motor::MotorConstant;
 
+/// Parse a `units` keyword ("si" or "imperial")
+fn parse_units(units: &str) -> PyResult<UnitSystem> {
+    UnitSystem::parse(units).ok_or_else(|| {
+        PyValueError::new_err(format!("unknown units '{}', expected 'si' or 'imperial'", units))
+    })
+}
+
+/// Torque-velocity curve as a dict of numpy arrays
+fn curve_dict<'py>(py: Python<'py>, result: &MotorAnalysisResult) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("velocities", result.velocities.to_pyarray_bound(py))?;
+    dict.set_item("torques", result.torques.to_pyarray_bound(py))?;
+    dict.set_item("currents", result.currents.to_pyarray_bound(py))?;
+    dict.set_item("powers", result.powers.to_pyarray_bound(py))?;
+    dict.set_item("efficiencies", result.efficiencies.to_pyarray_bound(py))?;
+    Ok(dict)
+}
+
 /// Python-accessible motor representation with analysis functions
 #[pyclass]
 #[derive(Clone)]
@@ -81,9 +101,10 @@ impl PyMotor {
         self.inner.ke()
     }
 
-    /// Get free speed at given voltage (rad/s)
-    fn free_speed(&self, voltage: f64) -> f64 {
-        self.inner.free_speed(voltage)
+    /// Get free speed at given voltage (rad/s, or RPM with units="imperial")
+    #[pyo3(signature = (voltage, units="si"))]
+    fn free_speed(&self, voltage: f64, units: &str) -> PyResult<f64> {
+        Ok(parse_units(units)?.speed(self.inner.free_speed(voltage)))
     }
 
     /// Get free speed at given voltage (RPM)
@@ -91,9 +112,10 @@ impl PyMotor {
         self.inner.free_speed(voltage) * 60.0 / (2.0 * std::f64::consts::PI)
     }
 
-    /// Get stall torque at given voltage (Nm)
-    fn stall_torque(&self, voltage: f64) -> f64 {
-        self.inner.stall_torque(voltage)
+    /// Get stall torque at given voltage (Nm, or lb*ft with units="imperial")
+    #[pyo3(signature = (voltage, units="si"))]
+    fn stall_torque(&self, voltage: f64, units: &str) -> PyResult<f64> {
+        Ok(parse_units(units)?.torque(self.inner.stall_torque(voltage)))
     }
 
     /// Get stall current at given voltage (A)
@@ -101,14 +123,18 @@ impl PyMotor {
         self.inner.stall_current(voltage)
     }
 
-    /// Get maximum power at given voltage (W)
-    fn max_power(&self, voltage: f64) -> f64 {
-        self.inner.max_power(voltage)
+    /// Get maximum power at given voltage (W, or hp with units="imperial")
+    #[pyo3(signature = (voltage, units="si"))]
+    fn max_power(&self, voltage: f64, units: &str) -> PyResult<f64> {
+        Ok(parse_units(units)?.power(self.inner.max_power(voltage)))
     }
 
-    /// Compute torque at given velocity and voltage
-    fn torque_at_velocity(&self, velocity: f64, voltage: f64) -> f64 {
-        self.inner.torque_at_velocity(velocity, voltage)
+    /// Compute torque at given velocity (rad/s) and voltage
+    ///
+    /// The result is in Nm, or lb*ft with units="imperial".
+    #[pyo3(signature = (velocity, voltage, units="si"))]
+    fn torque_at_velocity(&self, velocity: f64, voltage: f64, units: &str) -> PyResult<f64> {
+        Ok(parse_units(units)?.torque(self.inner.torque_at_velocity(velocity, voltage)))
     }
 
     /// Compute efficiency at given velocity and voltage
@@ -124,18 +150,37 @@ impl PyMotor {
     /// Args:
     ///     voltage: Supply voltage (V)
     ///     n_points: Number of sample points (default 100)
-    #[pyo3(signature = (voltage, n_points=100))]
-    fn torque_velocity_curve<'py>(&self, py: Python<'py>, voltage: f64, n_points: usize) -> PyResult<Bound<'py, PyDict>> {
-        let result = self.inner.torque_velocity_curve(voltage, n_points);
-        
-        let dict = PyDict::new_bound(py);
-        dict.set_item("velocities", result.velocities.to_pyarray_bound(py))?;
-        dict.set_item("torques", result.torques.to_pyarray_bound(py))?;
-        dict.set_item("currents", result.currents.to_pyarray_bound(py))?;
-        dict.set_item("powers", result.powers.to_pyarray_bound(py))?;
-        dict.set_item("efficiencies", result.efficiencies.to_pyarray_bound(py))?;
-        
-        Ok(dict)
+    ///     units: "si" (rad/s, Nm, W) or "imperial" (RPM, lb*ft, hp)
+    #[pyo3(signature = (voltage, n_points=100, units="si"))]
+    fn torque_velocity_curve<'py>(&self, py: Python<'py>, voltage: f64, n_points: usize, units: &str) -> PyResult<Bound<'py, PyDict>> {
+        let result = self.inner.torque_velocity_curve(voltage, n_points).in_units(parse_units(units)?);
+        curve_dict(py, &result)
+    }
+
+    /// Torque-velocity curve with the windings at a given temperature
+    ///
+    /// Winding resistance rises with temperature, lowering the current, stall
+    /// torque and power a hot motor delivers.
+    ///
+    /// Args:
+    ///     voltage: Supply voltage (V)
+    ///     temp_c: Winding temperature (C); resistance is nominal at 25 C
+    ///     n_points: Number of sample points (default 100)
+    ///     units: "si" (rad/s, Nm, W) or "imperial" (RPM, lb*ft, hp)
+    ///
+    /// Returns:
+    ///     Dict with numpy arrays, as torque_velocity_curve
+    #[pyo3(signature = (voltage, temp_c, n_points=100, units="si"))]
+    fn torque_velocity_curve_at_temp<'py>(
+        &self,
+        py: Python<'py>,
+        voltage: f64,
+        temp_c: f64,
+        n_points: usize,
+        units: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let result = self.inner.torque_velocity_curve_at_temp(voltage, temp_c, n_points).in_units(parse_units(units)?);
+        curve_dict(py, &result)
     }
 
     /// Calculate optimal gearing for desired wheel speed
@@ -145,6 +190,75 @@ impl PyMotor {
         self.inner.optimal_gearing(voltage, desired_wheel_speed)
     }
 
+    /// Find the gear ratio maximizing average efficiency over a duty profile
+    ///
+    /// Args:
+    ///     load_torques: Load-side torque at each operating point (Nm)
+    ///     load_speeds: Load-side speed at each operating point (rad/s)
+    ///     voltage: Supply voltage (V)
+    ///
+    /// Returns:
+    ///     Gear ratio (motor turns per load turn)
+    ///
+    /// Raises:
+    ///     ValueError: if the profiles differ in length or are empty
+    fn gear_ratio_for_duty_cycle(&self, load_torques: Vec<f64>, load_speeds: Vec<f64>, voltage: f64) -> PyResult<f64> {
+        if load_torques.is_empty() || load_torques.len() != load_speeds.len() {
+            return Err(PyValueError::new_err(
+                "load_torques and load_speeds must be non-empty and the same length",
+            ));
+        }
+        Ok(self.inner.gear_ratio_for_duty_cycle(&load_torques, &load_speeds, voltage))
+    }
+
+    /// Recommend preset motors and gear ratios for a mechanism requirement
+    ///
+    /// Args:
+    ///     torque: Required load-side torque (Nm)
+    ///     speed: Required load-side speed (rad/s)
+    ///     duty_cycle: Fraction of the time spent at this operating point
+    ///     voltage: Supply voltage (V)
+    ///     margin: Fractional margin on torque and speed
+    ///     current_limit: Continuous (RMS) current limit per motor (A)
+    ///     gear_ratios: Candidate ratios, default 200 log-spaced from 1 to 400
+    ///
+    /// Returns:
+    ///     List of dicts (motor, gear_ratio, current, rms_current, efficiency,
+    ///     current_headroom) for every feasible combination, best first
+    #[staticmethod]
+    #[pyo3(signature = (torque, speed, duty_cycle=1.0, voltage=12.0, margin=0.2, current_limit=40.0, gear_ratios=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn recommend<'py>(
+        py: Python<'py>,
+        torque: f64,
+        speed: f64,
+        duty_cycle: f64,
+        voltage: f64,
+        margin: f64,
+        current_limit: f64,
+        gear_ratios: Option<Vec<f64>>,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let requirement = MotorRequirement::new(torque, speed, duty_cycle)
+            .with_voltage(voltage)
+            .with_margin(margin)
+            .with_current_limit(current_limit);
+        let ratios = gear_ratios.unwrap_or_else(|| log_spaced_ratios(1.0, 400.0, 200));
+
+        requirement.recommend(&ratios)
+            .into_iter()
+            .map(|r| {
+                let dict = PyDict::new_bound(py);
+                dict.set_item("motor", r.preset)?;
+                dict.set_item("gear_ratio", r.gear_ratio)?;
+                dict.set_item("current", r.current)?;
+                dict.set_item("rms_current", r.rms_current)?;
+                dict.set_item("efficiency", r.efficiency)?;
+                dict.set_item("current_headroom", r.current_headroom)?;
+                Ok(dict)
+            })
+            .collect()
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "Motor(kt={:.4} Nm/A, ke={:.4} V/(rad/s), R={:.4} Ω)",
@@ -0,0 +1,46 @@
+//! Parameter sweep bindings
+//!
+//! Runs a Python-built simulation once per parameter value and collects a
+//! scalar metric from each, for "vary gear ratio, plot top speed" studies.
+
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use numpy::ToPyArray;
+
+use simcore::try_sweep;
+
+/// Sweep one parameter and measure a scalar metric at each value
+///
+/// For each value, calls base_sim_factory(**{param_name: value}) to build a
+/// simulator and metric_fn(sim) to reduce it to a float.
+///
+/// Args:
+///     base_sim_factory: Callable taking param_name as a keyword argument
+///     param_name: Keyword the swept value is passed as
+///     values: Parameter values to sweep
+///     metric_fn: Callable mapping a simulator to a float (e.g. top speed)
+///
+/// Returns:
+///     (values, metrics) as numpy arrays
+///
+/// Raises:
+///     Any exception raised by base_sim_factory or metric_fn
+#[pyfunction]
+pub fn sweep<'py>(
+    py: Python<'py>,
+    base_sim_factory: &Bound<'py, PyAny>,
+    param_name: &str,
+    values: Vec<f64>,
+    metric_fn: &Bound<'py, PyAny>,
+) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyAny>)> {
+    let result = try_sweep(
+        &values,
+        |value| {
+            let kwargs = PyDict::new_bound(py);
+            kwargs.set_item(param_name, value)?;
+            base_sim_factory.call((), Some(&kwargs))
+        },
+        |sim| metric_fn.call1((sim.clone(),))?.extract::<f64>(),
+    )?;
+    Ok((result.values.to_pyarray_bound(py).into_any(), result.metrics.to_pyarray_bound(py).into_any()))
+}
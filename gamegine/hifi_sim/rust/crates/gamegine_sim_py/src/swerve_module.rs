@@ -0,0 +1,212 @@
+//! Single swerve module bindings
+//!
+//! Wraps the swerve module test stand so drive and steer tuning can be done
+//! from Python without building a whole robot.
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::PyDict;
+use numpy::ToPyArray;
+
+use control::{ModuleSample, SteerMotorConfig, SwerveModuleSim};
+use mechanics::gearbox::{Gear, TwoSpeedGearbox};
+use mechanics::tire::TireConstants;
+use simcore::{checked_step_count, DEFAULT_MAX_STEPS};
+
+use crate::motor::PyMotor;
+
+/// One swerve module: drive motor, steer motor, tire and a share of robot mass
+///
+/// The steer motor runs a PD angle loop toward the steer target; the drive
+/// motor is commanded open loop by duty cycle. State persists between run()
+/// calls so commands can be changed from Python.
+#[pyclass]
+pub struct PySwerveModuleSim {
+    inner: SwerveModuleSim,
+    max_steps: u64,
+}
+
+#[pymethods]
+impl PySwerveModuleSim {
+    /// Create a swerve module simulator
+    ///
+    /// Args:
+    ///     drive_motor: Drive motor model (PyMotor)
+    ///     steer_motor: Steer motor model (PyMotor)
+    ///     drive_gear_ratio: Drive reduction (motor turns per wheel turn)
+    ///     steer_gear_ratio: Steer reduction (motor turns per module turn)
+    ///     carried_mass: Robot mass riding on this module (kg)
+    ///     wheel_radius: Wheel radius (m)
+    ///     friction_coefficient: Tire coefficient of friction
+    ///     steer_kp, steer_kd: Steer angle loop gains (duty per rad, duty per rad/s)
+    ///     voltage: Supply voltage (V)
+    ///     high_gear_ratio: High gear reduction of a two-speed gearbox, below
+    ///         drive_gear_ratio as low gear; None for a single-speed drive
+    ///     angle_offset: Absolute steer encoder reading with the wheel
+    ///         pointing forward (rad)
+    ///
+    /// Raises:
+    ///     ValueError: if a gear ratio, the wheel radius or the mass is not
+    ///         positive, or the high gear ratio is not below the low one
+    #[new]
+    #[pyo3(signature = (
+        drive_motor, steer_motor, drive_gear_ratio=6.75, steer_gear_ratio=12.8,
+        carried_mass=12.5, wheel_radius=0.0508, friction_coefficient=1.2,
+        steer_kp=1.0, steer_kd=0.05, voltage=12.0, high_gear_ratio=None, angle_offset=0.0
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        drive_motor: &PyMotor,
+        steer_motor: &PyMotor,
+        drive_gear_ratio: f64,
+        steer_gear_ratio: f64,
+        carried_mass: f64,
+        wheel_radius: f64,
+        friction_coefficient: f64,
+        steer_kp: f64,
+        steer_kd: f64,
+        voltage: f64,
+        high_gear_ratio: Option<f64>,
+        angle_offset: f64,
+    ) -> PyResult<Self> {
+        if drive_gear_ratio <= 0.0 || steer_gear_ratio <= 0.0 || high_gear_ratio.is_some_and(|ratio| ratio <= 0.0) {
+            return Err(PyValueError::new_err("gear ratios must be positive"));
+        }
+        if wheel_radius <= 0.0 || carried_mass <= 0.0 {
+            return Err(PyValueError::new_err("wheel_radius and carried_mass must be positive"));
+        }
+
+        let steer = SteerMotorConfig::new(*steer_motor.inner(), steer_gear_ratio).with_gains(steer_kp, steer_kd);
+        let tire = TireConstants::new(friction_coefficient, friction_coefficient, 2000.0, 2000.0, 0.0, 0.0);
+        let mut inner = SwerveModuleSim::new(*drive_motor.inner(), drive_gear_ratio, steer, tire)
+            .with_wheel_radius(wheel_radius)
+            .with_carried_mass(carried_mass)
+            .with_voltage(voltage)
+            .with_angle_offset(angle_offset);
+        if let Some(high_gear_ratio) = high_gear_ratio {
+            let gearbox = TwoSpeedGearbox::new(drive_gear_ratio, high_gear_ratio)
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+            inner = inner.with_two_speed_gearbox(gearbox);
+        }
+        Ok(PySwerveModuleSim { inner, max_steps: DEFAULT_MAX_STEPS })
+    }
+
+    /// Set the drive motor duty cycle (-1.0 to 1.0)
+    fn set_drive_duty(&mut self, duty: f64) {
+        self.inner.set_drive_duty(duty);
+    }
+
+    /// Set the raw encoder angle the steer loop turns to (rad)
+    ///
+    /// The wheel settles at angle - angle_offset; use encoder_target() to
+    /// convert a desired wheel direction.
+    fn set_steer_target(&mut self, angle: f64) {
+        self.inner.set_steer_target(angle);
+    }
+
+    /// Raw encoder angle that points the wheel at wheel_angle (rad)
+    fn encoder_target(&self, wheel_angle: f64) -> f64 {
+        self.inner.encoder_target(wheel_angle)
+    }
+
+    /// Shift the two-speed drive gearbox, keeping the wheel speed
+    ///
+    /// Args:
+    ///     gear: "low" or "high"
+    ///
+    /// Raises:
+    ///     ValueError: if the gear name is unknown
+    fn shift(&mut self, gear: &str) -> PyResult<()> {
+        let gear = Gear::parse(gear)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown gear '{}', expected 'low' or 'high'", gear)))?;
+        self.inner.shift(gear);
+        Ok(())
+    }
+
+    /// Get the active gear name ("low" or "high")
+    fn gear(&self) -> &'static str {
+        self.inner.gear().name()
+    }
+
+    /// Get drive motor shaft speed (rad/s)
+    fn drive_motor_speed(&self) -> f64 {
+        self.inner.drive_motor_speed()
+    }
+
+    /// Lift the wheel off the floor (True) or set it back down (False)
+    fn set_lifted(&mut self, lifted: bool) {
+        self.inner.set_lifted(lifted);
+    }
+
+    /// Get wheel angular velocity (rad/s)
+    fn wheel_speed(&self) -> f64 {
+        self.inner.sample().wheel_speed
+    }
+
+    /// Get module azimuth (rad)
+    fn steer_angle(&self) -> f64 {
+        self.inner.sample().steer_angle
+    }
+
+    /// Get (longitudinal, lateral) tire force (N); longitudinal is negative while driving
+    fn tire_forces(&self) -> (f64, f64) {
+        let sample = self.inner.sample();
+        (sample.longitudinal_force, sample.lateral_force)
+    }
+
+    /// Run the module for a duration
+    ///
+    /// Args:
+    ///     duration: Simulation time (seconds)
+    ///     dt: Time step (seconds), default 0.0001
+    ///
+    /// Returns:
+    ///     Dict of numpy arrays: times, wheel_speed, steer_angle (wheel
+    ///     direction), encoder_angle (raw steer encoder reading), steer_rate,
+    ///     longitudinal_force, lateral_force, ground_speed, drive_current,
+    ///     steer_current, commanded_speed (m/s the drive duty commands),
+    ///     wheel_surface_speed and tracking_error (commanded speed less the
+    ///     module's ground speed along its heading, not its wheel surface
+    ///     speed, so wheelspin shows as error; only this stand reports it)
+    ///
+    /// Raises:
+    ///     ValueError: if dt/duration are invalid or the run exceeds max_steps
+    #[pyo3(signature = (duration, dt=1e-4))]
+    fn run<'py>(&mut self, py: Python<'py>, duration: f64, dt: f64) -> PyResult<Bound<'py, PyDict>> {
+        checked_step_count(duration, dt, self.max_steps)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let samples = self.inner.run(duration, dt);
+
+        let column = |f: fn(&ModuleSample) -> f64| samples.iter().map(f).collect::<Vec<f64>>();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("times", column(|s| s.time).to_pyarray_bound(py))?;
+        dict.set_item("wheel_speed", column(|s| s.wheel_speed).to_pyarray_bound(py))?;
+        dict.set_item("steer_angle", column(|s| s.steer_angle).to_pyarray_bound(py))?;
+        dict.set_item("encoder_angle", column(|s| s.encoder_angle).to_pyarray_bound(py))?;
+        dict.set_item("steer_rate", column(|s| s.steer_rate).to_pyarray_bound(py))?;
+        dict.set_item("longitudinal_force", column(|s| s.longitudinal_force).to_pyarray_bound(py))?;
+        dict.set_item("lateral_force", column(|s| s.lateral_force).to_pyarray_bound(py))?;
+        dict.set_item("ground_speed", column(|s| s.ground_speed).to_pyarray_bound(py))?;
+        dict.set_item("drive_current", column(|s| s.drive_current).to_pyarray_bound(py))?;
+        dict.set_item("steer_current", column(|s| s.steer_current).to_pyarray_bound(py))?;
+        dict.set_item("commanded_speed", column(|s| s.commanded_speed).to_pyarray_bound(py))?;
+        dict.set_item("wheel_surface_speed", column(|s| s.wheel_surface_speed).to_pyarray_bound(py))?;
+        dict.set_item("tracking_error", column(|s| s.tracking_error).to_pyarray_bound(py))?;
+        Ok(dict)
+    }
+
+    /// Set the maximum number of steps a single run() may take
+    fn set_max_steps(&mut self, max_steps: u64) {
+        self.max_steps = max_steps;
+    }
+
+    /// Get the maximum number of steps a single run() may take
+    fn max_steps(&self) -> u64 {
+        self.max_steps
+    }
+
+    /// Reset to rest, pointing forward, on the floor
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
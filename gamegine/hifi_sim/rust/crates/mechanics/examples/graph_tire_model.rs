@@ -83,6 +83,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             tire_load,
         },
         angle: 0.0,
+        contact_fraction: 1.0,
     };
 
     let mut state = SimState {
@@ -0,0 +1,203 @@
+//! Two-Link Arm Dynamics
+//!
+//! A shoulder and elbow joint moving in a vertical plane, integrated from the
+//! manipulator equations `M(q) q'' + C(q, q') q' + G(q) = tau`. The shoulder
+//! angle is measured from horizontal and the elbow angle relative to the
+//! upper link, both counter-clockwise; gravity pulls along -y.
+
+use nalgebra::{Matrix2, Vector2};
+
+/// One rigid link of the arm
+#[derive(Debug, Clone, Copy)]
+pub struct ArmLink {
+    /// Joint-to-joint length (m)
+    pub length: f64,
+    /// Mass (kg)
+    pub mass: f64,
+    /// Distance from the driving joint to the center of mass (m)
+    pub center_of_mass: f64,
+    /// Moment of inertia about the center of mass (kg*m^2)
+    pub moment_of_inertia: f64,
+}
+
+impl ArmLink {
+    /// Uniform rod of `length` and `mass`
+    pub fn new(length: f64, mass: f64) -> Self {
+        ArmLink {
+            length,
+            mass,
+            center_of_mass: 0.5 * length,
+            moment_of_inertia: mass * length * length / 12.0,
+        }
+    }
+}
+
+/// Shoulder (upper link) and elbow (forearm) arm driven by joint torques
+#[derive(Debug, Clone)]
+pub struct DoubleJointedArm {
+    pub upper: ArmLink,
+    pub forearm: ArmLink,
+    /// Extra inertia on each joint, e.g. motor rotors reflected through the
+    /// gearbox (kg*m^2)
+    pub joint_inertia: [f64; 2],
+    /// Gravitational acceleration (m/s^2)
+    pub gravity: f64,
+    /// Shoulder and elbow angles (rad)
+    angles: [f64; 2],
+    /// Shoulder and elbow velocities (rad/s)
+    velocities: [f64; 2],
+}
+
+impl DoubleJointedArm {
+    /// Arm at rest with both links horizontal
+    pub fn new(upper: ArmLink, forearm: ArmLink) -> Self {
+        DoubleJointedArm {
+            upper,
+            forearm,
+            joint_inertia: [0.0; 2],
+            gravity: 9.81,
+            angles: [0.0; 2],
+            velocities: [0.0; 2],
+        }
+    }
+
+    /// Add reflected motor and gearbox inertia to the shoulder and elbow (kg*m^2)
+    pub fn with_joint_inertia(mut self, shoulder: f64, elbow: f64) -> Self {
+        self.joint_inertia = [shoulder.max(0.0), elbow.max(0.0)];
+        self
+    }
+
+    /// Shoulder and elbow angles (rad)
+    pub fn angles(&self) -> [f64; 2] {
+        self.angles
+    }
+
+    /// Shoulder and elbow velocities (rad/s)
+    pub fn velocities(&self) -> [f64; 2] {
+        self.velocities
+    }
+
+    /// Place the arm at `angles` moving at `velocities`
+    pub fn set_state(&mut self, angles: [f64; 2], velocities: [f64; 2]) {
+        self.angles = angles;
+        self.velocities = velocities;
+    }
+
+    /// Mass matrix M(q)
+    pub fn mass_matrix(&self) -> Matrix2<f64> {
+        let (m1, r1, i1) = (self.upper.mass, self.upper.center_of_mass, self.upper.moment_of_inertia);
+        let (m2, r2, i2) = (self.forearm.mass, self.forearm.center_of_mass, self.forearm.moment_of_inertia);
+        let l1 = self.upper.length;
+        let c2 = self.angles[1].cos();
+
+        let coupled = m2 * (r2 * r2 + l1 * r2 * c2) + i2;
+        Matrix2::new(
+            m1 * r1 * r1 + m2 * (l1 * l1 + r2 * r2 + 2.0 * l1 * r2 * c2) + i1 + i2 + self.joint_inertia[0],
+            coupled,
+            coupled,
+            m2 * r2 * r2 + i2 + self.joint_inertia[1],
+        )
+    }
+
+    /// Coriolis and centrifugal torques C(q, q') q'
+    pub fn coriolis_torques(&self) -> [f64; 2] {
+        let h = self.forearm.mass * self.upper.length * self.forearm.center_of_mass * self.angles[1].sin();
+        let [w1, w2] = self.velocities;
+        [-h * (2.0 * w1 * w2 + w2 * w2), h * w1 * w1]
+    }
+
+    /// Gravity vector G(q): the joint torques that hold the arm still
+    pub fn gravity_torques(&self) -> [f64; 2] {
+        let [q1, q2] = self.angles;
+        let forearm = self.forearm.mass * self.forearm.center_of_mass * self.gravity * (q1 + q2).cos();
+        let upper = (self.upper.mass * self.upper.center_of_mass + self.forearm.mass * self.upper.length)
+            * self.gravity
+            * q1.cos();
+        [upper + forearm, forearm]
+    }
+
+    /// Joint accelerations under `torques` (N*m) at the present state
+    pub fn accelerations(&self, torques: [f64; 2]) -> [f64; 2] {
+        let coriolis = self.coriolis_torques();
+        let gravity = self.gravity_torques();
+        let net = Vector2::new(
+            torques[0] - coriolis[0] - gravity[0],
+            torques[1] - coriolis[1] - gravity[1],
+        );
+        let accel = self.mass_matrix().try_inverse().map_or(Vector2::zeros(), |inverse| inverse * net);
+        [accel[0], accel[1]]
+    }
+
+    /// Advance the arm by `dt` under joint `torques` (semi-implicit Euler)
+    pub fn step(&mut self, torques: [f64; 2], dt: f64) {
+        let accel = self.accelerations(torques);
+        for ((angle, velocity), accel) in self.angles.iter_mut().zip(&mut self.velocities).zip(accel) {
+            *velocity += accel * dt;
+            *angle += *velocity * dt;
+        }
+    }
+
+    /// Kinetic plus gravitational potential energy (J), zero with both
+    /// links horizontal and at rest
+    pub fn energy(&self) -> f64 {
+        let velocity = Vector2::new(self.velocities[0], self.velocities[1]);
+        let kinetic = 0.5 * velocity.dot(&(self.mass_matrix() * velocity));
+        let [q1, q2] = self.angles;
+        let potential = self.gravity
+            * (self.upper.mass * self.upper.center_of_mass * q1.sin()
+                + self.forearm.mass * (self.upper.length * q1.sin() + self.forearm.center_of_mass * (q1 + q2).sin()));
+        kinetic + potential
+    }
+
+    /// Position of the end of the forearm relative to the shoulder (m)
+    pub fn end_effector(&self) -> [f64; 2] {
+        let [q1, q2] = self.angles;
+        [
+            self.upper.length * q1.cos() + self.forearm.length * (q1 + q2).cos(),
+            self.upper.length * q1.sin() + self.forearm.length * (q1 + q2).sin(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arm() -> DoubleJointedArm {
+        DoubleJointedArm::new(ArmLink::new(0.8, 4.0), ArmLink::new(0.6, 2.0))
+    }
+
+    #[test]
+    fn test_released_arm_collapses_under_gravity() {
+        let mut arm = arm();
+        let start_energy = arm.energy();
+        let dt = 1e-4;
+        let mut lowest_tip: f64 = 0.0;
+        for _ in 0..5000 {
+            arm.step([0.0; 2], dt);
+            lowest_tip = lowest_tip.min(arm.end_effector()[1]);
+        }
+
+        // The shoulder swings down and the tip drops well below the pivot
+        let [shoulder, _] = arm.angles();
+        assert!(shoulder < -0.5, "shoulder only fell to {}", shoulder);
+        assert!(lowest_tip < -0.8, "tip only dropped to {}", lowest_tip);
+        // Unpowered and frictionless, so energy is conserved
+        assert!((arm.energy() - start_energy).abs() < 0.05, "energy drifted to {}", arm.energy());
+    }
+
+    #[test]
+    fn test_gravity_torques_hold_arm_still() {
+        for angles in [[0.0, 0.0], [0.7, -1.2], [-0.4, 2.0]] {
+            let mut arm = arm().with_joint_inertia(0.3, 0.1);
+            arm.set_state(angles, [0.0; 2]);
+            for _ in 0..1000 {
+                let holding = arm.gravity_torques();
+                arm.step(holding, 1e-3);
+            }
+            let [q1, q2] = arm.angles();
+            assert!((q1 - angles[0]).abs() < 1e-9 && (q2 - angles[1]).abs() < 1e-9);
+            assert!(arm.velocities().iter().all(|w| w.abs() < 1e-9));
+        }
+    }
+}
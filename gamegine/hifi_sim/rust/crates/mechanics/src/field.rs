@@ -0,0 +1,421 @@
+//! Field Contact
+//!
+//! Robots and loose game pieces sharing the floor. Each robot is a rectangular
+//! footprint moved kinematically by its field-frame velocity; each game piece
+//! is a round point mass that slides on the floor with Coulomb friction. When
+//! a piece overlaps a footprint, a spring-damper contact pushes it out along
+//! the nearest face, so driving into a piece shoves it ahead of the bumper.
+//! Robots are treated as far heavier than pieces and feel no reaction.
+//! Robots given a mass also collide with each other: when two footprints
+//! overlap while closing, they exchange an impulse along the contact normal.
+
+/// A loose game piece sliding on the floor
+#[derive(Debug, Clone, Copy)]
+pub struct GamePiece {
+    /// Mass (kg)
+    pub mass: f64,
+    /// Field position [x, y] (m)
+    pub pos: [f64; 2],
+    /// Field velocity [vx, vy] (m/s)
+    pub vel: [f64; 2],
+    /// Coefficient of friction against the floor
+    pub friction: f64,
+    /// Contact radius (m)
+    pub radius: f64,
+}
+
+impl GamePiece {
+    /// Piece at rest at `pos` with zero contact radius
+    pub fn new(mass: f64, pos: [f64; 2], friction: f64) -> Self {
+        GamePiece { mass, pos, vel: [0.0; 2], friction, radius: 0.0 }
+    }
+
+    /// Contact radius (m)
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius.max(0.0);
+        self
+    }
+
+    /// Speed over the floor (m/s)
+    pub fn speed(&self) -> f64 {
+        self.vel[0].hypot(self.vel[1])
+    }
+}
+
+/// Rectangular robot footprint (bumper outline) moving over the field
+#[derive(Debug, Clone, Copy)]
+pub struct RobotFootprint {
+    /// Half the bumper length along the robot's forward axis (m)
+    pub half_length: f64,
+    /// Half the bumper width along the robot's left axis (m)
+    pub half_width: f64,
+    /// Field pose [x, y, heading] (m, m, rad)
+    pub pose: [f64; 3],
+    /// Field-frame velocity [vx, vy, omega] (m/s, m/s, rad/s)
+    pub velocity: [f64; 3],
+    /// Mass for robot-robot collisions (kg); zero leaves the robot out of them
+    pub mass: f64,
+}
+
+impl RobotFootprint {
+    /// Footprint of `length` by `width` at rest at the origin, facing +x
+    pub fn new(length: f64, width: f64) -> Self {
+        RobotFootprint {
+            half_length: 0.5 * length,
+            half_width: 0.5 * width,
+            pose: [0.0; 3],
+            velocity: [0.0; 3],
+            mass: 0.0,
+        }
+    }
+
+    /// Mass for robot-robot collisions (kg)
+    pub fn with_mass(mut self, mass: f64) -> Self {
+        self.mass = mass.max(0.0);
+        self
+    }
+
+    /// Linear momentum [px, py] (kg*m/s)
+    pub fn momentum(&self) -> [f64; 2] {
+        [self.mass * self.velocity[0], self.mass * self.velocity[1]]
+    }
+
+    /// Field velocity of the footprint at field point `point`
+    pub fn point_velocity(&self, point: [f64; 2]) -> [f64; 2] {
+        let [vx, vy, omega] = self.velocity;
+        let (rx, ry) = (point[0] - self.pose[0], point[1] - self.pose[1]);
+        [vx - omega * ry, vy + omega * rx]
+    }
+
+    /// Outward contact normal and penetration depth of a circle of `radius`
+    /// centered at `point`, if it overlaps the footprint
+    pub fn penetration(&self, point: [f64; 2], radius: f64) -> Option<([f64; 2], f64)> {
+        let (sin, cos) = self.pose[2].sin_cos();
+        let (dx, dy) = (point[0] - self.pose[0], point[1] - self.pose[1]);
+        let (local_x, local_y) = (dx * cos + dy * sin, -dx * sin + dy * cos);
+
+        let depth_x = self.half_length + radius - local_x.abs();
+        let depth_y = self.half_width + radius - local_y.abs();
+        if depth_x <= 0.0 || depth_y <= 0.0 {
+            return None;
+        }
+        // Push out through the nearest face
+        let (local_normal, depth) = if depth_x < depth_y {
+            ([sign(local_x), 0.0], depth_x)
+        } else {
+            ([0.0, sign(local_y)], depth_y)
+        };
+        let normal = [
+            local_normal[0] * cos - local_normal[1] * sin,
+            local_normal[0] * sin + local_normal[1] * cos,
+        ];
+        Some((normal, depth))
+    }
+
+    /// Contact normal pointing from this footprint toward `other` and the
+    /// overlap depth, if the two footprints overlap
+    pub fn overlap(&self, other: &RobotFootprint) -> Option<([f64; 2], f64)> {
+        let offset = [other.pose[0] - self.pose[0], other.pose[1] - self.pose[1]];
+        let axes = |robot: &RobotFootprint| {
+            let (sin, cos) = robot.pose[2].sin_cos();
+            [[cos, sin], [-sin, cos]]
+        };
+        let (own_axes, other_axes) = (axes(self), axes(other));
+        // Half extent of a footprint projected onto `axis`
+        let extent = |robot: &RobotFootprint, robot_axes: &[[f64; 2]; 2], axis: [f64; 2]| {
+            robot.half_length * dot(robot_axes[0], axis).abs() + robot.half_width * dot(robot_axes[1], axis).abs()
+        };
+
+        // Separating axis test over both footprints' edge normals
+        let mut contact: Option<([f64; 2], f64)> = None;
+        for axis in own_axes.into_iter().chain(other_axes) {
+            let distance = dot(offset, axis);
+            let depth = extent(self, &own_axes, axis) + extent(other, &other_axes, axis) - distance.abs();
+            if depth <= 0.0 {
+                return None;
+            }
+            if contact.is_none_or(|(_, deepest)| depth < deepest) {
+                contact = Some(([sign(distance) * axis[0], sign(distance) * axis[1]], depth));
+            }
+        }
+        contact
+    }
+}
+
+fn dot(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+/// +1 or -1, treating zero as positive
+fn sign(x: f64) -> f64 {
+    if x < 0.0 { -1.0 } else { 1.0 }
+}
+
+/// One robot-robot collision resolved during a step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionReport {
+    /// Indices of the colliding robots
+    pub robots: [usize; 2],
+    /// Contact normal, pointing from the first robot toward the second
+    pub normal: [f64; 2],
+    /// Impulse the first robot gave the second along the normal (N*s)
+    pub impulse: f64,
+    /// Each robot's linear momentum before the collision (kg*m/s)
+    pub momentum_before: [[f64; 2]; 2],
+    /// Each robot's linear momentum after the collision (kg*m/s)
+    pub momentum_after: [[f64; 2]; 2],
+}
+
+/// Robots and game pieces on one field
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub robots: Vec<RobotFootprint>,
+    pub pieces: Vec<GamePiece>,
+    /// Contact spring stiffness (N/m)
+    pub contact_stiffness: f64,
+    /// Contact damping on the normal closing speed (N*s/m)
+    pub contact_damping: f64,
+    /// Gravitational acceleration (m/s^2)
+    pub gravity: f64,
+    /// Coefficient of restitution between robot bumpers
+    pub robot_restitution: f64,
+    /// Record the collisions resolved each step
+    pub report_collisions: bool,
+    collisions: Vec<CollisionReport>,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Field {
+            robots: Vec::new(),
+            pieces: Vec::new(),
+            contact_stiffness: 2.0e4,
+            contact_damping: 20.0,
+            gravity: 9.81,
+            robot_restitution: 0.2,
+            report_collisions: false,
+            collisions: Vec::new(),
+        }
+    }
+}
+
+impl Field {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Contact spring stiffness (N/m) and damping (N*s/m)
+    pub fn with_contact(mut self, stiffness: f64, damping: f64) -> Self {
+        self.contact_stiffness = stiffness;
+        self.contact_damping = damping;
+        self
+    }
+
+    /// Coefficient of restitution between robot bumpers
+    pub fn with_robot_restitution(mut self, restitution: f64) -> Self {
+        self.robot_restitution = restitution.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Record the collisions resolved each step
+    pub fn with_collision_reporting(mut self) -> Self {
+        self.report_collisions = true;
+        self
+    }
+
+    /// Robot-robot collisions resolved during the last step, if reporting
+    pub fn collisions(&self) -> Option<&[CollisionReport]> {
+        self.report_collisions.then_some(self.collisions.as_slice())
+    }
+
+    /// Add a robot and return its index
+    pub fn add_robot(&mut self, robot: RobotFootprint) -> usize {
+        self.robots.push(robot);
+        self.robots.len() - 1
+    }
+
+    /// Add a game piece and return its index
+    pub fn add_piece(&mut self, piece: GamePiece) -> usize {
+        self.pieces.push(piece);
+        self.pieces.len() - 1
+    }
+
+    /// Total contact force from all robots on each game piece (N)
+    pub fn contact_forces(&self) -> Vec<[f64; 2]> {
+        self.pieces
+            .iter()
+            .map(|piece| {
+                let mut force = [0.0; 2];
+                for robot in &self.robots {
+                    let Some((normal, depth)) = robot.penetration(piece.pos, piece.radius) else {
+                        continue;
+                    };
+                    let robot_vel = robot.point_velocity(piece.pos);
+                    let closing = (robot_vel[0] - piece.vel[0]) * normal[0] + (robot_vel[1] - piece.vel[1]) * normal[1];
+                    // Contacts push but never pull
+                    let magnitude = (self.contact_stiffness * depth + self.contact_damping * closing).max(0.0);
+                    force[0] += magnitude * normal[0];
+                    force[1] += magnitude * normal[1];
+                }
+                force
+            })
+            .collect()
+    }
+
+    /// Exchange impulses between overlapping robots that are still closing
+    ///
+    /// Only linear velocities change; the impulse along the contact normal
+    /// is `(1 + e) * closing_speed / (1/m1 + 1/m2)`.
+    pub fn resolve_robot_collisions(&mut self) {
+        self.collisions.clear();
+        for i in 0..self.robots.len() {
+            for j in i + 1..self.robots.len() {
+                let (a, b) = (self.robots[i], self.robots[j]);
+                if a.mass <= 0.0 || b.mass <= 0.0 {
+                    continue;
+                }
+                let Some((normal, _)) = a.overlap(&b) else {
+                    continue;
+                };
+                let closing = (a.velocity[0] - b.velocity[0]) * normal[0] + (a.velocity[1] - b.velocity[1]) * normal[1];
+                if closing <= 0.0 {
+                    continue;
+                }
+                let impulse = (1.0 + self.robot_restitution) * closing / (1.0 / a.mass + 1.0 / b.mass);
+                for (robot, share) in [(i, -impulse / a.mass), (j, impulse / b.mass)] {
+                    self.robots[robot].velocity[0] += share * normal[0];
+                    self.robots[robot].velocity[1] += share * normal[1];
+                }
+                if self.report_collisions {
+                    self.collisions.push(CollisionReport {
+                        robots: [i, j],
+                        normal,
+                        impulse,
+                        momentum_before: [a.momentum(), b.momentum()],
+                        momentum_after: [self.robots[i].momentum(), self.robots[j].momentum()],
+                    });
+                }
+            }
+        }
+    }
+
+    /// Advance the field by `dt` seconds
+    ///
+    /// Pieces take the contact force, then floor friction removes up to
+    /// `friction * g * dt` of speed without reversing them. Colliding robots
+    /// exchange impulses, then move along their velocities; overwrite their
+    /// poses directly to follow a drivetrain simulation instead.
+    pub fn step(&mut self, dt: f64) {
+        let forces = self.contact_forces();
+        for (piece, force) in self.pieces.iter_mut().zip(forces) {
+            if piece.mass <= 0.0 {
+                continue;
+            }
+            piece.vel[0] += force[0] / piece.mass * dt;
+            piece.vel[1] += force[1] / piece.mass * dt;
+
+            let speed = piece.speed();
+            let friction_drop = piece.friction * self.gravity * dt;
+            if speed <= friction_drop {
+                piece.vel = [0.0; 2];
+            } else {
+                let scale = (speed - friction_drop) / speed;
+                piece.vel[0] *= scale;
+                piece.vel[1] *= scale;
+            }
+            piece.pos[0] += piece.vel[0] * dt;
+            piece.pos[1] += piece.vel[1] * dt;
+        }
+
+        self.resolve_robot_collisions();
+        for robot in &mut self.robots {
+            robot.pose[0] += robot.velocity[0] * dt;
+            robot.pose[1] += robot.velocity[1] * dt;
+            robot.pose[2] += robot.velocity[2] * dt;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robot_pushes_piece_which_slides_to_a_stop() {
+        let mut field = Field::new();
+        let mut robot = RobotFootprint::new(0.8, 0.8);
+        robot.velocity = [1.0, 0.0, 0.0];
+        let robot_index = field.add_robot(robot);
+        let piece = field.add_piece(GamePiece::new(0.25, [0.8, 0.0], 0.5).with_radius(0.1));
+        let dt = 1e-3;
+
+        // Drive into the piece and carry it along the bumper
+        for _ in 0..1000 {
+            field.step(dt);
+        }
+        let pushed = field.pieces[piece];
+        assert!(pushed.pos[0] > 1.3, "piece only reached x = {}", pushed.pos[0]);
+        assert!((pushed.vel[0] - 1.0).abs() < 0.1, "piece moving at {} m/s", pushed.vel[0]);
+        assert!(pushed.pos[1].abs() < 1e-9 && pushed.vel[1].abs() < 1e-9);
+
+        // Stop the robot: the piece separates and friction brings it to rest
+        field.robots[robot_index].velocity = [0.0; 3];
+        let mut last_speed = f64::INFINITY;
+        for _ in 0..500 {
+            field.step(dt);
+            let speed = field.pieces[piece].speed();
+            assert!(speed <= last_speed + 1e-9, "piece sped up to {}", speed);
+            last_speed = speed;
+        }
+        let stopped = field.pieces[piece];
+        assert_eq!(stopped.speed(), 0.0);
+        assert!(stopped.pos[0] > pushed.pos[0]);
+        let front = field.robots[robot_index].pose[0] + 0.4;
+        assert!(stopped.pos[0] - stopped.radius > front, "piece still touching the bumper");
+    }
+
+    #[test]
+    fn test_head_on_collision_conserves_momentum() {
+        let mass = 50.0;
+        let mut field = Field::new().with_collision_reporting();
+        let mut left = RobotFootprint::new(0.8, 0.8).with_mass(mass);
+        left.pose = [-1.0, 0.0, 0.0];
+        left.velocity = [2.0, 0.0, 0.0];
+        let mut right = RobotFootprint::new(0.8, 0.8).with_mass(mass);
+        right.pose = [1.0, 0.0, 0.0];
+        right.velocity = [-2.0, 0.0, 0.0];
+        field.add_robot(left);
+        field.add_robot(right);
+
+        let mut reports = Vec::new();
+        for _ in 0..500 {
+            field.step(1e-3);
+            reports.extend_from_slice(field.collisions().unwrap());
+        }
+        assert_eq!(reports.len(), 1, "collisions: {:?}", reports);
+        let report = reports[0];
+        assert_eq!(report.robots, [0, 1]);
+        assert!((report.normal[0] - 1.0).abs() < 1e-12 && report.normal[1].abs() < 1e-12);
+
+        // Equal and opposite momenta before and after
+        for momenta in [report.momentum_before, report.momentum_after] {
+            assert!((momenta[0][0] + momenta[1][0]).abs() < 1e-9);
+            assert!((momenta[0][1] + momenta[1][1]).abs() < 1e-9);
+        }
+        // The impulse is each robot's change in momentum
+        let left_change = report.momentum_after[0][0] - report.momentum_before[0][0];
+        let right_change = report.momentum_after[1][0] - report.momentum_before[1][0];
+        assert!((report.impulse + left_change).abs() < 1e-9);
+        assert!((report.impulse - right_change).abs() < 1e-9);
+        assert!((report.impulse - mass * (2.0 + 2.0 * field.robot_restitution)).abs() < 1e-9);
+        assert!((field.robots[0].velocity[0] + 2.0 * field.robot_restitution).abs() < 1e-9);
+
+        // Massless robots never collide
+        let mut ghosts = Field::new().with_collision_reporting();
+        ghosts.add_robot(RobotFootprint::new(0.8, 0.8));
+        ghosts.add_robot(RobotFootprint::new(0.8, 0.8));
+        ghosts.step(1e-3);
+        assert!(ghosts.collisions().unwrap().is_empty());
+        assert!(Field::new().collisions().is_none());
+    }
+}
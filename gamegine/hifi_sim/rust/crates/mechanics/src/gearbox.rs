@@ -0,0 +1,91 @@
+//! Two-Speed Gearbox
+//!
+//! A shifting drive reduction with a high-torque low gear and a high-speed
+//! high gear. Shifting changes the reduction between motor and wheel; the
+//! wheel keeps its speed through the shift and the motor follows it.
+
+use thiserror::Error;
+
+/// Gear of a two-speed drive gearbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gear {
+    /// High-torque, low-speed reduction
+    Low,
+    /// High-speed, low-torque reduction
+    High,
+}
+
+impl Gear {
+    /// Lowercase name, as accepted by `parse`
+    pub fn name(self) -> &'static str {
+        match self {
+            Gear::Low => "low",
+            Gear::High => "high",
+        }
+    }
+
+    /// Parse a gear name ("low" or "high")
+    pub fn parse(name: &str) -> Option<Self> {
+        [Gear::Low, Gear::High].into_iter().find(|gear| gear.name() == name)
+    }
+}
+
+/// Reasons a pair of gearbox reductions is rejected
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum GearboxError {
+    #[error("gear ratios must be positive, got {0}")]
+    NonPositiveRatio(f64),
+    #[error("high gear ratio {high} must be lower than the low gear ratio {low}")]
+    HighNotBelowLow { low: f64, high: f64 },
+}
+
+/// Low and high gear reductions (motor turns per wheel turn)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwoSpeedGearbox {
+    low_ratio: f64,
+    high_ratio: f64,
+}
+
+impl TwoSpeedGearbox {
+    /// Gearbox with the given reductions; high gear must reduce less than low gear
+    pub fn new(low_ratio: f64, high_ratio: f64) -> Result<Self, GearboxError> {
+        for ratio in [low_ratio, high_ratio] {
+            if !(ratio.is_finite() && ratio > 0.0) {
+                return Err(GearboxError::NonPositiveRatio(ratio));
+            }
+        }
+        if high_ratio >= low_ratio {
+            return Err(GearboxError::HighNotBelowLow { low: low_ratio, high: high_ratio });
+        }
+        Ok(TwoSpeedGearbox { low_ratio, high_ratio })
+    }
+
+    /// Reduction in `gear`
+    pub fn ratio(&self, gear: Gear) -> f64 {
+        match gear {
+            Gear::Low => self.low_ratio,
+            Gear::High => self.high_ratio,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gearbox_rejects_bad_ratios() {
+        let gearbox = TwoSpeedGearbox::new(10.0, 5.0).unwrap();
+        assert_eq!(gearbox.ratio(Gear::Low), 10.0);
+        assert_eq!(gearbox.ratio(Gear::High), 5.0);
+
+        assert_eq!(TwoSpeedGearbox::new(0.0, 5.0), Err(GearboxError::NonPositiveRatio(0.0)));
+        assert_eq!(TwoSpeedGearbox::new(10.0, -5.0), Err(GearboxError::NonPositiveRatio(-5.0)));
+        assert!(TwoSpeedGearbox::new(f64::NAN, 5.0).is_err());
+        assert_eq!(TwoSpeedGearbox::new(5.0, 5.0), Err(GearboxError::HighNotBelowLow { low: 5.0, high: 5.0 }));
+        assert!(TwoSpeedGearbox::new(5.0, 10.0).is_err());
+
+        assert_eq!(Gear::parse(Gear::High.name()), Some(Gear::High));
+        assert_eq!(Gear::parse("neutral"), None);
+    }
+}
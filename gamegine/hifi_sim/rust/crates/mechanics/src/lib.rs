@@ -1,6 +1,14 @@
 pub mod tire;
 pub mod link;
 pub mod swerve;
+pub mod mecanum;
+pub mod arm;
+pub mod field;
+pub mod gearbox;
 
-pub use swerve::{SwerveDrivetrain, SwerveDrivetrainConfig};
+pub use swerve::{PowerFlow, SwerveDrivetrain, SwerveDrivetrainConfig};
+pub use mecanum::{ground_wheel_speed, MecanumKinematics, MecanumOdometry};
+pub use arm::{ArmLink, DoubleJointedArm};
+pub use field::{CollisionReport, Field, GamePiece, RobotFootprint};
+pub use gearbox::{Gear, GearboxError, TwoSpeedGearbox};
 pub use link::{MechanicalLink, LinkConfig, FrictionModel, RotatingBody, LinkStepResult};
\ No newline at end of file
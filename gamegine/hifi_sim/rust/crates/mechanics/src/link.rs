@@ -5,6 +5,7 @@
 //! - Efficiency losses in power transfer
 //! - Friction modeling (Coulomb, viscous, combined)
 //! - Reflected inertia calculations
+//! - Self-locking (non-back-drivable) gearing such as worm drives
 
 use serde::{Deserialize, Serialize};
 
@@ -126,6 +127,12 @@ pub struct LinkConfig {
     
     /// Friction model
     pub friction: FrictionModel,
+
+    /// Largest load-side torque/force an unpowered, resting link holds
+    /// without back-driving (None = freely back-drivable, infinity = fully
+    /// self-locking)
+    #[serde(default)]
+    pub backdrive_threshold: Option<f64>,
 }
 
 impl Default for LinkConfig {
@@ -136,6 +143,7 @@ impl Default for LinkConfig {
             efficiency: 1.0,
             load_inertia: 1.0,
             friction: FrictionModel::None,
+            backdrive_threshold: None,
         }
     }
 }
@@ -175,6 +183,14 @@ impl LinkConfig {
         self.friction = friction;
         self
     }
+
+    /// Hold external loads up to `threshold` while unpowered and at rest
+    /// (worm gears, high reductions); use f64::INFINITY for a fully
+    /// self-locking link
+    pub fn with_backdrive_threshold(mut self, threshold: f64) -> Self {
+        self.backdrive_threshold = Some(threshold.abs());
+        self
+    }
 }
 
 /// Represents a rotating body that can be connected via a link
@@ -321,6 +337,22 @@ impl MechanicalLink {
         self.config.friction.compute(velocity_b)
     }
     
+    /// External load on B after a self-locking link has resisted it
+    ///
+    /// A resting link with no motor torque holds loads up to the back-drive
+    /// threshold; larger loads, a moving load or a powered motor pass the
+    /// load through unchanged.
+    pub fn unlocked_external_force(&self, motor_torque: f64, velocity_b: f64, external_force_b: f64) -> f64 {
+        const STICTION_THRESHOLD: f64 = 0.001;
+        match self.config.backdrive_threshold {
+            Some(threshold)
+                if motor_torque == 0.0
+                    && velocity_b.abs() < STICTION_THRESHOLD
+                    && external_force_b.abs() <= threshold => 0.0,
+            _ => external_force_b,
+        }
+    }
+
     // === Inertia ===
     
     /// Reflect inertia from A to B's frame
@@ -375,7 +407,8 @@ impl MechanicalLink {
         let torque_from_a = self.torque_a_to_b(body_a.torque);
         let torque_from_b = body_b.torque;
         let friction = self.compute_friction(body_b.velocity);
-        
+        let external_force_b = self.unlocked_external_force(body_a.torque, body_b.velocity, external_force_b);
+
         let net_torque_b = torque_from_a + torque_from_b + friction + external_force_b;
         
         // Total inertia in B's frame
@@ -409,6 +442,7 @@ impl MechanicalLink {
     ) -> (f64, f64) {
         let drive_force = self.motor_to_load_force(motor_torque);
         let friction_force = self.compute_friction(load_velocity);
+        let external_force = self.unlocked_external_force(motor_torque, load_velocity, external_force);
         let net_force = drive_force + friction_force + external_force;
         
         let total_inertia = self.total_effective_inertia(motor_inertia);
@@ -416,6 +450,18 @@ impl MechanicalLink {
         
         (acceleration, net_force)
     }
+
+    /// Motor torque that holds the load at rest against `external_force`
+    ///
+    /// Friction vanishes in the stiction zone, so at zero velocity the drive
+    /// force alone must cancel the external load.
+    pub fn holding_motor_torque(&self, external_force: f64) -> f64 {
+        let force_per_motor_torque = self.torque_a_to_b(1.0);
+        if force_per_motor_torque == 0.0 {
+            return 0.0;
+        }
+        -external_force / force_per_motor_torque
+    }
 }
 
 #[cfg(test)]
@@ -561,4 +607,52 @@ mod tests {
         // total = 0.5 + 0.00001 = 0.50001 kg·m²
         assert!((link.total_effective_inertia(0.001) - 0.50001).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_holding_torque_keeps_elevator_still() {
+        let link = MechanicalLink::new(LinkConfig {
+            gear_ratio: 12.0,
+            radius: 0.02,
+            efficiency: 0.9,
+            load_inertia: 8.0,
+            friction: FrictionModel::Viscous { damping: 5.0 },
+            ..Default::default()
+        });
+        let gravity = -8.0 * 9.81;
+        let torque = link.holding_motor_torque(gravity);
+        assert!(torque > 0.0);
+
+        let (mut velocity, dt) = (0.0, 0.001);
+        for _ in 0..1000 {
+            let (acceleration, _) = link.compute_load_acceleration(torque, 0.0001, velocity, gravity);
+            velocity += acceleration * dt;
+        }
+        assert!(velocity.abs() < 1e-9, "elevator drifted at {} m/s", velocity);
+    }
+
+    #[test]
+    fn test_self_locking_link_holds_gravity_load_unpowered() {
+        let config = LinkConfig::new()
+            .with_gear_ratio(60.0)
+            .with_radius(0.02)
+            .with_load_inertia(5.0);
+        let gravity = -5.0 * 9.81;
+        let drop = |link: MechanicalLink| {
+            let (mut velocity, mut position, dt) = (0.0, 0.0, 0.001);
+            for _ in 0..500 {
+                let (acceleration, _) = link.compute_load_acceleration(0.0, 0.0001, velocity, gravity);
+                velocity += acceleration * dt;
+                position += velocity * dt;
+            }
+            position
+        };
+
+        let free = drop(MechanicalLink::new(config.clone()));
+        let worm = drop(MechanicalLink::new(config.clone().with_backdrive_threshold(100.0)));
+        let overloaded = drop(MechanicalLink::new(config.with_backdrive_threshold(20.0)));
+
+        assert!(free < -0.01, "free link only dropped {} m", free);
+        assert_eq!(worm, 0.0);
+        assert!(overloaded < 0.0, "a load above the threshold still back-drives");
+    }
 }
@@ -0,0 +1,238 @@
+//! Mecanum Drive Kinematics and Odometry
+//!
+//! Converts between robot-relative chassis velocity and the four wheel speeds
+//! of an X-configured mecanum drive, and integrates wheel-speed odometry.
+//!
+//! Odometry is kept separate from any dynamics: it only sees wheel speeds, so
+//! feeding it the wheel speeds of a slipping robot shows how far the estimated
+//! pose drifts from the true one. Wheel order matches the swerve drivetrain:
+//! front left, front right, back left, back right.
+//!
+//! When the tire slip ratios are known, odometry can optionally correct each
+//! wheel's surface speed to the ground speed it actually covers.
+
+use simcore::integrate_twist;
+
+/// Geometry of a mecanum drive with rollers at 45 degrees
+#[derive(Debug, Clone, Copy)]
+pub struct MecanumKinematics {
+    /// Wheel radius in meters.
+    pub wheel_radius: f64,
+    /// Distance from the center to the front/back axles in meters.
+    pub half_wheelbase: f64,
+    /// Distance from the center to the left/right wheels in meters.
+    pub half_track_width: f64,
+}
+
+impl Default for MecanumKinematics {
+    fn default() -> Self {
+        MecanumKinematics {
+            wheel_radius: 0.0508, // 4 inch wheels
+            half_wheelbase: 0.25,
+            half_track_width: 0.25,
+        }
+    }
+}
+
+impl MecanumKinematics {
+    fn lever_arm(&self) -> f64 {
+        self.half_wheelbase + self.half_track_width
+    }
+
+    /// Wheel angular velocities (rad/s) for a robot-relative chassis velocity
+    ///
+    /// `vx` is forward, `vy` leftward (m/s) and `omega` counter-clockwise (rad/s).
+    pub fn to_wheel_speeds(&self, vx: f64, vy: f64, omega: f64) -> [f64; 4] {
+        let spin = self.lever_arm() * omega;
+        let r = self.wheel_radius;
+        [
+            (vx - vy - spin) / r, // Front Left
+            (vx + vy + spin) / r, // Front Right
+            (vx + vy - spin) / r, // Back Left
+            (vx - vy + spin) / r, // Back Right
+        ]
+    }
+
+    /// Robot-relative chassis velocity `[vx, vy, omega]` from wheel angular velocities
+    ///
+    /// This is the least-squares inverse of `to_wheel_speeds`; inconsistent
+    /// wheel speeds (e.g. one wheel slipping) are averaged rather than rejected.
+    pub fn to_chassis_speeds(&self, wheel_speeds: [f64; 4]) -> [f64; 3] {
+        let [fl, fr, bl, br] = wheel_speeds;
+        let r = self.wheel_radius;
+        [
+            r * (fl + fr + bl + br) / 4.0,
+            r * (-fl + fr + bl - br) / 4.0,
+            r * (-fl + fr - bl + br) / (4.0 * self.lever_arm()),
+        ]
+    }
+}
+
+/// Dead-reckoning pose estimate from mecanum wheel speeds
+#[derive(Debug, Clone)]
+pub struct MecanumOdometry {
+    pub kinematics: MecanumKinematics,
+    /// Divide wheel surface speed by (1 + slip ratio) in `update_with_slip`.
+    pub slip_compensation: bool,
+    /// Estimated field pose [x, y, heading] (m, m, rad).
+    pose: [f64; 3],
+}
+
+impl MecanumOdometry {
+    /// Start odometry at the given field pose [x, y, heading]
+    pub fn new(kinematics: MecanumKinematics, initial_pose: [f64; 3]) -> Self {
+        MecanumOdometry { kinematics, slip_compensation: false, pose: initial_pose }
+    }
+
+    /// Correct wheel speeds for tire slip when slip ratios are supplied
+    pub fn with_slip_compensation(mut self, enabled: bool) -> Self {
+        self.slip_compensation = enabled;
+        self
+    }
+
+    /// Estimated field pose [x, y, heading]
+    pub fn pose(&self) -> [f64; 3] {
+        self.pose
+    }
+
+    /// Overwrite the pose estimate, e.g. after a vision correction
+    pub fn reset_pose(&mut self, pose: [f64; 3]) {
+        self.pose = pose;
+    }
+
+    /// Integrate one step of measured wheel speeds (rad/s) and return the new pose
+    ///
+    /// The robot is taken to follow a constant-curvature arc over the step,
+    /// which is exact for constant chassis speeds.
+    pub fn update(&mut self, wheel_speeds: [f64; 4], dt: f64) -> [f64; 3] {
+        let [vx, vy, omega] = self.kinematics.to_chassis_speeds(wheel_speeds);
+        self.pose = integrate_twist(self.pose, [vx * dt, vy * dt, omega * dt]);
+        self.pose
+    }
+
+    /// Integrate one step of wheel speeds (rad/s) alongside each tire's slip ratio
+    ///
+    /// With slip compensation enabled each wheel contributes its ground speed,
+    /// surface speed / (1 + slip ratio), so a wheel spinning up under
+    /// acceleration is not counted as distance travelled. Otherwise the slip
+    /// ratios are ignored and this matches `update`.
+    pub fn update_with_slip(&mut self, wheel_speeds: [f64; 4], slip_ratios: [f64; 4], dt: f64) -> [f64; 3] {
+        if !self.slip_compensation {
+            return self.update(wheel_speeds, dt);
+        }
+        let mut ground_speeds = wheel_speeds;
+        for (speed, slip) in ground_speeds.iter_mut().zip(slip_ratios) {
+            *speed = ground_wheel_speed(*speed, slip);
+        }
+        self.update(ground_speeds, dt)
+    }
+}
+
+/// Ground speed covered by a wheel whose surface moves at `wheel_speed` with
+/// the given longitudinal slip ratio
+///
+/// A slip ratio at or below -1 (a locked wheel sliding) carries no
+/// information about ground speed, so the wheel speed is returned unchanged.
+pub fn ground_wheel_speed(wheel_speed: f64, slip_ratio: f64) -> f64 {
+    let scale = 1.0 + slip_ratio;
+    if scale <= 1e-6 {
+        wheel_speed
+    } else {
+        wheel_speed / scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Integrate a constant command for one second, optionally overspinning the
+    /// front-left wheel, and return (true pose, odometry pose)
+    fn drive(vx: f64, vy: f64, omega: f64, front_left_slip: f64) -> ([f64; 3], [f64; 3]) {
+        let kinematics = MecanumKinematics::default();
+        let mut odometry = MecanumOdometry::new(kinematics, [0.0; 3]);
+        let dt = 0.001;
+
+        for _ in 0..1000 {
+            let mut wheels = kinematics.to_wheel_speeds(vx, vy, omega);
+            wheels[0] *= 1.0 + front_left_slip;
+            odometry.update(wheels, dt);
+        }
+        (arc_pose(vx, vy, omega, 1.0), odometry.pose())
+    }
+
+    /// Closed-form pose after holding robot-frame speeds for `t` seconds from
+    /// the origin: the field velocity rotates with the heading omega * t
+    fn arc_pose(vx: f64, vy: f64, omega: f64, t: f64) -> [f64; 3] {
+        if omega == 0.0 {
+            return [vx * t, vy * t, 0.0];
+        }
+        let (sin, cos) = (omega * t).sin_cos();
+        [
+            (vx * sin - vy * (1.0 - cos)) / omega,
+            (vx * (1.0 - cos) + vy * sin) / omega,
+            omega * t,
+        ]
+    }
+
+    fn pose_error(a: [f64; 3], b: [f64; 3]) -> f64 {
+        (a[0] - b[0]).hypot(a[1] - b[1])
+    }
+
+    #[test]
+    fn test_round_trip_kinematics() {
+        let kinematics = MecanumKinematics::default();
+        let speeds = kinematics.to_chassis_speeds(kinematics.to_wheel_speeds(1.2, -0.7, 2.5));
+        assert!((speeds[0] - 1.2).abs() < 1e-12);
+        assert!((speeds[1] + 0.7).abs() < 1e-12);
+        assert!((speeds[2] - 2.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_odometry_recovers_commanded_motion_without_slip() {
+        // Straight, strafe, rotate, and all three at once
+        for &(vx, vy, omega) in &[(2.0, 0.0, 0.0), (0.0, 1.5, 0.0), (0.0, 0.0, 3.0), (1.0, 0.5, 1.0)] {
+            let (truth, estimate) = drive(vx, vy, omega, 0.0);
+            assert!(pose_error(truth, estimate) < 1e-9, "({}, {}, {}) drifted", vx, vy, omega);
+            assert!((truth[2] - estimate[2]).abs() < 1e-9);
+        }
+        let (_, straight) = drive(2.0, 0.0, 0.0, 0.0);
+        assert!((straight[0] - 2.0).abs() < 1e-9 && straight[1].abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_odometry_diverges_under_wheel_slip() {
+        // Front left wheel spins 20% faster than the ground it covers
+        let (truth, estimate) = drive(2.0, 0.0, 0.0, 0.2);
+        assert!(pose_error(truth, estimate) > 0.05, "error {}", pose_error(truth, estimate));
+        assert!(estimate[2].abs() > 0.1, "slip should corrupt the heading estimate");
+    }
+
+    #[test]
+    fn test_slip_compensation_reduces_odometry_error() {
+        let kinematics = MecanumKinematics::default();
+        let mut naive = MecanumOdometry::new(kinematics, [0.0; 3]);
+        let mut compensated = MecanumOdometry::new(kinematics, [0.0; 3]).with_slip_compensation(true);
+        let dt = 0.001;
+
+        // Accelerating from rest with all wheels slipping, most at the front
+        let slip_ratios = [0.25, 0.25, 0.1, 0.1];
+        let mut truth = [0.0; 3];
+        for i in 0..1000 {
+            let vx = 3.0 * i as f64 * dt;
+            let mut wheels = kinematics.to_wheel_speeds(vx, 0.0, 0.0);
+            for (speed, slip) in wheels.iter_mut().zip(slip_ratios) {
+                *speed *= 1.0 + slip;
+            }
+            naive.update_with_slip(wheels, slip_ratios, dt);
+            compensated.update_with_slip(wheels, slip_ratios, dt);
+            truth[0] += vx * dt;
+        }
+
+        let naive_error = pose_error(truth, naive.pose());
+        let compensated_error = pose_error(truth, compensated.pose());
+        assert!(naive_error > 0.1, "naive error {}", naive_error);
+        assert!(compensated_error < 1e-9, "compensated error {}", compensated_error);
+        assert_eq!(ground_wheel_speed(10.0, -1.0), 10.0);
+    }
+}
@@ -1,19 +1,165 @@
 use nalgebra::{Matrix2, Vector2};
-use simcore::{MechanicsModel, Model, SimContext, SimState, WheelState};
+use crate::gearbox::{Gear, TwoSpeedGearbox};
+use simcore::{BridgeMode, IntegratorKind, MechanicsModel, Model, NeutralMode, SimContext, SimState, VelocityFrame, WheelState};
+use std::f64::consts::PI;
+
+/// Gravitational acceleration used for tire loads (m/s^2)
+const GRAVITY: f64 = 9.81;
+
+/// Contact patch yaw rate (rad/s) above which the scrub torque is fully
+/// developed; below it the torque fades linearly so a still patch feels none
+const SCRUB_SATURATION_RATE: f64 = 0.2;
 
 /// Represents the physical configuration and properties of a swerve drivetrain.
 #[derive(Debug, Clone)]
 pub struct SwerveDrivetrainConfig {
-    /// Positions of each module relative to the robot's center of mass [x, y] in meters.
+    /// Positions of each module relative to the robot's geometric center [x, y] in meters.
     pub module_positions: Vec<[f64; 2]>,
+    /// Position of the center of mass relative to the geometric center
+    /// [x, y] in meters. The body rotates about the center of mass, so module
+    /// moment arms and static tire loads are measured from it. Zero by default.
+    pub com_offset: [f64; 2],
     /// Total mass of the robot in kg.
     pub mass: f64,
     /// Moment of inertia about the vertical axis (yaw) in kg*m^2.
     pub moment_of_inertia: f64,
     /// Rotational inertia of a single wheel about its axle in kg*m^2.
     pub wheel_inertia: f64,
+    /// Viscous damping on wheel rotation in N*m*s/rad (bearing and gearbox
+    /// drag). Bounds the spin of a wheel with no tire force acting on it.
+    pub wheel_viscous_damping: f64,
     /// Rotational inertia of a single steering mechanism in kg*m^2.
     pub steer_inertia: f64,
+    /// Maximum steering angular velocity in rad/s (physical steer motor speed cap).
+    pub max_steer_rate: f64,
+    /// Torsional stiffness between the module frame and the body in N*m/rad.
+    /// Infinite (the default) models a rigid chassis.
+    pub chassis_torsional_stiffness: f64,
+    /// Torsional damping between the module frame and the body in N*m*s/rad.
+    pub chassis_torsional_damping: f64,
+    /// Yaw inertia of the module frame that twists against the body in kg*m^2.
+    /// Only used when the chassis is compliant.
+    pub chassis_frame_inertia: f64,
+    /// Drive reduction from each motor to its wheel (motor turns per wheel
+    /// turn): the wheel sees the motor torque times this ratio. 1.0 (the
+    /// default) drives the wheels directly. A fitted two-speed gearbox sets
+    /// it to the active gear's reduction.
+    pub drive_gear_ratio: f64,
+    /// Per-module drive inversion for mirrored modules. An inverted module
+    /// spins its wheel backward for positive motor torque. Modules without an
+    /// entry are not inverted.
+    pub drive_inverted: Vec<bool>,
+    /// Largest change in yaw rate allowed in one step, in rad/s. A numerical
+    /// safeguard against stiff tires or chassis at large dt, not physical
+    /// damping. Infinite (the default) disables the clamp.
+    pub max_yaw_rate_step: f64,
+    /// Method used to integrate body velocity and pose from the net force and
+    /// torque, which are held constant over each step.
+    pub integrator: IntegratorKind,
+    /// Contact-point speed in m/s below which the tire forces blend toward a
+    /// quasi-static rolling model, fully at rest. The tires zero their slip
+    /// near standstill, so without this a slowly drifting robot never stops.
+    /// Zero (the default) disables creep mode.
+    pub creep_speed: f64,
+    /// Track the mechanical power delivered to the chassis and the electrical
+    /// power drawn each step, read back through `power_flow()`. Off by default.
+    pub report_power: bool,
+    /// Bridge state of a drive motor applying no torque. In brake mode the
+    /// shorted motor resists wheel spin through `brake_damping`.
+    pub neutral_mode: NeutralMode,
+    /// Wheel-side braking of a shorted drive motor in N*m*s/rad, about
+    /// `kt * ke * gear_ratio^2 / R` for the motor and gearing.
+    pub brake_damping: f64,
+    /// Torsional stiffness of the belt or chain between each drive motor and
+    /// its wheel in N*m/rad, at the wheel. Infinite (the default) models a
+    /// rigid coupling; finite values let the drivetrain wind up.
+    pub drive_coupling_stiffness: f64,
+    /// Torsional damping of the drive coupling in N*m*s/rad.
+    pub drive_coupling_damping: f64,
+    /// Inertia on the motor side of the drive coupling (rotor and gearing,
+    /// reflected to the wheel) in kg*m^2. Only used when the coupling is
+    /// compliant.
+    pub drive_motor_inertia: f64,
+    /// Height of the center of mass above the floor in meters. When positive,
+    /// the drivetrain rewrites the tire loads each step, shifting weight
+    /// against the body acceleration. Zero (the default) leaves them alone.
+    pub cg_height: f64,
+    /// Time constant of the load transfer in seconds. Compliant wheels and
+    /// chassis let the weight shift gradually rather than instantly. Zero
+    /// (the default) transfers load instantaneously.
+    pub load_transfer_time_constant: f64,
+    /// Effective lever arm of contact patch friction against the patch
+    /// yawing on the floor, in meters: each tire resists yaw with a scrub
+    /// torque of its load times this radius. Models the energy a swerve loses
+    /// grinding its patches while rotating in place. Zero (the default)
+    /// disables scrub.
+    ///
+    /// This is a lumped model kept apart from the tire slip model, whose
+    /// forces only see the patch translating: a patch can scrub while rolling
+    /// without slip. The chassis yaw rate scrubs against the chassis and the
+    /// module's steer rate against its steer axis, so steering alone never
+    /// yaws the robot.
+    pub scrub_radius: f64,
+}
+
+impl SwerveDrivetrainConfig {
+    /// Sign applied to the motor torque of a module: 1.0, or -1.0 if inverted
+    pub fn drive_direction(&self, module: usize) -> f64 {
+        if self.drive_inverted.get(module).copied().unwrap_or(false) {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Position of module `index` relative to the center of mass
+    pub fn module_arm(&self, index: usize) -> [f64; 2] {
+        let [x, y] = self.module_positions[index];
+        [x - self.com_offset[0], y - self.com_offset[1]]
+    }
+
+    /// Static load on each tire (N) of the robot at rest on level ground
+    ///
+    /// Loads sum to the weight and balance its moment about the center of
+    /// mass. With more than three modules the split is statically
+    /// indeterminate; this takes the one varying linearly across the module
+    /// layout, which for a rectangle is the usual front/rear, left/right
+    /// weight transfer. Modules in a line fall back to an even split.
+    pub fn static_wheel_loads(&self, gravity: f64) -> Vec<f64> {
+        self.wheel_loads(gravity, [0.0, 0.0])
+    }
+
+    /// Load on each tire (N) while the body accelerates at `acceleration`
+    /// [ax, ay] (m/s^2, body frame)
+    ///
+    /// The inertial force acting at `cg_height` moves the loads as if the
+    /// center of mass sat `cg_height * a / g` behind the acceleration; the
+    /// split is otherwise that of `static_wheel_loads`.
+    pub fn wheel_loads(&self, gravity: f64, acceleration: [f64; 2]) -> Vec<f64> {
+        let n = self.module_positions.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let weight = self.mass * gravity;
+        let even = weight / n as f64;
+        let centroid = self.module_positions.iter()
+            .fold(Vector2::zeros(), |sum, &[x, y]| sum + Vector2::new(x, y)) / n as f64;
+        let spread = self.module_positions.iter().fold(Matrix2::zeros(), |sum, &[x, y]| {
+            let d = Vector2::new(x, y) - centroid;
+            sum + d * d.transpose()
+        });
+        let shift = if gravity > 0.0 { self.cg_height / gravity } else { 0.0 };
+        let com = Vector2::new(
+            self.com_offset[0] - shift * acceleration[0],
+            self.com_offset[1] - shift * acceleration[1],
+        );
+        let Some(slope) = spread.try_inverse().map(|inverse| inverse * (com - centroid) * weight) else {
+            return vec![even; n];
+        };
+        self.module_positions.iter()
+            .map(|&[x, y]| even + slope.dot(&(Vector2::new(x, y) - centroid)))
+            .collect()
+    }
 }
 
 impl Default for SwerveDrivetrainConfig {
@@ -30,24 +176,230 @@ impl Default for SwerveDrivetrainConfig {
             mass: 50.0,             // 50 kg robot
             moment_of_inertia: 5.0, // Approximate for a solid rectangular robot
             wheel_inertia: 0.01,    // Small wheel inertia
+            wheel_viscous_damping: 1e-3, // Light bearing drag
             steer_inertia: 0.005,   // Steering mechanism inertia
+            max_steer_rate: f64::INFINITY, // Unlimited steer slew
+            chassis_torsional_stiffness: f64::INFINITY, // Rigid chassis
+            chassis_torsional_damping: 0.0,
+            chassis_frame_inertia: 0.5,
+            com_offset: [0.0, 0.0],
+            drive_gear_ratio: 1.0,
+            drive_inverted: Vec::new(),
+            max_yaw_rate_step: f64::INFINITY,
+            integrator: IntegratorKind::default(),
+            creep_speed: 0.0,
+            report_power: false,
+            neutral_mode: NeutralMode::Coast,
+            brake_damping: 0.5,     // Kraken X60 through a 6.75:1 reduction
+            drive_coupling_stiffness: f64::INFINITY, // Rigid drive coupling
+            drive_coupling_damping: 0.0,
+            drive_motor_inertia: 0.005,
+            cg_height: 0.0,
+            load_transfer_time_constant: 0.0,
+            scrub_radius: 0.0,
         }
     }
 }
 
+/// Power flowing through the drivetrain during one step
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PowerFlow {
+    /// Power delivered to the chassis, `F·v + Mz·ω` (W)
+    pub mechanical: f64,
+    /// Power drawn from the battery, voltage times total current (W)
+    pub electrical: f64,
+}
+
+impl PowerFlow {
+    /// Mechanical power out per electrical power in, or None with no input
+    pub fn efficiency(&self) -> Option<f64> {
+        (self.electrical > 0.0).then(|| self.mechanical / self.electrical)
+    }
+}
+
 /// The swerve drivetrain model that integrates tire forces and motor torques
 /// into overall robot body dynamics.
+///
+/// Sign convention: positive motor torque spins the wheel forward (positive
+/// `driving_angular_velocity`) unless the module is inverted. The tire's
+/// `longitudinal_force` follows the tire model's braking convention: it is the
+/// force on the wheel at the contact patch, negative while the wheel drives
+/// forward. Its reaction torque `longitudinal_force * wheel_radius` therefore
+/// opposes spin-up, and the chassis is pushed by `-longitudinal_force`.
 #[derive(Debug, Clone)]
 pub struct SwerveDrivetrain {
     pub config: SwerveDrivetrainConfig,
+    /// Yaw twist of the module frame relative to the body (rad)
+    twist_angle: f64,
+    /// Yaw twist rate of the module frame relative to the body (rad/s)
+    twist_rate: f64,
+    /// Number of steps in which the yaw rate clamp engaged since the last reset
+    yaw_clamp_count: u64,
+    /// Power flow of the last step, when `report_power` is set
+    power_flow: Option<PowerFlow>,
+    /// Wind-up angle (rad) and motor-side velocity (rad/s) of each
+    /// compliant drive coupling
+    drive_windup: Vec<[f64; 2]>,
+    /// Power each contact patch lost to chassis and steer scrub in the last step (W)
+    scrub_power: Vec<f64>,
+    /// Two-speed drive gearbox, if fitted
+    gearbox: Option<TwoSpeedGearbox>,
+    gear: Gear,
 }
 
 impl SwerveDrivetrain {
     pub fn new(config: SwerveDrivetrainConfig) -> Self {
-        SwerveDrivetrain { config }
+        SwerveDrivetrain {
+            config,
+            twist_angle: 0.0,
+            twist_rate: 0.0,
+            yaw_clamp_count: 0,
+            power_flow: None,
+            drive_windup: Vec::new(),
+            scrub_power: Vec::new(),
+            gearbox: None,
+            gear: Gear::Low,
+        }
     }
 
-    /// Calculate the velocity of a wheel module in the robot frame given body velocity.
+    /// Fit a two-speed drive gearbox, starting in low gear (builder pattern)
+    pub fn with_two_speed_gearbox(mut self, gearbox: TwoSpeedGearbox) -> Self {
+        self.set_two_speed_gearbox(gearbox);
+        self
+    }
+
+    /// Fit a two-speed drive gearbox and shift into low gear
+    pub fn set_two_speed_gearbox(&mut self, gearbox: TwoSpeedGearbox) {
+        self.gearbox = Some(gearbox);
+        self.shift(Gear::Low);
+    }
+
+    /// Shift every module's drive gearbox into `gear`
+    ///
+    /// The wheels keep their speed through the shift; the motors, geared to
+    /// them, take the new ratio's speed. Ignored without a two-speed gearbox.
+    pub fn shift(&mut self, gear: Gear) {
+        if let Some(gearbox) = self.gearbox {
+            self.gear = gear;
+            self.config.drive_gear_ratio = gearbox.ratio(gear);
+        }
+    }
+
+    /// Active gear (always `Gear::Low` without a two-speed gearbox)
+    pub fn gear(&self) -> Gear {
+        self.gear
+    }
+
+    /// Fitted two-speed gearbox, if any
+    pub fn two_speed_gearbox(&self) -> Option<TwoSpeedGearbox> {
+        self.gearbox
+    }
+
+    /// Current chassis twist angle between module frame and body (rad)
+    pub fn twist_angle(&self) -> f64 {
+        self.twist_angle
+    }
+
+    /// Number of steps in which `max_yaw_rate_step` limited the yaw rate
+    pub fn yaw_clamp_count(&self) -> u64 {
+        self.yaw_clamp_count
+    }
+
+    /// Wind-up angle of module `index`'s drive coupling (rad), zero when rigid
+    pub fn drive_windup(&self, index: usize) -> f64 {
+        self.drive_windup.get(index).map_or(0.0, |windup| windup[0])
+    }
+
+    /// Power flow of the last step; None unless `report_power` is set
+    pub fn power_flow(&self) -> Option<PowerFlow> {
+        self.power_flow
+    }
+
+    /// Power each contact patch lost to chassis and steer scrub in the last
+    /// step (W); None unless `scrub_radius` is set
+    pub fn scrub_power(&self) -> Option<&[f64]> {
+        (self.config.scrub_radius > 0.0).then_some(self.scrub_power.as_slice())
+    }
+
+    /// Scrub torque (N*m) on a patch carrying `load` (N) yawing at `patch_rate` (rad/s)
+    fn scrub_torque(&self, load: f64, patch_rate: f64) -> f64 {
+        -load * self.config.scrub_radius * (patch_rate / SCRUB_SATURATION_RATE).clamp(-1.0, 1.0)
+    }
+
+    /// Limit a per-step yaw rate change to `max_yaw_rate_step`, returning
+    /// whether the clamp engaged.
+    fn clamp_yaw_rate_step(&self, delta: &mut f64) -> bool {
+        let max_step = self.config.max_yaw_rate_step;
+        if delta.abs() > max_step {
+            *delta = delta.clamp(-max_step, max_step);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Body yaw rate change over one step from the module yaw torque, after
+    /// the yaw rate clamp.
+    ///
+    /// With a rigid chassis this is simply torque / inertia. With a compliant
+    /// chassis the module frame twists against the body through a torsional
+    /// spring-damper, so the body only sees the torque carried by the spring.
+    fn body_yaw_rate_step(&mut self, module_torque: f64, dt: f64) -> f64 {
+        let stiffness = self.config.chassis_torsional_stiffness;
+        let mut clamped = false;
+        let mut body_step = if stiffness.is_finite() {
+            let coupling_torque = stiffness * self.twist_angle
+                + self.config.chassis_torsional_damping * self.twist_rate;
+            let frame_accel = (module_torque - coupling_torque) / self.config.chassis_frame_inertia;
+            let body_accel = coupling_torque / self.config.moment_of_inertia;
+
+            // The module frame's yaw rate is clamped too, or the twist alone can diverge
+            let mut twist_step = (frame_accel - body_accel) * dt;
+            clamped |= self.clamp_yaw_rate_step(&mut twist_step);
+            self.twist_rate += twist_step;
+            self.twist_angle += self.twist_rate * dt;
+            body_accel * dt
+        } else {
+            module_torque / self.config.moment_of_inertia * dt
+        };
+
+        clamped |= self.clamp_yaw_rate_step(&mut body_step);
+        if clamped {
+            self.yaw_clamp_count += 1;
+            if self.yaw_clamp_count == 1 {
+                log::warn!(
+                    "yaw rate change clamped to {} rad/s per step; the time step may be too large",
+                    self.config.max_yaw_rate_step
+                );
+            }
+        }
+        body_step
+    }
+
+    /// Torque the drive coupling of module `index` delivers to its wheel
+    ///
+    /// A compliant coupling is a spring-damper between a motor-side inertia
+    /// and the wheel; its motor side is advanced by `dt`. A rigid one passes
+    /// the motor torque straight through.
+    fn coupled_drive_torque(&mut self, index: usize, motor_torque: f64, wheel_velocity: f64, dt: f64) -> f64 {
+        let stiffness = self.config.drive_coupling_stiffness;
+        if !stiffness.is_finite() {
+            return motor_torque;
+        }
+        // Couplings start relaxed with the motor turning with its wheel
+        while self.drive_windup.len() <= index {
+            self.drive_windup.push([0.0, wheel_velocity]);
+        }
+
+        let [windup, motor_velocity] = &mut self.drive_windup[index];
+        let coupling_torque =
+            stiffness * *windup + self.config.drive_coupling_damping * (*motor_velocity - wheel_velocity);
+        *motor_velocity += (motor_torque - coupling_torque) / self.config.drive_motor_inertia * dt;
+        *windup += (*motor_velocity - wheel_velocity) * dt;
+        coupling_torque
+    }
+
+    /// Calculate the velocity of a wheel module given the robot-frame body velocity.
     /// Returns (longitudinal_velocity, lateral_velocity) in the module's local frame.
     fn calculate_module_velocity(
         &self,
@@ -74,6 +426,100 @@ impl SwerveDrivetrain {
         (longitudinal, lateral)
     }
 
+    /// Set each wheel's ground velocity from the body state and spin it up to
+    /// roll without slip, so a run can start from a moving body without a
+    /// spurious braking transient.
+    pub fn sync_wheels_to_body(&self, state: &mut SimState) {
+        let body = &state.true_state.body_state;
+        let [body_vx, body_vy, _] = body.planar_velocity(VelocityFrame::Body);
+        let body_omega = body.angular_velocity[2] + self.twist_rate;
+
+        let num_modules = self.config.module_positions.len();
+        for (i, wheel) in state.true_state.wheel_states.iter_mut().take(num_modules).enumerate() {
+            let (v_long, v_lat) =
+                self.calculate_module_velocity(body_vx, body_vy, body_omega, &self.config.module_arm(i), wheel.angle);
+            wheel.longitudinal_translational_velocity = v_long;
+            wheel.lateral_translational_velocity = v_lat;
+            wheel.driving_angular_velocity = v_long / wheel.wheel_radius;
+        }
+    }
+
+    /// Advance a module's steering angle given the torque at the steering axis.
+    ///
+    /// Integrates `turning_angular_velocity` from the steer inertia, capped at
+    /// `max_steer_rate`, and keeps `angle` wrapped to [-pi, pi).
+    pub fn step_steer(&self, wheel: &mut WheelState, steer_torque: f64, dt: f64) {
+        let max_rate = self.config.max_steer_rate;
+        let angular_acceleration = steer_torque / self.config.steer_inertia;
+        wheel.turning_angular_velocity =
+            (wheel.turning_angular_velocity + angular_acceleration * dt).clamp(-max_rate, max_rate);
+        wheel.angle = (wheel.angle + wheel.turning_angular_velocity * dt + PI).rem_euclid(2.0 * PI) - PI;
+    }
+
+    /// Steering torque that would turn `wheel` onto `target` within one step
+    ///
+    /// Fed to `step_steer`, this is an ideal steer servo whose only limit is
+    /// `max_steer_rate`.
+    pub fn steer_servo_torque(&self, wheel: &WheelState, target: f64, dt: f64) -> f64 {
+        if dt <= 0.0 {
+            return 0.0;
+        }
+        let error = (target - wheel.angle + PI).rem_euclid(2.0 * PI) - PI;
+        self.config.steer_inertia * (error / dt - wheel.turning_angular_velocity) / dt
+    }
+
+    /// Steering torque of a steer servo that can exert at most `max_torque`
+    ///
+    /// Turns `wheel` toward `target` at up to `max_steer_rate`, braking in
+    /// time to stop on it under the torque cap. With an infinite cap this is
+    /// `steer_servo_torque`.
+    pub fn limited_steer_servo_torque(&self, wheel: &WheelState, target: f64, dt: f64, max_torque: f64) -> f64 {
+        if !max_torque.is_finite() {
+            return self.steer_servo_torque(wheel, target, dt);
+        }
+        if dt <= 0.0 {
+            return 0.0;
+        }
+        let inertia = self.config.steer_inertia;
+        let error = (target - wheel.angle + PI).rem_euclid(2.0 * PI) - PI;
+        // Fastest rate from which the capped torque still stops at the target
+        let stopping_rate = (2.0 * max_torque / inertia * error.abs()).sqrt();
+        let max_rate = stopping_rate.min(self.config.max_steer_rate);
+        let rate = (error / dt).clamp(-max_rate, max_rate);
+        (inertia * (rate - wheel.turning_angular_velocity) / dt).clamp(-max_torque, max_torque)
+    }
+
+    /// Weight of the quasi-static model: 1 at rest, falling to 0 at `creep_speed`.
+    fn creep_weight(&self, vx: f64, vy: f64, omega: f64) -> f64 {
+        let creep_speed = self.config.creep_speed;
+        if creep_speed <= 0.0 {
+            return 0.0;
+        }
+        let reach = (0..self.config.module_positions.len())
+            .map(|i| {
+                let [x, y] = self.config.module_arm(i);
+                x.hypot(y)
+            })
+            .fold(0.0, f64::max);
+        let speed = vx.hypot(vy) + omega.abs() * reach;
+        (1.0 - speed / creep_speed).max(0.0)
+    }
+
+    /// Move the tire loads toward the split for body acceleration
+    /// `acceleration`, lagged by `load_transfer_time_constant`. Does nothing
+    /// without a `cg_height`.
+    fn transfer_load(&self, state: &mut SimState, acceleration: [f64; 2], dt: f64) {
+        if self.config.cg_height <= 0.0 {
+            return;
+        }
+        let tau = self.config.load_transfer_time_constant;
+        let blend = if tau > 0.0 { 1.0 - (-dt / tau).exp() } else { 1.0 };
+        let targets = self.config.wheel_loads(GRAVITY, acceleration);
+        for (wheel, target) in state.true_state.wheel_states.iter_mut().zip(targets) {
+            wheel.tire.tire_load += blend * (target - wheel.tire.tire_load);
+        }
+    }
+
     /// Transform forces from module frame back to body frame.
     fn transform_forces_to_body(
         longitudinal_force: f64,
@@ -92,7 +538,13 @@ impl SwerveDrivetrain {
 
 impl Model for SwerveDrivetrain {
     fn reset(&mut self) {
-        // No internal state to reset beyond config
+        self.twist_angle = 0.0;
+        self.twist_rate = 0.0;
+        self.yaw_clamp_count = 0;
+        self.power_flow = None;
+        self.drive_windup.clear();
+        self.scrub_power.clear();
+        self.shift(Gear::Low);
     }
 }
 
@@ -101,15 +553,25 @@ impl MechanicsModel for SwerveDrivetrain {
         let dt = ctx.dt;
         let num_modules = self.config.module_positions.len();
 
-        // Get current body state
+        // Get current body state; the stored velocity is field-relative, but
+        // module kinematics and forces are worked out in the robot frame
         let body = &state.true_state.body_state;
-        let body_vx = body.velocity[0];
-        let body_vy = body.velocity[1];
-        let body_omega = body.angular_velocity[2]; // Yaw rate
+        let heading = body.orientation[2];
+        let [body_vx, body_vy, _] = body.planar_velocity(VelocityFrame::Body);
+        // Modules ride on the (possibly twisting) frame
+        let body_omega = body.angular_velocity[2] + self.twist_rate; // Yaw rate
 
         let mut net_force_x = 0.0;
         let mut net_force_y = 0.0;
         let mut net_torque = 0.0;
+        // Force and torque the motors would apply through non-slipping wheels
+        let mut drive_force_x = 0.0;
+        let mut drive_force_y = 0.0;
+        let mut drive_torque = 0.0;
+        if self.config.scrub_radius > 0.0 {
+            self.scrub_power.clear();
+            self.scrub_power.resize(num_modules, 0.0);
+        }
 
         // 1. Update kinematics for each module
         for i in 0..num_modules {
@@ -117,34 +579,71 @@ impl MechanicsModel for SwerveDrivetrain {
                 continue;
             }
 
-            let module_pos = &self.config.module_positions[i];
+            let module_pos = self.config.module_arm(i);
             let wheel = &mut state.true_state.wheel_states[i];
+            let patch_load = wheel.tire.tire_load * wheel.contact_fraction;
+            if let Some(&steer_torque) = state.control_input.steer_torques.get(i) {
+                // Turning the module grinds its patch; that scrub loads the steer axis
+                let mut steer_scrub = 0.0;
+                if self.config.scrub_radius > 0.0 {
+                    steer_scrub = self.scrub_torque(patch_load, wheel.turning_angular_velocity);
+                    self.scrub_power[i] += (steer_scrub * wheel.turning_angular_velocity).abs();
+                }
+                self.step_steer(wheel, steer_torque + steer_scrub, dt);
+            }
 
             // Calculate module velocities from body state
             let (v_long, v_lat) =
-                self.calculate_module_velocity(body_vx, body_vy, body_omega, module_pos, wheel.angle);
+                self.calculate_module_velocity(body_vx, body_vy, body_omega, &module_pos, wheel.angle);
 
             wheel.longitudinal_translational_velocity = v_long;
             wheel.lateral_translational_velocity = v_lat;
 
             // 2. Update wheel angular velocity based on motor torque
             // tau = I * alpha => alpha = tau / I
-            // The motor applies torque to the wheel
+            // The motor applies torque to the wheel through its reduction
+            // (reversed on mirrored modules)
             if i < state.true_state.motors.len() {
-                let motor_torque = state.true_state.motors[i].applied_torque;
-                // Subtract reaction torque from tire forces (tire generates torque back on wheel)
+                let motor_torque = state.true_state.motors[i].applied_torque
+                    * self.config.drive_direction(i)
+                    * self.config.drive_gear_ratio;
+                // The tire force acts on the wheel at the contact patch; in the
+                // braking convention it opposes spin-up directly
                 let tire_reaction_torque =
                     wheel.tire.longitudinal_force * wheel.wheel_radius;
 
-                let net_wheel_torque = motor_torque - tire_reaction_torque;
+                // An idle motor sits in its neutral mode; a shorted one brakes
+                let motor = &mut state.true_state.motors[i];
+                motor.bridge_mode = if motor_torque == 0.0 {
+                    self.config.neutral_mode.bridge_mode()
+                } else {
+                    BridgeMode::Closed
+                };
+                let mut damping = self.config.wheel_viscous_damping;
+                if matches!(motor.bridge_mode, BridgeMode::Shorted) {
+                    damping += self.config.brake_damping;
+                }
+                let damping_torque = -damping * wheel.driving_angular_velocity;
+
+                // Belt or chain compliance delays the motor torque reaching the wheel
+                let coupled_torque =
+                    self.coupled_drive_torque(i, motor_torque, wheel.driving_angular_velocity, dt);
+
+                let net_wheel_torque = coupled_torque + tire_reaction_torque + damping_torque;
                 let angular_acceleration = net_wheel_torque / self.config.wheel_inertia;
                 wheel.driving_angular_velocity += angular_acceleration * dt;
+
+                let (fx, fy) = Self::transform_forces_to_body(coupled_torque / wheel.wheel_radius, 0.0, wheel.angle);
+                drive_force_x += fx;
+                drive_force_y += fy;
+                drive_torque += module_pos[0] * fy - module_pos[1] * fx;
             }
 
             // 3. Sum tire forces into body dynamics
-            // Forces are already computed in tire.rs via TireManager
+            // Forces are already computed in tire.rs via TireManager; the
+            // ground pushes the chassis opposite to the braking-convention force
             let (fx, fy) = Self::transform_forces_to_body(
-                wheel.tire.longitudinal_force,
+                -wheel.tire.longitudinal_force,
                 wheel.tire.lateral_force,
                 wheel.angle,
             );
@@ -155,29 +654,78 @@ impl MechanicsModel for SwerveDrivetrain {
             // Torque about CoM from this module's forces
             // tau = r x F = rx * Fy - ry * Fx
             net_torque += module_pos[0] * fy - module_pos[1] * fx;
+
+            // The contact patch yaws with the chassis, grinding against the floor
+            if self.config.scrub_radius > 0.0 {
+                let scrub_torque = self.scrub_torque(patch_load, body_omega);
+                net_torque += scrub_torque;
+                self.scrub_power[i] += (scrub_torque * body_omega).abs();
+            }
+        }
+
+        // 4. Near standstill, blend toward quasi-static rolling: wheels neither
+        // slip nor skid, so the drive force moves the robot and friction
+        // removes any residual motion it does not explain
+        let creep = self.creep_weight(body_vx, body_vy, body_omega);
+        if creep > 0.0 && dt > 0.0 {
+            let body = &state.true_state.body_state;
+            let (mut residual_vx, mut residual_vy) = (body_vx, body_vy);
+            let drive_force = drive_force_x.hypot(drive_force_y);
+            if drive_force > 0.0 {
+                let (ux, uy) = (drive_force_x / drive_force, drive_force_y / drive_force);
+                let along = residual_vx * ux + residual_vy * uy;
+                residual_vx -= along * ux;
+                residual_vy -= along * uy;
+            }
+            let residual_omega = if drive_torque != 0.0 { 0.0 } else { body.angular_velocity[2] };
+
+            let static_force_x = drive_force_x - self.config.mass * residual_vx / dt;
+            let static_force_y = drive_force_y - self.config.mass * residual_vy / dt;
+            let static_torque = drive_torque - self.config.moment_of_inertia * residual_omega / dt;
+            net_force_x += creep * (static_force_x - net_force_x);
+            net_force_y += creep * (static_force_y - net_force_y);
+            net_torque += creep * (static_torque - net_torque);
+        }
+
+        if self.config.report_power {
+            let body = &state.true_state.body_state;
+            let battery = &state.true_state.battery_state;
+            self.power_flow = Some(PowerFlow {
+                mechanical: net_force_x * body_vx
+                    + net_force_y * body_vy
+                    + net_torque * body.angular_velocity[2],
+                electrical: battery.voltage * battery.total_current_draw,
+            });
         }
 
-        // 4. Integrate body accelerations
+        // 5. Integrate body accelerations, rotated from the robot frame into
+        // the field frame the body velocity is stored in
         let ax = net_force_x / self.config.mass;
         let ay = net_force_y / self.config.mass;
-        let alpha = net_torque / self.config.moment_of_inertia;
+        self.transfer_load(state, [ax, ay], dt);
+        let (sin, cos) = heading.sin_cos();
+        let (ax, ay) = (ax * cos - ay * sin, ax * sin + ay * cos);
+        let yaw_rate_step = self.body_yaw_rate_step(net_torque, dt);
 
-        // Update velocities (semi-implicit Euler)
-        state.true_state.body_state.velocity[0] += ax * dt;
-        state.true_state.body_state.velocity[1] += ay * dt;
-        state.true_state.body_state.angular_velocity[2] += alpha * dt;
+        let yaw_acceleration = if dt > 0.0 { yaw_rate_step / dt } else { 0.0 };
 
-        // Update positions
-        state.true_state.body_state.position[0] += state.true_state.body_state.velocity[0] * dt;
-        state.true_state.body_state.position[1] += state.true_state.body_state.velocity[1] * dt;
-        state.true_state.body_state.orientation[2] += state.true_state.body_state.angular_velocity[2] * dt;
+        // Update velocities and pose with the configured integrator
+        let integrator = self.config.integrator;
+        let body = &mut state.true_state.body_state;
+        for (axis, acceleration) in [(0, ax), (1, ay)] {
+            (body.position[axis], body.velocity[axis]) =
+                integrator.advance(body.position[axis], body.velocity[axis], dt, |_, _| acceleration);
+        }
+        (body.orientation[2], body.angular_velocity[2]) =
+            integrator.advance(body.orientation[2], body.angular_velocity[2], dt, |_, _| yaw_acceleration);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use simcore::{BodyState, TireState, MotorState, BatteryState, TrueState, ActuatorInput, SensorBus};
+    use simcore::{BodyState, TireState, MotorState, BatteryState, TrueState, ActuatorInput, SensorBus, WheelPeaks, WheelTrace};
+    use crate::tire::{TireConstants, TireManager};
 
     fn create_test_state(num_modules: usize) -> SimState {
         let wheel_states: Vec<WheelState> = (0..num_modules)
@@ -195,6 +743,7 @@ mod tests {
                     tire_load: 100.0,
                 },
                 angle: 0.0,
+                contact_fraction: 1.0,
             })
             .collect();
 
@@ -232,9 +781,9 @@ mod tests {
         let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig::default());
         let mut state = create_test_state(4);
 
-        // Apply forward force on all wheels
+        // Apply forward force on all wheels (braking convention: negative drives)
         for wheel in &mut state.true_state.wheel_states {
-            wheel.tire.longitudinal_force = 25.0; // 25N per wheel = 100N total
+            wheel.tire.longitudinal_force = -25.0; // 25N per wheel = 100N total
         }
 
         let ctx = SimContext { dt: 0.01, t: 0.0 };
@@ -244,4 +793,743 @@ mod tests {
         // After 0.01s, velocity should be 0.02 m/s
         assert!((state.true_state.body_state.velocity[0] - 0.02).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_driving_forward_at_a_heading_moves_along_the_heading() {
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig::default());
+        let mut state = create_test_state(4);
+        // Facing field +y, modules straight ahead, pushing forward
+        state.true_state.body_state = BodyState::planar(0.0, 0.0, PI / 2.0, 0.0, 0.0, 0.0);
+        for wheel in &mut state.true_state.wheel_states {
+            wheel.tire.longitudinal_force = -25.0;
+        }
+
+        for step in 0..10 {
+            drivetrain.step_physics(SimContext { dt: 0.01, t: step as f64 * 0.01 }, &mut state);
+        }
+
+        // 100 N on 50 kg for 0.1 s: 0.2 m/s along the heading
+        let body = &state.true_state.body_state;
+        let [field_vx, field_vy, _] = body.planar_velocity(VelocityFrame::Field);
+        assert!(field_vx.abs() < 1e-9, "field vx {}", field_vx);
+        assert!((field_vy - 0.2).abs() < 1e-6, "field vy {}", field_vy);
+        let [body_vx, body_vy, _] = body.planar_velocity(VelocityFrame::Body);
+        assert!((body_vx - 0.2).abs() < 1e-6, "body vx {}", body_vx);
+        assert!(body_vy.abs() < 1e-9, "body vy {}", body_vy);
+
+        // The modules see the motion as purely longitudinal
+        for wheel in &state.true_state.wheel_states {
+            assert!(wheel.longitudinal_translational_velocity > 0.1);
+            assert!(wheel.lateral_translational_velocity.abs() < 1e-9);
+        }
+    }
+
+    fn yaw_rate_after(config: SwerveDrivetrainConfig, steps: usize) -> f64 {
+        let mut drivetrain = SwerveDrivetrain::new(config);
+        let mut state = create_test_state(4);
+        // Left modules push back, right modules push forward => positive yaw torque
+        // (tire forces are in the braking convention, opposite to the chassis push)
+        for (wheel, pos) in state.true_state.wheel_states.iter_mut().zip(&drivetrain.config.module_positions) {
+            wheel.tire.longitudinal_force = if pos[1] > 0.0 { 25.0 } else { -25.0 };
+        }
+
+        let ctx = SimContext { dt: 0.0005, t: 0.0 };
+        for _ in 0..steps {
+            drivetrain.step_physics(ctx, &mut state);
+        }
+        state.true_state.body_state.angular_velocity[2]
+    }
+
+    #[test]
+    fn test_com_offset_loads_front_tires_and_yaws_under_side_push() {
+        let forward = SwerveDrivetrainConfig { com_offset: [0.1, 0.0], ..Default::default() };
+        let centered = SwerveDrivetrainConfig::default();
+
+        let weight = 50.0 * 9.81;
+        let even = centered.static_wheel_loads(9.81);
+        assert!(even.iter().all(|&load| (load - weight / 4.0).abs() < 1e-9));
+
+        // Front modules sit at x = +0.3: (0.3 + 0.1) / 0.6 of the weight on the front axle
+        let loads = forward.static_wheel_loads(9.81);
+        assert!((loads.iter().sum::<f64>() - weight).abs() < 1e-9);
+        assert!((loads[0] + loads[1] - weight * 0.4 / 0.6).abs() < 1e-9);
+        assert!(loads[0] > loads[2] && loads[1] > loads[3]);
+        let moment: f64 = (0..4).map(|i| loads[i] * forward.module_arm(i)[0]).sum();
+        assert!(moment.abs() < 1e-9);
+
+        // Equal side forces on every wheel push straight through a centered
+        // CoM but act behind a forward one, yawing the robot
+        let yaw_rate = |config: SwerveDrivetrainConfig| {
+            let mut drivetrain = SwerveDrivetrain::new(config);
+            let mut state = create_test_state(4);
+            for wheel in &mut state.true_state.wheel_states {
+                wheel.tire.lateral_force = 25.0;
+            }
+            drivetrain.step_physics(SimContext { dt: 0.01, t: 0.0 }, &mut state);
+            state.true_state.body_state.angular_velocity[2]
+        };
+        assert!(yaw_rate(centered).abs() < 1e-12);
+        // 100 N acting 0.1 m behind the CoM: -10 N*m over 5 kg*m^2 for 0.01 s
+        assert!((yaw_rate(forward) + 0.02).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lagged_load_transfer_shifts_weight_gradually() {
+        // Every tire pushes forward 50 N: 4 m/s^2 on the 50 kg robot
+        let front_load_after = |load_transfer_time_constant: f64, steps: usize| {
+            let config = SwerveDrivetrainConfig { cg_height: 0.3, load_transfer_time_constant, ..Default::default() };
+            let mut drivetrain = SwerveDrivetrain::new(config.clone());
+            let mut state = create_test_state(4);
+            for (wheel, load) in state.true_state.wheel_states.iter_mut().zip(config.static_wheel_loads(9.81)) {
+                wheel.tire.tire_load = load;
+                wheel.tire.longitudinal_force = -50.0;
+            }
+            for _ in 0..steps {
+                drivetrain.step_physics(SimContext { dt: 1e-3, t: 0.0 }, &mut state);
+            }
+            let loads: Vec<f64> = state.true_state.wheel_states.iter().map(|w| w.tire.tire_load).collect();
+            assert!((loads.iter().sum::<f64>() - 50.0 * 9.81).abs() < 1e-9);
+            loads[0] + loads[1]
+        };
+        // 50 kg * 4 m/s^2 * 0.3 m over the 0.6 m wheelbase moves 100 N rearward
+        let static_front = 50.0 * 9.81 / 2.0;
+        let settled_front = static_front - 100.0;
+
+        assert!((front_load_after(0.0, 1) - settled_front).abs() < 1e-9);
+
+        let early = front_load_after(0.05, 10);
+        let transferred = (static_front - early) / 100.0;
+        assert!(transferred > 0.1 && transferred < 0.3, "transferred {} after 10 ms", transferred);
+        assert!((front_load_after(0.05, 500) - settled_front).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_spinning_in_place_loses_power_to_scrub() {
+        let config = SwerveDrivetrainConfig { scrub_radius: 0.02, ..Default::default() };
+        let mut drivetrain = SwerveDrivetrain::new(config.clone());
+        let mut state = create_test_state(4);
+        state.true_state.body_state.angular_velocity[2] = 2.0;
+        // Modules point along their tangents and roll without slip
+        for (i, wheel) in state.true_state.wheel_states.iter_mut().enumerate() {
+            let [x, y] = config.module_arm(i);
+            wheel.angle = x.atan2(-y);
+        }
+        drivetrain.sync_wheels_to_body(&mut state);
+        for wheel in &state.true_state.wheel_states {
+            assert!(wheel.lateral_translational_velocity.abs() < 1e-12);
+        }
+
+        let dt = 1e-3;
+        drivetrain.step_physics(SimContext { dt, t: 0.0 }, &mut state);
+
+        // 100 N tires with a 2 cm scrub radius at 2 rad/s: 4 W per patch
+        let scrub = drivetrain.scrub_power().unwrap();
+        assert_eq!(scrub.len(), 4);
+        for &power in scrub {
+            assert!((power - 4.0).abs() < 1e-9, "scrub power {}", power);
+        }
+        // The chassis center stays put while the scrub slows the spin
+        let body = &state.true_state.body_state;
+        assert!(body.velocity[0].abs() < 1e-12 && body.velocity[1].abs() < 1e-12);
+        assert!((body.angular_velocity[2] - (2.0 - 8.0 / 5.0 * dt)).abs() < 1e-9);
+
+        assert!(SwerveDrivetrain::new(SwerveDrivetrainConfig::default()).scrub_power().is_none());
+    }
+
+    #[test]
+    fn test_steering_scrub_loads_the_steer_axis_not_the_chassis() {
+        let config = SwerveDrivetrainConfig { scrub_radius: 0.02, ..Default::default() };
+        let steer_rate = 5.0;
+        let dt = 1e-4;
+        // Modules turning at `steer_rate` with no drive or steer torque
+        let coast = |config: SwerveDrivetrainConfig| {
+            let mut drivetrain = SwerveDrivetrain::new(config);
+            let mut state = create_test_state(4);
+            for wheel in &mut state.true_state.wheel_states {
+                wheel.turning_angular_velocity = steer_rate;
+            }
+            state.control_input.steer_torques = vec![0.0; 4];
+            drivetrain.step_physics(SimContext { dt, t: 0.0 }, &mut state);
+            (drivetrain, state)
+        };
+
+        let (drivetrain, state) = coast(config.clone());
+        // 100 N tires with a 2 cm scrub radius: 2 N*m against each steer axis
+        let expected_rate = steer_rate - 2.0 / config.steer_inertia * dt;
+        for wheel in &state.true_state.wheel_states {
+            assert!((wheel.turning_angular_velocity - expected_rate).abs() < 1e-9,
+                "steer rate {}", wheel.turning_angular_velocity);
+        }
+        for &power in drivetrain.scrub_power().unwrap() {
+            assert!((power - 2.0 * steer_rate).abs() < 1e-9, "scrub power {}", power);
+        }
+        assert_eq!(state.true_state.body_state.angular_velocity[2], 0.0);
+
+        let (_, free) = coast(SwerveDrivetrainConfig::default());
+        assert!(free.true_state.wheel_states.iter().all(|wheel| wheel.turning_angular_velocity == steer_rate));
+    }
+
+    #[test]
+    fn test_compliant_chassis_delays_yaw_response() {
+        let compliant = SwerveDrivetrainConfig {
+            chassis_torsional_stiffness: 2000.0,
+            chassis_torsional_damping: 20.0,
+            ..Default::default()
+        };
+
+        // Early on the twisting frame absorbs torque before the body responds
+        let rigid_early = yaw_rate_after(SwerveDrivetrainConfig::default(), 10);
+        let compliant_early = yaw_rate_after(compliant.clone(), 10);
+        assert!(compliant_early < 0.5 * rigid_early,
+            "compliant {} should lag rigid {}", compliant_early, rigid_early);
+
+        // Once the spring winds up the body catches up
+        let rigid_late = yaw_rate_after(SwerveDrivetrainConfig::default(), 1000);
+        let compliant_late = yaw_rate_after(compliant, 1000);
+        assert!(compliant_late > 0.8 * rigid_late);
+    }
+
+    /// Peak yaw rate while a translating, spinning robot with stiff tires on
+    /// a stiff compliant chassis is integrated at a deliberately large dt
+    fn peak_yaw_rate_at_large_dt(max_yaw_rate_step: f64) -> (f64, u64) {
+        let config = SwerveDrivetrainConfig {
+            chassis_torsional_stiffness: 5e4,
+            max_yaw_rate_step,
+            ..SwerveDrivetrainConfig::default()
+        };
+        let mut drivetrain = SwerveDrivetrain::new(config);
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.2, 1.2, 1e5, 1e5, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        state.true_state.body_state = BodyState::planar(0.0, 0.0, 0.0, 2.0, 0.0, 2.0);
+        drivetrain.sync_wheels_to_body(&mut state);
+
+        let dt = 0.02;
+        let mut peak: f64 = 0.0;
+        for i in 0..200 {
+            let ctx = SimContext { dt, t: i as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+            let yaw_rate = state.true_state.body_state.angular_velocity[2].abs();
+            // NaN counts as unbounded
+            peak = if yaw_rate.is_nan() { f64::INFINITY } else { peak.max(yaw_rate) };
+        }
+        (peak, drivetrain.yaw_clamp_count())
+    }
+
+    #[test]
+    fn test_yaw_rate_clamp_prevents_divergence() {
+        let (unclamped_peak, unclamped_count) = peak_yaw_rate_at_large_dt(f64::INFINITY);
+        assert!(unclamped_peak > 100.0, "unclamped peak {}", unclamped_peak);
+        assert_eq!(unclamped_count, 0);
+
+        // Friction only slows the spin, so the initial 2 rad/s is a physical bound
+        let (clamped_peak, clamped_count) = peak_yaw_rate_at_large_dt(0.1);
+        assert!(clamped_peak <= 2.0 + 1e-9, "clamped peak {}", clamped_peak);
+        assert!(clamped_count > 0);
+    }
+
+    #[test]
+    fn test_lifted_wheel_contributes_no_force() {
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig::default());
+        let mut tires = TireManager::new();
+        tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        let mut state = create_test_state(1);
+        state.true_state.wheel_states[0].contact_fraction = 0.0;
+        state.true_state.wheel_states[0].driving_angular_velocity = 20.0;
+        state.true_state.motors[0].applied_torque = 1.0;
+
+        let ctx = SimContext { dt: 0.001, t: 0.0 };
+        for _ in 0..100 {
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+        }
+
+        let wheel = &state.true_state.wheel_states[0];
+        assert_eq!(wheel.tire.longitudinal_force, 0.0);
+        assert_eq!(wheel.tire.lateral_force, 0.0);
+        assert_eq!(state.true_state.body_state.velocity[0], 0.0);
+        // Free-spinning under motor torque
+        assert!(wheel.driving_angular_velocity > 20.0);
+    }
+
+    #[test]
+    fn test_steer_rate_limit_slows_half_turn() {
+        let max_steer_rate = 10.0;
+        let drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            max_steer_rate,
+            ..Default::default()
+        });
+        let mut state = create_test_state(1);
+        let wheel = &mut state.true_state.wheel_states[0];
+
+        // Huge PD output commanding a 180 degree step
+        let target = PI;
+        let dt = 0.0005;
+        let mut t = 0.0;
+        loop {
+            let error = (target - wheel.angle + PI).rem_euclid(2.0 * PI) - PI;
+            if error.abs() < 0.01 || t > 5.0 {
+                break;
+            }
+            let torque = 1000.0 * error - 10.0 * wheel.turning_angular_velocity;
+            drivetrain.step_steer(wheel, torque, dt);
+            assert!(wheel.turning_angular_velocity.abs() <= max_steer_rate);
+            t += dt;
+        }
+
+        assert!(t >= (PI - 0.01) / max_steer_rate, "half turn took only {} s", t);
+        assert!(t < 5.0, "steering never reached the target");
+    }
+
+    #[test]
+    fn test_torque_limited_steer_servo_stops_on_target() {
+        let drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            max_steer_rate: 20.0,
+            ..Default::default()
+        });
+        let mut state = create_test_state(1);
+        let wheel = &mut state.true_state.wheel_states[0];
+
+        // 0.5 N*m on 0.005 kg*m^2: a quarter turn needs ~0.25 s of bang-bang
+        let (target, max_torque, dt) = (PI / 2.0, 0.5, 1e-4);
+        let mut peak_angle: f64 = 0.0;
+        for _ in 0..5000 {
+            let torque = drivetrain.limited_steer_servo_torque(wheel, target, dt, max_torque);
+            assert!(torque.abs() <= max_torque);
+            drivetrain.step_steer(wheel, torque, dt);
+            peak_angle = peak_angle.max(wheel.angle);
+        }
+        assert!(peak_angle < target + 1e-3, "overshot to {}", peak_angle);
+        assert!((wheel.angle - target).abs() < 1e-6, "settled at {}", wheel.angle);
+        assert!(wheel.turning_angular_velocity.abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_step_physics_turns_modules_at_the_steer_rate_limit() {
+        let max_steer_rate = 10.0;
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            max_steer_rate,
+            ..Default::default()
+        });
+        let mut state = create_test_state(4);
+
+        // Without steer torques the modules hold their angle
+        drivetrain.step_physics(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+        assert!(state.true_state.wheel_states.iter().all(|wheel| wheel.angle == 0.0));
+
+        // Servo every module toward a half turn through the physics step
+        let target = PI - 0.001;
+        let dt = 0.0005;
+        let mut t = 0.0;
+        while state.true_state.wheel_states.iter().any(|wheel| (target - wheel.angle).abs() > 1e-9) && t < 5.0 {
+            state.control_input.steer_torques = state.true_state.wheel_states.iter()
+                .map(|wheel| drivetrain.steer_servo_torque(wheel, target, dt))
+                .collect();
+            drivetrain.step_physics(SimContext { dt, t }, &mut state);
+            for wheel in &state.true_state.wheel_states {
+                assert!(wheel.turning_angular_velocity.abs() <= max_steer_rate);
+            }
+            t += dt;
+        }
+
+        assert!(t >= target / max_steer_rate, "half turn took only {} s", t);
+        assert!(t < 5.0, "steering never reached the target");
+    }
+
+    #[test]
+    fn test_wheel_trace_matches_single_step() {
+        let config = SwerveDrivetrainConfig::default();
+        let wheel_inertia = config.wheel_inertia;
+        let mut drivetrain = SwerveDrivetrain::new(config);
+        let mut state = create_test_state(4);
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = 0.5;
+        }
+
+        let mut trace = WheelTrace::with_capacity(1);
+        let dt = 0.001;
+        drivetrain.step_physics(SimContext { dt, t: 0.0 }, &mut state);
+        trace.record(&state.true_state.wheel_states);
+
+        // No tire force yet, so omega = torque / inertia * dt
+        let expected_omega = 0.5 / wheel_inertia * dt;
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace.angular_velocity[0].len(), 4);
+        for omega in &trace.angular_velocity[0] {
+            assert!((omega - expected_omega).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_moving_start_makes_immediate_progress() {
+        // Ideal wheels: bearing drag would slow the coast
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            wheel_viscous_damping: 0.0,
+            ..Default::default()
+        });
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        // Heading 0.5 rad while moving along field x: the modules point along
+        // the motion, so they sit at -0.5 rad relative to the robot
+        state.true_state.body_state = BodyState::planar(1.0, 2.0, 0.5, 2.0, 0.0, 0.0);
+        for wheel in &mut state.true_state.wheel_states {
+            wheel.angle = -0.5;
+        }
+        drivetrain.sync_wheels_to_body(&mut state);
+
+        let dt = 0.001;
+        for step in 0..10 {
+            let ctx = SimContext { dt, t: step as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+        }
+
+        // Rolling wheels carry no slip, so the robot coasts at its initial speed
+        let body = &state.true_state.body_state;
+        assert!((body.position[0] - 1.02).abs() < 1e-6, "x = {}", body.position[0]);
+        assert!((body.velocity[0] - 2.0).abs() < 1e-6);
+        assert!((body.orientation[2] - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_hard_launch_peaks_slip_on_driven_wheels() {
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig::default());
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        // Only the front modules drive, hard enough to break traction
+        state.true_state.motors[0].applied_torque = 10.0;
+        state.true_state.motors[1].applied_torque = 10.0;
+
+        let mut peaks = WheelPeaks::default();
+        let dt = 0.0005;
+        for step in 0..400 {
+            let ctx = SimContext { dt, t: step as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+            peaks.record(&state.true_state.wheel_states);
+        }
+
+        assert_eq!(peaks.len(), 4);
+        for driven in 0..2 {
+            assert!(peaks.slip_ratio[driven] > 0.1, "driven slip {}", peaks.slip_ratio[driven]);
+            assert!(peaks.slip_ratio[driven] > peaks.slip_ratio[driven + 2]);
+            assert!(peaks.longitudinal_force[driven] > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_motor_torque_drives_robot_forward() {
+        // Undamped wheels, so the tire reaction alone opposes spin-up
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            wheel_viscous_damping: 0.0,
+            ..Default::default()
+        });
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = 0.5;
+        }
+
+        let dt = 0.0005;
+        for step in 0..200 {
+            let ctx = SimContext { dt, t: step as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+        }
+
+        for wheel in &state.true_state.wheel_states {
+            // Wheel spins forward and the tire reaction opposes the spin-up
+            assert!(wheel.driving_angular_velocity > 0.0);
+            assert!(wheel.tire.longitudinal_force * wheel.wheel_radius < 0.0);
+        }
+        // The chassis is pushed the same way the wheels roll
+        let body = &state.true_state.body_state;
+        assert!(body.velocity[0] > 0.0, "vx = {}", body.velocity[0]);
+        // Wheel surface speed stays bounded near chassis speed (no runaway)
+        let wheel = &state.true_state.wheel_states[0];
+        assert!(wheel.driving_angular_velocity * wheel.wheel_radius < body.velocity[0] + 1.0);
+    }
+
+    #[test]
+    fn test_off_ground_wheel_reaches_terminal_velocity() {
+        let config = SwerveDrivetrainConfig::default();
+        let damping = config.wheel_viscous_damping;
+        let mut drivetrain = SwerveDrivetrain::new(config);
+        let mut state = create_test_state(4);
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = 0.5;
+        }
+
+        // No tire manager: the wheels never touch the ground
+        let dt = 0.001;
+        for i in 0..200_000 {
+            drivetrain.step_physics(SimContext { dt, t: i as f64 * dt }, &mut state);
+        }
+
+        let terminal = 0.5 / damping;
+        for wheel in &state.true_state.wheel_states {
+            assert!(wheel.driving_angular_velocity.is_finite());
+            assert!((wheel.driving_angular_velocity - terminal).abs() < 0.01 * terminal,
+                "omega {} vs terminal {}", wheel.driving_angular_velocity, terminal);
+        }
+    }
+
+    #[test]
+    fn test_rk4_matches_constant_acceleration_closer_than_euler() {
+        // Hold a forward tire force on every module: a = F / m exactly
+        let run = |integrator: IntegratorKind| {
+            let config = SwerveDrivetrainConfig { integrator, ..Default::default() };
+            let acceleration = 4.0 * 25.0 / config.mass;
+            let mut drivetrain = SwerveDrivetrain::new(config);
+            let mut state = create_test_state(4);
+
+            let dt = 0.05;
+            for i in 0..20 {
+                for wheel in &mut state.true_state.wheel_states {
+                    wheel.tire.longitudinal_force = -25.0;
+                }
+                drivetrain.step_physics(SimContext { dt, t: i as f64 * dt }, &mut state);
+            }
+            (state.true_state.body_state.position[0] - 0.5 * acceleration * 1.0_f64.powi(2)).abs()
+        };
+
+        let euler_error = run(IntegratorKind::SemiImplicitEuler);
+        let rk4_error = run(IntegratorKind::RungeKutta4);
+        assert!(euler_error > 1e-3, "euler error {}", euler_error);
+        assert!(rk4_error < 1e-9, "rk4 error {}", rk4_error);
+    }
+
+    /// Robot left with a residual crawl below the tires' slip floor
+    fn run_residual_drift(config: SwerveDrivetrainConfig, torque: f64) -> SimState {
+        let mut drivetrain = SwerveDrivetrain::new(config);
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        state.true_state.body_state = BodyState::planar(0.0, 0.0, 0.0, 0.004, -0.003, 0.002);
+        drivetrain.sync_wheels_to_body(&mut state);
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = torque;
+        }
+
+        let dt = 0.001;
+        for step in 0..10_000 {
+            let ctx = SimContext { dt, t: step as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+        }
+        state
+    }
+
+    #[test]
+    fn test_creep_mode_holds_idle_robot_at_rest() {
+        let creeping = SwerveDrivetrainConfig { creep_speed: 0.05, ..Default::default() };
+        let body = run_residual_drift(creeping, 0.0).true_state.body_state;
+        // Only the first few steps of the crawl survive
+        assert!(body.position[0].hypot(body.position[1]) < 1e-6, "drifted to {:?}", body.position);
+        assert!(body.orientation[2].abs() < 1e-6);
+        assert!(body.velocity[0].hypot(body.velocity[1]) < 1e-12);
+
+        // Without creep mode the crawl never stops
+        let drifting = run_residual_drift(SwerveDrivetrainConfig::default(), 0.0).true_state.body_state;
+        assert!(drifting.position[0].hypot(drifting.position[1]) > 0.01);
+    }
+
+    #[test]
+    fn test_creep_mode_still_launches_under_torque() {
+        let creeping = SwerveDrivetrainConfig { creep_speed: 0.05, ..Default::default() };
+        let body = run_residual_drift(creeping, 0.5).true_state.body_state;
+        assert!(body.velocity[0] > 0.05, "vx = {}", body.velocity[0]);
+        assert!(body.position[0] > 0.1);
+    }
+
+    #[test]
+    fn test_inverted_module_spins_backward() {
+        let drivetrain_config = SwerveDrivetrainConfig {
+            drive_inverted: vec![false, true],
+            ..Default::default()
+        };
+        assert_eq!(drivetrain_config.drive_direction(1), -1.0);
+        assert_eq!(drivetrain_config.drive_direction(3), 1.0);
+
+        let mut drivetrain = SwerveDrivetrain::new(drivetrain_config);
+        let mut state = create_test_state(4);
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = 0.5;
+        }
+        drivetrain.step_physics(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+
+        let omegas: Vec<f64> = state.true_state.wheel_states.iter()
+            .map(|w| w.driving_angular_velocity)
+            .collect();
+        assert!(omegas[0] > 0.0);
+        assert!((omegas[1] + omegas[0]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_upshift_keeps_wheel_speed_and_trades_away_torque() {
+        let gearbox = TwoSpeedGearbox::new(8.0, 4.0).unwrap();
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig::default()).with_two_speed_gearbox(gearbox);
+        assert_eq!((drivetrain.gear(), drivetrain.config.drive_gear_ratio), (Gear::Low, 8.0));
+        // Wheel spin-up over one step from the same motor torque at 20 rad/s
+        let spin_up = |drivetrain: &mut SwerveDrivetrain| {
+            let mut state = create_test_state(4);
+            for (wheel, motor) in state.true_state.wheel_states.iter_mut().zip(&mut state.true_state.motors) {
+                wheel.driving_angular_velocity = 20.0;
+                motor.applied_torque = 0.5;
+            }
+            drivetrain.step_physics(SimContext { dt: 1e-4, t: 0.0 }, &mut state);
+            state.true_state.wheel_states[0].driving_angular_velocity - 20.0
+        };
+        let low = spin_up(&mut drivetrain);
+
+        drivetrain.shift(Gear::High);
+        assert_eq!((drivetrain.gear(), drivetrain.config.drive_gear_ratio), (Gear::High, 4.0));
+        let high = spin_up(&mut drivetrain);
+        // Half the reduction puts half the torque on the wheel (less bearing drag)
+        assert!(high > 0.0 && (high / low - 0.5).abs() < 1e-2, "{} vs {}", high, low);
+
+        drivetrain.reset();
+        assert_eq!(drivetrain.config.drive_gear_ratio, 8.0);
+        // A drivetrain without a gearbox ignores shifts
+        let mut direct = SwerveDrivetrain::new(SwerveDrivetrainConfig::default());
+        direct.shift(Gear::High);
+        assert_eq!((direct.gear(), direct.config.drive_gear_ratio), (Gear::Low, 1.0));
+    }
+
+    #[test]
+    fn test_cruise_reports_positive_power_below_unit_efficiency() {
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            report_power: true,
+            ..Default::default()
+        });
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        state.true_state.body_state.velocity[0] = 2.0;
+        drivetrain.sync_wheels_to_body(&mut state);
+        // Motors push lightly against the cruise while drawing 12 V * 20 A
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = 0.2;
+        }
+        state.true_state.battery_state.voltage = 12.0;
+        state.true_state.battery_state.total_current_draw = 20.0;
+        assert!(drivetrain.power_flow().is_none());
+
+        let dt = 0.0005;
+        for step in 0..1000 {
+            let ctx = SimContext { dt, t: step as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+        }
+
+        let power = drivetrain.power_flow().unwrap();
+        assert!(power.mechanical > 0.0, "mechanical power {}", power.mechanical);
+        assert_eq!(power.electrical, 240.0);
+        let efficiency = power.efficiency().unwrap();
+        assert!(efficiency > 0.0 && efficiency < 1.0, "efficiency {}", efficiency);
+
+        drivetrain.reset();
+        assert!(drivetrain.power_flow().is_none());
+    }
+
+    /// Forward speed after releasing a 2 m/s cruise for 0.5 s with the given
+    /// neutral mode and motor torque
+    fn speed_after_release(neutral_mode: NeutralMode, motor_torque: f64) -> f64 {
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            neutral_mode,
+            brake_damping: 0.02,
+            ..Default::default()
+        });
+        let mut tires = TireManager::new();
+        for _ in 0..4 {
+            tires.add_tire(TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0));
+        }
+        let mut state = create_test_state(4);
+        state.true_state.body_state.velocity[0] = 2.0;
+        drivetrain.sync_wheels_to_body(&mut state);
+        for motor in &mut state.true_state.motors {
+            motor.applied_torque = motor_torque;
+        }
+
+        let dt = 0.0005;
+        for step in 0..1000 {
+            let ctx = SimContext { dt, t: step as f64 * dt };
+            tires.step_physics(ctx, &mut state);
+            drivetrain.step_physics(ctx, &mut state);
+        }
+        state.true_state.body_state.velocity[0]
+    }
+
+    #[test]
+    fn test_brake_mode_slows_release_between_coast_and_reverse() {
+        let coast = speed_after_release(NeutralMode::Coast, 0.0);
+        let brake = speed_after_release(NeutralMode::Brake, 0.0);
+        let reverse = speed_after_release(NeutralMode::Brake, -2.0);
+
+        assert!(brake < coast, "brake {} vs coast {}", brake, coast);
+        assert!(reverse < brake, "reverse {} vs brake {}", reverse, brake);
+        assert!(coast > 1.9, "coasting robot slowed to {}", coast);
+    }
+
+    /// Per-step acceleration of a lifted front-left wheel after a step in motor torque
+    fn lifted_wheel_accelerations(config: SwerveDrivetrainConfig, steps: usize) -> Vec<f64> {
+        let mut drivetrain = SwerveDrivetrain::new(SwerveDrivetrainConfig {
+            wheel_viscous_damping: 0.0,
+            ..config
+        });
+        let mut state = create_test_state(4);
+        state.true_state.motors[0].applied_torque = 0.5;
+
+        let dt = 1e-4;
+        (0..steps)
+            .map(|step| {
+                let before = state.true_state.wheel_states[0].driving_angular_velocity;
+                drivetrain.step_physics(SimContext { dt, t: step as f64 * dt }, &mut state);
+                (state.true_state.wheel_states[0].driving_angular_velocity - before) / dt
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compliant_drive_coupling_delays_and_rings() {
+        let rigid = lifted_wheel_accelerations(SwerveDrivetrainConfig::default(), 1000);
+        let compliant = lifted_wheel_accelerations(
+            SwerveDrivetrainConfig {
+                drive_coupling_stiffness: 50.0,
+                drive_coupling_damping: 0.005,
+                drive_motor_inertia: 0.001,
+                ..Default::default()
+            },
+            1000,
+        );
+
+        // The rigid wheel takes the full torque from the first step
+        assert!((rigid[0] - 0.5 / 0.01).abs() < 1e-9);
+        assert!(rigid.iter().all(|a| (a - rigid[0]).abs() < 1e-9));
+
+        // The belt has to wind up first, then overshoots and rings around
+        // the acceleration of the combined inertia
+        assert!(compliant[0] < 0.1 * rigid[0], "first step {}", compliant[0]);
+        let peak = compliant.iter().cloned().fold(0.0, f64::max);
+        assert!(peak > rigid[0], "peak {} never overshot", peak);
+        let mean = 0.5 / 0.011;
+        let crossings = compliant.windows(2).filter(|w| (w[0] - mean) * (w[1] - mean) < 0.0).count();
+        assert!(crossings >= 4, "only {} crossings", crossings);
+    }
 }
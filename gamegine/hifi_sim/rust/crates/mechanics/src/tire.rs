@@ -1,6 +1,18 @@
 use nalgebra as na;
-use simcore::{MechanicsModel, Model, WheelState};
+use simcore::{MechanicsModel, Model, SimState, WheelState};
 
+/// How the tire relaxation parameters are interpreted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RelaxationMode {
+    /// Relaxation lengths are distances (m) rolled before slip builds up, so
+    /// the time constant `length / speed` shrinks as the wheel speeds up
+    #[default]
+    Distance,
+    /// Relaxation lengths are time constants (s), the same at every speed
+    TimeConstant,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct TireConstants {
     pub longitudinal_coefficient_of_friction: f64,
     pub lateral_coefficient_of_friction: f64,
@@ -8,6 +20,18 @@ pub struct TireConstants {
     pub longitudinal_stiffness: f64,
     pub longitudinal_relaxation_length: f64,
     pub lateral_relaxation_length: f64,
+    /// Whether the relaxation lengths are distances or time constants
+    pub relaxation_mode: RelaxationMode,
+    /// Exponent `p` of the combined-slip limit
+    /// `(|Fx|/μx·Fz)^p + (|Fy|/μy·Fz)^p <= 1`; 2 is the friction ellipse.
+    pub friction_exponent: f64,
+    /// Static toe of the module (rad), positive toward +lateral. Offsets the
+    /// slip angle, so a toed wheel pushes sideways even when rolling straight.
+    pub toe_rad: f64,
+    /// Static camber of the module (rad), positive leaning toward +lateral
+    pub camber_rad: f64,
+    /// Lateral camber thrust per radian of camber (N/rad)
+    pub camber_stiffness: f64,
 }
 
 impl TireConstants {
@@ -26,8 +50,42 @@ impl TireConstants {
             longitudinal_stiffness,
             longitudinal_relaxation_length,
             lateral_relaxation_length,
+            relaxation_mode: RelaxationMode::Distance,
+            friction_exponent: 2.0,
+            toe_rad: 0.0,
+            camber_rad: 0.0,
+            camber_stiffness: 0.1 * cornering_stiffness,
         }
-}
+    }
+
+    /// Interpret the relaxation lengths as distances (m) or time constants (s)
+    pub fn with_relaxation_mode(mut self, mode: RelaxationMode) -> Self {
+        self.relaxation_mode = mode;
+        self
+    }
+
+    /// Use a super-ellipse friction limit with exponent `p` (2 is an ellipse)
+    ///
+    /// `p` is clamped to [1, 16]: below 1 the limit is no longer convex, and
+    /// beyond 16 it is already indistinguishable from a rectangle. NaN keeps
+    /// the ellipse.
+    pub fn with_friction_exponent(mut self, p: f64) -> Self {
+        self.friction_exponent = if p.is_nan() { 2.0 } else { p.clamp(1.0, 16.0) };
+        self
+    }
+
+    /// Set the static toe and camber of the module (rad)
+    pub fn with_alignment(mut self, toe_rad: f64, camber_rad: f64) -> Self {
+        self.toe_rad = toe_rad;
+        self.camber_rad = camber_rad;
+        self
+    }
+
+    /// Set the camber thrust stiffness (N/rad)
+    pub fn with_camber_stiffness(mut self, camber_stiffness: f64) -> Self {
+        self.camber_stiffness = camber_stiffness;
+        self
+    }
 }
 
 impl Default for TireConstants {
@@ -39,32 +97,141 @@ impl Default for TireConstants {
             longitudinal_stiffness: 1.0,
             longitudinal_relaxation_length: 1.0,
             lateral_relaxation_length: 1.0,
+            relaxation_mode: RelaxationMode::Distance,
+            friction_exponent: 2.0,
+            toe_rad: 0.0,
+            camber_rad: 0.0,
+            camber_stiffness: 0.1,
         }
     }
 }
 
 pub struct TireManager {
     pub tire_constants: Vec<TireConstants>,
+    /// Track each wheel's slip power every step, read back through
+    /// `slip_power()`. Off by default.
+    pub report_slip_power: bool,
+    /// Slip power of each wheel in the last step, when `report_slip_power` is set
+    slip_power: Vec<f64>,
 }
 
 impl TireManager {
     pub fn new() -> Self {
         TireManager {
             tire_constants: vec![],
+            report_slip_power: false,
+            slip_power: vec![],
         }
     }
 
+    /// Power (W) each wheel dissipated in its contact patch in the last
+    /// step; None unless `report_slip_power` is set
+    pub fn slip_power(&self) -> Option<&[f64]> {
+        self.report_slip_power.then_some(self.slip_power.as_slice())
+    }
+
     pub fn add_tire(&mut self, tire: TireConstants) {
         self.tire_constants.push(tire);
     }
+
+    /// Replace the constants of the tire at `index` (ignored if out of range)
+    pub fn set_tire(&mut self, index: usize, tire: TireConstants) {
+        if let Some(slot) = self.tire_constants.get_mut(index) {
+            *slot = tire;
+        }
+    }
+
+    /// Replace the constants of every tire
+    pub fn set_all_tires(&mut self, tire: TireConstants) {
+        for slot in &mut self.tire_constants {
+            *slot = tire;
+        }
+    }
+
+    /// Each wheel's friction ellipse and current operating point.
+    ///
+    /// Semi-axes are `μ_long·load` and `μ_lat·load` with the load scaled by
+    /// ground contact; forces are those last written to `state`, after the
+    /// ellipse scaling, so each point lies within its ellipse.
+    pub fn friction_ellipses(&self, state: &SimState) -> Vec<FrictionEllipse> {
+        self.tire_constants
+            .iter()
+            .zip(&state.true_state.wheel_states)
+            .map(|(tire, wheel)| {
+                let load = wheel.tire.tire_load * wheel.contact_fraction.clamp(0.0, 1.0);
+                FrictionEllipse {
+                    longitudinal_limit: tire.longitudinal_coefficient_of_friction * load,
+                    lateral_limit: tire.lateral_coefficient_of_friction * load,
+                    longitudinal_force: wheel.tire.longitudinal_force,
+                    lateral_force: wheel.tire.lateral_force,
+                }
+            })
+            .collect()
+    }
+
+    /// Each wheel's current force as a point in its friction ellipse.
+    ///
+    /// Returns `[Fx / (μ_long·load), Fy / (μ_lat·load)]` per wheel, using the
+    /// tire forces last written to `state`. Points on the unit circle are at
+    /// the grip limit; a wheel without load or grip reports the origin.
+    pub fn traction_utilization(&self, state: &SimState) -> Vec<[f64; 2]> {
+        self.friction_ellipses(state)
+            .iter()
+            .map(|ellipse| {
+                if ellipse.longitudinal_limit <= 0.0 || ellipse.lateral_limit <= 0.0 {
+                    return [0.0, 0.0];
+                }
+                [
+                    ellipse.longitudinal_force / ellipse.longitudinal_limit,
+                    ellipse.lateral_force / ellipse.lateral_limit,
+                ]
+            })
+            .collect()
+    }
+}
+
+/// A wheel's friction ellipse and where its tire force sits inside it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrictionEllipse {
+    /// Longitudinal semi-axis, μ_long·load (N)
+    pub longitudinal_limit: f64,
+    /// Lateral semi-axis, μ_lat·load (N)
+    pub lateral_limit: f64,
+    /// Current longitudinal tire force (N)
+    pub longitudinal_force: f64,
+    /// Current lateral tire force (N)
+    pub lateral_force: f64,
+}
+
+impl TireConstants {
+    /// Time constant (s) for slip to relax toward its steady value given the
+    /// configured relaxation `length` and the wheel's longitudinal speed
+    fn relaxation_time_constant(&self, length: f64, longitudinal_velocity: f64) -> f64 {
+        match self.relaxation_mode {
+            RelaxationMode::Distance => length / longitudinal_velocity.abs().max(1e-6),
+            RelaxationMode::TimeConstant => length,
+        }
+    }
 }
 
 impl Model for TireManager {
     fn reset(&mut self) {
-        // No internal state to reset in this simple model
+        self.slip_power.clear();
     }
 }
 
+/// Power (W) dissipated in a wheel's contact patch: each tire force times the
+/// velocity at which the patch slides along it.
+///
+/// The longitudinal sliding speed is the difference between the tread speed
+/// and the ground speed, the lateral one is the ground speed across the wheel.
+/// A cleanly rolling wheel transmits force without sliding and dissipates
+/// nothing, however large the force.
+pub fn slip_power(wheel: &WheelState) -> f64 {
+    let longitudinal_slip = wheel.driving_angular_velocity * wheel.wheel_radius - wheel.longitudinal_translational_velocity;
+    (wheel.tire.longitudinal_force * longitudinal_slip).abs() + (wheel.tire.lateral_force * wheel.lateral_translational_velocity).abs()
+}
+
 fn update_slip_angle(wheel: &mut WheelState, tire: &TireConstants, dt: f64) {
     // Low-speed stability: when velocity is very small, zero out slip angle
     // to prevent numerical instability from atan2(small, small)
@@ -86,11 +253,12 @@ fn update_slip_angle(wheel: &mut WheelState, tire: &TireConstants, dt: f64) {
     } else {
         wheel.longitudinal_translational_velocity
     };
-    let actual_slip_angle = wheel.lateral_translational_velocity.atan2(v_long_clamped);
+    // A toed wheel points `toe_rad` off the module heading
+    let actual_slip_angle = wheel.lateral_translational_velocity.atan2(v_long_clamped) - tire.toe_rad;
     if tire.lateral_relaxation_length == 0.0 {
         wheel.tire.slip_angle = actual_slip_angle;
     } else {
-        let relaxation_time_constant = (tire.lateral_relaxation_length) / (wheel.longitudinal_translational_velocity.abs().max(1e-6));
+        let relaxation_time_constant = tire.relaxation_time_constant(tire.lateral_relaxation_length, wheel.longitudinal_translational_velocity);
         let update_rate = (actual_slip_angle - wheel.tire.slip_angle) / relaxation_time_constant;
         wheel.tire.slip_angle += update_rate * dt;
     }
@@ -115,7 +283,7 @@ fn update_slip_ratio(wheel: &mut WheelState, tire: &TireConstants, dt: f64) {
     if tire.longitudinal_relaxation_length == 0.0 {
         wheel.tire.slip_ratio = actual_slip_ratio;
     } else {
-        let relaxation_time_constant = (tire.longitudinal_relaxation_length) / (wheel.longitudinal_translational_velocity.abs().max(1e-6));
+        let relaxation_time_constant = tire.relaxation_time_constant(tire.longitudinal_relaxation_length, wheel.longitudinal_translational_velocity);
         let update_rate = (actual_slip_ratio - wheel.tire.slip_ratio) / relaxation_time_constant;
         wheel.tire.slip_ratio += update_rate * dt;
     }
@@ -135,6 +303,14 @@ fn get_fiala_longitudinal_force(wheel: &WheelState, tire: &TireConstants) -> f64
     }
 }
 
+/// Lateral thrust from static camber, generated only while the tire rolls
+fn get_camber_thrust(wheel: &WheelState, tire: &TireConstants) -> f64 {
+    if wheel.longitudinal_translational_velocity.abs() < 0.01 || wheel.tire.tire_load <= 0.0 {
+        return 0.0;
+    }
+    tire.camber_stiffness * tire.camber_rad
+}
+
 fn get_fiala_lateral_force(wheel: &WheelState, tire: &TireConstants) -> f64 {
     let slip_angle = wheel.tire.slip_angle;
     let tire_load = wheel.tire.tire_load;
@@ -149,8 +325,29 @@ fn get_fiala_lateral_force(wheel: &WheelState, tire: &TireConstants) -> f64 {
     }
 }
 
+/// Limit the combined force to the friction ellipse.
+///
+/// The ellipse has independent semi-axes `μ_long·load` and `μ_lat·load`, so a
+/// tire that grips harder along its rolling direction saturates at a larger
+/// longitudinal force than lateral force. With a friction exponent other than
+/// 2 the limit is a super-ellipse; forces beyond it are scaled back radially.
 fn elliptically_scale_forces(longitudinal_force: f64, lateral_force: f64, tire_load: f64, tire: &TireConstants) -> (f64, f64) {
-    let combined = (longitudinal_force / (tire.longitudinal_coefficient_of_friction * tire_load)).hypot(lateral_force / (tire.lateral_coefficient_of_friction * tire_load));
+    let longitudinal_limit = tire.longitudinal_coefficient_of_friction * tire_load;
+    let lateral_limit = tire.lateral_coefficient_of_friction * tire_load;
+    // A degenerate ellipse (no load or no grip on an axis) transmits no force on that axis
+    if longitudinal_limit <= 0.0 || lateral_limit <= 0.0 {
+        let fx = if longitudinal_limit > 0.0 { longitudinal_force.clamp(-longitudinal_limit, longitudinal_limit) } else { 0.0 };
+        let fy = if lateral_limit > 0.0 { lateral_force.clamp(-lateral_limit, lateral_limit) } else { 0.0 };
+        return (fx, fy);
+    }
+
+    let (x, y) = (longitudinal_force / longitudinal_limit, lateral_force / lateral_limit);
+    let p = tire.friction_exponent;
+    let combined = if p == 2.0 {
+        x.hypot(y)
+    } else {
+        (x.abs().powf(p) + y.abs().powf(p)).powf(1.0 / p)
+    };
     if combined > 1.0 {
         (longitudinal_force / combined, lateral_force / combined)
     } else {
@@ -167,16 +364,304 @@ impl MechanicsModel for TireManager {
             update_slip_angle(wheel, tire, dt);
             update_slip_ratio(wheel, tire, dt);
 
+            // A lifted wheel produces no force and spins freely
+            let contact = wheel.contact_fraction.clamp(0.0, 1.0);
+            if contact <= 0.0 {
+                wheel.tire.longitudinal_force = 0.0;
+                wheel.tire.lateral_force = 0.0;
+                continue;
+            }
+
             let (scaled_longitudinal_force, scaled_lateral_force) = elliptically_scale_forces(
                 get_fiala_longitudinal_force(wheel, tire),
-                get_fiala_lateral_force(wheel, tire),
+                get_fiala_lateral_force(wheel, tire) + get_camber_thrust(wheel, tire),
                 wheel.tire.tire_load,
                 tire,
             );
 
-            state.true_state.wheel_states[i].tire.longitudinal_force = scaled_longitudinal_force;
-            state.true_state.wheel_states[i].tire.lateral_force = scaled_lateral_force;
+            state.true_state.wheel_states[i].tire.longitudinal_force = scaled_longitudinal_force * contact;
+            state.true_state.wheel_states[i].tire.lateral_force = scaled_lateral_force * contact;
+
+        }
+
+        if self.report_slip_power {
+            self.slip_power.clear();
+            self.slip_power.extend(state.true_state.wheel_states.iter().take(self.tire_constants.len()).map(slip_power));
+        }
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simcore::{SimContext, SimState, TireState};
+
+    fn spinning_wheel() -> WheelState {
+        WheelState {
+            driving_angular_velocity: 24.0, // 1.2 m/s at the contact patch
+            wheel_radius: 0.05,
+            turning_angular_velocity: 0.0,
+            longitudinal_translational_velocity: 1.0,
+            lateral_translational_velocity: 0.0,
+            tire: TireState {
+                slip_angle: 0.0,
+                slip_ratio: 0.0,
+                longitudinal_force: 0.0,
+                lateral_force: 0.0,
+                tire_load: 100.0,
+            },
+            angle: 0.0,
+            contact_fraction: 1.0,
         }
     }
+
+    fn grippy_tire() -> TireConstants {
+        TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.0)
+    }
+
+    #[test]
+    fn test_lower_mu_reduces_tractive_force() {
+        let mut tires = TireManager::new();
+        tires.add_tire(grippy_tire());
+        tires.add_tire(grippy_tire());
+
+        let mut state = SimState::default();
+        state.true_state.wheel_states = vec![spinning_wheel(), spinning_wheel()];
+        let ctx = SimContext { dt: 0.001, t: 0.0 };
+
+        tires.step_physics(ctx, &mut state);
+        let baseline_total: f64 = state.true_state.wheel_states.iter()
+            .map(|w| w.tire.longitudinal_force)
+            .sum();
+
+        // Worn tire on module 1
+        tires.set_tire(1, TireConstants::new(0.5, 0.5, 2000.0, 2000.0, 0.0, 0.0));
+        tires.step_physics(ctx, &mut state);
+
+        let healthy = state.true_state.wheel_states[0].tire.longitudinal_force;
+        let worn = state.true_state.wheel_states[1].tire.longitudinal_force;
+        let worn_total = healthy + worn;
+
+        assert!(worn.abs() < healthy.abs(), "worn {} should be weaker than healthy {}", worn, healthy);
+        assert!(worn_total.abs() < baseline_total.abs());
+    }
+
+    #[test]
+    fn test_asymmetric_mu_saturates_per_axis() {
+        // Studded wheel: twice the grip along the rolling direction
+        let studded = TireConstants::new(1.2, 0.6, 2000.0, 2000.0, 0.0, 0.0);
+        let load = 100.0;
+
+        // Pure longitudinal saturation at μ_long·load
+        let (fx, fy) = elliptically_scale_forces(500.0, 0.0, load, &studded);
+        assert!((fx - 120.0).abs() < 1e-9, "fx = {}", fx);
+        assert!(fy.abs() < 1e-9);
+
+        // Pure lateral saturation at μ_lat·load
+        let (fx, fy) = elliptically_scale_forces(0.0, -500.0, load, &studded);
+        assert!(fx.abs() < 1e-9);
+        assert!((fy + 60.0).abs() < 1e-9, "fy = {}", fy);
+
+        // Combined saturation lands on the ellipse, not a circle
+        let (fx, fy) = elliptically_scale_forces(500.0, 500.0, load, &studded);
+        let on_ellipse = (fx / 120.0).hypot(fy / 60.0);
+        assert!((on_ellipse - 1.0).abs() < 1e-9);
+
+        // Same result end-to-end through the tire model under heavy slip
+        let mut tires = TireManager::new();
+        tires.add_tire(studded);
+        tires.add_tire(studded);
+        let mut sliding = spinning_wheel();
+        sliding.lateral_translational_velocity = 0.0;
+        sliding.driving_angular_velocity = 200.0;
+        let mut skidding = spinning_wheel();
+        skidding.driving_angular_velocity = 20.0; // rolling with the ground
+        skidding.lateral_translational_velocity = 5.0;
+
+        let mut state = SimState::default();
+        state.true_state.wheel_states = vec![sliding, skidding];
+        tires.step_physics(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+
+        let fx = state.true_state.wheel_states[0].tire.longitudinal_force;
+        let fy = state.true_state.wheel_states[1].tire.lateral_force;
+        assert!((fx.abs() - 120.0).abs() < 1e-6, "fx = {}", fx);
+        assert!((fy.abs() - 60.0).abs() < 1e-6, "fy = {}", fy);
+    }
+
+    #[test]
+    fn test_zero_load_or_zero_mu_transmits_no_force() {
+        // Zero load collapses the ellipse on both axes
+        let (fx, fy) = elliptically_scale_forces(10.0, 10.0, 0.0, &grippy_tire());
+        assert_eq!((fx, fy), (0.0, 0.0));
+
+        // Zero mu on one axis zeroes that axis and clamps the other to its limit
+        let no_lateral_grip = TireConstants { lateral_coefficient_of_friction: 0.0, ..grippy_tire() };
+        let limit = no_lateral_grip.longitudinal_coefficient_of_friction * 100.0;
+        let (fx, fy) = elliptically_scale_forces(2.0 * limit, 10.0, 100.0, &no_lateral_grip);
+        assert_eq!((fx, fy), (limit, 0.0));
+        let (fx, fy) = elliptically_scale_forces(0.5 * limit, 10.0, 100.0, &no_lateral_grip);
+        assert_eq!((fx, fy), (0.5 * limit, 0.0));
+    }
+
+    #[test]
+    fn test_time_constant_relaxation_is_speed_independent() {
+        // Fraction of the way the slip angle moves toward its target in one step
+        let response = |mode: RelaxationMode, speed: f64| {
+            let tire = TireConstants::new(1.0, 1.0, 2000.0, 2000.0, 0.0, 0.2).with_relaxation_mode(mode);
+            let mut wheel = spinning_wheel();
+            wheel.longitudinal_translational_velocity = speed;
+            wheel.lateral_translational_velocity = 0.1 * speed;
+            update_slip_angle(&mut wheel, &tire, 0.001);
+            wheel.tire.slip_angle / 0.1_f64.atan()
+        };
+
+        let slow = response(RelaxationMode::TimeConstant, 1.0);
+        let fast = response(RelaxationMode::TimeConstant, 4.0);
+        assert!((slow - 0.005).abs() < 1e-12 && (fast - slow).abs() < 1e-12);
+
+        // Rolling four times faster covers the relaxation length four times sooner
+        let slow = response(RelaxationMode::Distance, 1.0);
+        let fast = response(RelaxationMode::Distance, 4.0);
+        assert!((fast - 4.0 * slow).abs() < 1e-12, "slow {} fast {}", slow, fast);
+    }
+
+    #[test]
+    fn test_traction_circle_axes() {
+        let mut tires = TireManager::new();
+        tires.add_tire(grippy_tire());
+        tires.add_tire(grippy_tire());
+
+        // Wheel 0 locked while rolling forward; wheel 1 rolling but sliding sideways
+        let mut braking = spinning_wheel();
+        braking.driving_angular_velocity = 0.0;
+        let mut cornering = spinning_wheel();
+        cornering.driving_angular_velocity = 20.0;
+        cornering.lateral_translational_velocity = 0.5;
+
+        let mut state = SimState::default();
+        state.true_state.wheel_states = vec![braking, cornering];
+        tires.step_physics(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+        let points = tires.traction_utilization(&state);
+
+        let [brake_x, brake_y] = points[0];
+        assert!(brake_x.abs() > 0.5 && brake_y.abs() < 1e-12, "braking point {:?}", points[0]);
+        let [corner_x, corner_y] = points[1];
+        assert!(corner_y.abs() > 0.5 && corner_x.abs() < 1e-12, "cornering point {:?}", points[1]);
+        for &[x, y] in &points {
+            assert!(x.hypot(y) <= 1.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_friction_ellipse_axes_match_tire_grip() {
+        let tire = TireConstants::new(1.2, 0.6, 2000.0, 2000.0, 0.0, 0.0);
+        let mut tires = TireManager::new();
+        tires.add_tire(tire);
+
+        // Braking hard while sliding sideways saturates the combined force
+        let mut wheel = spinning_wheel();
+        wheel.driving_angular_velocity = 0.0;
+        wheel.lateral_translational_velocity = 0.5;
+        let load = wheel.tire.tire_load;
+
+        let mut state = SimState::default();
+        state.true_state.wheel_states = vec![wheel];
+        tires.step_physics(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+        let ellipse = tires.friction_ellipses(&state)[0];
+
+        assert!((ellipse.longitudinal_limit - tire.longitudinal_coefficient_of_friction * load).abs() < 1e-9);
+        assert!((ellipse.lateral_limit - tire.lateral_coefficient_of_friction * load).abs() < 1e-9);
+        let utilization = (ellipse.longitudinal_force / ellipse.longitudinal_limit)
+            .hypot(ellipse.lateral_force / ellipse.lateral_limit);
+        assert!(utilization > 0.5 && utilization <= 1.0 + 1e-9, "utilization {}", utilization);
+    }
+
+    #[test]
+    fn test_friction_exponent_shapes_combined_limit() {
+        let ellipse = TireConstants::new(1.2, 0.6, 2000.0, 2000.0, 0.0, 0.0);
+        let load = 100.0;
+
+        // An explicit p = 2 is the friction ellipse
+        let explicit = ellipse.with_friction_exponent(2.0);
+        for &(fx, fy) in &[(500.0, 500.0), (300.0, -40.0), (-90.0, 20.0)] {
+            let (ex, ey) = elliptically_scale_forces(fx, fy, load, &ellipse);
+            let (px, py) = elliptically_scale_forces(fx, fy, load, &explicit);
+            assert_eq!((ex, ey), (px, py));
+        }
+
+        // Near the longitudinal axis a squarer limit keeps more of the force
+        let squarer = ellipse.with_friction_exponent(4.0);
+        let (ex, ey) = elliptically_scale_forces(500.0, 80.0, load, &ellipse);
+        let (sx, sy) = elliptically_scale_forces(500.0, 80.0, load, &squarer);
+        assert!(sx.hypot(sy) > ex.hypot(ey));
+        let on_limit = ((sx / 120.0).abs().powi(4) + (sy / 60.0).abs().powi(4)).powf(0.25);
+        assert!((on_limit - 1.0).abs() < 1e-9);
+
+        // Pure-axis saturation is unchanged
+        let (sx, sy) = elliptically_scale_forces(500.0, 0.0, load, &squarer);
+        assert!((sx - 120.0).abs() < 1e-9 && sy == 0.0);
+
+        // Out-of-range exponents are clamped; NaN falls back to the ellipse
+        assert_eq!(ellipse.with_friction_exponent(0.0).friction_exponent, 1.0);
+        assert_eq!(ellipse.with_friction_exponent(-3.0).friction_exponent, 1.0);
+        assert_eq!(ellipse.with_friction_exponent(f64::INFINITY).friction_exponent, 16.0);
+        assert_eq!(ellipse.with_friction_exponent(f64::NAN).friction_exponent, 2.0);
+    }
+
+    #[test]
+    fn test_toe_and_camber_push_straight_rolling_wheel_sideways() {
+        let lateral_force = |tire: TireConstants| {
+            let mut tires = TireManager::new();
+            tires.add_tire(tire);
+            let mut state = SimState::default();
+            // Free rolling: no longitudinal slip to eat into the friction ellipse
+            let wheel = WheelState { driving_angular_velocity: 20.0, ..spinning_wheel() };
+            state.true_state.wheel_states = vec![wheel];
+            tires.step_physics(SimContext { dt: 0.001, t: 0.0 }, &mut state);
+            state.true_state.wheel_states[0].tire.lateral_force
+        };
+
+        // Rolling straight with no alignment gives no side force
+        assert_eq!(lateral_force(grippy_tire()), 0.0);
+
+        // Toe alone: slip angle of -toe, so the tire pushes toward the toe
+        let toed = lateral_force(grippy_tire().with_alignment(0.01, 0.0));
+        assert!(toed > 0.0);
+        assert!((toed - 2000.0 * 0.01_f64.tan()).abs() < 0.1 * toed);
+
+        // Camber alone adds its thrust, and stacks on top of toe
+        let cambered = lateral_force(grippy_tire().with_alignment(0.0, 0.05).with_camber_stiffness(300.0));
+        assert!((cambered - 300.0 * 0.05).abs() < 1e-9);
+        let both = lateral_force(grippy_tire().with_alignment(0.01, 0.05).with_camber_stiffness(300.0));
+        assert!((both - (toed + cambered)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slipping_wheel_dissipates_more_than_rolling_wheel() {
+        // 1.0 m/s ground speed with the same 80 N of traction: rolling
+        // cleanly at 20 rad/s, spinning at 40 rad/s
+        let wheel = |omega: f64, slip_ratio: f64| {
+            let tire = TireState { slip_ratio, longitudinal_force: -80.0, ..spinning_wheel().tire };
+            WheelState { driving_angular_velocity: omega, tire, ..spinning_wheel() }
+        };
+        let (rolling, spinning) = (wheel(20.0, 0.0), wheel(40.0, 1.0));
+        assert_eq!(slip_power(&rolling), 0.0);
+        assert!((slip_power(&spinning) - 80.0).abs() < 1e-9);
+
+        // Reported from step_physics only once enabled
+        let mut tires = TireManager::new();
+        tires.add_tire(grippy_tire());
+        tires.add_tire(grippy_tire());
+        let mut state = SimState::default();
+        state.true_state.wheel_states = vec![rolling, spinning];
+        let ctx = SimContext { dt: 0.001, t: 0.0 };
+        tires.step_physics(ctx, &mut state);
+        assert!(tires.slip_power().is_none());
+
+        tires.report_slip_power = true;
+        tires.step_physics(ctx, &mut state);
+        let power = tires.slip_power().unwrap();
+        assert_eq!(power.len(), 2);
+        assert!(power[1] > power[0]);
+    }
 }
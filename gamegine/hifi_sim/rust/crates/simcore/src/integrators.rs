@@ -65,6 +65,86 @@ impl Integrator for RungeKutta4 {
     }
 }
 
+/// Selectable method for integrating a second-order state (position and
+/// velocity) under an acceleration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntegratorKind {
+    /// Update velocity first, then position with the new velocity
+    #[default]
+    SemiImplicitEuler,
+    /// Classic fourth-order Runge-Kutta
+    RungeKutta4,
+}
+
+impl IntegratorKind {
+    /// Parse "euler"/"semi_implicit_euler" or "rk4"/"runge_kutta4" (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "euler" | "semi_implicit_euler" => Some(IntegratorKind::SemiImplicitEuler),
+            "rk4" | "runge_kutta4" => Some(IntegratorKind::RungeKutta4),
+            _ => None,
+        }
+    }
+
+    /// Name accepted by `parse`
+    pub fn name(self) -> &'static str {
+        match self {
+            IntegratorKind::SemiImplicitEuler => "euler",
+            IntegratorKind::RungeKutta4 => "rk4",
+        }
+    }
+
+    /// Advance `(position, velocity)` by `dt` under `acceleration(position, velocity)`
+    pub fn advance(
+        self,
+        position: f64,
+        velocity: f64,
+        dt: f64,
+        acceleration: impl Fn(f64, f64) -> f64,
+    ) -> (f64, f64) {
+        match self {
+            IntegratorKind::SemiImplicitEuler => {
+                let new_velocity = velocity + acceleration(position, velocity) * dt;
+                (position + new_velocity * dt, new_velocity)
+            }
+            IntegratorKind::RungeKutta4 => {
+                let half = 0.5 * dt;
+                let (x1, v1) = (velocity, acceleration(position, velocity));
+                let (x2, v2) = (velocity + half * v1, acceleration(position + half * x1, velocity + half * v1));
+                let (x3, v3) = (velocity + half * v2, acceleration(position + half * x2, velocity + half * v2));
+                let (x4, v4) = (velocity + dt * v3, acceleration(position + dt * x3, velocity + dt * v3));
+                (
+                    position + dt / 6.0 * (x1 + 2.0 * x2 + 2.0 * x3 + x4),
+                    velocity + dt / 6.0 * (v1 + 2.0 * v2 + 2.0 * v3 + v4),
+                )
+            }
+        }
+    }
+}
+
+/// Move `pose` [x, y, heading] along a robot-frame twist [dx, dy, dtheta]
+/// using the SE(2) exponential map
+///
+/// The twist is followed as a constant-curvature arc, so integrating
+/// constant chassis speeds step by step lands exactly on the arc instead of
+/// cutting its corners.
+pub fn integrate_twist(pose: [f64; 3], twist: [f64; 3]) -> [f64; 3] {
+    let [dx, dy, dtheta] = twist;
+    // sin(t)/t and (1 - cos(t))/t, by Taylor series near zero
+    let (s, c) = if dtheta.abs() < 1e-9 {
+        (1.0 - dtheta * dtheta / 6.0, 0.5 * dtheta)
+    } else {
+        (dtheta.sin() / dtheta, (1.0 - dtheta.cos()) / dtheta)
+    };
+    let (local_x, local_y) = (dx * s - dy * c, dx * c + dy * s);
+    let (sin, cos) = pose[2].sin_cos();
+    [
+        pose[0] + local_x * cos - local_y * sin,
+        pose[1] + local_x * sin + local_y * cos,
+        pose[2] + dtheta,
+    ]
+}
+
 /// Fixed-timestep integration wrapper that accumulates time and
 /// runs multiple sub-steps if needed.
 #[derive(Debug, Clone)]
@@ -106,6 +186,7 @@ impl<I: Integrator> FixedTimestepIntegrator<I> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::PI;
     use crate::{BodyState, TrueState, ActuatorInput, SensorBus, BatteryState};
 
     fn create_test_state() -> SimState {
@@ -141,6 +222,35 @@ mod tests {
         assert!((state.true_state.body_state.orientation[2] - 0.05).abs() < 1e-9);
     }
 
+    #[test]
+    fn test_rk4_tracks_harmonic_oscillator_closer_than_euler() {
+        // x'' = -x from (1, 0): x(t) = cos(t)
+        let run = |kind: IntegratorKind| {
+            let (mut x, mut v) = (1.0, 0.0);
+            for _ in 0..100 {
+                (x, v) = kind.advance(x, v, 0.1, |x, _| -x);
+            }
+            (x - 10.0_f64.cos()).abs()
+        };
+
+        assert!(run(IntegratorKind::RungeKutta4) < 1e-5);
+        assert!(run(IntegratorKind::RungeKutta4) < run(IntegratorKind::SemiImplicitEuler));
+        assert_eq!(IntegratorKind::parse("RK4"), Some(IntegratorKind::RungeKutta4));
+        assert_eq!(IntegratorKind::parse(IntegratorKind::SemiImplicitEuler.name()), Some(IntegratorKind::SemiImplicitEuler));
+    }
+
+    #[test]
+    fn test_twist_follows_the_arc() {
+        // Quarter turn on a 1 m radius arc, starting from a pose facing +y
+        let [x, y, heading] = integrate_twist([1.0, 0.0, PI / 2.0], [PI / 2.0, 0.0, PI / 2.0]);
+        assert!((x - 0.0).abs() < 1e-12 && (y - 1.0).abs() < 1e-12, "({}, {})", x, y);
+        assert!((heading - PI).abs() < 1e-12);
+
+        // Pure translation takes the small-angle branch
+        let [x, y, _] = integrate_twist([0.0; 3], [0.5, -0.25, 0.0]);
+        assert_eq!((x, y), (0.5, -0.25));
+    }
+
     #[test]
     fn test_fixed_timestep_accumulator() {
         let integrator = FixedTimestepIntegrator::new(SemiImplicitEuler, 0.01);
@@ -1,6 +1,18 @@
 
 pub mod traits;
 pub mod integrators;
+pub mod time;
+pub mod logging;
+pub mod scenario;
+pub mod noise;
+pub mod sweep;
+pub mod replay;
 
 pub use traits::*;
 pub use integrators::*;
+pub use time::*;
+pub use logging::*;
+pub use scenario::*;
+pub use noise::*;
+pub use sweep::*;
+pub use replay::*;
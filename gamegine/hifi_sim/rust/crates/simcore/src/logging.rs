@@ -0,0 +1,111 @@
+//! Recorders for time series captured during batch runs
+
+use crate::WheelState;
+
+/// Per-wheel time series, one row per recorded step and one column per wheel
+///
+/// Batch runs record a row before each step, so row k holds the wheel state
+/// step k starts from and the first row is the initial state.
+#[derive(Debug, Clone, Default)]
+pub struct WheelTrace {
+    pub slip_ratio: Vec<Vec<f64>>,
+    pub slip_angle: Vec<Vec<f64>>,
+    pub longitudinal_force: Vec<Vec<f64>>,
+    pub lateral_force: Vec<Vec<f64>>,
+    pub angular_velocity: Vec<Vec<f64>>,
+}
+
+impl WheelTrace {
+    /// Create an empty trace with room for `n_steps` rows
+    pub fn with_capacity(n_steps: usize) -> Self {
+        WheelTrace {
+            slip_ratio: Vec::with_capacity(n_steps),
+            slip_angle: Vec::with_capacity(n_steps),
+            longitudinal_force: Vec::with_capacity(n_steps),
+            lateral_force: Vec::with_capacity(n_steps),
+            angular_velocity: Vec::with_capacity(n_steps),
+        }
+    }
+
+    /// Append one row from the current wheel states
+    pub fn record(&mut self, wheels: &[WheelState]) {
+        self.slip_ratio.push(wheels.iter().map(|w| w.tire.slip_ratio).collect());
+        self.slip_angle.push(wheels.iter().map(|w| w.tire.slip_angle).collect());
+        self.longitudinal_force.push(wheels.iter().map(|w| w.tire.longitudinal_force).collect());
+        self.lateral_force.push(wheels.iter().map(|w| w.tire.lateral_force).collect());
+        self.angular_velocity.push(wheels.iter().map(|w| w.driving_angular_velocity).collect());
+    }
+
+    /// Number of recorded steps
+    pub fn len(&self) -> usize {
+        self.angular_velocity.len()
+    }
+
+    /// Check if nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.angular_velocity.is_empty()
+    }
+
+    /// Append the rows of a later trace
+    pub fn append(&mut self, mut other: WheelTrace) {
+        self.slip_ratio.append(&mut other.slip_ratio);
+        self.slip_angle.append(&mut other.slip_angle);
+        self.longitudinal_force.append(&mut other.longitudinal_force);
+        self.lateral_force.append(&mut other.lateral_force);
+        self.angular_velocity.append(&mut other.angular_velocity);
+    }
+}
+
+/// Largest magnitude of each per-wheel quantity seen over a run, one entry
+/// per wheel. A cheap alternative to `WheelTrace` when only extremes matter.
+#[derive(Debug, Clone, Default)]
+pub struct WheelPeaks {
+    pub longitudinal_force: Vec<f64>,
+    pub lateral_force: Vec<f64>,
+    pub slip_ratio: Vec<f64>,
+    pub slip_angle: Vec<f64>,
+}
+
+impl WheelPeaks {
+    /// Fold the current wheel states into the peaks
+    pub fn record(&mut self, wheels: &[WheelState]) {
+        for (i, wheel) in wheels.iter().enumerate() {
+            Self::raise(&mut self.longitudinal_force, i, wheel.tire.longitudinal_force);
+            Self::raise(&mut self.lateral_force, i, wheel.tire.lateral_force);
+            Self::raise(&mut self.slip_ratio, i, wheel.tire.slip_ratio);
+            Self::raise(&mut self.slip_angle, i, wheel.tire.slip_angle);
+        }
+    }
+
+    /// Number of wheels seen
+    pub fn len(&self) -> usize {
+        self.slip_ratio.len()
+    }
+
+    /// Check if nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.slip_ratio.is_empty()
+    }
+
+    /// Combine with the peaks of a later run
+    pub fn merge(&mut self, other: &WheelPeaks) {
+        let pairs = [
+            (&mut self.longitudinal_force, &other.longitudinal_force),
+            (&mut self.lateral_force, &other.lateral_force),
+            (&mut self.slip_ratio, &other.slip_ratio),
+            (&mut self.slip_angle, &other.slip_angle),
+        ];
+        for (peaks, later) in pairs {
+            for (i, &value) in later.iter().enumerate() {
+                Self::raise(peaks, i, value);
+            }
+        }
+    }
+
+    fn raise(peaks: &mut Vec<f64>, wheel: usize, value: f64) {
+        if peaks.len() <= wheel {
+            peaks.resize(wheel + 1, 0.0);
+        }
+        peaks[wheel] = peaks[wheel].max(value.abs());
+    }
+}
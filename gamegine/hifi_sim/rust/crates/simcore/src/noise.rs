@@ -0,0 +1,305 @@
+//! Seeded sensor noise and dropout
+//!
+//! Each noise source (encoders, IMU, battery voltage sense) draws from its own
+//! deterministic random stream, so one source can be reseeded for a
+//! controlled experiment without disturbing the noise on the others. Seeds are
+//! either set per source or derived from a single base seed.
+//!
+//! A source can also drop updates at random, as a CAN timeout or a vision
+//! camera losing its target would. A dropped update leaves the previous
+//! reading on the bus and marks the source invalid until its next update.
+
+use crate::{SensorBus, TrueState};
+
+/// A sensor whose readings carry independent noise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseSource {
+    /// Wheel speed and steer angle encoders
+    Encoder,
+    /// Gyro orientation and angular rate
+    Imu,
+    /// Battery voltage sense
+    Battery,
+}
+
+impl NoiseSource {
+    pub const ALL: [NoiseSource; 3] = [NoiseSource::Encoder, NoiseSource::Imu, NoiseSource::Battery];
+
+    /// Lowercase name, as accepted by `parse`
+    pub fn name(self) -> &'static str {
+        match self {
+            NoiseSource::Encoder => "encoder",
+            NoiseSource::Imu => "imu",
+            NoiseSource::Battery => "battery",
+        }
+    }
+
+    /// Parse a source name ("encoder", "imu" or "battery")
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.name() == name)
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// splitmix64 step: advances `state` and returns a well-mixed output
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Seed for every noise source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoiseSeeds {
+    seeds: [u64; 3],
+}
+
+impl NoiseSeeds {
+    /// Derive a distinct seed for each source from one base seed
+    pub fn from_base(base: u64) -> Self {
+        let mut state = base;
+        NoiseSeeds { seeds: [(); 3].map(|_| splitmix64(&mut state)) }
+    }
+
+    /// Replace the seed of one source
+    pub fn with_seed(mut self, source: NoiseSource, seed: u64) -> Self {
+        self.seeds[source.index()] = seed;
+        self
+    }
+
+    /// Seed of one source
+    pub fn get(&self, source: NoiseSource) -> u64 {
+        self.seeds[source.index()]
+    }
+}
+
+/// Deterministic normal random stream
+#[derive(Debug, Clone, Copy)]
+pub struct NoiseRng(u64);
+
+impl NoiseRng {
+    /// Stream starting from `seed`
+    pub fn new(seed: u64) -> Self {
+        NoiseRng(seed)
+    }
+
+    /// Uniform sample in (0, 1]
+    fn uniform(&mut self) -> f64 {
+        ((splitmix64(&mut self.0) >> 11) as f64 + 1.0) / (1u64 << 53) as f64
+    }
+
+    /// True with probability `probability`
+    fn chance(&mut self, probability: f64) -> bool {
+        probability > 0.0 && self.uniform() <= probability
+    }
+
+    /// Standard normal sample (Box-Muller)
+    pub fn gaussian(&mut self) -> f64 {
+        let (u1, u2) = (self.uniform(), self.uniform());
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+/// Fills the sensor bus from the true state with seeded Gaussian noise and
+/// random dropouts
+#[derive(Debug, Clone)]
+pub struct SensorNoise {
+    /// Encoder noise on wheel speed (rad/s) and steer angle (rad)
+    pub encoder_std: f64,
+    /// IMU noise on orientation (rad) and angular rate (rad/s)
+    pub imu_std: f64,
+    /// Battery voltage sense noise (V)
+    pub battery_std: f64,
+    /// Probability that an encoder update is dropped
+    pub encoder_dropout: f64,
+    /// Probability that an IMU update is dropped
+    pub imu_dropout: f64,
+    /// Probability that a battery voltage update is dropped
+    pub battery_dropout: f64,
+    seeds: NoiseSeeds,
+    streams: [NoiseRng; 3],
+    /// Separate per-source streams for dropouts, so enabling dropout does
+    /// not change the noise drawn on the updates that get through
+    dropout_streams: [NoiseRng; 3],
+}
+
+impl SensorNoise {
+    /// Noise-free sensors with the given seeds
+    pub fn new(seeds: NoiseSeeds) -> Self {
+        SensorNoise {
+            encoder_std: 0.0,
+            imu_std: 0.0,
+            battery_std: 0.0,
+            encoder_dropout: 0.0,
+            imu_dropout: 0.0,
+            battery_dropout: 0.0,
+            seeds,
+            streams: seeds.seeds.map(NoiseRng),
+            dropout_streams: Self::dropout_streams(seeds),
+        }
+    }
+
+    fn dropout_streams(seeds: NoiseSeeds) -> [NoiseRng; 3] {
+        seeds.seeds.map(|mut seed| NoiseRng(splitmix64(&mut seed)))
+    }
+
+    /// Standard deviation of each source's noise
+    pub fn with_std_devs(mut self, encoder_std: f64, imu_std: f64, battery_std: f64) -> Self {
+        self.encoder_std = encoder_std.max(0.0);
+        self.imu_std = imu_std.max(0.0);
+        self.battery_std = battery_std.max(0.0);
+        self
+    }
+
+    /// Probability that each source drops an update, clamped to [0, 1]
+    pub fn with_dropout(mut self, encoder: f64, imu: f64, battery: f64) -> Self {
+        self.encoder_dropout = encoder.clamp(0.0, 1.0);
+        self.imu_dropout = imu.clamp(0.0, 1.0);
+        self.battery_dropout = battery.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Seeds in effect
+    pub fn seeds(&self) -> NoiseSeeds {
+        self.seeds
+    }
+
+    /// Use new seeds and restart every stream
+    pub fn reseed(&mut self, seeds: NoiseSeeds) {
+        self.seeds = seeds;
+        self.reset();
+    }
+
+    /// Restart every stream from its seed, replaying the same noise
+    pub fn reset(&mut self) {
+        self.streams = self.seeds.seeds.map(NoiseRng);
+        self.dropout_streams = Self::dropout_streams(self.seeds);
+    }
+
+    /// Draw one noise sample from `source` scaled by `std`
+    fn sample(&mut self, source: NoiseSource, std: f64) -> f64 {
+        if std == 0.0 {
+            return 0.0;
+        }
+        std * self.streams[source.index()].gaussian()
+    }
+
+    /// Whether `source` drops this update
+    fn drops(&mut self, source: NoiseSource, probability: f64) -> bool {
+        self.dropout_streams[source.index()].chance(probability)
+    }
+
+    /// Write noisy readings of `truth` into `bus`
+    ///
+    /// The bus holds the first four wheels' speeds and steer angles, the
+    /// body orientation and angular rate as the IMU's six values, and the
+    /// battery voltage. Motor states are copied without noise. A source that
+    /// drops this update keeps its previous readings and is flagged invalid.
+    pub fn measure(&mut self, truth: &TrueState, bus: &mut SensorBus) {
+        bus.encoder_valid = !self.drops(NoiseSource::Encoder, self.encoder_dropout);
+        if bus.encoder_valid {
+            for (i, wheel) in truth.wheel_states.iter().take(4).enumerate() {
+                bus.wheel_omega[i] = wheel.driving_angular_velocity + self.sample(NoiseSource::Encoder, self.encoder_std);
+                bus.steer_angle[i] = wheel.angle + self.sample(NoiseSource::Encoder, self.encoder_std);
+            }
+        }
+
+        bus.imu_valid = !self.drops(NoiseSource::Imu, self.imu_dropout);
+        if bus.imu_valid {
+            let body = &truth.body_state;
+            let imu = [body.orientation, body.angular_velocity].concat();
+            for (reading, value) in bus.body_state.iter_mut().zip(imu) {
+                *reading = value + self.sample(NoiseSource::Imu, self.imu_std);
+            }
+        }
+
+        bus.battery_valid = !self.drops(NoiseSource::Battery, self.battery_dropout);
+        if bus.battery_valid {
+            bus.battery_voltage = truth.battery_state.voltage + self.sample(NoiseSource::Battery, self.battery_std);
+        }
+        bus.motors.clone_from(&truth.motors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TireState, WheelState};
+
+    fn wheel() -> WheelState {
+        WheelState {
+            driving_angular_velocity: 10.0,
+            wheel_radius: 0.05,
+            turning_angular_velocity: 0.0,
+            longitudinal_translational_velocity: 0.5,
+            lateral_translational_velocity: 0.0,
+            tire: TireState { slip_angle: 0.0, slip_ratio: 0.0, longitudinal_force: 0.0, lateral_force: 0.0, tire_load: 100.0 },
+            angle: 0.3,
+            contact_fraction: 1.0,
+        }
+    }
+
+    fn readings(seeds: NoiseSeeds) -> Vec<SensorBus> {
+        let mut truth = TrueState { wheel_states: (0..4).map(|_| wheel()).collect(), ..Default::default() };
+        truth.battery_state.voltage = 12.0;
+        let mut noise = SensorNoise::new(seeds).with_std_devs(0.1, 0.01, 0.05);
+        (0..50)
+            .map(|_| {
+                let mut bus = SensorBus::default();
+                noise.measure(&truth, &mut bus);
+                bus
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reseeding_imu_leaves_encoder_noise_unchanged() {
+        let base = NoiseSeeds::from_base(42);
+        let new_imu = base.with_seed(NoiseSource::Imu, 7);
+        assert_eq!(new_imu.get(NoiseSource::Encoder), base.get(NoiseSource::Encoder));
+        assert_ne!(base.get(NoiseSource::Encoder), base.get(NoiseSource::Imu));
+
+        let before = readings(base);
+        let after = readings(new_imu);
+        for (a, b) in before.iter().zip(&after) {
+            assert_eq!(a.wheel_omega, b.wheel_omega);
+            assert_eq!(a.steer_angle, b.steer_angle);
+            assert_eq!(a.battery_voltage, b.battery_voltage);
+        }
+        assert!(before.iter().zip(&after).any(|(a, b)| a.body_state != b.body_state));
+        assert!(before.iter().any(|bus| bus.wheel_omega[0] != 10.0));
+
+        // The same seeds replay the same noise
+        let replay = readings(base);
+        assert!(before.iter().zip(&replay).all(|(a, b)| a.body_state == b.body_state));
+    }
+
+    #[test]
+    fn test_dropout_holds_last_reading_at_configured_rate() {
+        let mut truth = TrueState { wheel_states: (0..4).map(|_| wheel()).collect(), ..Default::default() };
+        let mut noise = SensorNoise::new(NoiseSeeds::from_base(3)).with_dropout(0.2, 0.0, 0.0);
+        let mut bus = SensorBus::default();
+        let updates = 20_000;
+        let mut dropped = 0;
+        for i in 0..updates {
+            truth.wheel_states[0].driving_angular_velocity = i as f64;
+            let previous = bus.wheel_omega[0];
+            noise.measure(&truth, &mut bus);
+            if bus.encoder_valid {
+                assert_eq!(bus.wheel_omega[0], i as f64);
+            } else {
+                dropped += 1;
+                assert_eq!(bus.wheel_omega[0], previous);
+            }
+            assert!(bus.imu_valid && bus.battery_valid);
+        }
+
+        let rate = dropped as f64 / updates as f64;
+        assert!((rate - 0.2).abs() < 0.01, "dropout rate {}", rate);
+    }
+}
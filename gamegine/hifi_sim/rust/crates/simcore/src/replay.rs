@@ -0,0 +1,215 @@
+//! Command replay for sim-vs-real validation
+//!
+//! A recorded command log is fed back through the simulator and the
+//! resulting trajectory is scored against the trajectory the real robot
+//! recorded under the same commands.
+
+use crate::{checked_step_count, BodyState, CommandSpec, Scenario, StepBudgetError};
+
+/// A recorded command log, replayed as a schedule of held commands
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySource {
+    pub commands: Vec<CommandSpec>,
+}
+
+impl ReplaySource {
+    pub fn new(commands: Vec<CommandSpec>) -> Self {
+        ReplaySource { commands }
+    }
+
+    /// Replay a scenario's command schedule
+    pub fn from_scenario(scenario: &Scenario) -> Self {
+        Self::new(scenario.commands.clone())
+    }
+
+    /// Total length of the log (s)
+    pub fn duration(&self) -> f64 {
+        self.commands.iter().map(|c| c.duration).sum()
+    }
+
+    /// Drive a simulation through the log in steps of `dt`
+    ///
+    /// `step` applies a command, advances the simulation by `dt` and returns
+    /// the new body state. The trajectory starts with `initial` at t = 0 and
+    /// gains one sample per step. Each command's step count is checked
+    /// against `max_steps` before it runs.
+    pub fn replay(
+        &self,
+        dt: f64,
+        max_steps: u64,
+        initial: &BodyState,
+        mut step: impl FnMut(&CommandSpec, f64) -> BodyState,
+    ) -> Result<Trajectory, StepBudgetError> {
+        let mut trajectory = Trajectory::default();
+        let mut t = 0.0;
+        trajectory.push(t, initial);
+        for command in &self.commands {
+            let steps = checked_step_count(command.duration, dt, max_steps)?;
+            for _ in 0..steps {
+                t += dt;
+                trajectory.push(t, &step(command, dt));
+            }
+        }
+        Ok(trajectory)
+    }
+
+    /// Replay the log and score the simulated trajectory against `reference`
+    ///
+    /// Returns None if the reference is empty.
+    pub fn compare(
+        &self,
+        dt: f64,
+        max_steps: u64,
+        initial: &BodyState,
+        reference: &Trajectory,
+        step: impl FnMut(&CommandSpec, f64) -> BodyState,
+    ) -> Result<Option<Divergence>, StepBudgetError> {
+        Ok(Divergence::between(&self.replay(dt, max_steps, initial, step)?, reference))
+    }
+}
+
+/// Planar trajectory sampled at increasing times
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trajectory {
+    pub times: Vec<f64>,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub heading: Vec<f64>,
+    pub vx: Vec<f64>,
+    pub vy: Vec<f64>,
+}
+
+impl Trajectory {
+    /// Append the planar part of a body state at time `t`
+    pub fn push(&mut self, t: f64, body: &BodyState) {
+        self.times.push(t);
+        self.x.push(body.position[0]);
+        self.y.push(body.position[1]);
+        self.heading.push(body.orientation[2]);
+        self.vx.push(body.velocity[0]);
+        self.vy.push(body.velocity[1]);
+    }
+
+    /// Number of samples
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Check if there are no samples
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// `[x, y, heading, vx, vy]` at time `t`, linearly interpolated and held
+    /// at the ends. Heading is interpolated along the shorter arc.
+    pub fn sample(&self, t: f64) -> Option<[f64; 5]> {
+        let last = self.len().checked_sub(1)?;
+        let at = |i: usize| [self.x[i], self.y[i], self.heading[i], self.vx[i], self.vy[i]];
+        let upper = self.times.partition_point(|&time| time < t);
+        if upper == 0 {
+            return Some(at(0));
+        }
+        if upper > last {
+            return Some(at(last));
+        }
+
+        let (a, b) = (at(upper - 1), at(upper));
+        let span = self.times[upper] - self.times[upper - 1];
+        let frac = if span > 0.0 { (t - self.times[upper - 1]) / span } else { 1.0 };
+        let mut state = [0.0; 5];
+        for (i, value) in state.iter_mut().enumerate() {
+            let delta = if i == 2 { wrap_angle(b[i] - a[i]) } else { b[i] - a[i] };
+            *value = a[i] + frac * delta;
+        }
+        Some(state)
+    }
+}
+
+/// Wrap an angle into (-π, π]
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = angle.rem_euclid(std::f64::consts::TAU);
+    if wrapped > std::f64::consts::PI { wrapped - std::f64::consts::TAU } else { wrapped }
+}
+
+/// Per-channel RMS error of a simulated trajectory against a reference
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Divergence {
+    /// RMS distance between positions (m)
+    pub position_rmse: f64,
+    /// RMS heading difference, taken along the shorter arc (rad)
+    pub heading_rmse: f64,
+    /// RMS magnitude of the velocity difference (m/s)
+    pub velocity_rmse: f64,
+}
+
+impl Divergence {
+    /// Compare at each simulated sample time against the interpolated
+    /// reference. Returns None if either trajectory is empty.
+    pub fn between(simulated: &Trajectory, reference: &Trajectory) -> Option<Self> {
+        if simulated.is_empty() {
+            return None;
+        }
+        let mut sums = [0.0; 3];
+        for i in 0..simulated.len() {
+            let [x, y, heading, vx, vy] = reference.sample(simulated.times[i])?;
+            sums[0] += (simulated.x[i] - x).powi(2) + (simulated.y[i] - y).powi(2);
+            sums[1] += wrap_angle(simulated.heading[i] - heading).powi(2);
+            sums[2] += (simulated.vx[i] - vx).powi(2) + (simulated.vy[i] - vy).powi(2);
+        }
+        let n = simulated.len() as f64;
+        Some(Divergence {
+            position_rmse: (sums[0] / n).sqrt(),
+            heading_rmse: (sums[1] / n).sqrt(),
+            velocity_rmse: (sums[2] / n).sqrt(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(duration: f64, duty: f64, steer: f64) -> CommandSpec {
+        CommandSpec { duration, duty_cycles: vec![duty], steer_angles: vec![steer] }
+    }
+
+    /// Unicycle whose acceleration follows the duty and yaw rate the steer
+    fn unicycle() -> impl FnMut(&CommandSpec, f64) -> BodyState {
+        let mut body = BodyState::default();
+        move |command, dt| {
+            let speed = body.velocity[0].hypot(body.velocity[1]) + 2.0 * command.duty_cycles[0] * dt;
+            body.orientation[2] += command.steer_angles[0] * dt;
+            let heading = body.orientation[2];
+            body.velocity = [speed * heading.cos(), speed * heading.sin(), 0.0];
+            body.position[0] += body.velocity[0] * dt;
+            body.position[1] += body.velocity[1] * dt;
+            body
+        }
+    }
+
+    #[test]
+    fn test_replay_against_own_output_has_no_divergence() {
+        let source = ReplaySource::new(vec![command(1.0, 1.0, 0.0), command(1.0, 0.0, 0.5)]);
+        let start = BodyState::default();
+        let reference = source.replay(0.01, 1000, &start, unicycle()).unwrap();
+        assert_eq!(reference.len(), 201);
+
+        let own = source.compare(0.01, 1000, &start, &reference, unicycle()).unwrap().unwrap();
+        assert!(own.position_rmse < 1e-12 && own.heading_rmse < 1e-12 && own.velocity_rmse < 1e-12);
+
+        // A reference shifted 0.1 m sideways and turned 0.05 rad off
+        let mut perturbed = reference.clone();
+        perturbed.y.iter_mut().for_each(|y| *y += 0.1);
+        perturbed.heading.iter_mut().for_each(|heading| *heading += 0.05);
+        let off = source.compare(0.01, 1000, &start, &perturbed, unicycle()).unwrap().unwrap();
+        assert!((off.position_rmse - 0.1).abs() < 1e-9);
+        assert!((off.heading_rmse - 0.05).abs() < 1e-9);
+        assert!(off.velocity_rmse < 1e-12);
+
+        assert!(source.compare(0.01, 1000, &start, &Trajectory::default(), unicycle()).unwrap().is_none());
+        assert_eq!(
+            source.replay(0.01, 50, &start, unicycle()),
+            Err(StepBudgetError::TooManySteps { steps: 100, max_steps: 50 })
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Simulation scenarios
+//!
+//! A `Scenario` describes a complete experiment — drivetrain, drive motors,
+//! battery, tires, initial state and a command schedule — as plain data, so a
+//! reproducible run can be shared as a single JSON file. Building the actual
+//! simulator from a scenario is left to the crate that owns the models.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+use crate::BodyState;
+
+/// Errors from loading or validating a scenario
+#[derive(Debug, Error)]
+pub enum ScenarioError {
+    #[error("failed to read scenario: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse scenario: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("invalid scenario: {0}")]
+    Invalid(String),
+}
+
+/// Drivetrain layout and mass properties
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DrivetrainSpec {
+    Swerve {
+        /// Robot mass (kg)
+        mass: f64,
+        /// Yaw moment of inertia (kg*m^2)
+        #[serde(default = "default_moment_of_inertia")]
+        moment_of_inertia: f64,
+        /// Module [x, y] positions (m); defaults to a 0.6 m square
+        #[serde(default = "default_module_positions")]
+        module_positions: Vec<[f64; 2]>,
+        /// Per-module drive inversion for mirrored modules
+        #[serde(default)]
+        drive_inverted: Vec<bool>,
+    },
+}
+
+fn default_moment_of_inertia() -> f64 {
+    5.0
+}
+
+fn default_module_positions() -> Vec<[f64; 2]> {
+    let half_side = 0.3;
+    vec![
+        [half_side, half_side],
+        [half_side, -half_side],
+        [-half_side, half_side],
+        [-half_side, -half_side],
+    ]
+}
+
+impl DrivetrainSpec {
+    /// Robot mass (kg)
+    pub fn mass(&self) -> f64 {
+        match self {
+            DrivetrainSpec::Swerve { mass, .. } => *mass,
+        }
+    }
+
+    /// Number of drive modules
+    pub fn num_modules(&self) -> usize {
+        match self {
+            DrivetrainSpec::Swerve { module_positions, .. } => module_positions.len(),
+        }
+    }
+}
+
+/// Battery starting condition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatterySpec {
+    /// Initial state of charge (0-1)
+    #[serde(default = "default_soc")]
+    pub initial_soc: f64,
+}
+
+fn default_soc() -> f64 {
+    1.0
+}
+
+impl Default for BatterySpec {
+    fn default() -> Self {
+        BatterySpec { initial_soc: default_soc() }
+    }
+}
+
+/// Tire parameters applied to every module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TireSpec {
+    pub mu_long: f64,
+    pub mu_lat: f64,
+    pub cornering_stiffness: f64,
+    pub longitudinal_stiffness: f64,
+    #[serde(default)]
+    pub relaxation_long: f64,
+    #[serde(default)]
+    pub relaxation_lat: f64,
+}
+
+/// Initial pose and motion of the robot, with vx and vy in the field frame
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InitialStateSpec {
+    pub x: f64,
+    pub y: f64,
+    pub heading: f64,
+    pub vx: f64,
+    pub vy: f64,
+    pub omega: f64,
+}
+
+impl InitialStateSpec {
+    /// Body state for this initial condition
+    pub fn body_state(&self) -> BodyState {
+        BodyState::planar(self.x, self.y, self.heading, self.vx, self.vy, self.omega)
+    }
+}
+
+/// One segment of the command schedule, held for `duration` seconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandSpec {
+    /// How long the command is held (s)
+    pub duration: f64,
+    /// Drive duty cycle per module; missing modules get 0
+    #[serde(default)]
+    pub duty_cycles: Vec<f64>,
+    /// Steering angle per module (rad); missing modules get 0
+    #[serde(default)]
+    pub steer_angles: Vec<f64>,
+}
+
+/// A complete, reproducible simulation experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Optional human-readable name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Simulation time step (s)
+    #[serde(default = "default_dt")]
+    pub dt: f64,
+    pub drivetrain: DrivetrainSpec,
+    /// Drive motor preset name (e.g. "kraken_x60", "neo", "kraken_x44")
+    #[serde(default = "default_motor")]
+    pub motor: String,
+    #[serde(default)]
+    pub battery: BatterySpec,
+    /// Tire parameters; None keeps the simulator defaults
+    #[serde(default)]
+    pub tire: Option<TireSpec>,
+    #[serde(default)]
+    pub initial_state: InitialStateSpec,
+    /// Command schedule, executed in order
+    #[serde(default)]
+    pub commands: Vec<CommandSpec>,
+}
+
+fn default_dt() -> f64 {
+    0.001
+}
+
+fn default_motor() -> String {
+    "kraken_x60".to_string()
+}
+
+impl Scenario {
+    /// Parse and validate a scenario from JSON text
+    pub fn from_json(text: &str) -> Result<Self, ScenarioError> {
+        let scenario: Scenario = serde_json::from_str(text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Load and validate a scenario from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Serialize the scenario to pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, ScenarioError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Check physical parameters and schedule lengths
+    pub fn validate(&self) -> Result<(), ScenarioError> {
+        let invalid = |msg: String| Err(ScenarioError::Invalid(msg));
+        let num_modules = self.drivetrain.num_modules();
+
+        if !(self.dt.is_finite() && self.dt > 0.0) {
+            return invalid(format!("dt must be positive, got {}", self.dt));
+        }
+        let mass = self.drivetrain.mass();
+        if mass.is_nan() || mass <= 0.0 {
+            return invalid(format!("mass must be positive, got {}", mass));
+        }
+        if num_modules == 0 {
+            return invalid("drivetrain needs at least one module".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.battery.initial_soc) {
+            return invalid(format!("initial_soc must be in [0, 1], got {}", self.battery.initial_soc));
+        }
+        for (i, command) in self.commands.iter().enumerate() {
+            if !(command.duration.is_finite() && command.duration >= 0.0) {
+                return invalid(format!("command {} has invalid duration {}", i, command.duration));
+            }
+            if command.duty_cycles.len() > num_modules || command.steer_angles.len() > num_modules {
+                return invalid(format!("command {} addresses more than {} modules", i, num_modules));
+            }
+        }
+        Ok(())
+    }
+
+    /// Total length of the command schedule (s)
+    pub fn total_duration(&self) -> f64 {
+        self.commands.iter().map(|c| c.duration).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE_BOT: &str = r#"{
+        "name": "triangle launch",
+        "drivetrain": {
+            "type": "swerve",
+            "mass": 62.5,
+            "module_positions": [[0.3, 0.0], [-0.2, 0.25], [-0.2, -0.25]]
+        },
+        "motor": "neo",
+        "battery": { "initial_soc": 0.8 },
+        "initial_state": { "vx": 1.5 },
+        "commands": [
+            { "duration": 0.5, "duty_cycles": [1.0, 1.0, 1.0] },
+            { "duration": 0.25 }
+        ]
+    }"#;
+
+    #[test]
+    fn test_load_scenario_file() {
+        let path = std::env::temp_dir().join("gamegine_triangle_scenario.json");
+        std::fs::write(&path, TRIANGLE_BOT).unwrap();
+        let scenario = Scenario::from_file(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(scenario.drivetrain.mass(), 62.5);
+        assert_eq!(scenario.drivetrain.num_modules(), 3);
+        assert_eq!(scenario.motor, "neo");
+        assert_eq!(scenario.dt, 0.001);
+        assert_eq!(scenario.initial_state.body_state().velocity[0], 1.5);
+        assert!((scenario.total_duration() - 0.75).abs() < 1e-12);
+
+        // Round-trips through JSON unchanged
+        let reloaded = Scenario::from_json(&scenario.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.drivetrain.num_modules(), 3);
+        assert_eq!(reloaded.battery.initial_soc, 0.8);
+    }
+
+    #[test]
+    fn test_rejects_oversized_command() {
+        let text = TRIANGLE_BOT.replace("[1.0, 1.0, 1.0]", "[1.0, 1.0, 1.0, 1.0]");
+        assert!(matches!(Scenario::from_json(&text), Err(ScenarioError::Invalid(_))));
+    }
+}
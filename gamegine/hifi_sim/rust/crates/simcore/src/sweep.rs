@@ -0,0 +1,55 @@
+//! Single-parameter sweeps
+//!
+//! The "vary one design parameter, plot one number" workflow: build a fresh
+//! simulation for each value, reduce it to a scalar metric, and collect both
+//! into arrays ready to plot.
+
+/// Parameter values and the metric measured at each
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SweepResult {
+    pub values: Vec<f64>,
+    pub metrics: Vec<f64>,
+}
+
+/// Build a simulation for each of `values` and measure it with `metric`
+///
+/// Stops at the first error from either closure.
+pub fn try_sweep<S, E>(
+    values: &[f64],
+    mut build: impl FnMut(f64) -> Result<S, E>,
+    mut metric: impl FnMut(&mut S) -> Result<f64, E>,
+) -> Result<SweepResult, E> {
+    let mut metrics = Vec::with_capacity(values.len());
+    for &value in values {
+        let mut sim = build(value)?;
+        metrics.push(metric(&mut sim)?);
+    }
+    Ok(SweepResult { values: values.to_vec(), metrics })
+}
+
+/// Infallible form of `try_sweep`
+pub fn sweep<S>(values: &[f64], mut build: impl FnMut(f64) -> S, mut metric: impl FnMut(&mut S) -> f64) -> SweepResult {
+    let result: Result<_, std::convert::Infallible> =
+        try_sweep(values, |value| Ok(build(value)), |sim| Ok(metric(sim)));
+    match result {
+        Ok(result) => result,
+        Err(never) => match never {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_stops_at_first_error() {
+        let result = try_sweep(&[1.0, 2.0, -1.0, 3.0], Ok, |&mut value| {
+            if value < 0.0 { Err(value) } else { Ok(value * value) }
+        });
+        assert_eq!(result, Err(-1.0));
+
+        let squares = sweep(&[1.0, 2.0, 3.0], |value| value, |&mut value| value * value);
+        assert_eq!(squares.values, vec![1.0, 2.0, 3.0]);
+        assert_eq!(squares.metrics, vec![1.0, 4.0, 9.0]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Timestep bookkeeping shared by batch simulation runs
+
+use std::collections::VecDeque;
+use thiserror::Error;
+
+/// Default cap on the number of steps a single batch run may take
+pub const DEFAULT_MAX_STEPS: u64 = 10_000_000;
+
+/// Reasons a requested batch run is rejected before it starts
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum StepBudgetError {
+    #[error("dt must be positive and finite, got {0}")]
+    InvalidTimestep(f64),
+    #[error("duration must be non-negative and finite, got {0}")]
+    InvalidDuration(f64),
+    #[error("run of {steps} steps exceeds the limit of {max_steps} steps")]
+    TooManySteps { steps: u64, max_steps: u64 },
+}
+
+/// Number of steps needed to cover `duration` with step `dt`, checked against `max_steps`
+///
+/// Callers use this before pre-allocating result buffers so an accidental
+/// `duration=1e9, dt=1e-6` fails fast instead of exhausting memory.
+pub fn checked_step_count(duration: f64, dt: f64, max_steps: u64) -> Result<u64, StepBudgetError> {
+    if !(dt.is_finite() && dt > 0.0) {
+        return Err(StepBudgetError::InvalidTimestep(dt));
+    }
+    if !(duration.is_finite() && duration >= 0.0) {
+        return Err(StepBudgetError::InvalidDuration(duration));
+    }
+
+    let steps = (duration / dt).ceil();
+    if steps > max_steps as f64 {
+        return Err(StepBudgetError::TooManySteps {
+            steps: steps.min(u64::MAX as f64) as u64,
+            max_steps,
+        });
+    }
+    Ok(steps as u64)
+}
+
+/// Number of equal substeps splitting `dt` so none is longer than `max_dt`
+///
+/// `None` means a single step of `dt`.
+pub fn substep_count(dt: f64, max_dt: Option<f64>) -> usize {
+    max_dt.map_or(1, |max_dt| (dt / max_dt).ceil().max(1.0) as usize)
+}
+
+/// Solver steps taken over a run, reported so users can weigh the cost of
+/// their dt and substep settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepCounts {
+    /// Motor electrical model steps
+    pub electrical: u64,
+    /// Tire and drivetrain steps
+    pub mechanics: u64,
+    /// Battery model steps, one per electrical step
+    pub battery: u64,
+}
+
+impl StepCounts {
+    /// Count one mechanics step split into `elec_substeps` electrical steps
+    pub fn record_step(&mut self, elec_substeps: usize) {
+        self.electrical += elec_substeps as u64;
+        self.battery += elec_substeps as u64;
+        self.mechanics += 1;
+    }
+
+    /// Add the counts of a later run
+    pub fn merge(&mut self, other: &StepCounts) {
+        self.electrical += other.electrical;
+        self.mechanics += other.mechanics;
+        self.battery += other.battery;
+    }
+}
+
+/// Fires on every `every`-th tick, for thinning a per-step stream
+///
+/// Used to hand state to a slow consumer (a plot, a Python callback) while
+/// the simulation itself keeps stepping at full rate.
+#[derive(Debug, Clone, Copy)]
+pub struct Decimator {
+    every: u64,
+    ticks: u64,
+}
+
+impl Decimator {
+    /// Fire once every `every` ticks (at least 1)
+    pub fn new(every: u64) -> Self {
+        Self { every: every.max(1), ticks: 0 }
+    }
+
+    /// Count one tick and report whether it completes a period
+    pub fn tick(&mut self) -> bool {
+        self.ticks += 1;
+        self.ticks.is_multiple_of(self.every)
+    }
+
+    /// Start counting from zero again
+    pub fn reset(&mut self) {
+        self.ticks = 0;
+    }
+}
+
+/// Fixed transport delay for timestamped measurements
+///
+/// Values pushed at time `t` become visible once the caller's clock reaches
+/// `t + delay`; until the first value matures the oldest one is returned, so a
+/// consumer never sees a gap at startup.
+#[derive(Debug, Clone)]
+pub struct DelayLine<T> {
+    delay: f64,
+    buffer: VecDeque<(f64, T)>,
+}
+
+impl<T: Clone> DelayLine<T> {
+    /// Create a delay line holding values for `delay` seconds (clamped to >= 0)
+    pub fn new(delay: f64) -> Self {
+        Self {
+            delay: delay.max(0.0),
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Delay applied to pushed values (s)
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+
+    /// Record `value` measured at `time` and return the delayed value visible at `time`
+    pub fn push(&mut self, time: f64, value: T) -> T {
+        self.buffer.push_back((time, value));
+        // Drop entries once a newer one has also matured
+        while self.buffer.len() > 1 && self.buffer[1].0 <= time - self.delay {
+            self.buffer.pop_front();
+        }
+        self.buffer[0].1.clone()
+    }
+
+    /// Forget all buffered values
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reasonable_run_is_accepted() {
+        assert_eq!(checked_step_count(1.0, 0.001, DEFAULT_MAX_STEPS), Ok(1000));
+    }
+
+    #[test]
+    fn test_absurd_run_is_rejected() {
+        let err = checked_step_count(1e9, 1e-6, DEFAULT_MAX_STEPS).unwrap_err();
+        assert!(matches!(err, StepBudgetError::TooManySteps { steps, .. } if steps > DEFAULT_MAX_STEPS));
+        assert!(err.to_string().contains("exceeds the limit"));
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_rejected() {
+        assert_eq!(checked_step_count(1.0, 0.0, DEFAULT_MAX_STEPS), Err(StepBudgetError::InvalidTimestep(0.0)));
+        assert!(matches!(checked_step_count(f64::NAN, 0.001, DEFAULT_MAX_STEPS), Err(StepBudgetError::InvalidDuration(_))));
+    }
+
+    #[test]
+    fn test_substeps_cap_the_step_length() {
+        assert_eq!(substep_count(0.002, None), 1);
+        assert_eq!(substep_count(0.002, Some(0.001)), 2);
+        assert_eq!(substep_count(0.0025, Some(0.001)), 3);
+    }
+
+    #[test]
+    fn test_delay_line_returns_stale_values() {
+        let mut line = DelayLine::new(0.25);
+        // Before anything has matured, the oldest value is held
+        assert_eq!(line.push(0.0, 1), 1);
+        assert_eq!(line.push(0.125, 2), 1);
+        assert_eq!(line.push(0.25, 3), 1);
+        assert_eq!(line.push(0.375, 4), 2);
+
+        let mut passthrough = DelayLine::new(0.0);
+        assert_eq!(passthrough.push(0.0, 1), 1);
+        assert_eq!(passthrough.push(0.01, 2), 2);
+    }
+
+    #[test]
+    fn test_decimator_fires_every_nth_tick() {
+        let mut decimator = Decimator::new(10);
+        let fired: Vec<u64> = (1..=105).filter(|_| decimator.tick()).collect();
+        assert_eq!(fired.len(), 105 / 10);
+        assert_eq!(fired[0], 10);
+        assert!(fired.windows(2).all(|pair| pair[1] - pair[0] == 10));
+
+        // Zero is treated as every tick
+        let mut every_tick = Decimator::new(0);
+        assert!((0..5).all(|_| every_tick.tick()));
+    }
+}
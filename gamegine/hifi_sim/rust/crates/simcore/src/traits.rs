@@ -3,6 +3,8 @@
 pub struct TireState {
     pub slip_angle: f64,
     pub slip_ratio: f64,
+    /// Force on the wheel at the contact patch (braking convention: negative
+    /// while driving forward); the chassis is pushed by its negation
     pub longitudinal_force: f64,
     pub lateral_force: f64,
     pub tire_load: f64,
@@ -15,18 +17,72 @@ pub struct WheelState {
     pub longitudinal_translational_velocity: f64,
     pub lateral_translational_velocity: f64,
     pub tire: TireState,
-    pub angle: f64
+    pub angle: f64,
+    /// Fraction of the wheel's load carried by the ground (1.0 = full contact, 0.0 = lifted)
+    pub contact_fraction: f64
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct BodyState {
     pub position: [f64; 3],
+    /// Linear velocity in the field frame (m/s)
     pub velocity: [f64; 3],
     pub orientation: [f64; 3], // roll, pitch, yaw
     pub angular_velocity: [f64; 3],
     pub center_of_mass: [f64; 3]
 }
 
+impl BodyState {
+    /// Body state on the ground plane at pose (x, y, heading) moving at
+    /// field-relative (vx, vy) and yaw rate omega
+    pub fn planar(x: f64, y: f64, heading: f64, vx: f64, vy: f64, omega: f64) -> Self {
+        BodyState {
+            position: [x, y, 0.0],
+            velocity: [vx, vy, 0.0],
+            orientation: [0.0, 0.0, heading],
+            angular_velocity: [0.0, 0.0, omega],
+            ..Default::default()
+        }
+    }
+
+    /// Planar velocity (vx, vy, yaw rate) expressed in `frame`
+    ///
+    /// The stored velocity is field-relative; the body frame rotates it by
+    /// the heading so vx points along the robot's front.
+    pub fn planar_velocity(&self, frame: VelocityFrame) -> [f64; 3] {
+        let [vx, vy, _] = self.velocity;
+        let omega = self.angular_velocity[2];
+        match frame {
+            VelocityFrame::Field => [vx, vy, omega],
+            VelocityFrame::Body => {
+                let (sin, cos) = self.orientation[2].sin_cos();
+                [vx * cos + vy * sin, -vx * sin + vy * cos, omega]
+            }
+        }
+    }
+}
+
+/// Frame a planar velocity is expressed in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VelocityFrame {
+    /// Fixed to the field
+    #[default]
+    Field,
+    /// Fixed to the robot: x forward, y left
+    Body,
+}
+
+impl VelocityFrame {
+    /// Parse a frame name ("field" or "body")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "field" => Some(VelocityFrame::Field),
+            "body" => Some(VelocityFrame::Body),
+            _ => None,
+        }
+    }
+}
+
 
 // Electrical Traits
 #[derive(Debug, Clone, Copy)]
@@ -36,13 +92,37 @@ pub enum BridgeMode {
     Closed
 }
 
+/// What a motor controller does with its bridge while the motor is idle
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NeutralMode {
+    /// Leave the bridge open so the motor spins freely
+    #[default]
+    Coast,
+    /// Short the windings so back-EMF current brakes the motor
+    Brake,
+}
+
+impl NeutralMode {
+    /// Bridge state an idle motor is held in
+    pub fn bridge_mode(self) -> BridgeMode {
+        match self {
+            NeutralMode::Coast => BridgeMode::Open,
+            NeutralMode::Brake => BridgeMode::Shorted,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct MotorState {
     pub current_q: f64,
     pub current_d: f64,
     pub mechanical_velocity: f64,
     pub applied_torque: f64,
-    pub bridge_mode: BridgeMode
+    pub bridge_mode: BridgeMode,
+    /// Applied q-axis voltage, duty times bus voltage (V)
+    pub voltage_q: f64,
+    /// Applied d-axis voltage, duty times bus voltage (V)
+    pub voltage_d: f64,
 }
 
 impl Default for MotorState {
@@ -52,7 +132,9 @@ impl Default for MotorState {
             current_d: 0.0,
             mechanical_velocity: 0.0,
             applied_torque: 0.0,
-            bridge_mode: BridgeMode::Closed
+            bridge_mode: BridgeMode::Closed,
+            voltage_q: 0.0,
+            voltage_d: 0.0,
         }
     }
 }
@@ -69,7 +151,13 @@ pub struct BatteryState {
     pub voltage: f64, 
     pub fast_polarization_voltage: f64,
     pub slow_polarization_voltage: f64,
-    pub total_current_draw: f64
+    pub total_current_draw: f64,
+    /// Charge drawn from the battery since the start of the run (Ah)
+    pub amp_hours_drawn: f64,
+    /// Energy drawn from the battery since the start of the run (Wh)
+    pub watt_hours_drawn: f64,
+    /// Cell temperature (°C)
+    pub temperature: f64
 }
 
 impl Default for BatteryState {
@@ -79,7 +167,10 @@ impl Default for BatteryState {
             voltage: 12.0,
             fast_polarization_voltage: 0.0,
             slow_polarization_voltage: 0.0,
-            total_current_draw: 0.0
+            total_current_draw: 0.0,
+            amp_hours_drawn: 0.0,
+            watt_hours_drawn: 0.0,
+            temperature: 25.0
         }
     }
 }
@@ -92,7 +183,14 @@ pub struct SensorBus {
     pub steer_angle: [f64; 4],
     pub body_state: [f64; 6],
     pub motors: Vec<MotorState>,
-    pub battery_voltage: f64
+    pub battery_voltage: f64,
+    /// Whether the encoder readings were updated on the last measurement
+    /// (false until the first one, and while the encoders drop out)
+    pub encoder_valid: bool,
+    /// Whether the IMU readings were updated on the last measurement
+    pub imu_valid: bool,
+    /// Whether the battery voltage was updated on the last measurement
+    pub battery_valid: bool,
 }
 #[derive(Default)]
 pub struct TrueState {
@@ -104,7 +202,10 @@ pub struct TrueState {
 
 #[derive(Default)]
 pub struct ActuatorInput {
-    pub motor_inputs: Vec<MotorInput>
+    pub motor_inputs: Vec<MotorInput>,
+    /// Torque at each module's steering axis (N*m); modules without an entry
+    /// hold whatever angle they were set to
+    pub steer_torques: Vec<f64>,
 }
 
 #[derive(Default)]
@@ -146,3 +247,22 @@ pub trait ElectricalModel: Model {
 pub trait SensorModel: Model {
     fn step_sensor(&mut self, ctx: SimContext, state: &mut SimState);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_frame_velocity_follows_heading() {
+        // Facing +y (rotated 90 degrees) and driving forward at 2 m/s
+        let body = BodyState::planar(0.0, 0.0, std::f64::consts::FRAC_PI_2, 0.0, 2.0, 0.5);
+
+        let [field_vx, field_vy, field_omega] = body.planar_velocity(VelocityFrame::Field);
+        assert_eq!([field_vx, field_vy, field_omega], [0.0, 2.0, 0.5]);
+
+        let [body_vx, body_vy, body_omega] = body.planar_velocity(VelocityFrame::Body);
+        assert!((body_vx - 2.0).abs() < 1e-12);
+        assert!(body_vy.abs() < 1e-12);
+        assert_eq!(body_omega, 0.5);
+    }
+}